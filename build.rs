@@ -0,0 +1,41 @@
+// Records which commit the library is being built from and when, for sliderule::get_version_info.
+
+use std::env;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| String::from("."));
+    let git_dir = Path::new(&manifest_dir).join(".git");
+
+    // Only the crate's own repository counts, not one that it happens to be vendored into
+    if git_dir.exists() {
+        println!("cargo:rerun-if-changed={}", git_dir.join("HEAD").display());
+        println!("cargo:rerun-if-changed={}", git_dir.join("refs").display());
+
+        let sha = Command::new("git")
+            .args(["rev-parse", "--short=12", "HEAD"])
+            .current_dir(&manifest_dir)
+            .output()
+            .ok()
+            .filter(|out| out.status.success())
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string());
+        if let Some(sha) = sha {
+            println!("cargo:rustc-env=SR_BUILD_GIT_SHA={}", sha);
+        }
+    }
+
+    // Reproducible builds set the time themselves
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    let build_time = match env::var("SOURCE_DATE_EPOCH") {
+        Ok(t) => t.trim().parse::<u64>().ok(),
+        Err(_) => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_secs()),
+    };
+    if let Some(t) = build_time {
+        println!("cargo:rustc-env=SR_BUILD_TIME={}", t);
+    }
+}
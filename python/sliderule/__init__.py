@@ -0,0 +1,163 @@
+"""Manage Distributed OSHW Framework (DOF) projects from Python.
+
+This wraps the sliderule Rust crate. Operations return a `Result` with the same fields as the
+crate's `SROutput`, so the status codes in the Rust documentation apply here as well.
+"""
+
+from dataclasses import dataclass, field
+from os import PathLike
+from pathlib import Path
+from typing import List, Optional, Tuple, Union
+
+from . import _sliderule
+
+StrPath = Union[str, PathLike]
+
+__all__ = [
+    "ComponentHealth",
+    "Result",
+    "add_remote_component",
+    "change_licenses",
+    "create_component",
+    "download_component",
+    "get_level",
+    "get_licenses",
+    "get_version",
+    "health_check",
+    "list_all_licenses",
+    "list_changes",
+    "refactor",
+    "remove",
+    "update_dependencies",
+    "update_local_component",
+    "upload_component",
+    "validate_component",
+    "verify_locked",
+]
+
+
+@dataclass
+class Result:
+    """The outcome of an operation, with everything git and npm printed along the way."""
+
+    status: int
+    wrapped_status: int
+    stdout: List[str] = field(default_factory=list)
+    stderr: List[str] = field(default_factory=list)
+
+    @property
+    def ok(self) -> bool:
+        """True if neither Sliderule nor the tools it ran reported a problem."""
+        return self.status == 0 and self.wrapped_status == 0
+
+
+@dataclass
+class ComponentHealth:
+    """The problems found with one component during a health check."""
+
+    name: str
+    path: Path
+    validation: List[str] = field(default_factory=list)
+    licenses: List[str] = field(default_factory=list)
+    dependencies: List[str] = field(default_factory=list)
+    git_status: List[str] = field(default_factory=list)
+
+    @property
+    def healthy(self) -> bool:
+        return not (self.validation or self.licenses or self.dependencies or self.git_status)
+
+
+def _result(output: dict) -> Result:
+    return Result(**output)
+
+
+def create_component(
+    target_dir: StrPath, name: str, description: str, source_license: str, doc_license: str
+) -> Result:
+    return _result(
+        _sliderule.create_component(target_dir, name, description, source_license, doc_license)
+    )
+
+
+def add_remote_component(target_dir: StrPath, url: str, cache: Optional[str] = None) -> Result:
+    return _result(_sliderule.add_remote_component(target_dir, url, cache))
+
+
+def download_component(target_dir: StrPath, url: str) -> Result:
+    return _result(_sliderule.download_component(target_dir, url))
+
+
+def update_dependencies(target_dir: StrPath) -> Result:
+    return _result(_sliderule.update_dependencies(target_dir))
+
+
+def update_local_component(target_dir: StrPath) -> Result:
+    return _result(_sliderule.update_local_component(target_dir))
+
+
+def remove(target_dir: StrPath, name: str) -> Result:
+    return _result(_sliderule.remove(target_dir, name))
+
+
+def upload_component(
+    target_dir: StrPath,
+    message: str,
+    url: str,
+    username: Optional[str] = None,
+    password: Optional[str] = None,
+) -> Result:
+    return _result(_sliderule.upload_component(target_dir, message, url, username, password))
+
+
+def refactor(
+    target_dir: StrPath,
+    name: str,
+    url: str,
+    username: Optional[str] = None,
+    password: Optional[str] = None,
+) -> Result:
+    return _result(_sliderule.refactor(target_dir, name, url, username, password))
+
+
+def change_licenses(target_dir: StrPath, source_license: str, doc_license: str) -> Result:
+    return _result(_sliderule.change_licenses(target_dir, source_license, doc_license))
+
+
+def validate_component(target_dir: StrPath) -> Result:
+    return _result(_sliderule.validate_component(target_dir))
+
+
+def verify_locked(project_dir: StrPath) -> Result:
+    return _result(_sliderule.verify_locked(project_dir))
+
+
+def list_changes(target_dir: StrPath) -> Result:
+    return _result(_sliderule.list_changes(target_dir))
+
+
+def get_level(target_dir: StrPath) -> Tuple[str, Optional[Path]]:
+    """Where a directory sits in a project, as one of `not_a_component`, `top_level_project`,
+    `local_sub_component` or `remote_component`, along with the project's directory."""
+    level, project_dir = _sliderule.get_level(target_dir)
+    return level, Path(project_dir) if project_dir is not None else None
+
+
+def get_licenses(target_dir: StrPath) -> Tuple[str, str]:
+    """The source and documentation licenses of a component."""
+    return _sliderule.get_licenses(target_dir)
+
+
+def list_all_licenses(target_dir: StrPath) -> str:
+    return _sliderule.list_all_licenses(target_dir)
+
+
+def health_check(project_dir: StrPath) -> List[ComponentHealth]:
+    """Checks every component in a project, with the project itself first."""
+    return [
+        ComponentHealth(**dict(c, path=Path(c["path"])))
+        for c in _sliderule.health_check(project_dir)
+    ]
+
+
+def get_version() -> str:
+    return _sliderule.get_version()
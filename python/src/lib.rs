@@ -0,0 +1,218 @@
+//! Python bindings for sliderule. The native module only converts between Python and Rust types, the
+//! `sliderule` Python package wraps the results in dataclasses.
+
+extern crate pyo3;
+extern crate sliderule;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::path::{Path, PathBuf};
+
+/*
+ * Turns an SROutput into a dictionary with the same fields.
+ */
+fn output_to_dict(py: Python, output: sliderule::SROutput) -> PyResult<PyObject> {
+    let dict = PyDict::new(py);
+
+    dict.set_item("status", output.status)?;
+    dict.set_item("wrapped_status", output.wrapped_status)?;
+    dict.set_item("stdout", output.stdout)?;
+    dict.set_item("stderr", output.stderr)?;
+
+    Ok(dict.into())
+}
+
+/*
+ * The name Python sees for a component level.
+ */
+fn level_name(level: sliderule::ComponentLevel) -> &'static str {
+    match level {
+        sliderule::ComponentLevel::NotAComponent => "not_a_component",
+        sliderule::ComponentLevel::TopLevelProject => "top_level_project",
+        sliderule::ComponentLevel::LocalSubComponent => "local_sub_component",
+        sliderule::ComponentLevel::RemoteComponent => "remote_component",
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (target_dir, name, description, source_license, doc_license))]
+fn create_component(
+    py: Python,
+    target_dir: PathBuf,
+    name: String,
+    description: String,
+    source_license: String,
+    doc_license: String,
+) -> PyResult<PyObject> {
+    let output = py.allow_threads(|| {
+        sliderule::create_component(&target_dir, name, description, source_license, doc_license)
+    });
+
+    output_to_dict(py, output)
+}
+
+#[pyfunction]
+#[pyo3(signature = (target_dir, url, cache = None))]
+fn add_remote_component(
+    py: Python,
+    target_dir: PathBuf,
+    url: String,
+    cache: Option<String>,
+) -> PyResult<PyObject> {
+    let output = py.allow_threads(|| sliderule::add_remote_component(&target_dir, &url, cache));
+
+    output_to_dict(py, output)
+}
+
+#[pyfunction]
+fn download_component(py: Python, target_dir: PathBuf, url: String) -> PyResult<PyObject> {
+    let output = py.allow_threads(|| sliderule::download_component(&target_dir, &url));
+
+    output_to_dict(py, output)
+}
+
+#[pyfunction]
+fn update_dependencies(py: Python, target_dir: PathBuf) -> PyResult<PyObject> {
+    let output = py.allow_threads(|| sliderule::update_dependencies(&target_dir));
+
+    output_to_dict(py, output)
+}
+
+#[pyfunction]
+fn update_local_component(py: Python, target_dir: PathBuf) -> PyResult<PyObject> {
+    let output = py.allow_threads(|| sliderule::update_local_component(&target_dir));
+
+    output_to_dict(py, output)
+}
+
+#[pyfunction]
+fn remove(py: Python, target_dir: PathBuf, name: String) -> PyResult<PyObject> {
+    let output = py.allow_threads(|| sliderule::remove(&target_dir, &name));
+
+    output_to_dict(py, output)
+}
+
+#[pyfunction]
+#[pyo3(signature = (target_dir, message, url, username = None, password = None))]
+fn upload_component(
+    py: Python,
+    target_dir: PathBuf,
+    message: String,
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+) -> PyResult<PyObject> {
+    let output = py.allow_threads(|| {
+        sliderule::upload_component(&target_dir, message, url, username, password)
+    });
+
+    output_to_dict(py, output)
+}
+
+#[pyfunction]
+#[pyo3(signature = (target_dir, name, url, username = None, password = None))]
+fn refactor(
+    py: Python,
+    target_dir: PathBuf,
+    name: String,
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+) -> PyResult<PyObject> {
+    let output =
+        py.allow_threads(|| sliderule::refactor(&target_dir, name, url, username, password));
+
+    output_to_dict(py, output)
+}
+
+#[pyfunction]
+fn change_licenses(
+    py: Python,
+    target_dir: PathBuf,
+    source_license: String,
+    doc_license: String,
+) -> PyResult<PyObject> {
+    let output =
+        py.allow_threads(|| sliderule::change_licenses(&target_dir, source_license, doc_license));
+
+    output_to_dict(py, output)
+}
+
+#[pyfunction]
+fn validate_component(py: Python, target_dir: PathBuf) -> PyResult<PyObject> {
+    output_to_dict(py, sliderule::health::validate_component(&target_dir))
+}
+
+#[pyfunction]
+fn verify_locked(py: Python, project_dir: PathBuf) -> PyResult<PyObject> {
+    output_to_dict(py, sliderule::verify_locked(&project_dir))
+}
+
+#[pyfunction]
+fn list_changes(py: Python, target_dir: PathBuf) -> PyResult<PyObject> {
+    output_to_dict(py, sliderule::list_changes(&target_dir))
+}
+
+#[pyfunction]
+fn get_level(target_dir: PathBuf) -> (&'static str, Option<PathBuf>) {
+    let (level, project_dir) = sliderule::get_level(&target_dir);
+
+    (level_name(level), project_dir)
+}
+
+#[pyfunction]
+fn get_licenses(target_dir: PathBuf) -> (String, String) {
+    sliderule::get_licenses(&target_dir)
+}
+
+#[pyfunction]
+fn list_all_licenses(target_dir: PathBuf) -> String {
+    sliderule::list_all_licenses(&target_dir)
+}
+
+#[pyfunction]
+fn health_check(py: Python, project_dir: PathBuf) -> PyResult<Vec<PyObject>> {
+    let report = py.allow_threads(|| sliderule::health::health_check(Path::new(&project_dir)));
+
+    let mut components = Vec::new();
+    for component in report.components {
+        let dict = PyDict::new(py);
+        dict.set_item("name", component.name)?;
+        dict.set_item("path", component.path)?;
+        dict.set_item("validation", component.validation)?;
+        dict.set_item("licenses", component.licenses)?;
+        dict.set_item("dependencies", component.dependencies)?;
+        dict.set_item("git_status", component.git_status)?;
+        components.push(dict.into());
+    }
+
+    Ok(components)
+}
+
+#[pyfunction]
+fn get_version() -> String {
+    sliderule::get_version()
+}
+
+#[pymodule]
+fn _sliderule(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(create_component, m)?)?;
+    m.add_function(wrap_pyfunction!(add_remote_component, m)?)?;
+    m.add_function(wrap_pyfunction!(download_component, m)?)?;
+    m.add_function(wrap_pyfunction!(update_dependencies, m)?)?;
+    m.add_function(wrap_pyfunction!(update_local_component, m)?)?;
+    m.add_function(wrap_pyfunction!(remove, m)?)?;
+    m.add_function(wrap_pyfunction!(upload_component, m)?)?;
+    m.add_function(wrap_pyfunction!(refactor, m)?)?;
+    m.add_function(wrap_pyfunction!(change_licenses, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_component, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_locked, m)?)?;
+    m.add_function(wrap_pyfunction!(list_changes, m)?)?;
+    m.add_function(wrap_pyfunction!(get_level, m)?)?;
+    m.add_function(wrap_pyfunction!(get_licenses, m)?)?;
+    m.add_function(wrap_pyfunction!(list_all_licenses, m)?)?;
+    m.add_function(wrap_pyfunction!(health_check, m)?)?;
+    m.add_function(wrap_pyfunction!(get_version, m)?)?;
+
+    Ok(())
+}
@@ -0,0 +1,243 @@
+use std::fs;
+use std::path::Path;
+
+/// The file that tells git-annex which of a component's files it keeps, from the project's `annex_patterns` (see
+/// `config::ProjectConfig`).
+pub const ATTRIBUTES_FILE: &str = ".gitattributes";
+
+/// Returns true if a component's repository keeps files in git-annex, which is the case once it has a `git-annex`
+/// branch of its own or on `origin`.
+pub fn uses_annex(target_dir: &Path) -> bool {
+    if !target_dir.join(".git").exists() {
+        return false;
+    }
+
+    ["refs/heads/git-annex", "refs/remotes/origin/git-annex"]
+        .iter()
+        .any(|reference| {
+            let rev_output = super::git_sr::git_rev_parse(target_dir, reference);
+            rev_output.status == 0 && rev_output.wrapped_status == 0
+        })
+}
+
+/// Marks the files that match the project's `annex_patterns` as ones for git-annex to keep, in the component's
+/// `ATTRIBUTES_FILE`. Patterns that are already there aren't added again, and nothing is written when there are
+/// no patterns. `create_component` does this for every new component.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// // There are no patterns in the configuration, so nothing is kept in git-annex
+/// let output = sliderule::annex::write_attributes(&temp_dir.join("toplevel"));
+///
+/// assert_eq!(0, output.status);
+/// assert!(!temp_dir.join("toplevel").join(".gitattributes").exists());
+/// ```
+pub fn write_attributes(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let patterns = super::config::config_or_default(target_dir).annex_patterns;
+    if patterns.is_empty() {
+        return output;
+    }
+
+    let attributes_file = target_dir.join(ATTRIBUTES_FILE);
+    let mut contents = fs::read_to_string(&attributes_file).unwrap_or_default();
+    let nl = super::get_newline();
+
+    let mut added = 0;
+    for pattern in patterns {
+        let line = format!("{} annex.largefiles=anything", pattern);
+        if contents.lines().any(|l| l.trim() == line) {
+            continue;
+        }
+
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            contents.push_str(&nl);
+        }
+        contents.push_str(&line);
+        contents.push_str(&nl);
+        added += 1;
+    }
+
+    if added == 0 {
+        return output;
+    }
+
+    match fs::write(&attributes_file, contents) {
+        Ok(()) => output.stdout.push(format!(
+            "Added {} patterns for git-annex to {}.",
+            added, ATTRIBUTES_FILE
+        )),
+        Err(e) => {
+            output.status = 91;
+            output.stderr.push(format!(
+                "ERROR: Unable to write the patterns for git-annex to {}: {}",
+                ATTRIBUTES_FILE, e
+            ));
+        }
+    }
+
+    output
+}
+
+/// Sets up git-annex in a component's repository, marks the files from the project's `annex_patterns` as ones
+/// for it to keep, and adds the ones that are already there. This is done when the component's repository is
+/// created by `upload_component`, and nothing is done if there are no patterns.
+///
+/// git-annex has to be installed along with git.
+///
+/// The output status is 91 if the patterns can't be written and 92 if git-annex can't be set up or the files
+/// can't be added to it.
+///
+/// # Examples
+///
+/// ```no_run
+/// let output = sliderule::annex::init_annex(&std::env::temp_dir().join("toplevel"));
+///
+/// assert_eq!(0, output.status);
+/// ```
+pub fn init_annex(target_dir: &Path) -> super::SROutput {
+    let mut output = write_attributes(target_dir);
+    if output.status != 0
+        || super::config::config_or_default(target_dir)
+            .annex_patterns
+            .is_empty()
+    {
+        return output;
+    }
+
+    let init_output = super::git_sr::git_annex_init(target_dir);
+    let mut failed = init_output.status != 0 || init_output.wrapped_status != 0;
+    output = super::combine_sroutputs(output, init_output);
+
+    if !failed {
+        let add_output = super::git_sr::git_annex_add(target_dir);
+        failed = add_output.status != 0 || add_output.wrapped_status != 0;
+        output = super::combine_sroutputs(output, add_output);
+    }
+
+    if failed {
+        output.status = 92;
+        output.stderr.push(String::from(
+            "ERROR: Unable to set up git-annex for the component, make sure that it's installed.",
+        ));
+        return output;
+    }
+
+    output
+        .stdout
+        .push(String::from("Set up git-annex for the component."));
+
+    output
+}
+
+/// Downloads the contents of the files that a component keeps in git-annex, after bringing git-annex up to date
+/// with the component's remote repository. `download_component` and `update_local_component` do this for every
+/// component that `uses_annex`, and nothing is done for the ones that don't.
+///
+/// The output status is 93 if git-annex can't be updated or the contents can't be downloaded.
+///
+/// # Examples
+///
+/// ```no_run
+/// let output = sliderule::annex::fetch_annexed(&std::env::temp_dir().join("toplevel"));
+///
+/// assert_eq!(0, output.status);
+/// ```
+pub fn fetch_annexed(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    if !uses_annex(target_dir) {
+        return output;
+    }
+
+    // A fresh clone needs git-annex set up in it before anything can be downloaded
+    let init_output = super::git_sr::git_annex_init(target_dir);
+    let mut failed = init_output.status != 0 || init_output.wrapped_status != 0;
+    output = super::combine_sroutputs(output, init_output);
+
+    if !failed {
+        let sync_output = super::git_sr::git_annex_sync(target_dir, false);
+        failed = sync_output.status != 0 || sync_output.wrapped_status != 0;
+        output = super::combine_sroutputs(output, sync_output);
+    }
+
+    if !failed {
+        let get_output = super::git_sr::git_annex_get(target_dir);
+        failed = get_output.status != 0 || get_output.wrapped_status != 0;
+        output = super::combine_sroutputs(output, get_output);
+    }
+
+    if failed {
+        output.status = 93;
+        output.stderr.push(String::from(
+            "ERROR: Unable to download the files that the component keeps in git-annex.",
+        ));
+        return output;
+    }
+
+    output
+        .stdout
+        .push(String::from("Downloaded the files kept in git-annex."));
+
+    output
+}
+
+/// Sends the contents of the files that a component keeps in git-annex to its remote repository, which
+/// `upload_component` does after pushing a component that `uses_annex`. Nothing is done for components that
+/// don't.
+///
+/// The output status is 94 if the contents can't be sent.
+///
+/// # Examples
+///
+/// ```no_run
+/// let output = sliderule::annex::push_annexed(&std::env::temp_dir().join("toplevel"));
+///
+/// assert_eq!(0, output.status);
+/// ```
+pub fn push_annexed(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    if !uses_annex(target_dir) {
+        return output;
+    }
+
+    let sync_output = super::git_sr::git_annex_sync(target_dir, true);
+    let failed = sync_output.status != 0 || sync_output.wrapped_status != 0;
+    output = super::combine_sroutputs(output, sync_output);
+    if failed {
+        output.status = 94;
+        output.stderr.push(String::from(
+            "ERROR: Unable to upload the files that the component keeps in git-annex.",
+        ));
+        return output;
+    }
+
+    output
+        .stdout
+        .push(String::from("Uploaded the files kept in git-annex."));
+
+    output
+}
@@ -0,0 +1,1344 @@
+use std::fs;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+use super::json::JsonValue;
+
+/// The name of the manifest that is the first entry of every archive. It records the name of the project, the
+/// version of Sliderule that made the archive, and every remote component that was locked, with what it was
+/// installed from.
+pub const ARCHIVE_MANIFEST: &str = "sliderule-archive.json";
+
+/// The version of the archive layout that `export_archive` writes and `import_archive` understands.
+pub const ARCHIVE_FORMAT: u32 = 1;
+
+const BLOCK_SIZE: usize = 512;
+
+/*
+ * The largest number that fits in the size and time fields of a header.
+ */
+const MAX_HEADER_NUMBER: u64 = 0o77777777777;
+
+/// Takes a snapshot of a project, including its history and every remote component that is installed in it,
+/// and writes it to `out_file` as a single, uncompressed tar archive. Remote components are archived as they
+/// were installed, so they stay at the commits that their lock files pin them to. The manifest, `ARCHIVE_MANIFEST`,
+/// comes first and the project is under a directory with its name, so that the archive can still be read with
+/// ordinary tools long after Sliderule is gone.
+///
+/// `project_dir` must be a valid Sliderule component directory. Everything in its lock files needs to be
+/// installed, i.e. by running `update_dependencies`, otherwise the output status is 45 and nothing is written.
+/// The output status is also 45 if the archive can't be written.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let output = sliderule::archive::export_archive(&temp_dir.join("toplevel"), &temp_dir.join("toplevel.tar"));
+/// assert_eq!(0, output.status);
+///
+/// let output = sliderule::archive::import_archive(&temp_dir.join("toplevel.tar"), &temp_dir.join("restored"));
+/// assert_eq!(0, output.status);
+/// assert!(temp_dir.join("restored").join("toplevel").join(".sr").exists());
+/// ```
+pub fn export_archive(project_dir: &Path, out_file: &Path) -> super::SROutput {
+    export_archive_excluding(project_dir, out_file, &[])
+}
+
+/// Does the same thing as `export_archive`, but leaves out the project's history and its `dist` directory (or
+/// whichever directory the layout uses instead, see `config::Layout`), so that the archive can be attached to a
+/// release without carrying every earlier release along with it.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let out_file = temp_dir.join("toplevel").join("dist").join("toplevel.tar");
+/// let output = sliderule::archive::export_release_archive(&temp_dir.join("toplevel"), &out_file);
+/// assert_eq!(0, output.status);
+/// ```
+pub fn export_release_archive(project_dir: &Path, out_file: &Path) -> super::SROutput {
+    let mut excluded = vec![String::from(".git")];
+    if let Some(dist_dir) = super::config::config_or_default(project_dir)
+        .layout
+        .dist_dir
+    {
+        excluded.push(dist_dir);
+    }
+    let excluded: Vec<&str> = excluded.iter().map(|e| e.as_str()).collect();
+
+    export_archive_excluding(project_dir, out_file, &excluded)
+}
+
+/*
+ * Archives a project the way export_archive does, leaving out the files and directories at the given paths
+ * relative to the project.
+ */
+fn export_archive_excluding(
+    project_dir: &Path,
+    out_file: &Path,
+    excluded: &[&str],
+) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let name = match project_dir.file_name() {
+        Some(n) if project_dir.join(".sr").exists() => n.to_string_lossy().to_string(),
+        _ => {
+            output.status = 45;
+            output.stderr.push(format!(
+                "ERROR: {} is not a Sliderule component, so it can't be archived.",
+                project_dir.display()
+            ));
+            return output;
+        }
+    };
+
+    // Everything that's pinned has to be there to be archived
+    let mut components = Vec::new();
+    for lock_dir in super::lock::lock_dirs(project_dir) {
+        let packages = match super::lock::read_lock_file(&lock_dir) {
+            Ok(p) => p,
+            Err(e) => {
+                output.status = 45;
+                output.stderr.push(format!(
+                    "ERROR: Unable to read the lock file in {}: {}",
+                    lock_dir.display(),
+                    e
+                ));
+                continue;
+            }
+        };
+
+        let relative_dir = relative_name(project_dir, &lock_dir);
+        for package in packages {
+            if !lock_dir.join(&package.name).join("package.json").exists() {
+                output.status = 45;
+                output.stderr.push(format!(
+                    "ERROR: {} is locked in {}, but it isn't installed. Run update_dependencies first.",
+                    package.name,
+                    lock_dir.display()
+                ));
+                continue;
+            }
+
+            components.push(JsonValue::Object(vec![
+                (
+                    String::from("lock_dir"),
+                    JsonValue::String(relative_dir.clone()),
+                ),
+                (String::from("name"), JsonValue::String(package.name)),
+                (String::from("version"), JsonValue::String(package.version)),
+                (
+                    String::from("resolved"),
+                    JsonValue::String(super::strip_url_credentials(&package.resolved)),
+                ),
+                (
+                    String::from("commit"),
+                    match package.commit {
+                        Some(c) => JsonValue::String(c),
+                        None => JsonValue::Null,
+                    },
+                ),
+            ]));
+        }
+    }
+    if output.status != 0 {
+        return output;
+    }
+
+    let component_count = components.len();
+    let manifest = JsonValue::Object(vec![
+        (
+            String::from("format"),
+            JsonValue::Number(f64::from(ARCHIVE_FORMAT)),
+        ),
+        (String::from("name"), JsonValue::String(name.clone())),
+        (
+            String::from("sliderule_version"),
+            JsonValue::String(super::get_version()),
+        ),
+        (String::from("components"), JsonValue::Array(components)),
+    ]);
+
+    match write_archive(project_dir, &name, &manifest.to_json(), out_file, excluded) {
+        Ok(count) => output.stdout.push(format!(
+            "Archived {} files and {} remote components to {}.",
+            count,
+            component_count,
+            out_file.display()
+        )),
+        Err(e) => {
+            output.status = 45;
+            output.stderr.push(format!(
+                "ERROR: Unable to write the archive {}: {}",
+                out_file.display(),
+                e
+            ));
+        }
+    }
+
+    output
+}
+
+/// Rebuilds a project from an archive that was written by `export_archive`, in a directory with the project's
+/// name inside of `target_dir`. The remote components come back installed, so the project can be worked on
+/// without downloading anything.
+///
+/// The output status is 46 if the archive can't be read, isn't a Sliderule archive, has entries that would be
+/// written outside of the project or links that point outside of it, or if the project's directory already exists.
+pub fn import_archive(archive_file: &Path, target_dir: &Path) -> super::SROutput {
+    import_archive_named(archive_file, target_dir).0
+}
+
+/*
+ * Imports an archive like import_archive, also giving back the name of the project's directory that it was
+ * unpacked into, if it could be.
+ */
+fn import_archive_named(
+    archive_file: &Path,
+    target_dir: &Path,
+) -> (super::SROutput, Option<String>) {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    match read_archive(archive_file, target_dir) {
+        Ok((name, mut messages)) => {
+            output.stdout.append(&mut messages);
+            output.stdout.push(format!(
+                "Imported {} into {}.",
+                name,
+                target_dir.join(&name).display()
+            ));
+            (output, Some(name))
+        }
+        Err(e) => {
+            output.status = 46;
+            output.stderr.push(format!(
+                "ERROR: Unable to import {}: {}",
+                archive_file.display(),
+                e
+            ));
+            (output, None)
+        }
+    }
+}
+
+/// The name of the manifest in the directory written by `export_offline_bundle`. It records the name of the project,
+/// the archive of the project that's next to it, and each repository that was bundled, with the URL it came from.
+pub const BUNDLE_MANIFEST: &str = "sliderule-bundle.json";
+
+/// The directory inside of a project's `config::CONFIG_DIR` that `import_offline_bundle` restores the remote
+/// components' repositories into, as bare repositories named after the component.
+pub const OFFLINE_DIR: &str = "offline";
+
+/// Packages a project so that it can be carried over to a machine with no network access and rebuilt there with
+/// `import_offline_bundle`. The project is written with `export_archive`, with every remote component installed,
+/// and the whole history of each remote component's repository is written next to it as a git bundle. Nothing
+/// needs to be downloaded on the other side, and components can still be compared with, or rolled back to,
+/// other commits of their repositories there.
+///
+/// `project_dir` must be a valid Sliderule component directory, with everything in its lock files installed.
+/// `out_dir` The directory to write the bundle to, which must not exist yet.
+///
+/// The output status is 75 if `out_dir` already exists, can't be written to, or a remote component's repository
+/// can't be fetched or bundled. It's 45 if the project can't be archived, as with `export_archive`.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let output = sliderule::archive::export_offline_bundle(&temp_dir.join("toplevel"), &temp_dir.join("bundle"));
+/// assert_eq!(0, output.status);
+///
+/// let output = sliderule::archive::import_offline_bundle(&temp_dir.join("bundle"), &temp_dir.join("restored"));
+/// assert_eq!(0, output.status);
+/// assert!(temp_dir.join("restored").join("toplevel").join(".sr").exists());
+/// ```
+pub fn export_offline_bundle(project_dir: &Path, out_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    if out_dir.exists() {
+        output.status = 75;
+        output.stderr.push(format!(
+            "ERROR: {} already exists, so the bundle can't be written there.",
+            out_dir.display()
+        ));
+        return output;
+    }
+    if let Err(e) = fs::create_dir_all(out_dir.join("repositories")) {
+        output.status = 75;
+        output.stderr.push(format!(
+            "ERROR: Unable to create the bundle directory {}: {}",
+            out_dir.display(),
+            e
+        ));
+        return output;
+    }
+
+    let name = project_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let archive_name = format!("{}.tar", name);
+    let archive_output = export_archive(project_dir, &out_dir.join(&archive_name));
+    if archive_output.status != 0 {
+        return super::combine_sroutputs(output, archive_output);
+    }
+    output = super::combine_sroutputs(output, archive_output);
+
+    // The same repository can be installed in more than one place, but only has to be bundled once
+    let mut repositories: Vec<(String, String)> = Vec::new();
+    for lock_dir in super::lock::lock_dirs(project_dir) {
+        for package in super::lock::read_lock_file(&lock_dir).unwrap_or_default() {
+            let git_url = match super::dependency_url_to_git_url(&package.resolved) {
+                Some(u) => u,
+                None => continue,
+            };
+            if repositories.iter().any(|r| r.1 == git_url) {
+                continue;
+            }
+
+            let component_name = package.name.trim_start_matches("node_modules/").to_string();
+            repositories.push((component_name, git_url));
+        }
+    }
+
+    let mut entries = Vec::new();
+    for (component_name, git_url) in repositories {
+        let mut bundle_name = component_name.replace('@', "").replace('/', "_");
+        let mut n = 2;
+        while entries.iter().any(|e: &JsonValue| {
+            e.get_str("bundle") == format!("repositories/{}.bundle", bundle_name)
+        }) {
+            bundle_name = format!(
+                "{}_{}",
+                component_name.replace('@', "").replace('/', "_"),
+                n
+            );
+            n += 1;
+        }
+        let bundle_path = format!("repositories/{}.bundle", bundle_name);
+
+        let clone_dir = super::scratch_dir("bundle");
+        let clone_output = super::git_sr::git_clone_bare(&clone_dir, &git_url);
+        let bundle_output = if clone_output.status == 0 && clone_output.wrapped_status == 0 {
+            super::git_sr::git_bundle_create(&clone_dir, &out_dir.join(&bundle_path))
+        } else {
+            clone_output
+        };
+        let _ = fs::remove_dir_all(&clone_dir);
+
+        if bundle_output.status != 0 || bundle_output.wrapped_status != 0 {
+            output.status = 75;
+            output.stderr.push(format!(
+                "ERROR: Unable to fetch and bundle {} from {}.",
+                component_name,
+                super::redact::redact(&git_url)
+            ));
+            continue;
+        }
+
+        entries.push(JsonValue::Object(vec![
+            (String::from("name"), JsonValue::String(component_name)),
+            (
+                String::from("url"),
+                JsonValue::String(super::strip_url_credentials(&git_url)),
+            ),
+            (String::from("bundle"), JsonValue::String(bundle_path)),
+        ]));
+    }
+    if output.status != 0 {
+        return output;
+    }
+
+    let repository_count = entries.len();
+    let manifest = JsonValue::Object(vec![
+        (
+            String::from("format"),
+            JsonValue::Number(f64::from(ARCHIVE_FORMAT)),
+        ),
+        (String::from("name"), JsonValue::String(name.clone())),
+        (
+            String::from("sliderule_version"),
+            JsonValue::String(super::get_version()),
+        ),
+        (String::from("archive"), JsonValue::String(archive_name)),
+        (String::from("repositories"), JsonValue::Array(entries)),
+    ]);
+    if let Err(e) = fs::write(out_dir.join(BUNDLE_MANIFEST), manifest.to_json()) {
+        output.status = 75;
+        output.stderr.push(format!(
+            "ERROR: Unable to write the bundle manifest in {}: {}",
+            out_dir.display(),
+            e
+        ));
+        return output;
+    }
+
+    output.stdout.push(format!(
+        "Bundled {} and {} remote component repositories into {}.",
+        name,
+        repository_count,
+        out_dir.display()
+    ));
+
+    output
+}
+
+/// Rebuilds a project from a directory written by `export_offline_bundle`, without any network access. The project
+/// is imported with `import_archive`, in a directory with the project's name inside of `target_dir`, and each
+/// bundled repository is restored as a bare repository in the project's `OFFLINE_DIR`, which git ignores.
+///
+/// The output status is 76 if the bundle's manifest can't be read or one of its repositories can't be restored,
+/// and 46 if the project can't be imported, as with `import_archive`.
+pub fn import_offline_bundle(bundle_dir: &Path, target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let manifest = match fs::read_to_string(bundle_dir.join(BUNDLE_MANIFEST))
+        .map_err(|e| e.to_string())
+        .and_then(|c| super::json::parse(&c))
+    {
+        Ok(m) => m,
+        Err(e) => {
+            output.status = 76;
+            output.stderr.push(format!(
+                "ERROR: {} is not a Sliderule bundle: {}",
+                bundle_dir.display(),
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Only plain file names are accepted from the manifest, so that nothing outside of the bundle is read
+    let archive_name = manifest.get_str("archive");
+    if Path::new(&archive_name).components().count() != 1 {
+        output.status = 76;
+        output.stderr.push(format!(
+            "ERROR: {} is not a Sliderule bundle: The manifest doesn't name the project's archive.",
+            bundle_dir.display()
+        ));
+        return output;
+    }
+
+    // The project is wherever the archive was actually unpacked, whatever the bundle's manifest says
+    let (import_output, name) = import_archive_named(&bundle_dir.join(&archive_name), target_dir);
+    if import_output.status != 0 {
+        return super::combine_sroutputs(output, import_output);
+    }
+    output = super::combine_sroutputs(output, import_output);
+
+    let name = name.unwrap_or_default();
+    let mut parts = Path::new(&name).components();
+    if !matches!(
+        (parts.next(), parts.next()),
+        (Some(Component::Normal(_)), None)
+    ) {
+        output.status = 76;
+        output.stderr.push(format!(
+            "ERROR: {} is not a Sliderule bundle: The project's archive doesn't have the project name.",
+            bundle_dir.display()
+        ));
+        return output;
+    }
+
+    let project_dir = target_dir.join(&name);
+    let offline_dir = project_dir
+        .join(super::config::CONFIG_DIR)
+        .join(OFFLINE_DIR);
+    if let Err(e) = fs::create_dir_all(&offline_dir)
+        .and_then(|_| fs::write(offline_dir.join(".gitignore"), "*\n"))
+    {
+        output.status = 76;
+        output.stderr.push(format!(
+            "ERROR: Unable to create {}: {}",
+            offline_dir.display(),
+            e
+        ));
+        return output;
+    }
+
+    let mut count = 0;
+    if let Some(JsonValue::Array(repositories)) = manifest.get("repositories") {
+        for repository in repositories {
+            let name = repository.get_str("name");
+            let bundle = repository.get_str("bundle");
+            let valid = Path::new(&bundle)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+                && Path::new(&name)
+                    .components()
+                    .all(|c| matches!(c, Component::Normal(_)));
+
+            let restore_output = if valid {
+                let dest = offline_dir.join(format!("{}.git", name.replace('/', "_")));
+                super::git_sr::git_clone_bare(&dest, &bundle_dir.join(&bundle).to_string_lossy())
+            } else {
+                super::SROutput {
+                    status: 76,
+                    wrapped_status: 0,
+                    stdout: Vec::new(),
+                    stderr: Vec::new(),
+                }
+            };
+
+            if restore_output.status != 0 || restore_output.wrapped_status != 0 {
+                output.status = 76;
+                output.stderr.push(format!(
+                    "ERROR: Unable to restore the repository of {} from the bundle.",
+                    name
+                ));
+            } else {
+                count += 1;
+            }
+        }
+    }
+
+    output.stdout.push(format!(
+        "Restored {} remote component repositories into {}.",
+        count,
+        offline_dir.display()
+    ));
+
+    output
+}
+
+/// The file that `download_repository_archive` records the commit it downloaded in, inside of a component that
+/// was downloaded as an archive because git wasn't available.
+pub const ARCHIVE_COMMIT_FILE: &str = ".sr_commit";
+
+/// The URL of the API that says which commit a GitHub or GitLab repository is at, for downloading it without git.
+/// The branch, tag or commit after a `#` in `url` is looked up, and `HEAD` otherwise. Repositories on other hosts
+/// give `None`, since there's no way to know where their archives are.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(
+///     sliderule::archive::commit_api_url("https://github.com/jmwright/toplevel.git#v1.0.0"),
+///     Some(String::from("https://api.github.com/repos/jmwright/toplevel/commits/v1.0.0"))
+/// );
+/// assert_eq!(
+///     sliderule::archive::commit_api_url("git+https://gitlab.com/ourorg/parts/bracket"),
+///     Some(String::from("https://gitlab.com/api/v4/projects/ourorg%2Fparts%2Fbracket/repository/commits/HEAD"))
+/// );
+/// assert_eq!(sliderule::archive::commit_api_url("git@github.com:jmwright/toplevel.git"), None);
+/// ```
+pub fn commit_api_url(url: &str) -> Option<String> {
+    let (host, path, reference) = hosted_repository(url)?;
+
+    if host == "github.com" {
+        Some(format!(
+            "https://api.github.com/repos/{}/commits/{}",
+            path, reference
+        ))
+    } else {
+        Some(format!(
+            "https://{}/api/v4/projects/{}/repository/commits/{}",
+            host,
+            path.replace('/', "%2F"),
+            reference
+        ))
+    }
+}
+
+/// The URL of a gzipped tar archive of a GitHub or GitLab repository at `commit`. Repositories on other hosts give
+/// `None`, the same as with `commit_api_url`.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(
+///     sliderule::archive::repository_archive_url("https://github.com/jmwright/toplevel.git", "0123abc"),
+///     Some(String::from("https://github.com/jmwright/toplevel/archive/0123abc.tar.gz"))
+/// );
+/// assert_eq!(
+///     sliderule::archive::repository_archive_url("https://gitlab.com/ourorg/bracket.git", "0123abc"),
+///     Some(String::from("https://gitlab.com/ourorg/bracket/-/archive/0123abc/bracket-0123abc.tar.gz"))
+/// );
+/// ```
+pub fn repository_archive_url(url: &str, commit: &str) -> Option<String> {
+    let (host, path, _) = hosted_repository(url)?;
+
+    if host == "github.com" {
+        Some(format!(
+            "https://github.com/{}/archive/{}.tar.gz",
+            path, commit
+        ))
+    } else {
+        let name = path.rsplit('/').next().unwrap_or(&path);
+        Some(format!(
+            "https://{}/{}/-/archive/{}/{}-{}.tar.gz",
+            host, path, commit, name, commit
+        ))
+    }
+}
+
+/// Downloads a component from GitHub or GitLab over HTTPS without git, for machines that only need to read
+/// components and don't have git installed. The commit that the repository is at is looked up first, and an
+/// archive of that commit is downloaded and unpacked into `component_dir`. Nothing is kept of the repository's
+/// history, so the component can't be updated or uploaded with git afterwards.
+///
+/// curl and tar have to be installed. The commit is returned along with the output, and `component_dir` is
+/// removed again if anything goes wrong.
+///
+/// `url` The HTTPS URL of the repository, with an optional `#tag`, `#branch` or `#commit` on the end.
+/// `component_dir` The directory to unpack the component into, which must not exist yet.
+///
+/// The output status is 500 if the repository isn't on GitHub or GitLab or its commit can't be found, and 501
+/// if the archive can't be unpacked. Problems with running curl have the same statuses as in `curl_sr`.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+///
+/// # Examples
+///
+/// ```no_run
+/// let component_dir = std::env::temp_dir().join("toplevel");
+/// let (output, commit) = sliderule::archive::download_repository_archive("https://github.com/jmwright/toplevel.git", &component_dir);
+///
+/// assert_eq!(0, output.status);
+/// assert!(commit.is_some());
+/// ```
+pub fn download_repository_archive(
+    url: &str,
+    component_dir: &Path,
+) -> (super::SROutput, Option<String>) {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let api_url = match commit_api_url(url) {
+        Some(u) => u,
+        None => {
+            output.status = 500;
+            output.stderr.push(format!(
+                "ERROR: {} is not an HTTPS URL on GitHub or GitLab, so it can't be downloaded without git.",
+                super::redact::redact(url)
+            ));
+            return (output, None);
+        }
+    };
+
+    if component_dir.exists() {
+        output.status = 501;
+        output.stderr.push(format!(
+            "ERROR: {} already exists, so the archive was not unpacked.",
+            component_dir.display()
+        ));
+        return (output, None);
+    }
+
+    // GitHub calls the commit sha and GitLab calls it id
+    let api_output = super::curl_sr::curl_get(&api_url);
+    let commit = if api_output.status == 0 && api_output.wrapped_status == 0 {
+        super::json::parse(&api_output.stdout.join(""))
+            .ok()
+            .map(|c| {
+                let sha = c.get_str("sha");
+                if sha.is_empty() {
+                    c.get_str("id")
+                } else {
+                    sha
+                }
+            })
+            .filter(|c| c.len() == 40 && c.chars().all(|ch| ch.is_ascii_hexdigit()))
+    } else {
+        None
+    };
+    let commit = match commit {
+        Some(c) => c,
+        None => {
+            let curl_status = api_output.status;
+            output.stderr.extend(api_output.stderr);
+            output.status = if curl_status == 300 || curl_status == 301 {
+                curl_status
+            } else {
+                500
+            };
+            output.stderr.push(format!(
+                "ERROR: Unable to find the commit that {} is at.",
+                super::redact::redact(url)
+            ));
+            return (output, None);
+        }
+    };
+
+    let archive_file = component_dir.with_extension("sr-download.tar.gz");
+    let download_output = super::curl_sr::curl_download(
+        &repository_archive_url(url, &commit).unwrap_or_default(),
+        &archive_file,
+    );
+    if download_output.status != 0 || download_output.wrapped_status != 0 {
+        let _ = fs::remove_file(&archive_file);
+        output.status = download_output.status;
+        output.wrapped_status = download_output.wrapped_status;
+        output.stderr.extend(download_output.stderr);
+        output.stderr.push(format!(
+            "ERROR: Unable to download the archive of {}.",
+            super::redact::redact(url)
+        ));
+        return (output, None);
+    }
+
+    // Both hosts put everything inside of a directory named after the repository and commit
+    let unpacked = fs::create_dir_all(component_dir).map(|_| {
+        super::backend::run(
+            std::process::Command::new("tar")
+                .arg("-xzf")
+                .arg(&archive_file)
+                .arg("-C")
+                .arg(component_dir)
+                .arg("--strip-components=1"),
+        )
+    });
+    let _ = fs::remove_file(&archive_file);
+
+    let failure = match unpacked {
+        Ok(Ok(tar_output)) => {
+            super::backend::record_exit(&mut output, "tar", &tar_output.status);
+            if output.status == 0 && output.wrapped_status == 0 {
+                None
+            } else {
+                Some(
+                    String::from_utf8_lossy(&tar_output.stderr)
+                        .trim()
+                        .to_string(),
+                )
+            }
+        }
+        Ok(Err(e)) | Err(e) => Some(e.to_string()),
+    };
+    if let Some(e) = failure {
+        let _ = fs::remove_dir_all(component_dir);
+        if output.status == 0 {
+            output.status = 501;
+        }
+        output.stderr.push(format!(
+            "ERROR: Unable to unpack the archive of {}: {}",
+            super::redact::redact(url),
+            e
+        ));
+        return (output, None);
+    }
+
+    output.stdout.push(format!(
+        "Downloaded an archive of commit {} without git.",
+        commit
+    ));
+
+    (output, Some(commit))
+}
+
+/*
+ * Splits the URL of a repository on GitHub or GitLab into its host, its path without .git on the end, and the
+ * branch, tag or commit after the #, which is HEAD when there isn't one.
+ */
+fn hosted_repository(url: &str) -> Option<(String, String, String)> {
+    let url = url.trim();
+    let url = url.strip_prefix("git+").unwrap_or(url);
+    let (url, reference) = match url.split_once('#') {
+        Some((u, r)) if !r.is_empty() => (u, r.to_string()),
+        Some((u, _)) => (u, String::from("HEAD")),
+        None => (url, String::from("HEAD")),
+    };
+
+    let rest = url.strip_prefix("https://")?;
+    let (host, path) = rest.split_once('/')?;
+    // Credentials aren't used for archives, so they're not sent anywhere
+    let host = host.rsplit('@').next().unwrap_or(host).to_lowercase();
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+
+    if path.split('/').filter(|p| !p.is_empty()).count() < 2 || path.contains("//") {
+        return None;
+    }
+    if host != "github.com" && !host.split('.').any(|part| part == "gitlab") {
+        return None;
+    }
+
+    Some((host, path.to_string(), reference))
+}
+
+/*
+ * Writes the manifest and everything in the project to a tar file, returning how many files were written.
+ */
+fn write_archive(
+    project_dir: &Path,
+    name: &str,
+    manifest: &str,
+    out_file: &Path,
+    excluded: &[&str],
+) -> io::Result<usize> {
+    // Don't archive the archive if it's being written inside of the project
+    let skip = out_file
+        .parent()
+        .and_then(|p| fs::canonicalize(p).ok())
+        .map(|p| p.join(out_file.file_name().unwrap_or_default()));
+    let excluded: Vec<PathBuf> = excluded.iter().map(|e| project_dir.join(e)).collect();
+
+    let mut writer = BufWriter::new(fs::File::create(out_file)?);
+
+    write_entry(
+        &mut writer,
+        ARCHIVE_MANIFEST,
+        EntryKind::File,
+        0o644,
+        0,
+        manifest.as_bytes(),
+    )?;
+
+    let mut count = 0;
+    let walker = WalkDir::new(project_dir).sort_by(|a, b| a.file_name().cmp(b.file_name()));
+    for entry in walker
+        .into_iter()
+        .filter_entry(|e| !excluded.iter().any(|x| e.path() == x))
+    {
+        let entry = entry.map_err(io::Error::other)?;
+        if skip.is_some() && fs::canonicalize(entry.path()).ok() == skip {
+            continue;
+        }
+
+        let path = format!("{}/{}", name, relative_name(project_dir, entry.path()));
+        let path = path.trim_end_matches('/');
+        let metadata = entry.path().symlink_metadata()?;
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        if metadata.file_type().is_symlink() {
+            let target = fs::read_link(entry.path())?;
+            write_entry(
+                &mut writer,
+                path,
+                EntryKind::Symlink(target.to_string_lossy().replace('\\', "/")),
+                0o777,
+                mtime,
+                &[],
+            )?;
+        } else if metadata.is_dir() {
+            write_entry(
+                &mut writer,
+                &format!("{}/", path),
+                EntryKind::Dir,
+                mode(&metadata, 0o755),
+                mtime,
+                &[],
+            )?;
+        } else {
+            let contents = fs::read(entry.path())?;
+            write_entry(
+                &mut writer,
+                path,
+                EntryKind::File,
+                mode(&metadata, 0o644),
+                mtime,
+                &contents,
+            )?;
+            count += 1;
+        }
+    }
+
+    // The end of an archive is marked by two empty blocks
+    writer.write_all(&[0; BLOCK_SIZE * 2])?;
+    writer.flush()?;
+
+    Ok(count)
+}
+
+/*
+ * Unpacks an archive into target_dir, returning the name of the project and any warnings.
+ */
+fn read_archive(archive_file: &Path, target_dir: &Path) -> Result<(String, Vec<String>), String> {
+    let file = fs::File::open(archive_file).map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(file);
+
+    let manifest = match read_entry(&mut reader).map_err(|e| e.to_string())? {
+        Some(ref e) if e.path == ARCHIVE_MANIFEST => {
+            super::json::parse(&String::from_utf8_lossy(&e.contents))?
+        }
+        _ => return Err(String::from("It is not a Sliderule archive.")),
+    };
+
+    match manifest.get("format") {
+        Some(&JsonValue::Number(f)) if f == f64::from(ARCHIVE_FORMAT) => (),
+        _ => {
+            return Err(String::from(
+                "It was made by a version of Sliderule that this one doesn't understand.",
+            ))
+        }
+    }
+
+    let name = manifest.get_str("name");
+    let mut parts = Path::new(&name).components();
+    if !matches!(
+        (parts.next(), parts.next()),
+        (Some(Component::Normal(_)), None)
+    ) {
+        return Err(String::from("The manifest doesn't have the project name."));
+    }
+
+    let project_dir = target_dir.join(&name);
+    if project_dir.exists() {
+        return Err(format!("{} already exists.", project_dir.display()));
+    }
+    fs::create_dir_all(&project_dir).map_err(|e| e.to_string())?;
+
+    // Links are made last, so that nothing in the archive can be written through one of them
+    let mut links = Vec::new();
+    while let Some(entry) = read_entry(&mut reader).map_err(|e| e.to_string())? {
+        let relative = match entry.path.strip_prefix(&format!("{}/", name)) {
+            Some(r) => r.trim_end_matches('/').to_string(),
+            None => return Err(format!("{} is outside of the project.", entry.path)),
+        };
+        if relative.is_empty() {
+            continue;
+        }
+        if !Path::new(&relative)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(format!("{} is outside of the project.", entry.path));
+        }
+        let dest = project_dir.join(&relative);
+
+        match entry.kind {
+            EntryKind::Dir => fs::create_dir_all(&dest).map_err(|e| e.to_string())?,
+            EntryKind::File => {
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                fs::write(&dest, &entry.contents).map_err(|e| e.to_string())?;
+                set_mode(&dest, entry.mode);
+            }
+            EntryKind::Symlink(target) => links.push((PathBuf::from(relative), target)),
+        }
+    }
+
+    // A link can't be made through another link, or point anywhere outside of the project
+    let link_paths: Vec<PathBuf> = links.iter().map(|(path, _)| path.clone()).collect();
+    for (path, target) in &links {
+        if link_paths
+            .iter()
+            .any(|other| other != path && path.starts_with(other))
+        {
+            return Err(format!(
+                "{} goes through another link in the archive.",
+                path.display()
+            ));
+        }
+        if !link_stays_inside(path, target, &link_paths) {
+            return Err(format!(
+                "The link {} to {} is outside of the project.",
+                path.display(),
+                target
+            ));
+        }
+    }
+
+    let mut warnings = Vec::new();
+    for (path, target) in links {
+        let dest = project_dir.join(path);
+        if let Err(e) = make_link(&dest, &target) {
+            warnings.push(format!(
+                "Could not recreate the link {} to {}: {}",
+                dest.display(),
+                target,
+                e
+            ));
+        }
+    }
+
+    // Let the user know if the archive was missing anything it should have had
+    if let Some(JsonValue::Array(components)) = manifest.get("components") {
+        for component in components {
+            let installed = project_dir
+                .join(component.get_str("lock_dir"))
+                .join(component.get_str("name"))
+                .join("package.json");
+            if !installed.exists() {
+                warnings.push(format!(
+                    "{} is in the manifest, but it wasn't in the archive.",
+                    component.get_str("name")
+                ));
+            }
+        }
+    }
+
+    Ok((name, warnings))
+}
+
+enum EntryKind {
+    File,
+    Dir,
+    Symlink(String),
+}
+
+struct Entry {
+    path: String,
+    kind: EntryKind,
+    mode: u32,
+    contents: Vec<u8>,
+}
+
+/*
+ * Writes one entry in the ustar format, with a pax header before it if the path or link is too long to fit.
+ */
+fn write_entry(
+    writer: &mut dyn Write,
+    path: &str,
+    kind: EntryKind,
+    mode: u32,
+    mtime: u64,
+    contents: &[u8],
+) -> io::Result<()> {
+    let (type_flag, link) = match kind {
+        EntryKind::File => (b'0', String::new()),
+        EntryKind::Dir => (b'5', String::new()),
+        EntryKind::Symlink(ref target) => (b'2', target.to_owned()),
+    };
+
+    let mut extended = Vec::new();
+    if contents.len() as u64 > MAX_HEADER_NUMBER {
+        extended.extend(pax_record("size", &contents.len().to_string()));
+    }
+    if path.len() > 100 || !path.is_ascii() {
+        extended.extend(pax_record("path", path));
+    }
+    if link.len() > 100 || !link.is_ascii() {
+        extended.extend(pax_record("linkpath", &link));
+    }
+    if !extended.is_empty() {
+        let header = header(
+            "././@PaxHeader",
+            b'x',
+            0o644,
+            extended.len() as u64,
+            mtime,
+            "",
+        );
+        writer.write_all(&header)?;
+        write_padded(writer, &extended)?;
+    }
+
+    let header = header(
+        &truncate(path, 100),
+        type_flag,
+        mode,
+        contents.len() as u64,
+        mtime,
+        &truncate(&link, 100),
+    );
+    writer.write_all(&header)?;
+    write_padded(writer, contents)
+}
+
+/*
+ * Reads the next entry, with any pax header applied to it. Gives None at the end of the archive.
+ */
+fn read_entry(reader: &mut dyn Read) -> io::Result<Option<Entry>> {
+    let mut path_override = None;
+    let mut link_override = None;
+    let mut size_override = None;
+
+    loop {
+        let mut header = [0u8; BLOCK_SIZE];
+        if let Err(e) = reader.read_exact(&mut header) {
+            if e.kind() == io::ErrorKind::UnexpectedEof {
+                return Ok(None);
+            }
+            return Err(e);
+        }
+        if header.iter().all(|b| *b == 0) {
+            return Ok(None);
+        }
+
+        let size = match size_override.take() {
+            Some(s) => s,
+            None => parse_octal(&header[124..136])? as usize,
+        };
+        let mut contents = vec![0u8; size];
+        reader.read_exact(&mut contents)?;
+        let padding = (BLOCK_SIZE - size % BLOCK_SIZE) % BLOCK_SIZE;
+        reader.read_exact(&mut vec![0u8; padding])?;
+
+        let type_flag = header[156];
+        match type_flag {
+            b'x' => {
+                for (key, value) in parse_pax(&contents) {
+                    match key.as_str() {
+                        "path" => path_override = Some(value),
+                        "linkpath" => link_override = Some(value),
+                        "size" => size_override = value.parse().ok(),
+                        _ => (),
+                    }
+                }
+                continue;
+            }
+            b'0' | 0 | b'5' | b'2' => (),
+            // Anything else, like global headers and device files, doesn't belong in a project
+            _ => continue,
+        }
+
+        let prefix = field(&header[345..500]);
+        let name = field(&header[0..100]);
+        let path = match path_override.take() {
+            Some(p) => p,
+            None if prefix.is_empty() => name,
+            None => format!("{}/{}", prefix, name),
+        };
+        let link = link_override
+            .take()
+            .unwrap_or_else(|| field(&header[157..257]));
+
+        let kind = match type_flag {
+            b'5' => EntryKind::Dir,
+            b'2' => EntryKind::Symlink(link),
+            _ if path.ends_with('/') => EntryKind::Dir,
+            _ => EntryKind::File,
+        };
+
+        return Ok(Some(Entry {
+            path,
+            kind,
+            mode: parse_octal(&header[100..108])? as u32,
+            contents,
+        }));
+    }
+}
+
+/*
+ * Builds a ustar header block.
+ */
+fn header(
+    path: &str,
+    type_flag: u8,
+    mode: u32,
+    size: u64,
+    mtime: u64,
+    link: &str,
+) -> [u8; BLOCK_SIZE] {
+    let mut header = [0u8; BLOCK_SIZE];
+
+    header[..path.len()].copy_from_slice(path.as_bytes());
+    octal(&mut header[100..108], u64::from(mode & 0o7777));
+    octal(&mut header[108..116], 0);
+    octal(&mut header[116..124], 0);
+    // Sizes that don't fit are given in the pax header, which is only needed for files over 8GB
+    octal(&mut header[124..136], size.min(MAX_HEADER_NUMBER));
+    octal(&mut header[136..148], mtime.min(MAX_HEADER_NUMBER));
+    header[156] = type_flag;
+    header[157..157 + link.len()].copy_from_slice(link.as_bytes());
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    // The checksum is worked out with its own field set to spaces
+    header[148..156].copy_from_slice(b"        ");
+    let checksum: u32 = header.iter().map(|b| u32::from(*b)).sum();
+    header[148..155].copy_from_slice(format!("{:06o}\0", checksum).as_bytes());
+
+    header
+}
+
+/*
+ * Builds one `length key=value` record of a pax header, where the length includes itself.
+ */
+fn pax_record(key: &str, value: &str) -> Vec<u8> {
+    let rest = format!(" {}={}\n", key, value);
+
+    let mut length = rest.len() + 1;
+    while (length.to_string().len() + rest.len()) != length {
+        length = length.to_string().len() + rest.len();
+    }
+
+    format!("{}{}", length, rest).into_bytes()
+}
+
+fn parse_pax(contents: &[u8]) -> Vec<(String, String)> {
+    let text = String::from_utf8_lossy(contents);
+
+    text.lines()
+        .filter_map(|l| {
+            let record = &l[l.find(' ')? + 1..];
+            let i = record.find('=')?;
+            Some((record[..i].to_string(), record[i + 1..].to_string()))
+        })
+        .collect()
+}
+
+fn octal(dest: &mut [u8], value: u64) {
+    let digits = format!("{:0width$o}\0", value, width = dest.len() - 1);
+    dest.copy_from_slice(digits.as_bytes());
+}
+
+fn parse_octal(field: &[u8]) -> io::Result<u64> {
+    let text = String::from_utf8_lossy(field);
+    let text = text.trim_matches(|c: char| c == '\0' || c == ' ');
+    if text.is_empty() {
+        return Ok(0);
+    }
+
+    u64::from_str_radix(text, 8).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn field(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+
+    String::from_utf8_lossy(&bytes[..end]).to_string()
+}
+
+fn truncate(text: &str, length: usize) -> String {
+    if text.len() <= length && text.is_ascii() {
+        return text.to_string();
+    }
+
+    // The real value is in the pax header, so this only has to be readable by tools that don't know about it
+    text.chars().filter(|c| c.is_ascii()).take(length).collect()
+}
+
+fn write_padded(writer: &mut dyn Write, contents: &[u8]) -> io::Result<()> {
+    writer.write_all(contents)?;
+
+    let padding = (BLOCK_SIZE - contents.len() % BLOCK_SIZE) % BLOCK_SIZE;
+    writer.write_all(&vec![0u8; padding])
+}
+
+/*
+ * The path of an entry in the project, with forward slashes so that archives are the same on every platform.
+ */
+fn relative_name(project_dir: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(project_dir).unwrap_or(path);
+
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+
+    parts.join("/")
+}
+
+#[cfg(unix)]
+fn mode(metadata: &fs::Metadata, _default: u32) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.permissions().mode() & 0o7777
+}
+
+#[cfg(not(unix))]
+fn mode(_metadata: &fs::Metadata, default: u32) -> u32 {
+    default
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let _ = fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o777));
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) {}
+
+/*
+ * Whether a link's target stays inside the project once it's resolved from the directory the link is in. Targets
+ * are resolved by their path alone, so one that goes through another link and then on past it is refused, since
+ * where it would end up depends on that other link.
+ */
+fn link_stays_inside(path: &Path, target: &str, links: &[PathBuf]) -> bool {
+    let mut resolved = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+    let mut parts = Path::new(target).components().peekable();
+    while let Some(part) = parts.next() {
+        match part {
+            Component::Normal(name) => resolved.push(name),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !resolved.pop() {
+                    return false;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return false,
+        }
+
+        if parts.peek().is_some() && links.contains(&resolved) {
+            return false;
+        }
+    }
+
+    true
+}
+
+#[cfg(unix)]
+fn make_link(dest: &Path, target: &str) -> io::Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    std::os::unix::fs::symlink(target, dest)
+}
+
+#[cfg(windows)]
+fn make_link(dest: &Path, target: &str) -> io::Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let target = std::path::PathBuf::from(target.replace('/', "\\"));
+    if dest
+        .parent()
+        .map(|p| p.join(&target).is_dir())
+        .unwrap_or(false)
+    {
+        std::os::windows::fs::symlink_dir(target, dest)
+    } else {
+        std::os::windows::fs::symlink_file(target, dest)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn make_link(_dest: &Path, _target: &str) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "links can't be made on this platform",
+    ))
+}
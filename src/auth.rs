@@ -0,0 +1,199 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use super::git_sr::GitCredentials;
+
+/// The environment variable holding a personal access token to use for every HTTPS remote.
+pub const TOKEN_VAR: &str = "SR_GIT_TOKEN";
+
+/// The name of the file in the user's configuration directory that holds personal access tokens for specific
+/// hosts, one `host token` pair per line. Blank lines and lines starting with `#` are skipped.
+pub const TOKENS_FILE: &str = "tokens";
+
+type TokenProvider = Box<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+fn provider() -> &'static Mutex<Option<TokenProvider>> {
+    static PROVIDER: OnceLock<Mutex<Option<TokenProvider>>> = OnceLock::new();
+
+    PROVIDER.get_or_init(|| Mutex::new(None))
+}
+
+/// Sets a function that is asked for a personal access token whenever git needs to talk to an HTTPS remote,
+/// which lets applications get tokens from their own settings or a login flow. The function is given the
+/// host (i.e. `github.com`) and returns `None` to fall back to the other places tokens are looked for.
+/// Passing `None` removes the function.
+///
+/// # Examples
+///
+/// ```
+/// sliderule::auth::set_token_provider(Some(Box::new(|host: &str| {
+///     if host == "git.example.com" {
+///         Some(String::from("glpat-example"))
+///     } else {
+///         None
+///     }
+/// })));
+///
+/// let credentials = sliderule::auth::credentials_for_url("https://git.example.com/user/part").unwrap();
+/// assert_eq!(credentials.password, "glpat-example");
+///
+/// sliderule::auth::set_token_provider(None);
+/// ```
+pub fn set_token_provider(token_provider: Option<TokenProvider>) {
+    let mut guard = match provider().lock() {
+        Ok(g) => g,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    *guard = token_provider;
+}
+
+/// Finds the personal access token to use for a URL, looking in these places in order:
+///
+/// 1. The function given to `set_token_provider`
+/// 2. The `SR_GIT_TOKEN` environment variable
+/// 3. `GITHUB_TOKEN` or `GH_TOKEN` for github.com, and `GITLAB_TOKEN` for gitlab.com
+/// 4. The `tokens` file in the user's Sliderule configuration directory
+///
+/// Only HTTPS URLs use tokens, so anything else gives `None`. In CI mode only the environment variables are
+/// checked.
+pub fn token_for_url(url: &str) -> Option<String> {
+    let host = https_host(url)?;
+    let ci_mode = super::ci::ci_mode();
+
+    let from_provider = if ci_mode {
+        None
+    } else {
+        let guard = match provider().lock() {
+            Ok(g) => g,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        guard.as_ref().and_then(|p| p(&host))
+    };
+    if from_provider.is_some() {
+        return from_provider;
+    }
+
+    let mut vars = vec![TOKEN_VAR];
+    if host == "github.com" {
+        vars.push("GITHUB_TOKEN");
+        vars.push("GH_TOKEN");
+    } else if host == "gitlab.com" {
+        vars.push("GITLAB_TOKEN");
+    }
+    for var in vars {
+        if let Ok(token) = ::std::env::var(var) {
+            if !token.trim().is_empty() {
+                return Some(token.trim().to_string());
+            }
+        }
+    }
+
+    // Pipelines only get their secrets from the environment
+    if ci_mode {
+        return None;
+    }
+
+    let contents = fs::read_to_string(config_dir()?.join(TOKENS_FILE)).ok()?;
+    find_token(&contents, &host)
+}
+
+/// Turns the personal access token for a URL, if there is one, into credentials that git can use. The hosting
+/// providers ignore the username when a token is given, but GitLab wants it to be `oauth2`, so that is used for
+/// hosts with GitLab in their name and `x-access-token` for everything else.
+pub fn credentials_for_url(url: &str) -> Option<GitCredentials> {
+    let token = token_for_url(url)?;
+    let host = https_host(url)?;
+
+    let username = if host.contains("gitlab") {
+        "oauth2"
+    } else {
+        "x-access-token"
+    };
+
+    Some(GitCredentials {
+        username: username.to_string(),
+        password: token,
+    })
+}
+
+/// The directory that holds the user's Sliderule configuration, which is `sliderule` inside of
+/// `XDG_CONFIG_HOME` (or `~/.config`) on Linux and macOS, and inside of `APPDATA` on Windows.
+pub fn config_dir() -> Option<PathBuf> {
+    if let Ok(dir) = ::std::env::var("XDG_CONFIG_HOME") {
+        if !dir.is_empty() {
+            return Some(PathBuf::from(dir).join("sliderule"));
+        }
+    }
+
+    if cfg!(windows) {
+        if let Ok(dir) = ::std::env::var("APPDATA") {
+            return Some(PathBuf::from(dir).join("sliderule"));
+        }
+    }
+
+    match ::std::env::var("HOME") {
+        Ok(dir) => Some(PathBuf::from(dir).join(".config").join("sliderule")),
+        Err(_) => None,
+    }
+}
+
+/// Compares a token that was given with the one that is expected, without stopping at the first difference so
+/// that how long it takes doesn't give the expected token away.
+///
+/// This function is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn tokens_match(given: &str, expected: &str) -> bool {
+    let given = given.as_bytes();
+    let expected = expected.as_bytes();
+
+    let mut difference = given.len() ^ expected.len();
+    for (i, b) in expected.iter().enumerate() {
+        difference |= usize::from(b ^ given.get(i).cloned().unwrap_or(0));
+    }
+
+    difference == 0
+}
+
+/*
+ * Gets the lowercase host (and port, if there is one) out of an HTTPS URL.
+ */
+fn https_host(url: &str) -> Option<String> {
+    let rest = url.strip_prefix("https://")?;
+
+    let authority = match rest.find('/') {
+        Some(i) => &rest[..i],
+        None => rest,
+    };
+    let host = match authority.rfind('@') {
+        Some(i) => &authority[i + 1..],
+        None => authority,
+    };
+
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_lowercase())
+    }
+}
+
+/*
+ * Looks a host up in the contents of a tokens file.
+ */
+fn find_token(contents: &str, host: &str) -> Option<String> {
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        if let (Some(line_host), Some(token)) = (parts.next(), parts.next()) {
+            if line_host.to_lowercase() == host {
+                return Some(token.to_string());
+            }
+        }
+    }
+
+    None
+}
@@ -0,0 +1,902 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+
+/// The file operations that the parts of Sliderule which only deal with metadata need. Implementing this lets
+/// that logic (reading the configuration, licenses and package.json values, amalgamating licenses and validating
+/// a component's structure) work on something other than the local file system, like files held in memory by a
+/// browser-based editor. The functions that take a path on their own use `StdFileSystem`.
+pub trait FileSystem {
+    /// Reads a whole file as UTF-8 text.
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+
+    /// Creates or replaces a file, creating any directories it goes in.
+    fn write(&self, path: &Path, contents: &str) -> io::Result<()>;
+
+    /// Returns true if the path is a file.
+    fn is_file(&self, path: &Path) -> bool;
+
+    /// Returns true if the path is a directory.
+    fn is_dir(&self, path: &Path) -> bool;
+
+    /// Lists the entries directly inside a directory, sorted by name.
+    fn list_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// Returns true if the path is a file or a directory.
+    fn exists(&self, path: &Path) -> bool {
+        self.is_file(path) || self.is_dir(path)
+    }
+
+    /// Resolves a path to the one it's stored under, so that relative paths like `.` have ancestors.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        Ok(path.to_path_buf())
+    }
+}
+
+/// The local file system, which is what the rest of Sliderule works with.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdFileSystem;
+
+impl FileSystem for StdFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn write(&self, path: &Path, contents: &str) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+
+        fs::write(path, contents)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn list_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(path)? {
+            entries.push(entry?.path());
+        }
+        entries.sort();
+
+        Ok(entries)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::canonicalize(path)
+    }
+}
+
+/// A file system that only exists in memory. Directories are created as files are written into them, and paths
+/// are compared after `.` and `..` have been resolved, so `a/./b` and `a/b` are the same file.
+///
+/// # Examples
+///
+/// ```
+/// use sliderule::backend::FileSystem;
+/// use std::path::Path;
+///
+/// let memory = sliderule::backend::MemoryFileSystem::new();
+/// memory.write(Path::new("/part/.sr"), "source_license: MIT,\ndocumentation_license: CC-BY-4.0\n").unwrap();
+///
+/// assert!(memory.is_dir(Path::new("/part")));
+/// assert_eq!(
+///     sliderule::backend::read_licenses(&memory, Path::new("/part")),
+///     (String::from("MIT"), String::from("CC-BY-4.0"))
+/// );
+/// ```
+#[derive(Debug, Default)]
+pub struct MemoryFileSystem {
+    files: Mutex<BTreeMap<PathBuf, String>>,
+    dirs: Mutex<BTreeSet<PathBuf>>,
+}
+
+impl MemoryFileSystem {
+    /// Creates an empty file system.
+    pub fn new() -> MemoryFileSystem {
+        MemoryFileSystem::default()
+    }
+
+    /// Creates a directory, along with any of its parents that don't exist yet.
+    pub fn create_dir_all(&self, path: &Path) {
+        let mut dirs = lock(&self.dirs);
+
+        for ancestor in clean_path(path).ancestors() {
+            if !ancestor.as_os_str().is_empty() {
+                dirs.insert(ancestor.to_path_buf());
+            }
+        }
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        match lock(&self.files).get(&clean_path(path)) {
+            Some(contents) => Ok(contents.to_owned()),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} does not exist", path.display()),
+            )),
+        }
+    }
+
+    fn write(&self, path: &Path, contents: &str) -> io::Result<()> {
+        let path = clean_path(path);
+        if self.is_dir(&path) {
+            return Err(io::Error::other(format!(
+                "{} is a directory",
+                path.display()
+            )));
+        }
+
+        if let Some(parent) = path.parent() {
+            self.create_dir_all(parent);
+        }
+        lock(&self.files).insert(path, contents.to_string());
+
+        Ok(())
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        lock(&self.files).contains_key(&clean_path(path))
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        lock(&self.dirs).contains(&clean_path(path))
+    }
+
+    fn list_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let path = clean_path(path);
+        if !self.is_dir(&path) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not a directory", path.display()),
+            ));
+        }
+
+        let mut entries: Vec<PathBuf> = lock(&self.files)
+            .keys()
+            .chain(lock(&self.dirs).iter())
+            .filter(|p| p.parent() == Some(path.as_path()))
+            .cloned()
+            .collect();
+        entries.sort();
+        entries.dedup();
+
+        Ok(entries)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        Ok(clean_path(path))
+    }
+}
+
+/// The output status of an operation whose tool was stopped by a signal before it could exit, i.e. because it
+/// was killed for using too much memory. The signal's number is the output's `wrapped_status`, see `record_exit`.
+pub const SIGNAL_STATUS: i32 = 499;
+
+/// How a tool that was run finished.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToolStatus {
+    /// The exit code, or `None` if the tool was stopped by a signal
+    pub code: Option<i32>,
+    /// The number of the signal that stopped the tool, which is only ever set on Unix
+    pub signal: Option<i32>,
+}
+
+impl ToolStatus {
+    /// Returns true if the tool exited with a code of 0.
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+
+    /// The exit code, or `None` if the tool was stopped by a signal.
+    pub fn code(&self) -> Option<i32> {
+        self.code
+    }
+
+    /// The number of the signal that stopped the tool, or `None` if it exited.
+    pub fn signal(&self) -> Option<i32> {
+        self.signal
+    }
+}
+
+/// What a tool printed, along with how it finished.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolOutput {
+    pub status: ToolStatus,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+/// A tool, like git or npm, that Sliderule wants to run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    /// The directory to run the tool in, or `None` for the current one
+    pub current_dir: Option<PathBuf>,
+    /// Environment variables to set, or to remove when the value is `None`
+    pub envs: Vec<(String, Option<String>)>,
+    /// Anything that should be written to the tool's standard input
+    pub input: Option<Vec<u8>>,
+}
+
+/// Runs the tools that Sliderule wraps. Setting a runner with `set_command_runner` sends every git, npm, curl and
+/// ssh invocation to it instead of starting a process, so that they can be stubbed out or sent to a server where
+/// processes can't be started, like in a browser.
+pub trait CommandRunner: Send + Sync {
+    /// Runs a tool and waits for it to finish.
+    fn run(&self, command: &ToolCommand) -> io::Result<ToolOutput>;
+}
+
+/// A runner for places where no tools are available. Everything it's asked to run fails with
+/// `io::ErrorKind::Unsupported`, which the wrappers report like any other failure to start a tool.
+///
+/// # Examples
+///
+/// ```
+/// sliderule::backend::set_command_runner(Some(Box::new(sliderule::backend::UnavailableRunner)));
+///
+/// let output = sliderule::git_sr::git_version();
+/// assert_eq!(output.status, 113);
+///
+/// sliderule::backend::set_command_runner(None);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnavailableRunner;
+
+impl CommandRunner for UnavailableRunner {
+    fn run(&self, command: &ToolCommand) -> io::Result<ToolOutput> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} can't be run here", command.program),
+        ))
+    }
+}
+
+fn runner() -> &'static Mutex<Option<Box<dyn CommandRunner>>> {
+    static RUNNER: OnceLock<Mutex<Option<Box<dyn CommandRunner>>>> = OnceLock::new();
+
+    RUNNER.get_or_init(|| Mutex::new(default_runner()))
+}
+
+/*
+ * Processes can't be started on wasm32, so tools are never run there unless a runner is set.
+ */
+fn default_runner() -> Option<Box<dyn CommandRunner>> {
+    if cfg!(target_arch = "wasm32") {
+        Some(Box::new(UnavailableRunner))
+    } else {
+        None
+    }
+}
+
+/// Sets the runner that every tool is run with for the rest of the process. Passing `None` goes back to starting
+/// processes on this machine, or to `UnavailableRunner` on wasm32.
+pub fn set_command_runner(command_runner: Option<Box<dyn CommandRunner>>) {
+    *lock(runner()) = command_runner.or_else(default_runner);
+}
+
+/// The environment variables that tools aren't given, unless the configuration lists them in `pass_env` (see
+/// `config::ProjectConfig`) or the wrapper sets them itself. The git ones are set for whatever is calling
+/// Sliderule, i.e. by git while it runs a hook, and would make git work on that repository instead of the
+/// component's. The npm ones would send npm through a proxy that was set up for something else, so a proxy for npm
+/// is set with `npm_proxy` instead.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// // Left over from the program that is calling Sliderule
+/// std::env::set_var("GIT_DIR", temp_dir.join("elsewhere.git"));
+///
+/// let output = sliderule::git_sr::git_init(&temp_dir.join("toplevel"), "");
+///
+/// assert_eq!(0, output.status);
+/// assert!(temp_dir.join("toplevel").join(".git").exists());
+/// assert!(!temp_dir.join("elsewhere.git").exists());
+/// ```
+pub const SANITIZED_ENV: [&str; 14] = [
+    "GIT_DIR",
+    "GIT_WORK_TREE",
+    "GIT_INDEX_FILE",
+    "GIT_OBJECT_DIRECTORY",
+    "GIT_ALTERNATE_OBJECT_DIRECTORIES",
+    "GIT_COMMON_DIR",
+    "GIT_NAMESPACE",
+    "GIT_PREFIX",
+    "npm_config_proxy",
+    "npm_config_https_proxy",
+    "npm_config_noproxy",
+    "NPM_CONFIG_PROXY",
+    "NPM_CONFIG_HTTPS_PROXY",
+    "NPM_CONFIG_NOPROXY",
+];
+
+/// Removes the variables in `SANITIZED_ENV` from the environment that a tool is run with, other than the ones in
+/// the configuration's `pass_env` and the ones that have already been set on the command. `run` does this for
+/// every tool.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn sanitize_env(command: &mut Command, config: &super::config::ProjectConfig) {
+    for var in SANITIZED_ENV.iter() {
+        let passed = config.pass_env.iter().any(|p| p == var);
+        let is_set = command.get_envs().any(|(k, _)| k == *var);
+        if !passed && !is_set {
+            command.env_remove(var);
+        }
+    }
+}
+
+/// Runs a tool that has been set up as a `Command`, with the runner from `set_command_runner` if there is one.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn run(command: &mut Command) -> io::Result<ToolOutput> {
+    configure(command)?;
+    report_start(command);
+    if super::ci::ci_mode() {
+        super::ci::prepare_command(command);
+    }
+
+    if let Some(ref r) = *lock(runner()) {
+        return r.run(&to_tool_command(command, None));
+    }
+
+    let output = command.output()?;
+
+    Ok(ToolOutput {
+        status: tool_status(output.status),
+        stdout: output.stdout,
+        stderr: output.stderr,
+    })
+}
+
+/// Runs a tool like `run` does, writing `input` to its standard input. This is how secrets are handed to tools,
+/// since anything on the command line shows up in the system's process list.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn run_with_input(command: &mut Command, input: &[u8]) -> io::Result<ToolOutput> {
+    configure(command)?;
+    report_start(command);
+    if super::ci::ci_mode() {
+        super::ci::prepare_command(command);
+    }
+
+    if let Some(ref r) = *lock(runner()) {
+        return r.run(&to_tool_command(command, Some(input)));
+    }
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input)?;
+    }
+
+    let output = child.wait_with_output()?;
+
+    Ok(ToolOutput {
+        status: tool_status(output.status),
+        stdout: output.stdout,
+        stderr: output.stderr,
+    })
+}
+
+/// Runs a tool like `run` does, passing the progress lines that git writes to standard error along to the progress
+/// callback as they come in (see `progress::parse_transfer`). This is for git commands that are run with
+/// `--progress`, so that a download which is still going can be told apart from one that has stopped. The
+/// progress lines are left out of the tool's `stderr`, and everything else that it writes there is kept.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(unix)]
+/// # {
+/// use std::sync::{Arc, Mutex};
+///
+/// let seen = Arc::new(Mutex::new(Vec::new()));
+/// let recorder = seen.clone();
+/// sliderule::progress::set_progress_callback(Some(Box::new(move |p| {
+///     if let Some(ref t) = p.transfer {
+///         recorder.lock().unwrap().push((t.objects, t.total_objects));
+///     }
+/// })));
+///
+/// // Git rewrites its progress line in place as objects come in
+/// let mut command = std::process::Command::new("sh");
+/// command.args(["-c", "printf 'Receiving objects:  50%% (1/2)\\rReceiving objects: 100%% (2/2), done.\\nwarning: slow server\\n' >&2"]);
+/// let output = sliderule::backend::run_with_progress(&mut command).unwrap();
+///
+/// sliderule::progress::set_progress_callback(None);
+///
+/// assert_eq!(vec![(1, Some(2)), (2, Some(2))], *seen.lock().unwrap());
+/// assert_eq!("warning: slow server\n", String::from_utf8_lossy(&output.stderr));
+/// # }
+/// ```
+pub fn run_with_progress(command: &mut Command) -> io::Result<ToolOutput> {
+    configure(command)?;
+    report_start(command);
+    if super::ci::ci_mode() {
+        super::ci::prepare_command(command);
+    }
+
+    if let Some(ref r) = *lock(runner()) {
+        let mut output = r.run(&to_tool_command(command, None))?;
+        output.stderr = forward_progress(&output.stderr[..])?;
+        return Ok(output);
+    }
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Standard output is read on its own so that a tool which fills it can't hold up its progress lines
+    let stdout_pipe = child.stdout.take();
+    let stdout_reader = std::thread::spawn(move || {
+        let mut stdout = Vec::new();
+        if let Some(mut pipe) = stdout_pipe {
+            let _ = pipe.read_to_end(&mut stdout);
+        }
+        stdout
+    });
+
+    let stderr = match child.stderr.take() {
+        Some(pipe) => forward_progress(pipe),
+        None => Ok(Vec::new()),
+    };
+    let stdout = stdout_reader.join().unwrap_or_default();
+    let status = child.wait()?;
+
+    Ok(ToolOutput {
+        status: tool_status(status),
+        stdout,
+        stderr: stderr?,
+    })
+}
+
+/// Puts how a tool finished into the output of the operation that ran it. A non-zero exit code becomes the
+/// output's `wrapped_status`. A tool that was stopped by a signal doesn't have an exit code, so the output gets a
+/// status of `SIGNAL_STATUS` instead, unless it already has one, along with the signal's number as its
+/// `wrapped_status` and an error that says which tool it was.
+///
+/// # Examples
+///
+/// ```
+/// let mut output = sliderule::SROutput {
+///     status: 0,
+///     wrapped_status: 0,
+///     stdout: Vec::new(),
+///     stderr: Vec::new(),
+/// };
+///
+/// let status = sliderule::backend::ToolStatus { code: None, signal: Some(9) };
+/// sliderule::backend::record_exit(&mut output, "git", &status);
+///
+/// assert_eq!(output.status, sliderule::backend::SIGNAL_STATUS);
+/// assert_eq!(output.wrapped_status, 9);
+/// ```
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn record_exit(output: &mut super::SROutput, program: &str, status: &ToolStatus) {
+    match (status.code, status.signal) {
+        (Some(0), _) => {}
+        (Some(code), _) => output.wrapped_status = code,
+        (None, signal) => {
+            if output.status == 0 {
+                output.status = SIGNAL_STATUS;
+            }
+            output.wrapped_status = signal.unwrap_or(-1);
+            match signal {
+                Some(s) => output
+                    .stderr
+                    .push(format!("ERROR: {} was stopped by signal {}.", program, s)),
+                None => output.stderr.push(format!(
+                    "ERROR: {} was stopped before it could finish.",
+                    program
+                )),
+            }
+        }
+    }
+}
+
+/// Reads the source and documentation licenses from a component's .sr file, falling back to the default licenses
+/// in the configuration on the same file system if there is no .sr file. `get_licenses` does this for the local
+/// file system.
+pub fn read_licenses(file_system: &dyn FileSystem, component_dir: &Path) -> (String, String) {
+    match file_system.read_to_string(&component_dir.join(".sr")) {
+        Ok(contents) => (
+            super::yaml_value(&contents, "source_license"),
+            super::yaml_value(&contents, "documentation_license"),
+        ),
+        Err(_) => {
+            let config = super::config::config_or_default_in(file_system, component_dir);
+            (config.default_source_license, config.default_doc_license)
+        }
+    }
+}
+
+/// Lists the licenses of a component and every component inside of it, as (directory, source license,
+/// documentation license) entries with the component itself first.
+pub fn list_licenses(
+    file_system: &dyn FileSystem,
+    component_dir: &Path,
+) -> Vec<(PathBuf, String, String)> {
+    let mut licenses = Vec::new();
+
+    let mut dirs = vec![component_dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        if file_system.is_file(&dir.join(".sr")) {
+            let (source_license, doc_license) = read_licenses(file_system, &dir);
+            licenses.push((dir.to_owned(), source_license, doc_license));
+        }
+
+        // Push in reverse so that the components come out in name order
+        for sub_dir in &["node_modules", "components"] {
+            let mut children = file_system.list_dir(&dir.join(sub_dir)).unwrap_or_default();
+            children.retain(|c| file_system.is_dir(c));
+            dirs.extend(children.into_iter().rev());
+        }
+    }
+
+    licenses
+}
+
+/// Works out the license expression that the license amalgamation of the other operations writes to a component's
+/// package.json, from the licenses of the component, every component inside of it and the files in their
+/// `file_licenses::LICENSES_FILE`s.
+///
+/// # Examples
+///
+/// ```
+/// use sliderule::backend::FileSystem;
+/// use std::path::Path;
+///
+/// let memory = sliderule::backend::MemoryFileSystem::new();
+/// memory.write(Path::new("/part/.sr"), "source_license: MIT,\ndocumentation_license: CC-BY-4.0\n").unwrap();
+/// memory.write(Path::new("/part/components/gear/.sr"), "source_license: Unlicense,\ndocumentation_license: CC-BY-4.0\n").unwrap();
+///
+/// assert_eq!(
+///     sliderule::backend::amalgamated_license(&memory, Path::new("/part")),
+///     "(MIT AND Unlicense AND CC-BY-4.0)"
+/// );
+/// ```
+pub fn amalgamated_license(file_system: &dyn FileSystem, component_dir: &Path) -> String {
+    let (own, others): (Vec<_>, Vec<_>) = list_licenses(file_system, component_dir)
+        .into_iter()
+        .partition(|l| l.0 == component_dir);
+
+    let own_files = super::annotated_licenses_in(file_system, component_dir);
+    let other_files = others
+        .iter()
+        .flat_map(|l| super::annotated_licenses_in(file_system, &l.0))
+        .collect();
+
+    super::license_expression(
+        own.into_iter().map(|l| (l.1, l.2)).collect(),
+        others.into_iter().map(|l| (l.1, l.2)).collect(),
+        own_files,
+        other_files,
+    )
+}
+
+/// Checks that a component has all of the directories and files that Sliderule expects, like
+/// `health::validate_component` does for the local file system. Permissions aren't checked, since not every
+/// file system has them.
+///
+/// The output status is 23 if the component is missing any part of its structure.
+pub fn validate_component(file_system: &dyn FileSystem, component_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    for problem in super::health::structure_problems_in(file_system, component_dir) {
+        output.status = 23;
+        output.stderr.push(format!("ERROR: {}", problem));
+    }
+
+    if output.status == 0 {
+        output
+            .stdout
+            .push(String::from("Component structure is valid."));
+    }
+
+    output
+}
+
+/*
+ * Gets the guarded value even if another thread panicked while holding the lock.
+ */
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(g) => g,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/*
+ * Resolves the `.` and `..` parts of a path without touching any file system.
+ */
+fn clean_path(path: &Path) -> PathBuf {
+    let mut clean = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                clean.pop();
+            }
+            c => clean.push(c.as_os_str()),
+        }
+    }
+
+    clean
+}
+
+/*
+ * Applies the tool settings from the configuration of the project that a tool is being run in, keeps tools
+ * from contacting remotes in offline mode, and keeps the caller's environment from changing what they work on.
+ */
+fn configure(command: &mut Command) -> io::Result<()> {
+    let dir = command
+        .get_current_dir()
+        .map(|d| d.to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("."));
+    let config = super::config::config_or_default(&dir);
+    let program = command.get_program().to_string_lossy().to_string();
+
+    if config.offline {
+        let needs_network = match program.as_str() {
+            "git" => {
+                // Skip over options like -c name=value to get to the subcommand
+                let mut args = command.get_args().map(|a| a.to_string_lossy().to_string());
+                let mut subcommand = None;
+                while let Some(arg) = args.next() {
+                    if arg == "-c" || arg == "-C" {
+                        args.next();
+                    } else if !arg.starts_with('-') {
+                        subcommand = Some(arg);
+                        break;
+                    }
+                }
+
+                match subcommand {
+                    Some(s) => {
+                        ["clone", "fetch", "pull", "push", "ls-remote"].contains(&s.as_str())
+                    }
+                    None => false,
+                }
+            }
+            "curl" | "ssh" | "scp" => true,
+            _ => false,
+        };
+
+        if needs_network {
+            return Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                format!("offline mode is on, so {} can't contact a remote", program),
+            ));
+        }
+    }
+
+    sanitize_env(command, &config);
+
+    // npm can be configured to be somewhere else, or to be npm-cli.js run by node
+    let stem = Path::new(&program)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let is_npm = stem == "npm"
+        || (stem == "node"
+            && command
+                .get_args()
+                .next()
+                .map(|a| a.to_string_lossy().ends_with("npm-cli.js"))
+                == Some(true));
+    if is_npm {
+        if config.offline {
+            command.env("npm_config_offline", "true");
+        }
+        if let Some(cache) = config.npm_cache {
+            command.env("npm_config_cache", cache);
+        }
+        if let Some(ref proxy) = config.npm_proxy {
+            command
+                .env("npm_config_proxy", proxy)
+                .env("npm_config_https_proxy", proxy);
+        }
+    }
+
+    if program == "git" && config.git_bin != "git" {
+        let mut replacement = Command::new(&config.git_bin);
+        replacement.args(command.get_args());
+        for (key, value) in command.get_envs() {
+            match value {
+                Some(v) => replacement.env(key, v),
+                None => replacement.env_remove(key),
+            };
+        }
+        if let Some(d) = command.get_current_dir() {
+            replacement.current_dir(d);
+        }
+
+        *command = replacement;
+    }
+
+    // The version is only asked for the first time that each git or npm is run
+    let asks_version = command.get_args().last() == Some(std::ffi::OsStr::new("--version"));
+    if program == "git" && !asks_version {
+        check_version(command, "git", &[], &config.minimum_git_version)?;
+    } else if is_npm && !asks_version {
+        let script: Vec<std::ffi::OsString> = if stem == "node" {
+            command
+                .get_args()
+                .take(1)
+                .map(|a| a.to_os_string())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        check_version(command, "npm", &script, &config.minimum_npm_version)?;
+    }
+
+    Ok(())
+}
+
+/*
+ * Refuses to run a git or npm that is older than the configured minimum version. Tools that can't say what their
+ * version is are left to fail, or not, on their own.
+ */
+fn check_version(
+    command: &Command,
+    name: &str,
+    args: &[std::ffi::OsString],
+    minimum: &str,
+) -> io::Result<()> {
+    let version = match super::environment::tool_version(command.get_program(), args) {
+        Some(v) => v,
+        None => return Ok(()),
+    };
+
+    if super::environment::compare_versions(&version, minimum) == std::cmp::Ordering::Less {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "{} {} is installed, but at least version {} is required",
+                name, version, minimum
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/*
+ * How a process finished, with the signal that stopped it on the platforms that have them.
+ */
+fn tool_status(status: std::process::ExitStatus) -> ToolStatus {
+    #[cfg(unix)]
+    let signal = {
+        use std::os::unix::process::ExitStatusExt;
+        status.signal()
+    };
+
+    #[cfg(not(unix))]
+    let signal = None;
+
+    ToolStatus {
+        code: status.code(),
+        signal,
+    }
+}
+
+/*
+ * Lets the progress callback know that a tool is being started.
+ */
+fn report_start(command: &Command) {
+    let mut message = format!("Running {}", command.get_program().to_string_lossy());
+    for arg in command.get_args() {
+        message.push(' ');
+        message.push_str(&arg.to_string_lossy());
+    }
+
+    super::progress::report(&message);
+}
+
+/*
+ * Reads what a tool writes to standard error, reporting git's progress lines as they come in and returning
+ * everything else. Git ends each update of a progress line with a carriage return, and the last one with a
+ * newline.
+ */
+fn forward_progress<R: Read>(mut reader: R) -> io::Result<Vec<u8>> {
+    let mut kept = Vec::new();
+    let mut line = Vec::new();
+    let mut buffer = [0u8; 4096];
+
+    let handle_line = |line: &[u8], kept: &mut Vec<u8>| {
+        let text = String::from_utf8_lossy(line);
+        match super::progress::parse_transfer(&text) {
+            Some(transfer) => super::progress::report_transfer(text.trim(), transfer),
+            None if !text.trim().is_empty() => {
+                kept.extend_from_slice(line);
+                kept.push(b'\n');
+            }
+            None => {}
+        }
+    };
+
+    loop {
+        let count = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(c) => c,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        for byte in &buffer[..count] {
+            if *byte == b'\r' || *byte == b'\n' {
+                handle_line(&line, &mut kept);
+                line.clear();
+            } else {
+                line.push(*byte);
+            }
+        }
+    }
+    handle_line(&line, &mut kept);
+
+    Ok(kept)
+}
+
+/*
+ * Describes a Command so that it can be handed to a runner.
+ */
+fn to_tool_command(command: &Command, input: Option<&[u8]>) -> ToolCommand {
+    ToolCommand {
+        program: command.get_program().to_string_lossy().to_string(),
+        args: command
+            .get_args()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect(),
+        current_dir: command.get_current_dir().map(|d| d.to_path_buf()),
+        envs: command
+            .get_envs()
+            .map(|(k, v)| {
+                (
+                    k.to_string_lossy().to_string(),
+                    v.map(|v| v.to_string_lossy().to_string()),
+                )
+            })
+            .collect(),
+        input: input.map(|i| i.to_vec()),
+    }
+}
@@ -0,0 +1,392 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// The marker that starts a section of a README or docs page that `update_bom_docs` manages.
+pub const BOM_START: &str = "<!-- sliderule:bom:start -->";
+
+/// The marker that ends a section of a README or docs page that `update_bom_docs` manages.
+pub const BOM_END: &str = "<!-- sliderule:bom:end -->";
+
+/// One part in a component's `bom_data.yaml`, as read by `parse_bom`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BomItem {
+    /// The key of the part under `parts`
+    pub id: String,
+    pub name: String,
+    pub quantity: String,
+    pub quantity_units: String,
+    /// The entry in the part's `options` that `default_option` picks, which is empty if there isn't one
+    pub option: String,
+    pub notes: String,
+}
+
+/// The kinds of tables that `render_table` can write.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TableFormat {
+    Markdown,
+    Html,
+}
+
+/// Reads the parts out of the contents of a `bom_data.yaml` file, in the order of its `order` list. Parts that
+/// aren't in the list come after the ones that are, in the order they're in the file.
+///
+/// Only the layout of the `bom_data.yaml` template is understood, with one `key: value` per line.
+///
+/// # Examples
+///
+/// ```
+/// let items = sliderule::bom::parse_bom(
+///     "parts:\n  bolt:\n    options:\n    - M3x10\n    default_option: 0\n    quantity: 4\n    quantity_units: part\n    name: Bolt\n    notes: ''\n\norder:\n  - bolt\n",
+/// );
+///
+/// assert_eq!(items[0].name, "Bolt");
+/// assert_eq!(items[0].option, "M3x10");
+/// ```
+pub fn parse_bom(contents: &str) -> Vec<BomItem> {
+    let mut items: Vec<(BomItem, Vec<String>, usize)> = Vec::new();
+    let mut order: Vec<String> = Vec::new();
+    let mut section = String::new();
+    let mut in_options = false;
+
+    for line in contents.trim_start_matches('\u{feff}').lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+
+        if indent == 0 {
+            section = trimmed.trim_end_matches(':').to_string();
+            in_options = false;
+            continue;
+        }
+
+        // The template writes the order as `-component_1`, without a space
+        if section == "order" {
+            if let Some(id) = trimmed.strip_prefix('-') {
+                order.push(unquote(id));
+            }
+            continue;
+        }
+        if section != "parts" {
+            continue;
+        }
+
+        if let Some(option) = trimmed.strip_prefix('-') {
+            if in_options {
+                if let Some(item) = items.last_mut() {
+                    item.1.push(unquote(option));
+                }
+            }
+            continue;
+        }
+
+        let (key, value) = match trimmed.find(':') {
+            Some(i) => (trimmed[..i].trim(), unquote(&trimmed[i + 1..])),
+            None => continue,
+        };
+        in_options = false;
+
+        // A new part starts at the first level of indentation under parts
+        if indent <= 2 {
+            items.push((
+                BomItem {
+                    id: key.to_string(),
+                    name: key.to_string(),
+                    quantity: String::new(),
+                    quantity_units: String::new(),
+                    option: String::new(),
+                    notes: String::new(),
+                },
+                Vec::new(),
+                0,
+            ));
+            continue;
+        }
+
+        let item = match items.last_mut() {
+            Some(i) => i,
+            None => continue,
+        };
+        match key {
+            "name" => item.0.name = value,
+            "quantity" => item.0.quantity = value,
+            "quantity_units" => item.0.quantity_units = value,
+            "notes" => item.0.notes = value,
+            "default_option" => item.2 = value.parse().unwrap_or(0),
+            "options" => in_options = true,
+            _ => {}
+        }
+    }
+
+    let mut items: Vec<BomItem> = items
+        .into_iter()
+        .map(|(mut item, options, default_option)| {
+            item.option = options.get(default_option).cloned().unwrap_or_default();
+            item
+        })
+        .collect();
+
+    items.sort_by_key(|item| {
+        order
+            .iter()
+            .position(|id| id == &item.id)
+            .unwrap_or(order.len())
+    });
+
+    items
+}
+
+/// Reads the parts out of a component's `bom_data.yaml`, see `parse_bom`.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+pub fn read_bom(target_dir: &Path) -> Result<Vec<BomItem>, String> {
+    match fs::read_to_string(target_dir.join("bom_data.yaml")) {
+        Ok(contents) => Ok(parse_bom(&contents)),
+        Err(e) => Err(format!("ERROR: Unable to read bom_data.yaml: {}", e)),
+    }
+}
+
+/// Writes a table of parts, with a row for each part and its option, quantity and notes. Each line ends with `nl`,
+/// but the last one doesn't have one.
+///
+/// # Examples
+///
+/// ```
+/// let items = sliderule::bom::parse_bom("parts:\n  bolt:\n    quantity: 4\n    quantity_units: part\n    name: Bolt\n");
+///
+/// let table = sliderule::bom::render_table(&items, sliderule::bom::TableFormat::Markdown, "\n");
+///
+/// assert!(table.contains("| Bolt |  | 4 part |  |"));
+/// ```
+pub fn render_table(items: &[BomItem], format: TableFormat, nl: &str) -> String {
+    let rows: Vec<[String; 4]> = items
+        .iter()
+        .map(|item| {
+            [
+                item.name.clone(),
+                item.option.clone(),
+                format!("{} {}", item.quantity, item.quantity_units)
+                    .trim()
+                    .to_string(),
+                item.notes.clone(),
+            ]
+        })
+        .collect();
+
+    let lines: Vec<String> = match format {
+        TableFormat::Markdown => {
+            let mut lines = vec![
+                String::from("| Part | Option | Quantity | Notes |"),
+                String::from("| --- | --- | --- | --- |"),
+            ];
+            lines.extend(rows.iter().map(|row| {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|c| c.replace('|', "\\|").replace(['\r', '\n'], " "))
+                    .collect();
+                format!("| {} |", cells.join(" | "))
+            }));
+            lines
+        }
+        TableFormat::Html => {
+            let mut lines = vec![
+                String::from("<table>"),
+                String::from(
+                    "  <thead><tr><th>Part</th><th>Option</th><th>Quantity</th><th>Notes</th></tr></thead>",
+                ),
+                String::from("  <tbody>"),
+            ];
+            lines.extend(rows.iter().map(|row| {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|c| format!("<td>{}</td>", escape_html(c)))
+                    .collect();
+                format!("    <tr>{}</tr>", cells.concat())
+            }));
+            lines.extend([String::from("  </tbody>"), String::from("</table>")]);
+            lines
+        }
+    };
+
+    lines.join(nl)
+}
+
+/// Writes a table of the component's `bom_data.yaml` into its README, and into every page in its docs directory
+/// (see `config::ProjectConfig`) that has a section for it, between `BOM_START` and `BOM_END`. Markdown pages
+/// get a Markdown table and HTML pages get an HTML one. If the README doesn't have the markers yet, a Bill of
+/// Materials section holding them is added to the end. Anything outside of the markers is never changed, so the
+/// documentation can be written around the table, and running this whenever `bom_data.yaml` changes keeps the
+/// two from drifting apart.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+///
+/// The output status is 504 if `bom_data.yaml` can't be read, and 505 if a page can't be read or written.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// std::fs::write(
+///     temp_dir.join("toplevel").join("bom_data.yaml"),
+///     "parts:\n  bolt:\n    quantity: 4\n    quantity_units: part\n    name: Bolt\n",
+/// ).expect("Unable to write bom_data.yaml.");
+///
+/// let output = sliderule::bom::update_bom_docs(&temp_dir.join("toplevel"));
+///
+/// assert_eq!(0, output.status);
+///
+/// let readme = std::fs::read_to_string(temp_dir.join("toplevel").join("README.md")).unwrap();
+/// assert!(readme.contains("| Bolt |  | 4 part |  |"));
+/// ```
+pub fn update_bom_docs(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let items = match read_bom(target_dir) {
+        Ok(i) => i,
+        Err(e) => {
+            output.status = 504;
+            output.stderr.push(e);
+            return output;
+        }
+    };
+
+    let mut pages = vec![target_dir.join("README.md")];
+    let docs_dir = target_dir.join(super::config::config_or_default(target_dir).layout.docs_dir);
+    pages.extend(doc_pages(&docs_dir));
+
+    let mut updated = 0;
+    for (i, page) in pages.iter().enumerate() {
+        let is_readme = i == 0;
+        let contents = match fs::read_to_string(page) {
+            Ok(c) => c,
+            Err(e) => {
+                output.status = 505;
+                output
+                    .stderr
+                    .push(format!("ERROR: Unable to read {}: {}", page.display(), e));
+                continue;
+            }
+        };
+
+        let has_section = match (contents.find(BOM_START), contents.find(BOM_END)) {
+            (Some(start), Some(end)) => start < end,
+            _ => false,
+        };
+        if !has_section && !is_readme {
+            continue;
+        }
+
+        let nl = if contents.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let format = match page.extension().and_then(|e| e.to_str()) {
+            Some("html") | Some("htm") => TableFormat::Html,
+            _ => TableFormat::Markdown,
+        };
+
+        let mut section = String::from(BOM_START);
+        section.push_str(nl);
+        section.push_str(&render_table(&items, format, nl));
+        section.push_str(nl);
+        section.push_str(BOM_END);
+
+        let new_contents = if has_section {
+            let start = contents.find(BOM_START).unwrap_or(0);
+            let end = contents.find(BOM_END).unwrap_or(0);
+            format!(
+                "{}{}{}",
+                &contents[..start],
+                section,
+                &contents[end + BOM_END.len()..]
+            )
+        } else {
+            let mut new_contents = contents.clone();
+            if !new_contents.is_empty() && !new_contents.ends_with('\n') {
+                new_contents.push_str(nl);
+            }
+            new_contents.push_str(nl);
+            new_contents.push_str("## Bill of Materials");
+            new_contents.push_str(nl);
+            new_contents.push_str(nl);
+            new_contents.push_str(&section);
+            new_contents.push_str(nl);
+            new_contents
+        };
+
+        if new_contents != contents {
+            if let Err(e) = fs::write(page, new_contents) {
+                output.status = 505;
+                output
+                    .stderr
+                    .push(format!("ERROR: Unable to write {}: {}", page.display(), e));
+                continue;
+            }
+        }
+        updated += 1;
+    }
+
+    output.stdout.push(format!(
+        "Wrote the bill of materials into {} pages.",
+        updated
+    ));
+
+    output
+}
+
+/*
+ * The Markdown and HTML pages in a docs directory and the directories inside it, sorted so that they're always
+ * updated in the same order.
+ */
+fn doc_pages(docs_dir: &Path) -> Vec<PathBuf> {
+    let mut pages: Vec<PathBuf> = WalkDir::new(docs_dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .filter(|path| {
+            matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("md") | Some("html") | Some("htm")
+            )
+        })
+        .collect();
+    pages.sort();
+
+    pages
+}
+
+/*
+ * Takes the quotes off of a YAML value, if it has any.
+ */
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    for quote in ["'", "\""] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return value[1..value.len() - 1].to_string();
+        }
+    }
+
+    value.to_string()
+}
+
+/*
+ * Escapes the characters that mean something in HTML.
+ */
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,157 @@
+use std::fs;
+use std::path::Path;
+
+use super::json::JsonValue;
+
+/// A shared component that is listed in a catalog, with enough information to decide whether to add it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogEntry {
+    pub name: String,
+    pub description: String,
+    pub url: String,
+    pub source_license: String,
+    pub doc_license: String,
+}
+
+/// Searches a catalog of shared components for ones whose name or description contains `query`,
+/// ignoring case. An empty query matches every component in the catalog.
+///
+/// `catalog` is where the catalog is stored. It can be an `http://` or `https://` URL, which is downloaded
+/// with curl, or a path to a local file (optionally as a `file://` URL).
+///
+/// A catalog is a static JSON file holding an array of components, or an object with the array in its
+/// `components` member. Each component is an object like the following, and the `url` is what gets
+/// passed to `add_remote_component`.
+///
+/// ```json
+/// {
+///     "name": "arduino-sr",
+///     "description": "Sliderule Arduino Uno component",
+///     "url": "https://github.com/jmwright/arduino-sr.git",
+///     "source_license": "Unlicense",
+///     "documentation_license": "CC0-1.0"
+/// }
+/// ```
+///
+/// The output status is 29 if the catalog could not be read, and 30 if it is not a valid catalog.
+/// Matches are returned in the order they are listed in the catalog.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// let catalog_file = temp_dir.join("catalog.json");
+/// std::fs::write(&catalog_file, r#"[
+///     {"name": "arduino-sr", "description": "Arduino Uno", "url": "https://github.com/jmwright/arduino-sr.git",
+///      "source_license": "Unlicense", "documentation_license": "CC0-1.0"}
+/// ]"#).expect("Unable to write catalog file.");
+///
+/// let (output, matches) = sliderule::catalog::search(&catalog_file.to_string_lossy(), "arduino");
+///
+/// assert_eq!(0, output.status);
+/// assert_eq!(matches[0].url, "https://github.com/jmwright/arduino-sr.git");
+/// ```
+pub fn search(catalog: &str, query: &str) -> (super::SROutput, Vec<CatalogEntry>) {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let contents = match read_catalog(catalog) {
+        Ok(c) => c,
+        Err(e) => {
+            output.status = 29;
+            output.stderr.push(format!(
+                "ERROR: Unable to read the component catalog at {}: {}",
+                catalog, e
+            ));
+            return (output, Vec::new());
+        }
+    };
+
+    let entries = match parse_catalog(&contents) {
+        Ok(e) => e,
+        Err(e) => {
+            output.status = 30;
+            output.stderr.push(format!(
+                "ERROR: The component catalog at {} is not valid: {}",
+                catalog, e
+            ));
+            return (output, Vec::new());
+        }
+    };
+
+    let query = query.to_lowercase();
+    let matches: Vec<CatalogEntry> = entries
+        .into_iter()
+        .filter(|e| {
+            e.name.to_lowercase().contains(&query) || e.description.to_lowercase().contains(&query)
+        })
+        .collect();
+
+    output
+        .stdout
+        .push(format!("Found {} matching components.", matches.len()));
+
+    (output, matches)
+}
+
+/*
+ * Gets the text of the catalog from either a web server or the local file system.
+ */
+fn read_catalog(catalog: &str) -> Result<String, String> {
+    if catalog.starts_with("http://") || catalog.starts_with("https://") {
+        let output = super::curl_sr::curl_get(catalog);
+
+        if output.status != 0 || output.wrapped_status != 0 {
+            return Err(output.stderr.join(" ").trim().to_string());
+        }
+
+        return Ok(output.stdout.join(""));
+    }
+
+    let path = Path::new(catalog.trim_start_matches("file://"));
+
+    fs::read_to_string(path).map_err(|e| e.to_string())
+}
+
+/*
+ * Pulls the components out of the catalog JSON.
+ */
+fn parse_catalog(contents: &str) -> Result<Vec<CatalogEntry>, String> {
+    let document = super::json::parse(contents)?;
+
+    let components = match document {
+        JsonValue::Array(items) => items,
+        JsonValue::Object(_) => match document.get("components") {
+            Some(JsonValue::Array(items)) => items.clone(),
+            _ => return Err(String::from("There is no components array in the catalog")),
+        },
+        _ => return Err(String::from("The catalog must be an array or an object")),
+    };
+
+    let mut entries = Vec::new();
+    for component in components {
+        let name = component.get_str("name");
+        let url = component.get_str("url");
+
+        // Without these there is nothing useful to tell the user about the component
+        if name.is_empty() || url.is_empty() {
+            return Err(String::from("Every component needs a name and a url"));
+        }
+
+        entries.push(CatalogEntry {
+            name,
+            description: component.get_str("description"),
+            url,
+            source_license: component.get_str("source_license"),
+            doc_license: component.get_str("documentation_license"),
+        });
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,235 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use super::json::JsonValue;
+
+/// The exit code for an operation that succeeded.
+pub const EXIT_OK: i32 = 0;
+/// The exit code for an operation that failed for any reason not covered by the other codes.
+pub const EXIT_FAILURE: i32 = 1;
+/// The exit code for a component that didn't pass a check, i.e. validation, the pre-upload check, the allowed
+/// hosts or license policies, signature verification or the lock files.
+pub const EXIT_CHECK_FAILED: i32 = 2;
+/// The exit code for git, npm, curl or ssh failing.
+pub const EXIT_TOOL_FAILED: i32 = 3;
+
+/*
+ * The output statuses that mean a component didn't pass a check.
+ */
+const CHECK_STATUSES: [i32; 8] = [23, 26, 27, 38, 39, 40, 41, 47];
+
+/*
+ * 0 to detect CI mode from the environment, 1 if it was turned off and 2 if it was turned on.
+ */
+static CI_MODE: AtomicU8 = AtomicU8::new(0);
+
+/// Turns CI mode on or off for the rest of the process. Passing `None` goes back to turning it on when the `CI`
+/// environment variable is set to `true` or `1`, which GitHub Actions, GitLab CI and most other services do.
+///
+/// In CI mode, nothing is allowed to wait for input. git is never allowed to prompt for a password, and ssh is run
+/// in batch mode. Access tokens are only read from environment variables, i.e. `auth::TOKEN_VAR`, and never from
+/// the tokens file or a token provider. Nothing is written outside of the workspace, so the disk cache isn't
+/// saved, credentials aren't handed to the system's credential helper, and npm's cache is kept in `scratch_dir`.
+///
+/// # Examples
+///
+/// ```
+/// sliderule::ci::set_ci_mode(Some(true));
+/// assert!(sliderule::ci::ci_mode());
+///
+/// sliderule::ci::set_ci_mode(None);
+/// ```
+pub fn set_ci_mode(enabled: Option<bool>) {
+    let value = match enabled {
+        None => 0,
+        Some(false) => 1,
+        Some(true) => 2,
+    };
+
+    CI_MODE.store(value, Ordering::SeqCst);
+}
+
+/// Returns true if Sliderule is running in CI mode, as described for `set_ci_mode`.
+pub fn ci_mode() -> bool {
+    match CI_MODE.load(Ordering::SeqCst) {
+        1 => false,
+        2 => true,
+        _ => match env::var("CI") {
+            Ok(v) => v.eq_ignore_ascii_case("true") || v == "1",
+            Err(_) => false,
+        },
+    }
+}
+
+/*
+ * 0 to read strict mode from the environment, 1 if it was turned off and 2 if it was turned on.
+ */
+static STRICT_MODE: AtomicU8 = AtomicU8::new(0);
+
+/// Turns strict mode on or off for the rest of the process. Passing `None` goes back to turning it on when the
+/// `SR_STRICT` environment variable is set to `true` or `1`.
+///
+/// Operations made of several steps usually carry on after a step fails, doing as much as they can and collecting
+/// every problem in their output. In strict mode, `create_component`, `upload_component` and `refactor` stop at
+/// the first step that fails instead, and take back what they had done so far where they can. A component that
+/// couldn't be created completely is removed, a repository that an upload set up is removed again if the upload
+/// didn't get as far as pushing, and a component that couldn't be refactored is left in the `components`
+/// directory. The output status is the one of the step that failed. This suits scripts and pipelines, which would
+/// rather start over than pick through follow-on failures.
+///
+/// # Examples
+///
+/// ```
+/// sliderule::ci::set_strict_mode(Some(true));
+/// assert!(sliderule::ci::strict_mode());
+///
+/// sliderule::ci::set_strict_mode(None);
+/// ```
+pub fn set_strict_mode(enabled: Option<bool>) {
+    let value = match enabled {
+        None => 0,
+        Some(false) => 1,
+        Some(true) => 2,
+    };
+
+    STRICT_MODE.store(value, Ordering::SeqCst);
+}
+
+/// Returns true if Sliderule is running in strict mode, as described for `set_strict_mode`.
+pub fn strict_mode() -> bool {
+    match STRICT_MODE.load(Ordering::SeqCst) {
+        1 => false,
+        2 => true,
+        _ => match env::var("SR_STRICT") {
+            Ok(v) => v.eq_ignore_ascii_case("true") || v == "1",
+            Err(_) => false,
+        },
+    }
+}
+
+/// The directory that the pipeline is working in. This is `GITHUB_WORKSPACE` on GitHub Actions and
+/// `CI_PROJECT_DIR` on GitLab CI, falling back to the current directory.
+pub fn workspace_dir() -> PathBuf {
+    for var in ["GITHUB_WORKSPACE", "CI_PROJECT_DIR"] {
+        if let Ok(dir) = env::var(var) {
+            if !dir.is_empty() {
+                return PathBuf::from(dir);
+            }
+        }
+    }
+
+    env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+}
+
+/// Where caches go in CI mode. This is the runner's temporary directory on GitHub Actions, which is cleaned up
+/// after every job, and the `.sliderule-ci` directory of the workspace everywhere else.
+pub fn scratch_dir() -> PathBuf {
+    match env::var("RUNNER_TEMP") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir).join("sliderule"),
+        _ => workspace_dir().join(".sliderule-ci"),
+    }
+}
+
+/// Maps the output of an operation to a process exit code that stays the same between releases, even when new
+/// output statuses are added. It is `EXIT_OK`, `EXIT_CHECK_FAILED`, `EXIT_TOOL_FAILED` or `EXIT_FAILURE`.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// let output = sliderule::health::validate_component(&temp_dir);
+///
+/// assert_eq!(sliderule::ci::exit_code(&output), sliderule::ci::EXIT_CHECK_FAILED);
+/// ```
+pub fn exit_code(output: &super::SROutput) -> i32 {
+    match output.status {
+        0 if output.wrapped_status == 0 => EXIT_OK,
+        0 => EXIT_TOOL_FAILED,
+        s if CHECK_STATUSES.contains(&s) => EXIT_CHECK_FAILED,
+        100..=499 => EXIT_TOOL_FAILED,
+        _ => EXIT_FAILURE,
+    }
+}
+
+/// Writes the output of an operation as a single line of JSON for pipelines to read, with the `status`,
+/// `wrapped_status`, `exit_code`, `stdout` and `stderr` members. Empty lines of output are left out.
+///
+/// # Examples
+///
+/// ```
+/// let output = sliderule::SROutput {
+///     status: 0,
+///     wrapped_status: 0,
+///     stdout: vec![String::from("Component is valid."), String::new()],
+///     stderr: Vec::new(),
+/// };
+///
+/// assert_eq!(
+///     sliderule::ci::to_json(&output),
+///     r#"{"status":0,"wrapped_status":0,"exit_code":0,"stdout":["Component is valid."],"stderr":[]}"#
+/// );
+/// ```
+pub fn to_json(output: &super::SROutput) -> String {
+    let lines = |lines: &[String]| {
+        JsonValue::Array(
+            lines
+                .iter()
+                .filter(|l| !l.trim().is_empty())
+                .map(|l| JsonValue::String(super::redact::redact(l)))
+                .collect(),
+        )
+    };
+
+    JsonValue::Object(vec![
+        (
+            String::from("status"),
+            JsonValue::Number(f64::from(output.status)),
+        ),
+        (
+            String::from("wrapped_status"),
+            JsonValue::Number(f64::from(output.wrapped_status)),
+        ),
+        (
+            String::from("exit_code"),
+            JsonValue::Number(f64::from(exit_code(output))),
+        ),
+        (String::from("stdout"), lines(&output.stdout)),
+        (String::from("stderr"), lines(&output.stderr)),
+    ])
+    .to_json()
+}
+
+/// Sets a tool up so that it can't wait for input and keeps its caches out of the user's home directory. It's
+/// called for every tool that's run while CI mode is on.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn prepare_command(command: &mut Command) {
+    // The configuration can point at a git or npm somewhere other than the PATH
+    let program = Path::new(command.get_program())
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let is_set = |command: &Command, var: &str| {
+        env::var_os(var).is_some() || command.get_envs().any(|(k, _)| k == var)
+    };
+
+    if program == "git" {
+        command
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .env("GCM_INTERACTIVE", "never");
+        if !is_set(command, "GIT_SSH_COMMAND") {
+            command.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+        }
+    } else if program == "npm" {
+        if !is_set(command, "npm_config_cache") {
+            command.env("npm_config_cache", scratch_dir().join("npm-cache"));
+        }
+
+        // Checking for updates writes to the user's configuration directory
+        command.env("npm_config_update_notifier", "false");
+    }
+}
@@ -0,0 +1,946 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// The directory in a project's top level directory that holds its Sliderule configuration.
+pub const CONFIG_DIR: &str = ".sliderule";
+
+/// The name of the configuration file, both inside of a project's `CONFIG_DIR` and inside of the user's
+/// configuration directory, `auth::config_dir`.
+///
+/// The file is a small subset of TOML, made of `[sections]` holding `key = value` lines, where a value is a quoted
+/// string, `true`, `false` or an array of quoted strings. Everything is optional, and a project's file overrides the
+/// user's.
+///
+/// ```toml
+/// [git]
+/// # merge, rebase or ff-only
+/// pull_strategy = "rebase"
+/// default_branch = "main"
+/// # Who commits are made by, even on machines where git has never been set up
+/// author_name = "Lab Workstation"
+/// author_email = "lab@example.com"
+/// # What the hooks from git_hooks::install_hooks run to check a component, as the program followed by its arguments
+/// hook_command = ["sliderule-cli", "validate"]
+///
+/// [remote]
+/// # Where a component's repository is, used by upload_component and refactor when they aren't given a URL
+/// url_template = "git@github.com:ourorg/{name}.git"
+///
+/// [files]
+/// # lf or crlf, for the files that Sliderule generates
+/// line_endings = "lf"
+/// # Globs relative to the project, skipped the same way as the ones in .srignore
+/// exclude = ["docs/renders", "**/*.bak"]
+///
+/// [policy]
+/// # Added to the patterns in .srhosts
+/// allowed_hosts = ["github.com/ourorg/*"]
+/// # The licenses that components may use, where an empty list allows any license
+/// allowed_licenses = ["Unlicense", "CC0-1.0", "CERN-OHL-S-2.0"]
+///
+/// [licenses]
+/// # The licenses for create_component_with_default_licenses, and for directories that aren't components
+/// default_source = "Unlicense"
+/// default_documentation = "CC0-1.0"
+///
+/// [tools]
+/// # Only honored in the user's file, unless allow_project is on, see ProjectConfig::allow_project_tools
+/// allow_project = false
+/// # The git executable, when it isn't the one on the PATH
+/// git = "/opt/git/bin/git"
+/// # The npm executable, or npm's npm-cli.js, which is run with node
+/// npm = "/opt/node/bin/npm"
+/// node = "/opt/node/bin/node"
+/// npm_cache = "/var/cache/sliderule/npm"
+/// # Variables from the environment that tools are given even though they'd normally be removed, see
+/// # backend::SANITIZED_ENV
+/// pass_env = ["GIT_DIR"]
+/// # The oldest versions of git and npm that are run, anything older fails before it's used
+/// minimum_git = "2.19.0"
+/// minimum_npm = "7.0.0"
+///
+/// [layout]
+/// # The names of a component's directories, where dist can be false for components that don't have one
+/// source = "hardware"
+/// docs = "docs"
+/// dist = false
+///
+/// [hooks]
+/// # Commands that are run at the events in events::Event, as the program followed by its arguments. Relative
+/// # paths to a program are relative to the project's top level directory. A before_upload hook that fails stops
+/// # the upload.
+/// before_upload = ["scripts/lint.sh", "--strict"]
+/// dependency_added = ["notify-send", "A dependency was added"]
+/// # Only honored in the user's file, see ProjectConfig::allow_project_hooks
+/// allow_project = true
+///
+/// [community]
+/// # Give new projects a CONTRIBUTING.md, a CODE_OF_CONDUCT.md and issue templates
+/// files = true
+///
+/// [annex]
+/// # Files that are kept in git-annex instead of git, for design data that's too big even for git LFS
+/// patterns = ["scans/**", "simulations/**/*.h5"]
+///
+/// [network]
+/// # Refuse to run anything that would contact a remote, i.e. on a machine without network access
+/// offline = false
+/// # The proxy that npm goes through, in place of any npm proxy settings in the environment
+/// npm_proxy = "http://proxy.example.com:3128"
+///
+/// [install]
+/// # full, or dist to only keep what's needed to build from released artifacts, see InstallProfile
+/// profile = "dist"
+/// ```
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// The environment variables that override the configuration files, and the settings they stand for. They're
+/// meant for containers and pipelines, where it's easier to set a variable than to write a file. Arguments passed
+/// to an operation still win over all of them.
+///
+/// `SR_OFFLINE`, `SR_ALLOW_PROJECT_HOOKS`, `SR_ALLOW_PROJECT_TOOLS` and `SR_COMMUNITY_FILES` take `1`, `true` or `yes` to turn the setting on, and `0`, `false`
+/// or `no` to turn it off.
+pub const ENV_SETTINGS: [(&str, &str); 18] = [
+    ("SR_GIT_BIN", "tools.git"),
+    ("SR_NPM_BIN", "tools.npm"),
+    ("SR_NODE_BIN", "tools.node"),
+    ("SR_NPM_CACHE", "tools.npm_cache"),
+    ("SR_OFFLINE", "network.offline"),
+    ("SR_NPM_PROXY", "network.npm_proxy"),
+    ("SR_DEFAULT_SOURCE_LICENSE", "licenses.default_source"),
+    ("SR_DEFAULT_DOC_LICENSE", "licenses.default_documentation"),
+    ("SR_DEFAULT_BRANCH", "git.default_branch"),
+    ("SR_PULL_STRATEGY", "git.pull_strategy"),
+    ("SR_LINE_ENDINGS", "files.line_endings"),
+    ("SR_AUTHOR_NAME", "git.author_name"),
+    ("SR_AUTHOR_EMAIL", "git.author_email"),
+    ("SR_ALLOW_PROJECT_HOOKS", "hooks.allow_project"),
+    ("SR_ALLOW_PROJECT_TOOLS", "tools.allow_project"),
+    ("SR_REMOTE_URL_TEMPLATE", "remote.url_template"),
+    ("SR_COMMUNITY_FILES", "community.files"),
+    ("SR_INSTALL_PROFILE", "install.profile"),
+];
+
+/// How `update_local_component` brings in the changes from a component's remote repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PullStrategy {
+    /// Merge the remote changes in, which is what git does by default
+    Merge,
+    /// Put local commits on top of the remote changes
+    Rebase,
+    /// Refuse to pull if the local and remote changes have diverged
+    FastForwardOnly,
+}
+
+/// How much of each remote component is kept when it's installed into a project's `node_modules` directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallProfile {
+    /// Everything in the component's repository
+    Full,
+    /// Everything except the source directories of the component and of the components inside of it, which is
+    /// all that's needed to build from released artifacts, i.e. the dist and docs directories, the bill of
+    /// materials and the metadata. npm still downloads the whole component.
+    Dist,
+}
+
+/// The line endings of the text files that Sliderule generates for new components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEndings {
+    Lf,
+    Crlf,
+}
+
+/// The names of the directories that every component has, as `create_component` makes them and
+/// `health::validate_component` expects them. The `components` directory always keeps its name, because it's
+/// where Sliderule finds a project's local components.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    /// Holds the design files, i.e. CAD models and schematics
+    pub source_dir: String,
+    /// Holds the documentation
+    pub docs_dir: String,
+    /// Holds the files that are built from the source, and that `publish::publish_dist` publishes. Components
+    /// don't have one when it's `None`.
+    pub dist_dir: Option<String>,
+}
+
+impl Default for Layout {
+    fn default() -> Layout {
+        Layout {
+            source_dir: String::from("source"),
+            docs_dir: String::from("docs"),
+            dist_dir: Some(String::from("dist")),
+        }
+    }
+}
+
+impl Layout {
+    /// The directories that a component needs, in the order that they're created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut config = sliderule::config::ProjectConfig::default();
+    /// assert_eq!(config.layout.dirs(), vec!["components", "dist", "docs", "source"]);
+    ///
+    /// config
+    ///     .apply("[layout]\nsource = \"hardware\"\ndist = false\n")
+    ///     .expect("Unable to read configuration.");
+    ///
+    /// assert_eq!(config.layout.dirs(), vec!["components", "docs", "hardware"]);
+    /// ```
+    pub fn dirs(&self) -> Vec<&str> {
+        let mut dirs = vec!["components"];
+        if let Some(dist_dir) = &self.dist_dir {
+            dirs.push(dist_dir);
+        }
+        dirs.push(&self.docs_dir);
+        dirs.push(&self.source_dir);
+
+        dirs
+    }
+}
+
+/// The settings that a project, or the user, can change. Anything that isn't set in either configuration file or
+/// in `ENV_SETTINGS` keeps the value from `ProjectConfig::default`, which matches what Sliderule does without any
+/// configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectConfig {
+    pub pull_strategy: PullStrategy,
+    /// The branch that is pulled from and pushed to
+    pub default_branch: String,
+    /// The name that commits are made with, instead of git's `user.name`
+    pub author_name: Option<String>,
+    /// The email address that commits are made with, instead of git's `user.email`
+    pub author_email: Option<String>,
+    /// The command that the git hooks from `git_hooks::install_hooks` run to check a component, as the program
+    /// followed by its arguments. It's run in the top level directory of the repository.
+    pub hook_command: Vec<String>,
+    /// The URL of a component's repository, with `{name}` where the component's name goes
+    pub remote_url_template: Option<String>,
+    pub line_endings: LineEndings,
+    /// Glob patterns relative to the project's top level directory that walks skip, along with `.srignore`
+    pub excluded_dirs: Vec<String>,
+    /// Patterns that are allowed along with the ones in `.srhosts`, in the same format
+    pub allowed_hosts: Vec<String>,
+    /// The licenses that the project's components are allowed to use. Every license is allowed when it's empty.
+    pub allowed_licenses: Vec<String>,
+    /// The source license that `create_component_with_default_licenses` uses
+    pub default_source_license: String,
+    /// The documentation license that `create_component_with_default_licenses` uses
+    pub default_doc_license: String,
+    /// The program that is run for git
+    pub git_bin: String,
+    /// The program that is run for npm, instead of looking for it. If it's a `.js` file, it is run with `node_bin`.
+    pub npm_bin: Option<String>,
+    /// The program that runs npm when `npm_bin` is a script
+    pub node_bin: String,
+    /// Where npm keeps its cache, instead of its own default
+    pub npm_cache: Option<PathBuf>,
+    /// The oldest version of git that operations will run, see `environment::MINIMUM_GIT_VERSION`
+    pub minimum_git_version: String,
+    /// The oldest version of npm that operations will run, see `environment::MINIMUM_NPM_VERSION`
+    pub minimum_npm_version: String,
+    /// The variables in `backend::SANITIZED_ENV` that tools are given from the environment anyway
+    pub pass_env: Vec<String>,
+    /// Whether the `[tools]` settings in a project's own file are used. Like `allow_project_hooks`, this can only
+    /// be turned on in the user's file or the environment, since a project that picks the programs that are run,
+    /// or the variables they're given, can run commands by itself.
+    pub allow_project_tools: bool,
+    /// The `[tools]` settings in the project's file that are skipped because `allow_project_tools` is off
+    pub untrusted_tools: Vec<String>,
+    /// Whether tools are kept from contacting remotes
+    pub offline: bool,
+    /// The proxy that npm goes through for both HTTP and HTTPS, instead of the one in the environment
+    pub npm_proxy: Option<String>,
+    pub layout: Layout,
+    /// The commands that are run at events, each as the program followed by its arguments, after the ones added
+    /// with `events::add_event_command`
+    pub hooks: Vec<(super::events::Event, Vec<String>)>,
+    /// Whether the hooks in a project's own file are run. This can only be turned on in the user's file or the
+    /// environment, so that a project that was downloaded from somewhere can't run commands by itself.
+    pub allow_project_hooks: bool,
+    /// The hooks in the project's file that are skipped because `allow_project_hooks` is off
+    pub untrusted_hooks: Vec<(super::events::Event, Vec<String>)>,
+    /// Whether `create_component` gives new projects the files from `add_community_files`
+    pub community_files: bool,
+    /// Globs for the files that are kept in git-annex instead of git, see the `annex` module. git-annex is only
+    /// set up for new components when there are some.
+    pub annex_patterns: Vec<String>,
+    pub install_profile: InstallProfile,
+}
+
+impl Default for ProjectConfig {
+    fn default() -> ProjectConfig {
+        ProjectConfig {
+            pull_strategy: PullStrategy::Merge,
+            default_branch: String::from("master"),
+            author_name: None,
+            author_email: None,
+            hook_command: vec![String::from("sliderule-cli"), String::from("validate")],
+            remote_url_template: None,
+            line_endings: LineEndings::Lf,
+            excluded_dirs: Vec::new(),
+            allowed_hosts: Vec::new(),
+            allowed_licenses: Vec::new(),
+            default_source_license: String::from("Unlicense"),
+            default_doc_license: String::from("CC0-1.0"),
+            git_bin: String::from("git"),
+            npm_bin: None,
+            node_bin: String::from("node"),
+            npm_cache: None,
+            minimum_git_version: String::from(super::environment::MINIMUM_GIT_VERSION),
+            minimum_npm_version: String::from(super::environment::MINIMUM_NPM_VERSION),
+            pass_env: Vec::new(),
+            allow_project_tools: false,
+            untrusted_tools: Vec::new(),
+            offline: false,
+            npm_proxy: None,
+            layout: Layout::default(),
+            hooks: Vec::new(),
+            allow_project_hooks: false,
+            untrusted_hooks: Vec::new(),
+            community_files: false,
+            annex_patterns: Vec::new(),
+            install_profile: InstallProfile::Full,
+        }
+    }
+}
+
+impl ProjectConfig {
+    /// Reads the settings in a configuration file's contents over the top of these ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut config = sliderule::config::ProjectConfig::default();
+    ///
+    /// config
+    ///     .apply("[git]\npull_strategy = \"ff-only\"\n\n[policy]\nallowed_licenses = [\"Unlicense\"]\n")
+    ///     .expect("Unable to read configuration.");
+    ///
+    /// assert_eq!(config.pull_strategy, sliderule::config::PullStrategy::FastForwardOnly);
+    /// assert_eq!(config.default_branch, "master");
+    /// assert_eq!(config.allowed_licenses, vec!["Unlicense"]);
+    /// ```
+    pub fn apply(&mut self, contents: &str) -> Result<(), String> {
+        for (key, value) in parse_toml(contents)? {
+            self.set(&key, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the settings in `ENV_SETTINGS` that are set in the environment over the top of these ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// std::env::set_var("SR_GIT_BIN", "/opt/git/bin/git");
+    ///
+    /// let mut config = sliderule::config::ProjectConfig::default();
+    /// config.apply_env().expect("Unable to read the environment.");
+    ///
+    /// assert_eq!(config.git_bin, "/opt/git/bin/git");
+    /// ```
+    pub fn apply_env(&mut self) -> Result<(), String> {
+        for &(var, key) in ENV_SETTINGS.iter() {
+            let value = match env::var(var) {
+                Ok(v) if !v.trim().is_empty() => v.trim().to_string(),
+                _ => continue,
+            };
+
+            let value = match key {
+                "network.offline"
+                | "hooks.allow_project"
+                | "tools.allow_project"
+                | "community.files" => match value.to_lowercase().as_str() {
+                    "1" | "true" | "yes" => Value::Bool(true),
+                    "0" | "false" | "no" => Value::Bool(false),
+                    _ => return Err(format!("{} has to be true or false.", var)),
+                },
+                _ => Value::String(value),
+            };
+
+            self.set(key, value)
+                .map_err(|e| format!("{}: {}", var, e))?;
+        }
+
+        Ok(())
+    }
+
+    /*
+     * Changes one setting, making sure that the value makes sense for it.
+     */
+    fn set(&mut self, key: &str, value: Value) -> Result<(), String> {
+        match (key, value) {
+            ("git.pull_strategy", Value::String(s)) => {
+                self.pull_strategy = match s.as_str() {
+                    "merge" => PullStrategy::Merge,
+                    "rebase" => PullStrategy::Rebase,
+                    "ff-only" => PullStrategy::FastForwardOnly,
+                    _ => {
+                        return Err(format!(
+                            "{} is not a pull strategy, it has to be merge, rebase or ff-only.",
+                            s
+                        ))
+                    }
+                }
+            }
+            ("git.default_branch", Value::String(s)) => {
+                if s.is_empty() || s.starts_with('-') || s.contains(char::is_whitespace) {
+                    return Err(format!("{} is not a valid branch name.", s));
+                }
+                self.default_branch = s;
+            }
+            ("git.author_name", Value::String(s)) => self.author_name = Some(s),
+            ("git.author_email", Value::String(s)) => {
+                if !s.contains('@') {
+                    return Err(format!("{} is not an email address.", s));
+                }
+                self.author_email = Some(s);
+            }
+            ("git.hook_command", Value::Array(a)) => {
+                if a.first().map(|p| p.trim().is_empty()).unwrap_or(true) {
+                    return Err(String::from("The hook command needs a program to run."));
+                }
+                self.hook_command = a;
+            }
+            ("remote.url_template", Value::String(s)) => {
+                if !s.contains("{name}") {
+                    return Err(format!(
+                        "{} doesn't have {{name}} in it to put the component's name in.",
+                        s
+                    ));
+                }
+                self.remote_url_template = Some(s);
+            }
+            ("files.line_endings", Value::String(s)) => {
+                self.line_endings = match s.as_str() {
+                    "lf" => LineEndings::Lf,
+                    "crlf" => LineEndings::Crlf,
+                    _ => {
+                        return Err(format!(
+                            "{} is not a kind of line ending, it has to be lf or crlf.",
+                            s
+                        ))
+                    }
+                }
+            }
+            ("files.exclude", Value::Array(a)) => self.excluded_dirs = a,
+            ("policy.allowed_hosts", Value::Array(a)) => self.allowed_hosts = a,
+            ("policy.allowed_licenses", Value::Array(a)) => self.allowed_licenses = a,
+            ("licenses.default_source", Value::String(s)) => self.default_source_license = s,
+            ("licenses.default_documentation", Value::String(s)) => self.default_doc_license = s,
+            ("tools.git", Value::String(s)) => {
+                if s.is_empty() {
+                    return Err(String::from("The git executable can't be empty."));
+                }
+                self.git_bin = s;
+            }
+            ("tools.npm", Value::String(s)) => {
+                if s.is_empty() {
+                    return Err(String::from("The npm executable can't be empty."));
+                }
+                self.npm_bin = Some(s);
+            }
+            ("tools.node", Value::String(s)) => {
+                if s.is_empty() {
+                    return Err(String::from("The node executable can't be empty."));
+                }
+                self.node_bin = s;
+            }
+            ("tools.npm_cache", Value::String(s)) => self.npm_cache = Some(PathBuf::from(s)),
+            ("tools.minimum_git", Value::String(s)) => {
+                check_version(&s)?;
+                self.minimum_git_version = s;
+            }
+            ("tools.minimum_npm", Value::String(s)) => {
+                check_version(&s)?;
+                self.minimum_npm_version = s;
+            }
+            ("tools.pass_env", Value::Array(a)) => self.pass_env = a,
+            ("tools.allow_project", Value::Bool(b)) => self.allow_project_tools = b,
+            ("network.offline", Value::Bool(b)) => self.offline = b,
+            ("network.npm_proxy", Value::String(s)) => {
+                if !s.contains("://") {
+                    return Err(format!(
+                        "{} is not a proxy URL, i.e. http://proxy.example.com:3128.",
+                        s
+                    ));
+                }
+                self.npm_proxy = Some(s);
+            }
+            ("layout.source", Value::String(s)) => {
+                check_layout_dir(&s, &[&self.layout.docs_dir], &self.layout.dist_dir)?;
+                self.layout.source_dir = s;
+            }
+            ("layout.docs", Value::String(s)) => {
+                check_layout_dir(&s, &[&self.layout.source_dir], &self.layout.dist_dir)?;
+                self.layout.docs_dir = s;
+            }
+            ("layout.dist", Value::String(s)) => {
+                check_layout_dir(&s, &[&self.layout.source_dir, &self.layout.docs_dir], &None)?;
+                self.layout.dist_dir = Some(s);
+            }
+            ("hooks.allow_project", Value::Bool(b)) => self.allow_project_hooks = b,
+            ("community.files", Value::Bool(b)) => self.community_files = b,
+            ("annex.patterns", Value::Array(a)) => self.annex_patterns = a,
+            ("install.profile", Value::String(s)) => {
+                self.install_profile = match s.as_str() {
+                    "full" => InstallProfile::Full,
+                    "dist" => InstallProfile::Dist,
+                    _ => {
+                        return Err(format!(
+                            "{} is not an install profile, it has to be full or dist.",
+                            s
+                        ))
+                    }
+                }
+            }
+            (k, Value::Array(a)) if k.starts_with("hooks.") => {
+                let event = match super::events::Event::from_name(&k["hooks.".len()..]) {
+                    Some(e) => e,
+                    None => return Err(format!("{} is not a setting.", key)),
+                };
+
+                // A project's hooks for an event take the place of the user's, and an empty list removes them
+                self.hooks.retain(|h| h.0 != event);
+                if !a.is_empty() {
+                    self.hooks.push((event, a));
+                }
+            }
+            ("layout.dist", Value::Bool(false)) => self.layout.dist_dir = None,
+            ("layout.dist", Value::Bool(true)) => {
+                self.layout.dist_dir = Some(String::from("dist"));
+            }
+            (
+                "git.pull_strategy"
+                | "git.default_branch"
+                | "git.author_name"
+                | "git.author_email"
+                | "git.hook_command"
+                | "remote.url_template"
+                | "files.line_endings"
+                | "files.exclude"
+                | "policy.allowed_hosts"
+                | "policy.allowed_licenses"
+                | "licenses.default_source"
+                | "licenses.default_documentation"
+                | "tools.git"
+                | "tools.npm"
+                | "tools.node"
+                | "tools.npm_cache"
+                | "tools.minimum_git"
+                | "tools.minimum_npm"
+                | "tools.pass_env"
+                | "tools.allow_project"
+                | "network.offline"
+                | "network.npm_proxy"
+                | "layout.source"
+                | "layout.docs"
+                | "layout.dist"
+                | "hooks.allow_project"
+                | "community.files"
+                | "annex.patterns"
+                | "install.profile",
+                _,
+            ) => return Err(format!("{} has the wrong type of value.", key)),
+            (k, _)
+                if k.starts_with("hooks.")
+                    && super::events::Event::from_name(&k["hooks.".len()..]).is_some() =>
+            {
+                return Err(format!("{} has the wrong type of value.", key))
+            }
+            _ => return Err(format!("{} is not a setting.", key)),
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the policy allows a license, which can be an SPDX expression like `MIT OR Apache-2.0`. An
+    /// expression is only allowed if every license in it is.
+    pub fn allows_license(&self, license: &str) -> bool {
+        if self.allowed_licenses.is_empty() {
+            return true;
+        }
+
+        license
+            .replace(['(', ')'], " ")
+            .split_whitespace()
+            .filter(|l| !["AND", "OR", "WITH"].contains(l))
+            .all(|l| self.allowed_licenses.iter().any(|a| a == l))
+    }
+
+    /// The identity that commits are made with, if both the name and email address are set.
+    pub fn author(&self) -> Option<super::git_sr::GitAuthor> {
+        match (&self.author_name, &self.author_email) {
+            (Some(name), Some(email)) => Some(super::git_sr::GitAuthor {
+                name: name.clone(),
+                email: email.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The URL of the repository for a component, made from `remote_url_template`, if there is one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut config = sliderule::config::ProjectConfig::default();
+    /// assert_eq!(config.remote_url_for("level1"), None);
+    ///
+    /// config
+    ///     .apply("[remote]\nurl_template = \"git@github.com:ourorg/{name}.git\"\n")
+    ///     .expect("Unable to read configuration.");
+    ///
+    /// assert_eq!(
+    ///     config.remote_url_for("level1"),
+    ///     Some(String::from("git@github.com:ourorg/level1.git"))
+    /// );
+    /// ```
+    pub fn remote_url_for(&self, name: &str) -> Option<String> {
+        self.remote_url_template
+            .as_ref()
+            .map(|t| t.replace("{name}", name))
+    }
+
+    /// Changes the line endings of generated text to the configured ones.
+    pub fn line_endings_for(&self, text: &str) -> String {
+        let text = text.replace("\r\n", "\n");
+
+        match self.line_endings {
+            LineEndings::Lf => text,
+            LineEndings::Crlf => text.replace('\n', "\r\n"),
+        }
+    }
+}
+
+/// Loads the configuration for the project that a component belongs to, starting from the defaults, then
+/// applying the user's configuration file, then the project's and then the environment variables in
+/// `ENV_SETTINGS`. The project's file is always read from the top level of the project, so that every component in
+/// it shares the same settings. Its hooks and `[tools]` settings are skipped unless the user has allowed them, see
+/// `ProjectConfig::allow_project_hooks` and `ProjectConfig::allow_project_tools`.
+///
+/// Operations call this themselves, so it only needs to be used to look at the settings. The error names the
+/// file that couldn't be read.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// sliderule::create_component(&temp_dir.join("toplevel"), String::from("level1"), String::from("Level 1 component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let config_dir = temp_dir.join("toplevel").join(".sliderule");
+/// std::fs::create_dir(&config_dir).expect("Unable to create configuration directory.");
+/// std::fs::write(config_dir.join("config.toml"), "[git]\ndefault_branch = \"main\"\n").expect("Unable to write configuration.");
+///
+/// let config = sliderule::config::load_config(&temp_dir.join("toplevel").join("components").join("level1")).unwrap();
+///
+/// assert_eq!(config.default_branch, "main");
+/// ```
+pub fn load_config(target_dir: &Path) -> Result<ProjectConfig, String> {
+    load_config_in(&super::backend::StdFileSystem, target_dir)
+}
+
+/// Loads the configuration like `load_config`, reading the configuration files from any file system, see
+/// `backend::FileSystem`.
+///
+/// # Examples
+///
+/// ```
+/// use sliderule::backend::FileSystem;
+/// use std::path::Path;
+///
+/// let memory = sliderule::backend::MemoryFileSystem::new();
+/// memory.write(Path::new("/part/.sr"), "source_license: MIT,\ndocumentation_license: CC-BY-4.0\n").unwrap();
+/// memory.write(Path::new("/part/.sliderule/config.toml"), "[licenses]\ndefault_source = \"0BSD\"\n\n[tools]\ngit = \"scripts/git.sh\"\n").unwrap();
+///
+/// let config = sliderule::config::load_config_in(&memory, Path::new("/part/components/gear")).unwrap();
+///
+/// assert_eq!(config.default_source_license, "0BSD");
+///
+/// // The project can't pick the programs that are run by itself
+/// assert_eq!(config.git_bin, "git");
+/// assert_eq!(config.untrusted_tools, vec!["tools.git"]);
+/// ```
+pub fn load_config_in(
+    file_system: &dyn super::backend::FileSystem,
+    target_dir: &Path,
+) -> Result<ProjectConfig, String> {
+    let mut config = ProjectConfig::default();
+
+    let mut files = Vec::new();
+    if let Some(dir) = super::auth::config_dir() {
+        files.push((dir.join(CONFIG_FILE), false));
+    }
+    let project_file = project_root_in(file_system, target_dir)
+        .join(CONFIG_DIR)
+        .join(CONFIG_FILE);
+    files.push((project_file.clone(), true));
+
+    let mut project_tools = Vec::new();
+    for (file, is_project) in files {
+        let contents = match file_system.read_to_string(&file) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+
+        let user_hooks = config.hooks.clone();
+        let allow_project_hooks = config.allow_project_hooks;
+
+        let settings = parse_toml(&contents).map_err(|e| format!("{}: {}", file.display(), e))?;
+        for (key, value) in settings {
+            // The project's tools are held back until it's known whether the user allows them
+            if is_project && key.starts_with("tools.") {
+                if key != "tools.allow_project" {
+                    config.untrusted_tools.push(key.clone());
+                    project_tools.push((key, value));
+                }
+                continue;
+            }
+
+            config
+                .set(&key, value)
+                .map_err(|e| format!("{}: {}", file.display(), e))?;
+        }
+
+        // The project can't decide for itself that its hooks should be run
+        if is_project {
+            config.allow_project_hooks = allow_project_hooks;
+            config.untrusted_hooks = config
+                .hooks
+                .iter()
+                .filter(|h| !user_hooks.contains(h))
+                .cloned()
+                .collect();
+            config.hooks = user_hooks;
+        }
+    }
+
+    config.apply_env()?;
+
+    // With the environment allowing them, the project's hooks are run after all
+    if config.allow_project_hooks {
+        for hook in config.untrusted_hooks.drain(..) {
+            config.hooks.retain(|h| h.0 != hook.0);
+            config.hooks.push(hook);
+        }
+    }
+
+    // The same goes for the project's tools, which the environment still wins over
+    if config.allow_project_tools && !project_tools.is_empty() {
+        for (key, value) in project_tools {
+            config
+                .set(&key, value)
+                .map_err(|e| format!("{}: {}", project_file.display(), e))?;
+        }
+        config.untrusted_tools.clear();
+        config.apply_env()?;
+    }
+
+    Ok(config)
+}
+
+/// Loads the configuration like `load_config`, but falls back to the defaults if a configuration file is broken,
+/// which `health::validate_component` reports. The environment variables are still honored.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn config_or_default(target_dir: &Path) -> ProjectConfig {
+    config_or_default_in(&super::backend::StdFileSystem, target_dir)
+}
+
+/// Loads the configuration like `config_or_default`, reading the configuration files from any file system.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn config_or_default_in(
+    file_system: &dyn super::backend::FileSystem,
+    target_dir: &Path,
+) -> ProjectConfig {
+    load_config_in(file_system, target_dir).unwrap_or_else(|_| {
+        let mut config = ProjectConfig::default();
+        let _ = config.apply_env();
+        config
+    })
+}
+
+/// The top level directory of the project that a component belongs to.
+pub fn project_root(target_dir: &Path) -> PathBuf {
+    project_root_in(&super::backend::StdFileSystem, target_dir)
+}
+
+/// The top level directory of the project that a component belongs to, on any file system.
+pub fn project_root_in(file_system: &dyn super::backend::FileSystem, target_dir: &Path) -> PathBuf {
+    let mut root = target_dir.to_path_buf();
+
+    while let Some(parent) = super::find_enclosing_sr_dir_in(file_system, &root) {
+        root = parent;
+    }
+
+    root
+}
+
+/*
+ * Makes sure that a directory in the layout is a plain name that doesn't clash with anything else in a component.
+ */
+fn check_layout_dir(
+    name: &str,
+    others: &[&String],
+    dist_dir: &Option<String>,
+) -> Result<(), String> {
+    let lower = name.to_lowercase();
+
+    if name.is_empty()
+        || name == "."
+        || name == ".."
+        || name.contains(['/', '\\'])
+        || name.starts_with('.')
+    {
+        return Err(format!(
+            "{} is not a directory name that can be used in the layout.",
+            name
+        ));
+    }
+
+    if ["components", "node_modules"].contains(&lower.as_str()) {
+        return Err(format!(
+            "{} is already used by Sliderule, so it can't be in the layout.",
+            name
+        ));
+    }
+
+    if others.iter().any(|o| o.to_lowercase() == lower)
+        || dist_dir.as_ref().map(|d| d.to_lowercase() == lower) == Some(true)
+    {
+        return Err(format!("{} is used more than once in the layout.", name));
+    }
+
+    Ok(())
+}
+
+/*
+ * Makes sure that a minimum version is a dotted version number, i.e. 2.19.0.
+ */
+fn check_version(version: &str) -> Result<(), String> {
+    let parts: Vec<&str> = version.trim_start_matches('v').split('.').collect();
+    if parts
+        .iter()
+        .any(|p| p.is_empty() || !p.chars().all(|c| c.is_ascii_digit()))
+    {
+        return Err(format!("{} is not a version number, i.e. 2.19.0.", version));
+    }
+
+    Ok(())
+}
+
+enum Value {
+    String(String),
+    Bool(bool),
+    Array(Vec<String>),
+}
+
+/*
+ * Reads the subset of TOML that configuration files use into a list of dotted keys and their values.
+ */
+fn parse_toml(contents: &str) -> Result<Vec<(String, Value)>, String> {
+    let mut values = Vec::new();
+    let mut section = String::new();
+
+    let mut lines = contents.lines().enumerate();
+    while let Some((number, line)) = lines.next() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            if !line.ends_with(']') {
+                return Err(format!("Line {} has an unclosed section name.", number + 1));
+            }
+            section = line[1..line.len() - 1].trim().to_string();
+            continue;
+        }
+
+        let i = match line.find('=') {
+            Some(i) => i,
+            None => return Err(format!("Line {} is not a key = value pair.", number + 1)),
+        };
+        let key = line[..i].trim().to_string();
+        let mut value = line[i + 1..].trim().to_string();
+
+        // Arrays can go over more than one line
+        if value.starts_with('[') {
+            while !value.ends_with(']') {
+                match lines.next() {
+                    Some((_, l)) => {
+                        value.push(' ');
+                        value.push_str(strip_comment(l).trim());
+                    }
+                    None => return Err(format!("The array for {} is never closed.", key)),
+                }
+            }
+        }
+
+        let value = if value.starts_with('[') {
+            let mut items = Vec::new();
+            for item in value[1..value.len() - 1].split(',') {
+                let item = item.trim();
+                if item.is_empty() {
+                    continue;
+                }
+                items.push(unquote(item).ok_or_else(|| {
+                    format!("The array for {} can only hold quoted strings.", key)
+                })?);
+            }
+            Value::Array(items)
+        } else if value == "true" || value == "false" {
+            Value::Bool(value == "true")
+        } else {
+            Value::String(
+                unquote(&value)
+                    .ok_or_else(|| format!("The value of {} has to be a quoted string.", key))?,
+            )
+        };
+
+        let key = if section.is_empty() {
+            key
+        } else {
+            format!("{}.{}", section, key)
+        };
+        values.push((key, value));
+    }
+
+    Ok(values)
+}
+
+/*
+ * Removes a # comment from the end of a line, leaving any # inside of a string alone.
+ */
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '#') => return &line[..i],
+            _ => (),
+        }
+    }
+
+    line
+}
+
+fn unquote(text: &str) -> Option<String> {
+    for quote in ['"', '\''] {
+        if text.len() >= 2 && text.starts_with(quote) && text.ends_with(quote) {
+            return Some(text[1..text.len() - 1].to_string());
+        }
+    }
+
+    None
+}
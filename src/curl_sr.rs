@@ -0,0 +1,199 @@
+use std::process::Command;
+
+/// Uses curl to download the contents of a URL, which are added to `stdout` as a single entry.
+///
+/// `url` The HTTP or HTTPS URL to download. Redirects are followed, and HTTP error responses are treated
+/// as failures.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn curl_get(url: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(Command::new("curl").args([
+        "--silent",
+        "--show-error",
+        "--fail",
+        "--location",
+        url,
+    ])) {
+        Ok(out) => out,
+        Err(e) => {
+            if let std::io::ErrorKind::NotFound = e.kind() {
+                output.status = 300;
+                output.stderr.push(format!(
+                    "ERROR: `curl` was not found, please install: {}",
+                    e
+                ));
+            } else {
+                output.status = 301;
+                output
+                    .stderr
+                    .push(format!("ERROR: Unable to run curl: {}", e));
+            }
+            return output;
+        }
+    };
+
+    output
+        .stdout
+        .push(String::from_utf8_lossy(&stdoutput.stdout).to_string());
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "curl", &stdoutput.status);
+
+    output
+}
+
+/// Uses curl to download from or upload to an S3-compatible object store, signing the request with AWS
+/// signature version 4. A download's contents are added to `stdout` as a single entry.
+///
+/// `url` The HTTP or HTTPS URL of the object, i.e. `https://s3.example.com/bucket/path/file.zip`.
+/// `upload_file` The file to upload with a PUT request, or `None` to download the object.
+/// `region` The region that the object store expects in the signature, i.e. `us-east-1`.
+/// `access_key` and `secret_key` The credentials to sign the request with. They are handed to curl on its
+/// standard input so that they don't show up in the process list.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn curl_s3(
+    url: &str,
+    upload_file: Option<&std::path::Path>,
+    region: &str,
+    access_key: &str,
+    secret_key: &str,
+) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let mut args: Vec<std::ffi::OsString> = vec![
+        "--silent".into(),
+        "--show-error".into(),
+        "--fail".into(),
+        "--config".into(),
+        "-".into(),
+        "--aws-sigv4".into(),
+        format!("aws:amz:{}:s3", region).into(),
+    ];
+    if let Some(file) = upload_file {
+        args.push("--upload-file".into());
+        args.push(file.as_os_str().to_owned());
+    }
+    args.push(url.into());
+
+    // curl reads the credentials as a config file line, so quotes and backslashes have to be escaped
+    let config = format!(
+        "user = \"{}:{}\"\n",
+        access_key.replace('\\', "\\\\").replace('"', "\\\""),
+        secret_key.replace('\\', "\\\\").replace('"', "\\\"")
+    );
+
+    let stdoutput =
+        match super::backend::run_with_input(Command::new("curl").args(&args), config.as_bytes()) {
+            Ok(out) => out,
+            Err(e) => {
+                if let std::io::ErrorKind::NotFound = e.kind() {
+                    output.status = 300;
+                    output.stderr.push(format!(
+                        "ERROR: `curl` was not found, please install: {}",
+                        e
+                    ));
+                } else {
+                    output.status = 301;
+                    output
+                        .stderr
+                        .push(format!("ERROR: Unable to run curl: {}", e));
+                }
+                return output;
+            }
+        };
+
+    output
+        .stdout
+        .push(String::from_utf8_lossy(&stdoutput.stdout).to_string());
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "curl", &stdoutput.status);
+
+    output
+}
+
+/// Uses curl to download a URL into a file, which works for binary files like archives that can't be added to
+/// `stdout`.
+///
+/// `url` The HTTP or HTTPS URL to download. Redirects are followed, and HTTP error responses are treated
+/// as failures.
+/// `dest_file` The file to write, which is replaced if it's already there.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn curl_download(url: &str, dest_file: &std::path::Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("curl")
+            .args([
+                "--silent",
+                "--show-error",
+                "--fail",
+                "--location",
+                "--output",
+            ])
+            .arg(dest_file)
+            .arg(url),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            if let std::io::ErrorKind::NotFound = e.kind() {
+                output.status = 300;
+                output.stderr.push(format!(
+                    "ERROR: `curl` was not found, please install: {}",
+                    e
+                ));
+            } else {
+                output.status = 301;
+                output
+                    .stderr
+                    .push(format!("ERROR: Unable to run curl: {}", e));
+            }
+            return output;
+        }
+    };
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "curl", &stdoutput.status);
+
+    output
+}
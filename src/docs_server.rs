@@ -0,0 +1,742 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// The directory inside of a project's dist directory that `build_docs` writes the rendered documentation to.
+pub const DOCS_OUTPUT_DIR: &str = "docs";
+
+/// The path that pages served by `serve_docs` ask for the number of the latest build, so that they can reload
+/// themselves when the documentation is rebuilt.
+pub const BUILD_PATH: &str = "/__sliderule/build";
+
+/// How long a connection can sit without sending anything before it's closed.
+pub const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/*
+ * The script that `serve_docs` adds to the end of every page it serves, which reloads the page when the build
+ * number changes.
+ */
+const RELOAD_SCRIPT: &str = "<script>
+(function () {
+  var build = null;
+  setInterval(function () {
+    fetch('/__sliderule/build').then(function (r) { return r.text(); }).then(function (b) {
+      if (build !== null && b !== build) { location.reload(); }
+      build = b;
+    }).catch(function () {});
+  }, 1000);
+})();
+</script>
+";
+
+/// Renders the documentation of every component in a project, i.e. its assembly instructions, into HTML in the
+/// project's dist directory. The docs directory of each component is rendered into `DOCS_OUTPUT_DIR` at the
+/// component's path relative to the project, so the project's own pages are at the top and those of
+/// `components/level1` are in `components/level1`. Markdown files become HTML pages, with links to other Markdown
+/// files pointed at their pages, and everything else, like images, is copied as it is. An `index.html` that lists
+/// every page is added at the top, unless the project's docs directory has an `index.md` of its own.
+///
+/// `DOCS_OUTPUT_DIR` is replaced each time, so nothing should be kept there by hand.
+///
+/// The output status is 77 if the layout doesn't have a dist directory, or the documentation can't be written.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// std::fs::write(temp_dir.join("toplevel").join("docs").join("assembly.md"), "# Assembly\n\nBolt it together.\n").expect("Unable to write the docs.");
+///
+/// let output = sliderule::docs_server::build_docs(&temp_dir.join("toplevel"));
+///
+/// assert_eq!(0, output.status);
+/// assert!(temp_dir.join("toplevel").join("dist").join("docs").join("assembly.html").exists());
+/// ```
+pub fn build_docs(project_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let layout = super::config::config_or_default(project_dir).layout;
+    let out_dir = match layout.dist_dir {
+        Some(ref d) => project_dir.join(d).join(DOCS_OUTPUT_DIR),
+        None => {
+            output.status = 77;
+            output.stderr.push(format!(
+                "ERROR: The layout of {} doesn't have a dist directory, so the documentation can't be built.",
+                project_dir.display()
+            ));
+            return output;
+        }
+    };
+
+    match render_all(project_dir, &layout, &out_dir) {
+        Ok(count) => {
+            output.stdout.push(format!(
+                "Built {} documentation pages into {}.",
+                count,
+                out_dir.display()
+            ));
+        }
+        Err(e) => {
+            output.status = 77;
+            output.stderr.push(format!(
+                "ERROR: Unable to build the documentation into {}: {}",
+                out_dir.display(),
+                e
+            ));
+        }
+    }
+
+    output
+}
+
+/// Builds the documentation with `build_docs` and serves it at `http://127.0.0.1:<port>/` so that authors can
+/// preview it while they write it. The documentation is rebuilt whenever anything in a docs directory changes,
+/// and open pages reload themselves when it is. This only returns if the documentation can't be built the first
+/// time or the port can't be listened on.
+///
+/// The server only listens on the machine it runs on, and only answers `GET` requests for files in the rendered
+/// documentation.
+///
+/// # Examples
+///
+/// ```no_run
+/// let project_dir = std::path::Path::new("/srv/projects/toplevel");
+///
+/// sliderule::docs_server::serve_docs(project_dir, 8000).expect("Unable to serve the documentation.");
+/// ```
+pub fn serve_docs(project_dir: &Path, port: u16) -> io::Result<()> {
+    serve_docs_listener(TcpListener::bind(("127.0.0.1", port))?, project_dir)
+}
+
+/// Serves the documentation on a listener that has already been bound, the same way as `serve_docs`.
+pub fn serve_docs_listener(listener: TcpListener, project_dir: &Path) -> io::Result<()> {
+    // The sources are looked at before the first build so that nothing changed after it can be missed
+    let layout = super::config::config_or_default(project_dir).layout;
+    let mut sources = docs_snapshot(project_dir, &layout);
+
+    let output = build_docs(project_dir);
+    if output.status != 0 {
+        return Err(io::Error::other(output.stderr.join("\n")));
+    }
+
+    let out_dir = project_dir
+        .join(layout.dist_dir.clone().unwrap_or_default())
+        .join(DOCS_OUTPUT_DIR);
+    let build = Arc::new(AtomicU64::new(1));
+
+    let rebuild_dir = project_dir.to_path_buf();
+    let rebuild_layout = layout.clone();
+    let rebuild_build = Arc::clone(&build);
+    thread::spawn(move || loop {
+        thread::sleep(super::watch::POLL_INTERVAL);
+
+        let current = docs_snapshot(&rebuild_dir, &rebuild_layout);
+        if current != sources {
+            if build_docs(&rebuild_dir).status == 0 {
+                rebuild_build.fetch_add(1, Ordering::SeqCst);
+            }
+            sources = current;
+        }
+    });
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(s) => s,
+            Err(_) => continue,
+        };
+
+        let out_dir = out_dir.clone();
+        let build = Arc::clone(&build);
+        thread::spawn(move || {
+            let _ = handle_connection(stream, &out_dir, &build);
+        });
+    }
+
+    Ok(())
+}
+
+/// Renders the Markdown that documentation is written in to HTML. Headings, paragraphs, bulleted and numbered
+/// lists, block quotes, fenced code blocks, horizontal rules, links, images, inline code, bold and italics are
+/// supported, and everything else is shown as text. Links to other Markdown files are pointed at the pages that
+/// `build_docs` renders them to, and links using a `javascript:` URL are dropped.
+///
+/// # Examples
+///
+/// ```
+/// let html = sliderule::docs_server::render_markdown("# Assembly\n\nSee [the parts](parts.md).\n");
+///
+/// assert_eq!(html, "<h1 id=\"assembly\">Assembly</h1>\n<p>See <a href=\"parts.html\">the parts</a>.</p>\n");
+/// ```
+pub fn render_markdown(markdown: &str) -> String {
+    let mut html = String::new();
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut quote: Vec<String> = Vec::new();
+    let mut list: Option<&str> = None;
+    let mut in_code = false;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+
+        if in_code {
+            if trimmed.starts_with("```") {
+                html.push_str("</code></pre>\n");
+                in_code = false;
+            } else {
+                html.push_str(&escape(line));
+                html.push('\n');
+            }
+            continue;
+        }
+
+        let fence = trimmed.strip_prefix("```");
+        let title = heading(trimmed);
+        let rule = trimmed == "---" || trimmed == "***" || trimmed == "___";
+        let item = if rule { None } else { list_item(trimmed) };
+        let quoted = trimmed.strip_prefix('>');
+        let text = !trimmed.is_empty()
+            && fence.is_none()
+            && title.is_none()
+            && !rule
+            && item.is_none()
+            && quoted.is_none();
+
+        // Anything other than more of the same ends the block that came before it
+        if !text {
+            end_block(&mut html, "p", &mut paragraph);
+        }
+        if quoted.is_none() {
+            end_block(&mut html, "blockquote", &mut quote);
+        }
+        if let Some(kind) = list {
+            if item.map(|i| i.0) != Some(kind) {
+                html.push_str(&format!("</{}>\n", kind));
+                list = None;
+            }
+        }
+
+        if let Some(info) = fence {
+            let info = info.trim();
+            if info.is_empty() {
+                html.push_str("<pre><code>");
+            } else {
+                html.push_str(&format!("<pre><code class=\"language-{}\">", escape(info)));
+            }
+            in_code = true;
+        } else if let Some((level, title)) = title {
+            html.push_str(&format!(
+                "<h{} id=\"{}\">{}</h{}>\n",
+                level,
+                anchor(title),
+                inline(title),
+                level
+            ));
+        } else if rule {
+            html.push_str("<hr>\n");
+        } else if let Some((kind, item_text)) = item {
+            if list.is_none() {
+                html.push_str(&format!("<{}>\n", kind));
+                list = Some(kind);
+            }
+            html.push_str(&format!("<li>{}</li>\n", inline(item_text)));
+        } else if let Some(quoted) = quoted {
+            quote.push(quoted.trim().to_string());
+        } else if text {
+            paragraph.push(trimmed.to_string());
+        }
+    }
+
+    if in_code {
+        html.push_str("</code></pre>\n");
+    }
+    end_block(&mut html, "p", &mut paragraph);
+    end_block(&mut html, "blockquote", &mut quote);
+    if let Some(kind) = list {
+        html.push_str(&format!("</{}>\n", kind));
+    }
+
+    html
+}
+
+/*
+ * Writes out the lines of a paragraph or block quote that have been collected, if there are any.
+ */
+fn end_block(html: &mut String, tag: &str, lines: &mut Vec<String>) {
+    if lines.is_empty() {
+        return;
+    }
+
+    let text = inline(&lines.join("\n"));
+    if tag == "blockquote" {
+        html.push_str(&format!("<blockquote><p>{}</p></blockquote>\n", text));
+    } else {
+        html.push_str(&format!("<{}>{}</{}>\n", tag, text, tag));
+    }
+    lines.clear();
+}
+
+/*
+ * Renders the docs directory of every component into the output directory, returning how many pages were written.
+ */
+fn render_all(
+    project_dir: &Path,
+    layout: &super::config::Layout,
+    out_dir: &Path,
+) -> io::Result<usize> {
+    if out_dir.exists() {
+        fs::remove_dir_all(out_dir)?;
+    }
+    fs::create_dir_all(out_dir)?;
+
+    let mut pages: Vec<(String, String)> = Vec::new();
+    let mut has_index = false;
+    for (relative_dir, docs_dir) in docs_dirs(project_dir, layout) {
+        for file in super::walker::TreeWalker::new(&docs_dir)
+            .use_ignore_file(false)
+            .walk()
+        {
+            if !file.is_file() {
+                continue;
+            }
+            let relative_file = match file.strip_prefix(&docs_dir) {
+                Ok(r) => relative_dir.join(r),
+                Err(_) => continue,
+            };
+
+            let dest = out_dir.join(&relative_file);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            if file.extension().map(|e| e == "md") == Some(true) {
+                let markdown = fs::read_to_string(&file)?;
+                let dest = dest.with_extension("html");
+                let href = url_path(&relative_file.with_extension("html"));
+                let title = markdown
+                    .lines()
+                    .find_map(|l| heading(l.trim()).map(|h| h.1.to_string()))
+                    .unwrap_or_else(|| {
+                        file.file_stem()
+                            .map(|s| s.to_string_lossy().to_string())
+                            .unwrap_or_default()
+                    });
+                let depth = relative_file.components().count() - 1;
+
+                fs::write(
+                    &dest,
+                    page(&title, &render_markdown(&markdown), &"../".repeat(depth)),
+                )?;
+
+                if href == "index.html" {
+                    has_index = true;
+                }
+                pages.push((href, title));
+            } else {
+                fs::copy(&file, &dest)?;
+            }
+        }
+    }
+
+    if !has_index {
+        let mut body = String::from("<h1>Documentation</h1>\n<ul>\n");
+        for (href, title) in &pages {
+            body.push_str(&format!(
+                "<li><a href=\"{}\">{}</a> <small>{}</small></li>\n",
+                escape(href),
+                escape(title),
+                escape(href)
+            ));
+        }
+        body.push_str("</ul>\n");
+
+        let name = project_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        fs::write(out_dir.join("index.html"), page(&name, &body, ""))?;
+    }
+
+    Ok(pages.len())
+}
+
+/*
+ * The docs directory of every component in the project, with the component's path relative to the project.
+ */
+fn docs_dirs(project_dir: &Path, layout: &super::config::Layout) -> Vec<(PathBuf, PathBuf)> {
+    let mut dirs = Vec::new();
+
+    for sr_file in super::walker::TreeWalker::new(project_dir)
+        .include("**/.sr")
+        .walk()
+    {
+        let component_dir = match sr_file.parent() {
+            Some(d) => d,
+            None => continue,
+        };
+        let relative = match component_dir.strip_prefix(project_dir) {
+            Ok(r) => r.to_path_buf(),
+            Err(_) => continue,
+        };
+
+        let docs_dir = component_dir.join(&layout.docs_dir);
+        if docs_dir.is_dir() {
+            dirs.push((relative, docs_dir));
+        }
+    }
+
+    dirs
+}
+
+/*
+ * The modification time and size of every file in the project's docs directories, to tell when to rebuild.
+ */
+fn docs_snapshot(
+    project_dir: &Path,
+    layout: &super::config::Layout,
+) -> BTreeMap<PathBuf, (Option<SystemTime>, u64)> {
+    let mut files = BTreeMap::new();
+
+    for (_, docs_dir) in docs_dirs(project_dir, layout) {
+        for file in super::walker::TreeWalker::new(&docs_dir)
+            .use_ignore_file(false)
+            .walk()
+        {
+            if let Ok(metadata) = fs::metadata(&file) {
+                files.insert(file, (metadata.modified().ok(), metadata.len()));
+            }
+        }
+    }
+
+    files
+}
+
+/*
+ * Wraps rendered Markdown in a page, with a link back to the index at `root`.
+ */
+fn page(title: &str, body: &str, root: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n<nav><a href=\"{}index.html\">Index</a></nav>\n<main>\n{}</main>\n</body>\n</html>\n",
+        escape(title),
+        root,
+        body
+    )
+}
+
+/*
+ * The heading level and text of a Markdown heading line.
+ */
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+
+    let rest = &line[level..];
+    if rest.is_empty() || rest.starts_with(' ') {
+        Some((level, rest.trim().trim_end_matches('#').trim()))
+    } else {
+        None
+    }
+}
+
+/*
+ * The id that a heading's text is linked to with, i.e. `#bolting-the-frame`.
+ */
+fn anchor(text: &str) -> String {
+    let mut id = String::new();
+
+    for c in text.to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            id.push(c);
+        } else if !id.ends_with('-') && (c.is_whitespace() || c == '-' || c == '_') {
+            id.push('-');
+        }
+    }
+
+    id.trim_matches('-').to_string()
+}
+
+/*
+ * The kind of list and the text of a Markdown list item line.
+ */
+fn list_item(line: &str) -> Option<(&'static str, &str)> {
+    for marker in ["- ", "* ", "+ "].iter() {
+        if let Some(text) = line.strip_prefix(marker) {
+            return Some(("ul", text.trim()));
+        }
+    }
+
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        if let Some(text) = line[digits..].strip_prefix(". ") {
+            return Some(("ol", text.trim()));
+        }
+    }
+
+    None
+}
+
+/*
+ * Renders the inline parts of a block of Markdown: code, images, links, bold and italics.
+ */
+fn inline(text: &str) -> String {
+    let mut html = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                html.push_str(&format!("<code>{}</code>", escape(&rest[1..end + 1])));
+                rest = &rest[end + 2..];
+                continue;
+            }
+        } else if c == '!' && rest[1..].starts_with('[') {
+            if let Some((label, url, len)) = link(&rest[1..]) {
+                html.push_str(&format!(
+                    "<img src=\"{}\" alt=\"{}\">",
+                    escape(&link_target(url)),
+                    escape(label)
+                ));
+                rest = &rest[len + 1..];
+                continue;
+            }
+        } else if c == '[' {
+            if let Some((label, url, len)) = link(rest) {
+                html.push_str(&format!(
+                    "<a href=\"{}\">{}</a>",
+                    escape(&link_target(url)),
+                    inline(label)
+                ));
+                rest = &rest[len..];
+                continue;
+            }
+        } else if rest.starts_with("**") {
+            if let Some(end) = rest[2..].find("**") {
+                html.push_str(&format!("<strong>{}</strong>", inline(&rest[2..end + 2])));
+                rest = &rest[end + 4..];
+                continue;
+            }
+        } else if c == '*' {
+            if let Some(end) = rest[1..].find('*') {
+                if end > 0 {
+                    html.push_str(&format!("<em>{}</em>", inline(&rest[1..end + 1])));
+                    rest = &rest[end + 2..];
+                    continue;
+                }
+            }
+        }
+
+        html.push_str(&escape(&rest[..c.len_utf8()]));
+        rest = &rest[c.len_utf8()..];
+    }
+
+    html
+}
+
+/*
+ * The label and URL of a Markdown link at the start of some text, with how long the link is.
+ */
+fn link(text: &str) -> Option<(&str, &str, usize)> {
+    let label_end = text.find("](")?;
+    let url_end = text[label_end + 2..].find(')')? + label_end + 2;
+
+    Some((
+        &text[1..label_end],
+        text[label_end + 2..url_end].trim(),
+        url_end + 1,
+    ))
+}
+
+/*
+ * Where a link in the documentation should go once it's rendered.
+ */
+fn link_target(url: &str) -> String {
+    if url.trim().to_lowercase().starts_with("javascript:") {
+        return String::from("#");
+    }
+    if url.contains("://") {
+        return url.to_string();
+    }
+
+    // Other Markdown files are rendered to pages with the same name
+    match url.find('#') {
+        Some(i) if url[..i].ends_with(".md") => {
+            format!("{}.html{}", &url[..i - 3], &url[i..])
+        }
+        None if url.ends_with(".md") => format!("{}.html", &url[..url.len() - 3]),
+        _ => url.to_string(),
+    }
+}
+
+/*
+ * Escapes text so that it can be put in HTML, in element content or attribute values.
+ */
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/*
+ * A relative path as it appears in a URL.
+ */
+fn url_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+/*
+ * Reads one request from a connection and answers it with a file from the rendered documentation.
+ */
+fn handle_connection(stream: TcpStream, out_dir: &Path, build: &AtomicU64) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim_end().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = line.split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => {
+            let path = target.split('?').next().unwrap_or_default();
+            if path == BUILD_PATH {
+                (
+                    "200 OK",
+                    "text/plain",
+                    build.load(Ordering::SeqCst).to_string().into_bytes(),
+                )
+            } else {
+                match find_file(out_dir, path) {
+                    Some(file) => match fs::read(&file) {
+                        Ok(contents) => {
+                            let content_type = content_type(&file);
+                            let contents = if content_type.starts_with("text/html") {
+                                let page = String::from_utf8_lossy(&contents)
+                                    .replace("</body>", &format!("{}</body>", RELOAD_SCRIPT));
+                                page.into_bytes()
+                            } else {
+                                contents
+                            };
+                            ("200 OK", content_type, contents)
+                        }
+                        Err(_) => ("404 Not Found", "text/plain", b"Not found".to_vec()),
+                    },
+                    None => ("404 Not Found", "text/plain", b"Not found".to_vec()),
+                }
+            }
+        }
+        (Some(_), Some(_)) => (
+            "405 Method Not Allowed",
+            "text/plain",
+            b"Only GET is allowed".to_vec(),
+        ),
+        _ => ("400 Bad Request", "text/plain", b"Bad request".to_vec()),
+    };
+
+    writer.write_all(
+        format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+            status,
+            content_type,
+            body.len()
+        )
+        .as_bytes(),
+    )?;
+    writer.write_all(&body)?;
+    writer.flush()
+}
+
+/*
+ * The file in the rendered documentation that a request's path is for, if there is one. Paths that would leave
+ * the documentation, or that point at hidden files, don't find anything.
+ */
+fn find_file(out_dir: &Path, path: &str) -> Option<PathBuf> {
+    let mut file = out_dir.to_path_buf();
+    for segment in path.split('/').filter(|s| !s.is_empty()) {
+        let segment = percent_decode(segment)?;
+        let mut components = Path::new(&segment).components();
+        let plain =
+            matches!(components.next(), Some(Component::Normal(_))) && components.next().is_none();
+        if !plain || segment.starts_with('.') {
+            return None;
+        }
+        file.push(segment);
+    }
+
+    if file.is_dir() {
+        file.push("index.html");
+    }
+    if file.is_file() {
+        Some(file)
+    } else {
+        None
+    }
+}
+
+/*
+ * Decodes a percent-encoded path segment, which is None if the encoding is broken.
+ */
+fn percent_decode(segment: &str) -> Option<String> {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = segment.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).ok()
+}
+
+/*
+ * The content type to serve a file with, going by its extension.
+ */
+fn content_type(file: &Path) -> &'static str {
+    let extension = file
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "html" => "text/html; charset=utf-8",
+        "css" => "text/css",
+        "js" => "text/javascript",
+        "json" => "application/json",
+        "txt" | "md" => "text/plain; charset=utf-8",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
@@ -0,0 +1,348 @@
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// The oldest version of git that Sliderule is known to work with, which is the default for the configuration's
+/// `minimum_git_version`. Blobless clones need 2.19.0 or newer, and are replaced by full clones on older versions.
+pub const MINIMUM_GIT_VERSION: &str = "2.0.0";
+
+/// The oldest version of npm that Sliderule is known to work with, which is the default for the configuration's
+/// `minimum_npm_version`.
+pub const MINIMUM_NPM_VERSION: &str = "5.0.0";
+
+// How long to wait for a host to answer before calling it unreachable
+const CONNECT_TIMEOUT_SECS: u64 = 5;
+
+/// Whether one of the external tools that Sliderule wraps is installed and new enough.
+pub struct ToolCheck {
+    pub name: String,
+    pub found: bool,
+    pub version: String,
+    pub minimum_version: String,
+    pub meets_minimum: bool,
+}
+
+/// Whether a remote host could be connected to.
+pub struct HostCheck {
+    pub host: String,
+    pub reachable: bool,
+    pub message: String,
+}
+
+/// Whether the cache directory that npm will use can be written to.
+pub struct CacheCheck {
+    pub path: PathBuf,
+    pub writable: bool,
+    pub message: String,
+}
+
+/// The result of checking the environment that Sliderule is running in.
+pub struct EnvironmentReport {
+    pub git: ToolCheck,
+    pub npm: ToolCheck,
+    pub hosts: Vec<HostCheck>,
+    pub cache: CacheCheck,
+}
+
+impl EnvironmentReport {
+    /// Returns a human readable description of every problem found, which is empty if the environment
+    /// is ready to use.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        for tool in &[&self.git, &self.npm] {
+            if !tool.found {
+                problems.push(format!("{} is not installed.", tool.name));
+            } else if !tool.meets_minimum {
+                problems.push(format!(
+                    "{} {} is installed, but at least version {} is required.",
+                    tool.name, tool.version, tool.minimum_version
+                ));
+            }
+        }
+
+        for host in &self.hosts {
+            if !host.reachable {
+                problems.push(format!("{} is not reachable: {}", host.host, host.message));
+            }
+        }
+
+        if !self.cache.writable {
+            problems.push(format!(
+                "The cache directory {} is not writable: {}",
+                self.cache.path.display(),
+                self.cache.message
+            ));
+        }
+
+        problems
+    }
+
+    /// Returns true if no problems were found with the environment.
+    pub fn is_ok(&self) -> bool {
+        self.problems().is_empty()
+    }
+}
+
+/// Checks that the tools and resources Sliderule relies on are available, so that frontends can report
+/// problems before the first operation fails.
+///
+/// git and npm must be installed and at least the `minimum_git_version` and `minimum_npm_version` from the
+/// configuration of the current directory's project (see `config::ProjectConfig`), which are
+/// `MINIMUM_GIT_VERSION` and `MINIMUM_NPM_VERSION` unless they've been changed.
+/// Each of the `hosts` (i.e. `github.com` or `gitlab.example.com:22`, port 443 is used if none is given)
+/// must accept a connection. `cache` is the npm cache directory that will be used, npm's own cache
+/// directory is checked if it is `None`.
+///
+/// # Examples
+///
+/// ```no_run
+/// let report = sliderule::environment::check_environment(vec![String::from("github.com")], None);
+///
+/// for problem in report.problems() {
+///     println!("{}", problem);
+/// }
+/// ```
+pub fn check_environment(hosts: Vec<String>, cache: Option<String>) -> EnvironmentReport {
+    let config = super::config::config_or_default(Path::new("."));
+    let git = check_tool(
+        "git",
+        super::git_sr::git_version(),
+        &config.minimum_git_version,
+    );
+    let npm = check_tool(
+        "npm",
+        super::npm_sr::npm_version(),
+        &config.minimum_npm_version,
+    );
+
+    let mut host_checks = Vec::new();
+    for host in hosts {
+        host_checks.push(check_host(&host));
+    }
+
+    // Fall back to wherever npm says its cache is
+    let cache_path = match cache {
+        Some(c) => PathBuf::from(c),
+        None => {
+            let cache_output = super::npm_sr::npm_cache_dir();
+            if cache_output.status == 0 && !cache_output.stdout[0].is_empty() {
+                PathBuf::from(&cache_output.stdout[0])
+            } else {
+                PathBuf::new()
+            }
+        }
+    };
+
+    EnvironmentReport {
+        git,
+        npm,
+        hosts: host_checks,
+        cache: check_cache(cache_path),
+    }
+}
+
+/// Compares two dotted version numbers like `2.20.1`, ignoring anything that isn't a number
+/// (i.e. `2.20.1.windows.1`). Missing parts are treated as 0.
+///
+/// # Examples
+///
+/// ```
+/// use std::cmp::Ordering;
+///
+/// assert_eq!(sliderule::environment::compare_versions("2.20.1", "2.3.0"), Ordering::Greater);
+/// assert_eq!(sliderule::environment::compare_versions("2.0", "2.0.0"), Ordering::Equal);
+/// ```
+pub fn compare_versions(a: &str, b: &str) -> ::std::cmp::Ordering {
+    let a = version_parts(a);
+    let b = version_parts(b);
+
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).cloned().unwrap_or(0);
+        let y = b.get(i).cloned().unwrap_or(0);
+
+        if x != y {
+            return x.cmp(&y);
+        }
+    }
+
+    ::std::cmp::Ordering::Equal
+}
+
+/// The version of a tool, i.e. `2.39.2` for git, which is what the first number in the output of
+/// `program args --version` is. Each program is only asked the first time it's needed, and the answer is kept
+/// for the rest of the process. It's `None` if the program can't be run or doesn't say what its version is.
+///
+/// `args` are the arguments that come before `--version`, which is the npm-cli.js script when npm is run by
+/// node.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn tool_version(program: &OsStr, args: &[OsString]) -> Option<String> {
+    let mut key = program.to_os_string();
+    for arg in args {
+        key.push(" ");
+        key.push(arg);
+    }
+
+    if let Some(version) = lock(versions()).get(&key) {
+        return Some(version.clone());
+    }
+
+    let version_output =
+        super::backend::run(Command::new(program).args(args).arg("--version")).ok()?;
+    if !version_output.status.success() {
+        return None;
+    }
+
+    // git says `git version 2.39.3 (Apple Git-145)` and npm just says `9.6.7`
+    let version = String::from_utf8_lossy(&version_output.stdout)
+        .split_whitespace()
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))?
+        .to_string();
+    lock(versions()).insert(key, version.clone());
+
+    Some(version)
+}
+
+/*
+ * The versions that tool_version has found, by the program and arguments that were run.
+ */
+fn versions() -> &'static Mutex<BTreeMap<OsString, String>> {
+    static VERSIONS: OnceLock<Mutex<BTreeMap<OsString, String>>> = OnceLock::new();
+
+    VERSIONS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/*
+ * Gets the guarded value even if another thread panicked while holding the lock.
+ */
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(g) => g,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/*
+ * Breaks a version string into its numeric parts, stopping at the first part that isn't a number.
+ */
+fn version_parts(version: &str) -> Vec<u64> {
+    let mut parts = Vec::new();
+
+    for part in version.trim().trim_start_matches('v').split('.') {
+        match part.parse::<u64>() {
+            Ok(p) => parts.push(p),
+            Err(_) => break,
+        }
+    }
+
+    parts
+}
+
+/*
+ * Converts the output of a version command into a check against the minimum version.
+ */
+fn check_tool(name: &str, output: super::SROutput, minimum_version: &str) -> ToolCheck {
+    let found = output.status == 0 && output.wrapped_status == 0;
+
+    let version = if found {
+        output.stdout[0].clone()
+    } else {
+        String::new()
+    };
+
+    ToolCheck {
+        name: name.to_string(),
+        found,
+        meets_minimum: found
+            && compare_versions(&version, minimum_version) != ::std::cmp::Ordering::Less,
+        version,
+        minimum_version: minimum_version.to_string(),
+    }
+}
+
+/*
+ * Tries to open a connection to a host to see if it can be reached.
+ */
+fn check_host(host: &str) -> HostCheck {
+    // Use the HTTPS port if the caller didn't ask for a specific one
+    let address = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{}:443", host)
+    };
+
+    let addrs = match address.to_socket_addrs() {
+        Ok(a) => a,
+        Err(e) => {
+            return HostCheck {
+                host: host.to_string(),
+                reachable: false,
+                message: format!("Could not resolve host: {}", e),
+            };
+        }
+    };
+
+    let mut message = String::from("Host did not resolve to any addresses.");
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, Duration::from_secs(CONNECT_TIMEOUT_SECS)) {
+            Ok(_) => {
+                return HostCheck {
+                    host: host.to_string(),
+                    reachable: true,
+                    message: format!("Connected to {}.", addr),
+                };
+            }
+            Err(e) => message = format!("Could not connect to {}: {}", addr, e),
+        }
+    }
+
+    HostCheck {
+        host: host.to_string(),
+        reachable: false,
+        message,
+    }
+}
+
+/*
+ * Makes sure that a file can be created in the cache directory, creating the directory if needed.
+ */
+fn check_cache(path: PathBuf) -> CacheCheck {
+    if path.as_os_str().is_empty() {
+        return CacheCheck {
+            path,
+            writable: false,
+            message: String::from("The cache directory could not be determined."),
+        };
+    }
+
+    if let Err(e) = fs::create_dir_all(&path) {
+        return CacheCheck {
+            path,
+            writable: false,
+            message: format!("Could not create the cache directory: {}", e),
+        };
+    }
+
+    let test_file = path.join(".sr_write_test");
+    let result = fs::write(&test_file, "");
+    let _ = fs::remove_file(&test_file);
+
+    match result {
+        Ok(_) => CacheCheck {
+            path,
+            writable: true,
+            message: String::from("The cache directory is writable."),
+        },
+        Err(e) => CacheCheck {
+            path,
+            writable: false,
+            message: format!("Could not write to the cache directory: {}", e),
+        },
+    }
+}
@@ -0,0 +1,257 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// The points in an operation that handlers can be run at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// A new component was set up by `create_component`
+    ComponentCreated,
+    /// A remote component was installed by `add_remote_component`
+    DependencyAdded,
+    /// A component is about to be uploaded. A handler that fails stops the upload.
+    BeforeUpload,
+    /// A component was uploaded successfully
+    AfterUpload,
+    /// The dependencies of a component, or the component itself, were updated
+    AfterUpdate,
+}
+
+impl Event {
+    /// The name of the event, which is what external commands get in `SR_EVENT`.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Event::ComponentCreated => "component_created",
+            Event::DependencyAdded => "dependency_added",
+            Event::BeforeUpload => "before_upload",
+            Event::AfterUpload => "after_upload",
+            Event::AfterUpdate => "after_update",
+        }
+    }
+
+    /// The event with a name, as returned by `Event::name`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let event = sliderule::events::Event::from_name("before_upload");
+    ///
+    /// assert_eq!(event, Some(sliderule::events::Event::BeforeUpload));
+    /// ```
+    pub fn from_name(name: &str) -> Option<Event> {
+        [
+            Event::ComponentCreated,
+            Event::DependencyAdded,
+            Event::BeforeUpload,
+            Event::AfterUpload,
+            Event::AfterUpdate,
+        ]
+        .iter()
+        .find(|e| e.name() == name)
+        .copied()
+    }
+}
+
+/// What a handler is told about the event it is being run for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventContext {
+    pub event: Event,
+    /// The component that the operation was run on
+    pub component_dir: PathBuf,
+    /// The URL of the remote component or repository involved, without any credentials
+    pub url: Option<String>,
+}
+
+/// A Rust callback that is run on an event. Returning an error is reported in the operation's output, and stops
+/// the operation for `Event::BeforeUpload`.
+pub type EventHandler = Box<dyn Fn(&EventContext) -> Result<(), String> + Send + Sync>;
+
+enum Registration {
+    Handler(Arc<EventHandler>),
+    Command(String, Vec<String>),
+}
+
+fn registrations() -> &'static Mutex<Vec<(Event, Registration)>> {
+    static REGISTRATIONS: OnceLock<Mutex<Vec<(Event, Registration)>>> = OnceLock::new();
+
+    REGISTRATIONS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Registers a callback to run every time an event happens, for the rest of the process. Handlers are run in
+/// the order they were added, along with any commands added with `add_event_command`.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::events::add_event_handler(
+///     sliderule::events::Event::ComponentCreated,
+///     Box::new(|context| {
+///         println!("Created {}", context.component_dir.display());
+///         Ok(())
+///     }),
+/// );
+///
+/// let output = sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// assert_eq!(output.status, 0);
+///
+/// sliderule::events::clear_event_handlers();
+/// ```
+pub fn add_event_handler(event: Event, handler: EventHandler) {
+    lock(registrations()).push((event, Registration::Handler(Arc::new(handler))));
+}
+
+/// Registers an external command to run every time an event happens, for the rest of the process, i.e. a script
+/// that posts to a team chat after a component is uploaded. The command is run in the component's directory with
+/// the name of the event in `SR_EVENT`, the component's directory in `SR_COMPONENT_DIR`, the top level directory
+/// of its project in `SR_PROJECT_DIR`, and the URL involved, if there is one, in `SR_URL`. What it prints is
+/// added to the operation's output, and exiting with anything other than 0 counts as the handler failing.
+///
+/// Commands can also be set up for a project in its configuration file, see `config::ProjectConfig::hooks`.
+pub fn add_event_command(event: Event, program: &str, args: &[&str]) {
+    lock(registrations()).push((
+        event,
+        Registration::Command(
+            program.to_string(),
+            args.iter().map(|a| a.to_string()).collect(),
+        ),
+    ));
+}
+
+/// Removes every handler and command that has been registered.
+pub fn clear_event_handlers() {
+    lock(registrations()).clear();
+}
+
+/// Runs the handlers and commands that are registered for an event, in the order they were registered, followed
+/// by the hooks in the configuration.
+///
+/// The output status is 43 if any of them failed, with the exit code of a failed command in `wrapped_status`.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn fire(event: Event, component_dir: &Path, url: Option<&str>) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let context = EventContext {
+        event,
+        component_dir: component_dir.to_path_buf(),
+        url: url.map(super::strip_url_credentials),
+    };
+
+    // Handlers are run without holding the lock, so that they can run operations of their own
+    let to_run: Vec<Registration> = lock(registrations())
+        .iter()
+        .filter(|r| r.0 == event)
+        .map(|r| match r.1 {
+            Registration::Handler(ref h) => Registration::Handler(Arc::clone(h)),
+            Registration::Command(ref p, ref a) => Registration::Command(p.clone(), a.clone()),
+        })
+        .collect();
+
+    for registration in to_run {
+        match registration {
+            Registration::Handler(handler) => {
+                if let Err(e) = handler(&context) {
+                    output.status = 43;
+                    output
+                        .stderr
+                        .push(format!("ERROR: A {} handler failed: {}", event.name(), e));
+                }
+            }
+            Registration::Command(program, args) => {
+                run_command(&context, &program, &args, &mut output);
+            }
+        }
+    }
+
+    let config = super::config::config_or_default(component_dir);
+    for (hook_event, hook) in config.untrusted_hooks.iter() {
+        if *hook_event == event {
+            output.stderr.push(format!(
+                "WARNING: Skipped the {} hook {} in the project's configuration, since hooks.allow_project isn't turned on.",
+                event.name(),
+                hook[0]
+            ));
+        }
+    }
+    for (hook_event, hook) in config.hooks.iter() {
+        if *hook_event == event {
+            run_command(&context, &hook[0], &hook[1..], &mut output);
+        }
+    }
+
+    output
+}
+
+/*
+ * Runs an external command for an event, with the event's context in its environment, adding what it printed to
+ * the output.
+ */
+fn run_command(
+    context: &EventContext,
+    program: &str,
+    args: &[String],
+    output: &mut super::SROutput,
+) {
+    let event = context.event;
+    let project_dir = super::config::project_root(&context.component_dir);
+
+    // Scripts that are kept in the project are found from its top level directory, wherever the event happened
+    let mut program_path = PathBuf::from(program);
+    if program_path.is_relative() && program.contains(['/', '\\']) {
+        program_path = project_dir.join(program_path);
+    }
+
+    let mut command = Command::new(&program_path);
+    command
+        .args(args)
+        .current_dir(&context.component_dir)
+        .env("SR_EVENT", event.name())
+        .env("SR_COMPONENT_DIR", &context.component_dir)
+        .env("SR_PROJECT_DIR", &project_dir)
+        .env("SR_URL", context.url.clone().unwrap_or_default());
+
+    match super::backend::run(&mut command) {
+        Ok(o) => {
+            output
+                .stdout
+                .push(String::from_utf8_lossy(&o.stdout).to_string());
+            output
+                .stderr
+                .push(String::from_utf8_lossy(&o.stderr).to_string());
+
+            if !o.status.success() {
+                output.status = 43;
+                output.wrapped_status = o.status.code().unwrap_or(1);
+                output.stderr.push(format!(
+                    "ERROR: The {} command {} failed.",
+                    event.name(),
+                    program
+                ));
+            }
+        }
+        Err(e) => {
+            output.status = 43;
+            output.stderr.push(format!(
+                "ERROR: Unable to run the {} command {}: {}",
+                event.name(),
+                program,
+                e
+            ));
+        }
+    }
+}
+
+/*
+ * Locks the registrations, even if a handler panicked while they were locked.
+ */
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
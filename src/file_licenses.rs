@@ -0,0 +1,207 @@
+use std::path::{Path, PathBuf};
+
+/// The optional file in a component's directory that gives the licenses of files that don't use the component's
+/// own source or documentation license, i.e. a single CC-BY-4.0 photo in an otherwise CERN-OHL-S-2.0 component.
+///
+/// Each line maps a glob pattern, relative to the component and in the same style as `.gitignore`, to an SPDX
+/// license expression. Patterns that start with `*` have to be quoted, since YAML reads those as aliases.
+///
+/// ```yaml
+/// # Licenses of files that aren't under the component's own licenses
+/// docs/images/photo.jpg: CC-BY-4.0
+/// "*.woff2": OFL-1.1
+/// ```
+pub const LICENSES_FILE: &str = "licenses.yaml";
+
+/// One of the lines of a `LICENSES_FILE`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileLicense {
+    /// The glob pattern of the files that the license applies to
+    pub pattern: String,
+    /// The SPDX license expression of those files
+    pub license: String,
+}
+
+/// Reads the file licenses out of the contents of a `LICENSES_FILE`, in the order that they're in the file.
+///
+/// # Examples
+///
+/// ```
+/// let file_licenses = sliderule::file_licenses::parse_file_licenses(
+///     "# The photo is someone else's\ndocs/images/photo.jpg: CC-BY-4.0\n\"*.woff2\": OFL-1.1\n",
+/// ).unwrap();
+///
+/// assert_eq!(file_licenses[0].pattern, "docs/images/photo.jpg");
+/// assert_eq!(file_licenses[1].license, "OFL-1.1");
+/// ```
+pub fn parse_file_licenses(contents: &str) -> Result<Vec<FileLicense>, String> {
+    let mut file_licenses = Vec::new();
+
+    for (i, line) in contents.trim_start_matches('\u{feff}').lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        // Quoted patterns can have colons in them, so look for the separator after the closing quote
+        let search_from = match trimmed.chars().next() {
+            Some(q) if q == '"' || q == '\'' => trimmed[1..].find(q).map(|p| p + 2).unwrap_or(0),
+            _ => 0,
+        };
+        let separator = match trimmed[search_from..].find(": ") {
+            Some(p) => search_from + p,
+            None => {
+                return Err(format!(
+                    "line {} is not written as `pattern: license`",
+                    i + 1
+                ))
+            }
+        };
+
+        let pattern = unquote(&trimmed[..separator]);
+        let mut license = trimmed[separator + 2..].trim();
+        if let Some(p) = license.find(" #") {
+            license = license[..p].trim();
+        }
+        let license = unquote(license);
+
+        if pattern.is_empty() || license.is_empty() {
+            return Err(format!("line {} needs both a pattern and a license", i + 1));
+        }
+
+        file_licenses.push(FileLicense { pattern, license });
+    }
+
+    Ok(file_licenses)
+}
+
+/// Reads a component's `LICENSES_FILE`, which is an empty list if the component doesn't have one.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+pub fn read_file_licenses(target_dir: &Path) -> Result<Vec<FileLicense>, String> {
+    read_file_licenses_in(&super::backend::StdFileSystem, target_dir)
+}
+
+/// Reads a component's `LICENSES_FILE` like `read_file_licenses`, on any file system.
+pub fn read_file_licenses_in(
+    file_system: &dyn super::backend::FileSystem,
+    target_dir: &Path,
+) -> Result<Vec<FileLicense>, String> {
+    let licenses_file = target_dir.join(LICENSES_FILE);
+    if !file_system.exists(&licenses_file) {
+        return Ok(Vec::new());
+    }
+
+    match file_system.read_to_string(&licenses_file) {
+        Ok(contents) => parse_file_licenses(&contents)
+            .map_err(|e| format!("{} can't be read, {}", LICENSES_FILE, e)),
+        Err(e) => Err(format!("{} can't be read, {}", LICENSES_FILE, e)),
+    }
+}
+
+/// The files in a component that a file license's pattern matches. The files of the component's own components,
+/// in `components` and `node_modules`, never match, since they have licenses of their own.
+pub fn annotated_files(target_dir: &Path, file_license: &FileLicense) -> Vec<PathBuf> {
+    super::walker::TreeWalker::new(target_dir)
+        .include(&file_license.pattern)
+        .exclude("components")
+        .exclude("node_modules")
+        .walk()
+        .into_iter()
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Checks a component's `LICENSES_FILE`, making sure that it can be read, that every license in it is a valid
+/// SPDX license expression and that every pattern matches at least one of the component's files, so that a
+/// license isn't claimed for a file that has been renamed or removed. Warnings about license identifiers that
+/// aren't on the SPDX license list, see `spdx::check_ids`, are added to `stderr` as well.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+///
+/// The output status is 510 if there are any problems, and each problem is added to `stderr`.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("CERN-OHL-S-2.0"), String::from("CC0-1.0"));
+/// std::fs::write(temp_dir.join("toplevel").join("licenses.yaml"), "docs/photo.jpg: CC-BY-4.0\n").unwrap();
+///
+/// // The photo hasn't been added yet
+/// let output = sliderule::file_licenses::validate_file_licenses(&temp_dir.join("toplevel"));
+/// assert_eq!(510, output.status);
+///
+/// std::fs::write(temp_dir.join("toplevel").join("docs").join("photo.jpg"), "").unwrap();
+///
+/// let output = sliderule::file_licenses::validate_file_licenses(&temp_dir.join("toplevel"));
+/// assert_eq!(0, output.status);
+/// ```
+pub fn validate_file_licenses(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    for problem in file_license_problems(target_dir) {
+        output.status = 510;
+        output.stderr.push(format!("ERROR: {}", problem));
+    }
+
+    // The warnings come with the licenses that are valid, since the others are already problems
+    for file_license in read_file_licenses(target_dir).unwrap_or_default() {
+        let license_output = super::validate_license(&file_license.license);
+        if license_output.status == 0 {
+            output.stderr.extend(license_output.stderr);
+        }
+    }
+
+    output
+}
+
+/// Collects the problems with a component's `LICENSES_FILE`, see `validate_file_licenses`.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn file_license_problems(target_dir: &Path) -> Vec<String> {
+    let file_licenses = match read_file_licenses(target_dir) {
+        Ok(f) => f,
+        Err(e) => return vec![e],
+    };
+
+    let mut problems = Vec::new();
+    for file_license in file_licenses {
+        if super::validate_license(&file_license.license).status != 0 {
+            problems.push(format!(
+                "The license {} of {} in {} is not a valid SPDX license expression.",
+                file_license.license, file_license.pattern, LICENSES_FILE
+            ));
+        }
+
+        if annotated_files(target_dir, &file_license).is_empty() {
+            problems.push(format!(
+                "No files match {} in {}, it needs to be updated or removed.",
+                file_license.pattern, LICENSES_FILE
+            ));
+        }
+    }
+
+    problems
+}
+
+/*
+ * Takes the quotes off of a YAML key or value, if it has any.
+ */
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    for quote in ["'", "\""] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return value[1..value.len() - 1].to_string();
+        }
+    }
+
+    value.to_string()
+}
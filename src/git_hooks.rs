@@ -0,0 +1,220 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The git hooks that `install_hooks` writes.
+pub const HOOKS: [&str; 2] = ["pre-commit", "pre-push"];
+
+/// The line that marks a hook as one that `install_hooks` wrote, so that `uninstall_hooks` only ever removes those.
+pub const HOOK_MARKER: &str = "# sliderule-managed-hook";
+
+/// What is added to the name of a hook that was already there when `install_hooks` ran. The hook is kept and still
+/// runs first, and `uninstall_hooks` puts it back.
+pub const BACKUP_SUFFIX: &str = ".pre-sliderule";
+
+/// Writes git hooks into a component's repository that check the component before every commit and push, so that
+/// contributors to a shared component can't push changes that break its structure, licenses or bill of materials.
+/// The hooks run the project's `hook_command` (see `config::ProjectConfig`) in the top level directory of the
+/// repository, and stop the commit or push when it fails. A contributor who doesn't have the command installed is
+/// told so, and can still skip the check with git's `--no-verify`.
+///
+/// Hooks that were already there are kept, with `BACKUP_SUFFIX` added to their names, and run before the check.
+/// Installing the hooks again brings them up to date with the configuration.
+///
+/// `target_dir` must be a git repository.
+///
+/// The output status is 89 if the repository's hooks directory can't be found or the hooks can't be written.
+///
+/// # Examples
+///
+/// ```no_run
+/// let output = sliderule::git_hooks::install_hooks(&std::env::temp_dir().join("toplevel"));
+///
+/// assert_eq!(0, output.status);
+/// ```
+pub fn install_hooks(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let hooks_dir = match hooks_dir(target_dir) {
+        Ok(d) => d,
+        Err(e) => {
+            output.status = 89;
+            output.stderr.push(e);
+            return output;
+        }
+    };
+
+    let command = super::config::config_or_default(target_dir).hook_command;
+
+    for hook in HOOKS.iter() {
+        let hook_file = hooks_dir.join(hook);
+        let backup_file = hooks_dir.join(format!("{}{}", hook, BACKUP_SUFFIX));
+
+        let result = (|| -> std::io::Result<()> {
+            fs::create_dir_all(&hooks_dir)?;
+
+            // Keep a hook that someone else wrote, unless it's already been kept
+            if hook_file.exists() && !is_managed(&hook_file) && !backup_file.exists() {
+                fs::rename(&hook_file, &backup_file)?;
+            }
+
+            fs::write(&hook_file, hook_script(hook, &command))?;
+            make_executable(&hook_file)
+        })();
+
+        match result {
+            Ok(()) => output.stdout.push(format!("Installed the {} hook.", hook)),
+            Err(e) => {
+                output.status = 89;
+                output
+                    .stderr
+                    .push(format!("ERROR: Unable to install the {} hook: {}", hook, e));
+            }
+        }
+    }
+
+    output
+}
+
+/// Removes the hooks that `install_hooks` wrote, and puts back any hooks that were there before them. Hooks that
+/// Sliderule didn't write are left alone.
+///
+/// `target_dir` must be a git repository.
+///
+/// The output status is 90 if the repository's hooks directory can't be found or a hook can't be removed.
+///
+/// # Examples
+///
+/// ```no_run
+/// let output = sliderule::git_hooks::uninstall_hooks(&std::env::temp_dir().join("toplevel"));
+///
+/// assert_eq!(0, output.status);
+/// ```
+pub fn uninstall_hooks(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let hooks_dir = match hooks_dir(target_dir) {
+        Ok(d) => d,
+        Err(e) => {
+            output.status = 90;
+            output.stderr.push(e);
+            return output;
+        }
+    };
+
+    for hook in HOOKS.iter() {
+        let hook_file = hooks_dir.join(hook);
+        let backup_file = hooks_dir.join(format!("{}{}", hook, BACKUP_SUFFIX));
+
+        if hook_file.exists() && !is_managed(&hook_file) {
+            continue;
+        }
+
+        let result = (|| -> std::io::Result<()> {
+            if hook_file.exists() {
+                fs::remove_file(&hook_file)?;
+            }
+            if backup_file.exists() {
+                fs::rename(&backup_file, &hook_file)?;
+            }
+            Ok(())
+        })();
+
+        match result {
+            Ok(()) => output
+                .stdout
+                .push(format!("Uninstalled the {} hook.", hook)),
+            Err(e) => {
+                output.status = 90;
+                output.stderr.push(format!(
+                    "ERROR: Unable to uninstall the {} hook: {}",
+                    hook, e
+                ));
+            }
+        }
+    }
+
+    output
+}
+
+/*
+ * The directory that git runs the repository's hooks from.
+ */
+fn hooks_dir(target_dir: &Path) -> Result<PathBuf, String> {
+    let hooks_output = super::git_sr::git_hooks_dir(target_dir);
+    let dir = hooks_output.stdout.first().cloned().unwrap_or_default();
+    if hooks_output.status != 0 || hooks_output.wrapped_status != 0 || dir.is_empty() {
+        return Err(String::from(
+            "ERROR: Component is not set up as a repository, so it has no git hooks.",
+        ));
+    }
+
+    Ok(target_dir.join(dir))
+}
+
+/*
+ * Whether a hook is one that install_hooks wrote.
+ */
+fn is_managed(hook_file: &Path) -> bool {
+    fs::read_to_string(hook_file)
+        .map(|contents| contents.lines().any(|line| line == HOOK_MARKER))
+        .unwrap_or(false)
+}
+
+/*
+ * The shell script for a hook, which git runs with sh on every platform. It always uses LF line endings, since
+ * sh doesn't understand CRLF ones.
+ */
+fn hook_script(hook: &str, command: &[String]) -> String {
+    let quoted: Vec<String> = command
+        .iter()
+        .map(|arg| format!("'{}'", arg.replace('\'', "'\\''")))
+        .collect();
+    let program = quoted.first().cloned().unwrap_or_default();
+    let action = if hook == "pre-push" { "push" } else { "commit" };
+
+    let mut script = String::from("#!/bin/sh\n");
+    script.push_str(HOOK_MARKER);
+    script.push('\n');
+    script.push_str(&format!(
+        "# Checks the component before every {}. Written by Sliderule, remove it with uninstall_hooks.\n\n",
+        action
+    ));
+    script.push_str(&format!(
+        "if [ -x \"$0{}\" ]; then\n    \"$0{}\" \"$@\" || exit $?\nfi\n\n",
+        BACKUP_SUFFIX, BACKUP_SUFFIX
+    ));
+    script.push_str("cd \"$(git rev-parse --show-toplevel)\" || exit 1\n\n");
+    script.push_str(&format!(
+        "if ! command -v {0} >/dev/null 2>&1; then\n    printf '%s is needed to check this component before a {1}, install it or skip the check with --no-verify.\\n' {0} >&2\n    exit 1\nfi\n\n",
+        program, action
+    ));
+    script.push_str(&format!("exec {}\n", quoted.join(" ")));
+
+    script
+}
+
+/*
+ * Lets git run a hook, which it skips unless the hook is executable.
+ */
+fn make_executable(path: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    #[cfg(not(unix))]
+    let _ = path;
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
-extern crate os_info;
-
-use std::path::Path;
+use std::ffi::OsString;
+use std::fmt;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 struct Args {
@@ -22,28 +22,25 @@ pub fn git_init(target_dir: &Path, url: &str) -> super::SROutput {
     };
 
     // Initialize the current directory as a git repo
-    let stdoutput = match Command::new("git")
-        .args(&["init"])
-        .current_dir(target_dir)
-        .output()
-    {
-        Ok(out) => out,
-        Err(e) => {
-            if let std::io::ErrorKind::NotFound = e.kind() {
-                output.status = 106;
-                output
-                    .stderr
-                    .push(format!("ERROR: `git` was not found, please install: {}", e));
-                return output;
-            } else {
-                output.status = 107;
-                output
-                    .stderr
-                    .push(format!("ERROR: Could not initialize git repository: {}", e));
-                return output;
+    let stdoutput =
+        match super::backend::run(Command::new("git").args(["init"]).current_dir(target_dir)) {
+            Ok(out) => out,
+            Err(e) => {
+                if let std::io::ErrorKind::NotFound = e.kind() {
+                    output.status = 106;
+                    output
+                        .stderr
+                        .push(format!("ERROR: `git` was not found, please install: {}", e));
+                    return output;
+                } else {
+                    output.status = 107;
+                    output
+                        .stderr
+                        .push(format!("ERROR: Could not initialize git repository: {}", e));
+                    return output;
+                }
             }
-        }
-    };
+        };
     // init success
     output
         .stderr
@@ -52,15 +49,30 @@ pub fn git_init(target_dir: &Path, url: &str) -> super::SROutput {
     if !output.stderr.is_empty() {
         output
             .stderr
-            .push(String::from_utf8_lossy(&stdoutput.stderr).to_string());
+            .push(super::redact::redact(&String::from_utf8_lossy(
+                &stdoutput.stderr,
+            )));
+    }
+
+    // Start out on the branch that changes will be pushed to, whatever git's own default is
+    let branch = super::config::config_or_default(target_dir).default_branch;
+    if let Err(e) = super::backend::run(
+        Command::new("git")
+            .args(["symbolic-ref", "HEAD", &format!("refs/heads/{}", branch)])
+            .current_dir(target_dir),
+    ) {
+        output.stderr.push(format!(
+            "WARNING: Unable to switch the repository to the {} branch: {}",
+            branch, e
+        ));
     }
 
     // Add the remote URL
-    let stdoutput = match Command::new("git")
-        .args(&["remote", "add", "origin", url])
-        .current_dir(target_dir)
-        .output()
-    {
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["remote", "add", "origin", url])
+            .current_dir(target_dir),
+    ) {
         Ok(out) => out,
         Err(e) => {
             output.status = 108;
@@ -79,12 +91,93 @@ pub fn git_init(target_dir: &Path, url: &str) -> super::SROutput {
     if !output.stderr.is_empty() {
         output
             .stderr
-            .push(String::from_utf8_lossy(&stdoutput.stderr).to_string());
+            .push(super::redact::redact(&String::from_utf8_lossy(
+                &stdoutput.stderr,
+            )));
     }
 
     output
 }
 
+/// A username and password (or access token) for an HTTPS remote. They are handed to git for the length of a
+/// single command and are never written to the repository's configuration.
+#[derive(Clone, PartialEq)]
+pub struct GitCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+// Keep the password out of anything that gets printed
+impl fmt::Debug for GitCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GitCredentials")
+            .field("username", &self.username)
+            .field("password", &"********")
+            .finish()
+    }
+}
+
+/// The name and email address that commits are made with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitAuthor {
+    pub name: String,
+    pub email: String,
+}
+
+// A credential helper that answers git from environment variables, so the secret is never on a command line
+const ENV_CREDENTIAL_HELPER: &str =
+    "!f() { test \"$1\" = get && echo \"username=$SR_GIT_USERNAME\" && echo \"password=$SR_GIT_PASSWORD\"; }; f";
+
+/*
+ * Sets a git command up to use the given credentials instead of any configured credential helpers.
+ * This has to be called before the subcommand's arguments are added.
+ */
+fn use_credentials(command: &mut Command, credentials: Option<&GitCredentials>) {
+    if let Some(creds) = credentials {
+        // git shouldn't echo the password, but make sure it can't end up in the output if it does
+        super::redact::add_secret(&creds.password);
+
+        command
+            .args([
+                "-c",
+                "credential.helper=",
+                "-c",
+                &format!("credential.helper={}", ENV_CREDENTIAL_HELPER),
+            ])
+            .env("SR_GIT_USERNAME", &creds.username)
+            .env("SR_GIT_PASSWORD", &creds.password)
+            .env("GIT_TERMINAL_PROMPT", "0");
+    }
+}
+
+/*
+ * Arguments that keep git from escaping the spaces and non-ASCII characters in the paths that it lists, i.e.
+ * `"\303\274.stl"` for `ü.stl`, so that they can be matched against the files in the component.
+ */
+const UNQUOTED_PATHS: [&str; 2] = ["-c", "core.quotePath=false"];
+
+/*
+ * Sets a git command up to authenticate with the personal access token for an HTTPS URL, if one can be found.
+ */
+fn use_token(command: &mut Command, url: &str) {
+    let credentials = super::auth::credentials_for_url(url);
+    use_credentials(command, credentials.as_ref());
+}
+
+/*
+ * Gets the URL of a component's origin remote, which is empty if it doesn't have one.
+ */
+fn origin_url(target_dir: &Path) -> String {
+    match super::backend::run(
+        Command::new("git")
+            .args(["config", "--get", "remote.origin.url"])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => String::from_utf8_lossy(&out.stdout).trim().to_string(),
+        Err(_) => String::new(),
+    }
+}
+
 /// Adds, commits and pushes any local component changes to the remote git repo.
 ///
 /// `target_dir` must be a valid Sliderule component directory.
@@ -92,50 +185,50 @@ pub fn git_init(target_dir: &Path, url: &str) -> super::SROutput {
 ///
 /// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
 pub fn git_add_and_commit(target_dir: &Path, message: String) -> super::SROutput {
-    let mut output = super::SROutput {
-        status: 0,
-        wrapped_status: 0,
-        stdout: Vec::new(),
-        stderr: Vec::new(),
-    };
+    git_add_commit_and_push(target_dir, message, None)
+}
 
-    // git add .
-    let stdoutput = match Command::new("git")
-        .args(&["add", "."])
-        .current_dir(target_dir)
-        .output()
-    {
-        Ok(out) => out,
-        Err(e) => {
-            output.status = 103;
-            output
-                .stderr
-                .push(format!("ERROR: Unable to stage changes using git: {}", e));
-            return output;
-        }
-    };
-    // Collect all of the other stdout entries
-    output
-        .stdout
-        .push(String::from_utf8_lossy(&stdoutput.stdout).to_string());
-    // Staging success
-    output
-        .stdout
-        .push(String::from("Changes staged using git."));
-    // Staging stderr
-    output
-        .stderr
-        .push(String::from_utf8_lossy(&stdoutput.stderr).to_string());
+/// Adds, commits and pushes any local component changes to the remote git repo, the same as
+/// `git_add_and_commit`, but authenticates the push with `credentials` if they are given.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+/// `message` commit message to attach to the changes when pushing to the remote repository.
+/// `credentials` are used for the push only, and otherwise git's own credential helpers are used.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_add_commit_and_push(
+    target_dir: &Path,
+    message: String,
+    credentials: Option<&GitCredentials>,
+) -> super::SROutput {
+    git_add_commit_and_push_as(target_dir, message, credentials, None)
+}
 
-    let info = os_info::get();
+/// Adds, commits and pushes any local component changes the same way as `git_add_commit_and_push`, with the
+/// commit made by `author`. Without an author, the one from the project's configuration is used, and if that
+/// isn't set either, git's own `user.name` and `user.email`.
+///
+/// The output status is 126 if there is no identity that git can commit with.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_add_commit_and_push_as(
+    target_dir: &Path,
+    message: String,
+    credentials: Option<&GitCredentials>,
+    author: Option<&GitAuthor>,
+) -> super::SROutput {
+    let mut output = git_add_and_commit_as(target_dir, message, author);
+    if output.status != 0 {
+        return output;
+    }
 
     // git push will hang in some configurations on Windows if we don't disable the git sendpack.sideband option
-    if info.os_type() == os_info::Type::Windows {
-        let stdoutput = match Command::new("git")
-            .args(&["config", "--local", "sendpack.sideband", "false"])
-            .current_dir(target_dir)
-            .output()
-        {
+    if super::on_windows() {
+        let stdoutput = match super::backend::run(
+            Command::new("git")
+                .args(["config", "--local", "sendpack.sideband", "false"])
+                .current_dir(target_dir),
+        ) {
             Ok(out) => out,
             Err(e) => {
                 output.status = 109;
@@ -149,69 +242,166 @@ pub fn git_add_and_commit(target_dir: &Path, message: String) -> super::SROutput
         // Collect all of the other stdout entries
         output
             .stdout
-            .push(String::from_utf8_lossy(&stdoutput.stdout).to_string());
+            .push(super::redact::redact(&String::from_utf8_lossy(
+                &stdoutput.stdout,
+            )));
         // Staging stderr
         output
             .stderr
-            .push(String::from_utf8_lossy(&stdoutput.stderr).to_string());
+            .push(super::redact::redact(&String::from_utf8_lossy(
+                &stdoutput.stderr,
+            )));
     }
 
-    // git commit -m [message]
-    let stdoutput = match Command::new("git")
-        .args(&["commit", "-m", &message])
-        .current_dir(target_dir)
-        .output()
-    {
+    // git push origin [branch]
+    let branch = super::config::config_or_default(target_dir).default_branch;
+    let mut push = Command::new("git");
+    match credentials {
+        Some(_) => use_credentials(&mut push, credentials),
+        None => use_token(&mut push, &origin_url(target_dir)),
+    }
+    let stdoutput = match super::backend::run(
+        push.args(["push", "origin", &branch])
+            .current_dir(target_dir),
+    ) {
         Ok(out) => out,
         Err(e) => {
-            output.status = 104;
+            output.status = 105;
+            output.stderr.push(format!(
+                "ERROR: Unable to push changes to remote git repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+    // Push success
+    output
+        .stdout
+        .push(String::from("Changes pushed using git."));
+    // Push stderr
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    output
+}
+
+/// Adds and commits any local component changes without pushing them anywhere. The commit is made by `author`
+/// in the same way as `git_add_commit_and_push_as`.
+///
+/// The output status is 126 if there is no identity that git can commit with.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_add_and_commit_as(
+    target_dir: &Path,
+    message: String,
+    author: Option<&GitAuthor>,
+) -> super::SROutput {
+    let author = match author {
+        Some(a) => Some(a.clone()),
+        None => super::config::config_or_default(target_dir).author(),
+    };
+
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    // git add .
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["add", "."])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 103;
             output
                 .stderr
-                .push(format!("ERROR: Unable to commit changes using git: {}", e));
+                .push(format!("ERROR: Unable to stage changes using git: {}", e));
             return output;
         }
     };
     // Collect all of the other stdout entries
     output
         .stdout
-        .push(String::from_utf8_lossy(&stdoutput.stdout).to_string());
-    // Commit success
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+    // Staging success
     output
         .stdout
-        .push(String::from("Changes committed using git."));
-    // Commit stderr
+        .push(String::from("Changes staged using git."));
+    // Staging stderr
     output
         .stderr
-        .push(String::from_utf8_lossy(&stdoutput.stderr).to_string());
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
 
-    // git push origin master
-    let stdoutput = match Command::new("git")
-        .args(&["push", "origin", "master"])
-        .current_dir(target_dir)
-        .output()
-    {
+    // git commit -m [message]
+    let mut commit = Command::new("git");
+    if let Some(ref a) = author {
+        // Used for both the author and the committer
+        commit
+            .arg("-c")
+            .arg(format!("user.name={}", a.name))
+            .arg("-c")
+            .arg(format!("user.email={}", a.email));
+    }
+    let stdoutput = match super::backend::run(
+        commit
+            .args(["commit", "-m", &message])
+            .current_dir(target_dir),
+    ) {
         Ok(out) => out,
         Err(e) => {
-            output.status = 105;
-            output.stderr.push(format!(
-                "ERROR: Unable to push changes to remote git repository: {}",
-                e
-            ));
+            output.status = 104;
+            output
+                .stderr
+                .push(format!("ERROR: Unable to commit changes using git: {}", e));
             return output;
         }
     };
+    // Machines that have never had git set up on them don't have anyone to commit as
+    let commit_stderr = String::from_utf8_lossy(&stdoutput.stderr);
+    if commit_stderr.contains("Please tell me who you are")
+        || commit_stderr.contains("unable to auto-detect email address")
+    {
+        output.status = 126;
+        output.wrapped_status = stdoutput.status.code().unwrap_or(1);
+        output.stderr.push(String::from(
+            "ERROR: git has no identity to commit with, set author_name and author_email in the [git] section of the configuration or pass an author.",
+        ));
+        return output;
+    }
+
     // Collect all of the other stdout entries
     output
         .stdout
-        .push(String::from_utf8_lossy(&stdoutput.stdout).to_string());
-    // Push success
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+    // Commit success
     output
         .stdout
-        .push(String::from("Changes pushed using git."));
-    // Push stderr
+        .push(String::from("Changes committed using git."));
+    // Commit stderr
     output
         .stderr
-        .push(String::from_utf8_lossy(&stdoutput.stderr).to_string());
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
 
     output
 }
@@ -220,6 +410,9 @@ pub fn git_add_and_commit(target_dir: &Path, message: String) -> super::SROutput
 ///
 /// `target_dir` must be a valid Sliderule component directory.
 ///
+/// The branch that is pulled and whether the changes are merged, rebased or only fast forwarded come from the
+/// project's configuration, see `config::ProjectConfig`.
+///
 /// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
 pub fn git_pull(target_dir: &Path) -> super::SROutput {
     let mut output = super::SROutput {
@@ -229,12 +422,20 @@ pub fn git_pull(target_dir: &Path) -> super::SROutput {
         stderr: Vec::new(),
     };
 
+    let config = super::config::config_or_default(target_dir);
+    let strategy = match config.pull_strategy {
+        super::config::PullStrategy::Merge => "--no-rebase",
+        super::config::PullStrategy::Rebase => "--rebase",
+        super::config::PullStrategy::FastForwardOnly => "--ff-only",
+    };
+
     // Run the pull command
-    let stdoutput = match Command::new("git")
-        .args(&["pull", "origin", "master"])
-        .current_dir(target_dir)
-        .output()
-    {
+    let mut pull = Command::new("git");
+    use_token(&mut pull, &origin_url(target_dir));
+    let stdoutput = match super::backend::run(
+        pull.args(["pull", strategy, "origin", &config.default_branch])
+            .current_dir(target_dir),
+    ) {
         Ok(out) => out,
         Err(e) => {
             output.status = 100;
@@ -257,28 +458,32 @@ pub fn git_pull(target_dir: &Path) -> super::SROutput {
     // Collect all of the other stdout entries
     output
         .stdout
-        .push(String::from_utf8_lossy(&stdoutput.stdout).to_string());
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
 
     // If there were errors, make sure we collect them
     output
         .stderr
-        .push(String::from_utf8_lossy(&stdoutput.stderr).to_string());
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
 
     // If we have something other than a 0 exit status, report that
-    if stdoutput.status.code().unwrap() != 0 {
-        output.wrapped_status = stdoutput.status.code().unwrap();
-    }
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
 
     output
 }
 
-/// Interface to the git command to download a component from a repo.
+/// Pulls the default branch of the repository at `url` into a component, which doesn't have to be one of the
+/// component's remotes. The changes are merged, rebased or only fast forwarded according to the project's
+/// configuration, the same as `git_pull`, and any commit that's needed is made by the configured author.
 ///
 /// `target_dir` must be a valid Sliderule component directory.
-/// 'url' The URL of the remote repository to clone (copy).
+/// `url` URL of the repository to pull from.
 ///
 /// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
-pub fn git_clone(target_dir: &Path, url: &str) -> super::SROutput {
+pub fn git_pull_from(target_dir: &Path, url: &str) -> super::SROutput {
     let mut output = super::SROutput {
         status: 0,
         wrapped_status: 0,
@@ -286,16 +491,33 @@ pub fn git_clone(target_dir: &Path, url: &str) -> super::SROutput {
         stderr: Vec::new(),
     };
 
-    let stdoutput = match Command::new("git")
-        .args(&["clone", "--recursive", url])
-        .current_dir(target_dir)
-        .output()
-    {
+    let config = super::config::config_or_default(target_dir);
+    let strategy = match config.pull_strategy {
+        super::config::PullStrategy::Merge => "--no-rebase",
+        super::config::PullStrategy::Rebase => "--rebase",
+        super::config::PullStrategy::FastForwardOnly => "--ff-only",
+    };
+
+    let mut pull = Command::new("git");
+    if let Some(a) = config.author() {
+        // Merge commits and rebased commits need someone to commit as
+        pull.arg("-c")
+            .arg(format!("user.name={}", a.name))
+            .arg("-c")
+            .arg(format!("user.email={}", a.email));
+    }
+    use_token(&mut pull, url);
+    let stdoutput = match super::backend::run(
+        pull.args(["pull", "--no-edit", strategy, "--", url])
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .current_dir(target_dir),
+    ) {
         Ok(out) => out,
         Err(e) => {
-            output.status = 102;
+            output.status = 128;
             output.stderr.push(format!(
-                "ERROR: Unable to clone component repository: {}",
+                "ERROR: Unable to pull from {}: {}",
+                super::redact::redact(url),
                 e
             ));
             return output;
@@ -305,28 +527,29 @@ pub fn git_clone(target_dir: &Path, url: &str) -> super::SROutput {
     // Collect all of the other stdout entries
     output
         .stdout
-        .push(String::from_utf8_lossy(&stdoutput.stdout).to_string());
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
 
     // If there were errors, make sure we collect them
     output
         .stderr
-        .push(String::from_utf8_lossy(&stdoutput.stderr).to_string());
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
 
     // If we have something other than a 0 exit status, report that
-    if stdoutput.status.code().unwrap() != 0 {
-        output.wrapped_status = stdoutput.status.code().unwrap();
-    }
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
 
     output
 }
 
-/// Changes the URL for the remote repository for the component.
+/// Lists the files in a component that have unresolved merge conflicts, one per line of `stdout`.
 ///
 /// `target_dir` must be a valid Sliderule component directory.
-/// `url` URL to set for the remote repository.
 ///
 /// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
-pub fn git_set_remote_url(target_dir: &Path, url: &str) -> super::SROutput {
+pub fn git_conflicted_files(target_dir: &Path) -> super::SROutput {
     let mut output = super::SROutput {
         status: 0,
         wrapped_status: 0,
@@ -334,16 +557,71 @@ pub fn git_set_remote_url(target_dir: &Path, url: &str) -> super::SROutput {
         stderr: Vec::new(),
     };
 
-    let stdoutput = match Command::new("git")
-        .args(&["remote", "set-url", "origin", url])
-        .current_dir(target_dir)
-        .output()
-    {
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(UNQUOTED_PATHS)
+            .args(["diff", "--name-only", "--diff-filter=U"])
+            .current_dir(target_dir),
+    ) {
         Ok(out) => out,
         Err(e) => {
-            output.status = 110;
+            output.status = 129;
             output.stderr.push(format!(
-                "ERROR: Unable to change the URL on the component repository: {}",
+                "ERROR: Unable to list the conflicted files in the component: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    output.stdout.extend(
+        String::from_utf8_lossy(&stdoutput.stdout)
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.to_string()),
+    );
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Interface to the git command to download a component from a repo.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+/// 'url' The URL of the remote repository to clone (copy).
+///
+/// How far along the clone is gets passed to the progress callback as git downloads the repository.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_clone(target_dir: &Path, url: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let mut clone = Command::new("git");
+    use_token(&mut clone, url);
+    let stdoutput = match super::backend::run_with_progress(
+        clone
+            .args(["clone", "--recursive", "--progress", url])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 102;
+            output.stderr.push(format!(
+                "ERROR: Unable to clone component repository: {}",
                 e
             ));
             return output;
@@ -353,27 +631,56 @@ pub fn git_set_remote_url(target_dir: &Path, url: &str) -> super::SROutput {
     // Collect all of the other stdout entries
     output
         .stdout
-        .push(String::from_utf8_lossy(&stdoutput.stdout).to_string());
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
 
     // If there were errors, make sure we collect them
     output
         .stderr
-        .push(String::from_utf8_lossy(&stdoutput.stderr).to_string());
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
 
     // If we have something other than a 0 exit status, report that
-    if stdoutput.status.code().unwrap() != 0 {
-        output.wrapped_status = stdoutput.status.code().unwrap();
-    }
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
 
     output
 }
 
-/// Runs the equivalent of `git status` on a component to get a listing of the high-level changes.
+/// The oldest version of git that can make a blobless clone with `--filter=blob:none`.
+pub const MINIMUM_PARTIAL_CLONE_VERSION: &str = "2.19.0";
+
+/// Clones a component without downloading the contents of any files that aren't checked out, which leaves out
+/// every old revision of large binary files like CAD models. The missing contents are downloaded by git later
+/// if they are ever needed, i.e. when checking out an older commit.
+///
+/// If the installed version of git is older than `MINIMUM_PARTIAL_CLONE_VERSION`, a normal clone is made
+/// instead and a warning is added to `stderr`. Servers that don't support partial clones are also handled
+/// by git falling back to a normal clone. Progress is passed along the same way as for `git_clone`.
 ///
 /// `target_dir` must be a valid Sliderule component directory.
+/// `url` URL of the remote repository to clone.
 ///
 /// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
-pub fn git_status(target_dir: &Path) -> super::SROutput {
+pub fn git_clone_blobless(target_dir: &Path, url: &str) -> super::SROutput {
+    let version_output = git_version();
+    let supported = version_output.status == 0
+        && version_output.wrapped_status == 0
+        && super::environment::compare_versions(
+            &version_output.stdout[0],
+            MINIMUM_PARTIAL_CLONE_VERSION,
+        ) != ::std::cmp::Ordering::Less;
+
+    if !supported {
+        let mut output = git_clone(target_dir, url);
+        output.stderr.push(format!(
+            "WARNING: git {} or newer is needed for a blobless clone, a full clone was made instead.",
+            MINIMUM_PARTIAL_CLONE_VERSION
+        ));
+        return output;
+    }
+
     let mut output = super::SROutput {
         status: 0,
         wrapped_status: 0,
@@ -381,16 +688,24 @@ pub fn git_status(target_dir: &Path) -> super::SROutput {
         stderr: Vec::new(),
     };
 
-    let stdoutput = match Command::new("git")
-        .args(&["status"])
-        .current_dir(target_dir)
-        .output()
-    {
+    let mut clone = Command::new("git");
+    use_token(&mut clone, url);
+    let stdoutput = match super::backend::run_with_progress(
+        clone
+            .args([
+                "clone",
+                "--recursive",
+                "--progress",
+                "--filter=blob:none",
+                url,
+            ])
+            .current_dir(target_dir),
+    ) {
         Ok(out) => out,
         Err(e) => {
-            output.status = 111;
+            output.status = 102;
             output.stderr.push(format!(
-                "ERROR: Unable to change the URL on the component repository: {}",
+                "ERROR: Unable to clone component repository: {}",
                 e
             ));
             return output;
@@ -400,27 +715,30 @@ pub fn git_status(target_dir: &Path) -> super::SROutput {
     // Collect all of the other stdout entries
     output
         .stdout
-        .push(String::from_utf8_lossy(&stdoutput.stdout).to_string());
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
 
     // If there were errors, make sure we collect them
     output
         .stderr
-        .push(String::from_utf8_lossy(&stdoutput.stderr).to_string());
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
 
     // If we have something other than a 0 exit status, report that
-    if stdoutput.status.code().unwrap() != 0 {
-        output.wrapped_status = stdoutput.status.code().unwrap();
-    }
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
 
-    return output;
+    output
 }
 
-/// Runs the equivalent of `git status` on a component to get the detailed changes per file.
+/// Changes the URL for the remote repository for the component.
 ///
 /// `target_dir` must be a valid Sliderule component directory.
+/// `url` URL to set for the remote repository.
 ///
 /// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
-pub fn git_diff(target_dir: &Path) -> super::SROutput {
+pub fn git_set_remote_url(target_dir: &Path, url: &str) -> super::SROutput {
     let mut output = super::SROutput {
         status: 0,
         wrapped_status: 0,
@@ -428,14 +746,14 @@ pub fn git_diff(target_dir: &Path) -> super::SROutput {
         stderr: Vec::new(),
     };
 
-    let stdoutput = match Command::new("git")
-        .args(&["--no-pager", "diff"])
-        .current_dir(target_dir)
-        .output()
-    {
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["remote", "set-url", "origin", url])
+            .current_dir(target_dir),
+    ) {
         Ok(out) => out,
         Err(e) => {
-            output.status = 112;
+            output.status = 110;
             output.stderr.push(format!(
                 "ERROR: Unable to change the URL on the component repository: {}",
                 e
@@ -447,17 +765,1934 @@ pub fn git_diff(target_dir: &Path) -> super::SROutput {
     // Collect all of the other stdout entries
     output
         .stdout
-        .push(String::from_utf8_lossy(&stdoutput.stdout).to_string());
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
 
     // If there were errors, make sure we collect them
     output
         .stderr
-        .push(String::from_utf8_lossy(&stdoutput.stderr).to_string());
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
 
     // If we have something other than a 0 exit status, report that
-    if stdoutput.status.code().unwrap() != 0 {
-        output.wrapped_status = stdoutput.status.code().unwrap();
-    }
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
 
-    return output;
+    output
+}
+
+/// Pushes the branch that's checked out in a component, along with all of its tags, to the repository at `url`.
+/// The branch keeps the same name in the other repository.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+/// `url` URL of the repository to push to, which doesn't have to be one of the component's remotes.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_push_to(target_dir: &Path, url: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let mut push = Command::new("git");
+    use_token(&mut push, url);
+    let stdoutput = match super::backend::run(
+        push.args(["push", "--tags", url, "HEAD"])
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 127;
+            output.stderr.push(format!(
+                "ERROR: Unable to push the component to {}: {}",
+                super::redact::redact(url),
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Runs the equivalent of `git status` on a component to get a listing of the high-level changes.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_status(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput =
+        match super::backend::run(Command::new("git").args(["status"]).current_dir(target_dir)) {
+            Ok(out) => out,
+            Err(e) => {
+                output.status = 111;
+                output.stderr.push(format!(
+                    "ERROR: Unable to change the URL on the component repository: {}",
+                    e
+                ));
+                return output;
+            }
+        };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    return output;
+}
+
+/// Runs the equivalent of `git status` on a component to get the detailed changes per file.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_diff(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["--no-pager", "diff"])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 112;
+            output.stderr.push(format!(
+                "ERROR: Unable to change the URL on the component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    return output;
+}
+
+/// Runs `git --version` to find out which version of git is installed.
+///
+/// The version number alone (i.e. `2.20.1`) is placed in `stdout`. The output status is 106 if git
+/// could not be found.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_version() -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(Command::new("git").args(["--version"])) {
+        Ok(out) => out,
+        Err(e) => {
+            if let std::io::ErrorKind::NotFound = e.kind() {
+                output.status = 106;
+                output
+                    .stderr
+                    .push(format!("ERROR: `git` was not found, please install: {}", e));
+            } else {
+                output.status = 113;
+                output
+                    .stderr
+                    .push(format!("ERROR: Unable to get the version of git: {}", e));
+            }
+            return output;
+        }
+    };
+
+    // The output looks like `git version 2.20.1.windows.1`, so keep just the number
+    let version = String::from_utf8_lossy(&stdoutput.stdout)
+        .trim()
+        .trim_start_matches("git version")
+        .trim()
+        .to_string();
+    output.stdout.push(version);
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Returns true if git can be run from `target_dir`, using the git from the project's configuration there if it
+/// has one. Components are downloaded as archives instead when it can't.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_installed(target_dir: &Path) -> bool {
+    match super::backend::run(
+        Command::new("git")
+            .args(["--version"])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out.status.success(),
+        Err(_) => false,
+    }
+}
+
+/// Runs `git ls-remote` against a URL to check that the remote repository exists and can be reached.
+///
+/// `url` The URL of the remote repository to check.
+///
+/// git is not allowed to prompt for credentials, so a repository that needs them is reported as a
+/// failure through `wrapped_status` instead of hanging.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_ls_remote(url: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let mut ls_remote = Command::new("git");
+    use_token(&mut ls_remote, url);
+    let stdoutput = match super::backend::run(
+        ls_remote
+            .args(["ls-remote", "--heads", url])
+            .env("GIT_TERMINAL_PROMPT", "0"),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 114;
+            output.stderr.push(format!(
+                "ERROR: Unable to list the contents of the remote repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Runs the equivalent of `git fetch` on a component so that its upstream branch is up to date, without changing
+/// any of the files in the component. How far along the fetch is gets passed to the progress callback.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_fetch(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let mut fetch = Command::new("git");
+    use_token(&mut fetch, &origin_url(target_dir));
+    let stdoutput = match super::backend::run_with_progress(
+        fetch
+            .args(["fetch", "--progress"])
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 115;
+            output.stderr.push(format!(
+                "ERROR: Unable to fetch from the component's remote repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Runs the equivalent of `git status --porcelain --branch` on a component, which gives a machine readable
+/// summary of the branch, how far it is from its upstream branch, and which files have changed.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_status_porcelain(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(UNQUOTED_PATHS)
+            .args(["status", "--porcelain", "--branch"])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 116;
+            output.stderr.push(format!(
+                "ERROR: Unable to get the status of the component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Asks a remote repository which commit a branch, tag or other reference points to, without cloning it.
+/// The commit hash is added to `stdout`, and is empty if the reference doesn't exist.
+///
+/// `url` The URL of the remote repository.
+/// `reference` The reference to look up, i.e. `HEAD`, `master` or `v1.0.0`.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_remote_head(url: &str, reference: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let mut ls_remote = Command::new("git");
+    use_token(&mut ls_remote, url);
+    let stdoutput = match super::backend::run(
+        ls_remote
+            .args(["ls-remote", url, reference])
+            .env("GIT_TERMINAL_PROMPT", "0"),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 114;
+            output.stderr.push(format!(
+                "ERROR: Unable to list the contents of the remote repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Each line is a commit hash and reference name separated by a tab, the first match is the one we want
+    let listing = super::redact::redact(&String::from_utf8_lossy(&stdoutput.stdout));
+    let commit = match listing.lines().next() {
+        Some(line) => line.split('\t').next().unwrap_or("").trim().to_string(),
+        None => String::new(),
+    };
+    output.stdout.push(commit);
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Hands a username and password to whichever credential helper the user has configured for git, i.e. the
+/// operating system's keychain, so that later pushes to `url` can authenticate without the password being
+/// stored in the repository. If no credential helper is configured, git discards them.
+///
+/// `url` The HTTPS URL that the credentials are for.
+/// `credentials` The username and password to store.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_credential_approve(url: &str, credentials: &GitCredentials) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    // The credentials go in on stdin so that they never show up in the process list
+    let input = format!(
+        "url={}\nusername={}\npassword={}\n\n",
+        url, credentials.username, credentials.password
+    );
+    let stdoutput = match super::backend::run_with_input(
+        Command::new("git").args(["credential", "approve"]),
+        input.as_bytes(),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 119;
+            output.stderr.push(format!(
+                "ERROR: Unable to store the credentials with git: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Lists the URLs of all of a component's remote repositories, with one `remote.<name>.url <url>` line per remote
+/// in the first `stdout` entry. A repository without any remotes gives an empty list and a `wrapped_status` of 1.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_remote_urls(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["config", "--local", "--get-regexp", "^remote\\..*\\.url$"])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 117;
+            output.stderr.push(format!(
+                "ERROR: Unable to read the remote URLs of the component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Sets a value in a component repository's local git configuration.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+/// `key` The configuration key, i.e. `remote.origin.url`.
+/// `value` The value to set the key to.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_config_set(target_dir: &Path, key: &str, value: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["config", "--local", key, value])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 118;
+            output.stderr.push(format!(
+                "ERROR: Unable to change the configuration of the component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// The keys that signatures on downloaded components are checked against. Either or both can be given, and
+/// anything that isn't given falls back to the user's own git and GnuPG configuration.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SignatureKeyring {
+    /// A GnuPG home directory holding the trusted public keys, used in place of `~/.gnupg`
+    pub gpg_home: Option<PathBuf>,
+    /// An ssh allowed signers file (see `ssh-keygen(1)`) listing the trusted ssh keys
+    pub allowed_signers: Option<PathBuf>,
+}
+
+/*
+ * Sets a git command up to check signatures against the keyring. This has to be called before the
+ * subcommand's arguments are added.
+ */
+fn use_keyring(command: &mut Command, keyring: &SignatureKeyring) {
+    if let Some(ref signers) = keyring.allowed_signers {
+        // Built from the path as is, since a lossy conversion would point git at the wrong file
+        let mut setting = OsString::from("gpg.ssh.allowedSignersFile=");
+        setting.push(signers);
+        command.arg("-c").arg(setting);
+    }
+
+    if let Some(ref home) = keyring.gpg_home {
+        command.env("GNUPGHOME", home);
+    }
+}
+
+/// Checks the GPG or ssh signature on a commit in a component's repository.
+///
+/// `target_dir` must be a git repository.
+/// `revision` The commit to check, i.e. `HEAD` or a commit hash.
+/// `keyring` The trusted keys that the signature has to be made with.
+///
+/// The `wrapped_status` is non-zero if the commit isn't signed by a trusted key.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_verify_commit(
+    target_dir: &Path,
+    revision: &str,
+    keyring: &SignatureKeyring,
+) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let mut verify = Command::new("git");
+    use_keyring(&mut verify, keyring);
+    let stdoutput = match super::backend::run(
+        verify
+            .args(["verify-commit", "--verbose", revision])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 120;
+            output.stderr.push(format!(
+                "ERROR: Unable to check the signature on the commit: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Checks the GPG or ssh signature on an annotated tag in a component's repository.
+///
+/// `target_dir` must be a git repository.
+/// `tag` The name of the tag to check.
+/// `keyring` The trusted keys that the signature has to be made with.
+///
+/// The `wrapped_status` is non-zero if the tag isn't signed by a trusted key.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_verify_tag(target_dir: &Path, tag: &str, keyring: &SignatureKeyring) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let mut verify = Command::new("git");
+    use_keyring(&mut verify, keyring);
+    let stdoutput = match super::backend::run(
+        verify
+            .args(["verify-tag", "--verbose", tag])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 121;
+            output.stderr.push(format!(
+                "ERROR: Unable to check the signature on the tag: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Makes a bare clone of a remote repository, which has the history and signatures without any checked out files.
+///
+/// `target_dir` The directory to clone into, which must not exist or be empty.
+/// `url` URL of the remote repository to clone.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_clone_bare(target_dir: &Path, url: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let mut clone = Command::new("git");
+    use_token(&mut clone, url);
+    let stdoutput = match super::backend::run(
+        clone
+            .args(["clone", "--bare", "--quiet", url])
+            .arg(target_dir)
+            .env("GIT_TERMINAL_PROMPT", "0"),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 122;
+            output.stderr.push(format!(
+                "ERROR: Unable to clone component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Lists the tags that point at a revision, with one tag per line in the first `stdout` entry.
+///
+/// `target_dir` must be a git repository.
+/// `revision` The revision to find tags for, i.e. `HEAD`.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_tags_at(target_dir: &Path, revision: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["tag", "--points-at", revision])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 123;
+            output.stderr.push(format!(
+                "ERROR: Unable to list the tags in the component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Finds the commit hash that a revision (i.e. a branch, tag or `HEAD`) refers to, which is put in `stdout[0]`.
+///
+/// `target_dir` must be a git repository.
+/// `revision` The revision to look up.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_rev_parse(target_dir: &Path, revision: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(format!("{}^{{commit}}", revision))
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 124;
+            output.stderr.push(format!(
+                "ERROR: Unable to look up the revision in the component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    output.stdout.push(
+        String::from_utf8_lossy(&stdoutput.stdout)
+            .trim()
+            .to_string(),
+    );
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Checks whether one commit is an ancestor of another, i.e. whether history was rewritten between them.
+///
+/// `target_dir` must be a git repository.
+/// `ancestor` The commit that should come first.
+/// `descendant` The commit that should contain `ancestor` in its history.
+///
+/// The `wrapped_status` is 0 if `ancestor` is an ancestor of `descendant`, 1 if it isn't, and something else
+/// if either commit doesn't exist in the repository.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_is_ancestor(target_dir: &Path, ancestor: &str, descendant: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["merge-base", "--is-ancestor", ancestor, descendant])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 125;
+            output.stderr.push(format!(
+                "ERROR: Unable to compare commits in the component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Lists the commits that are in `to` but not in `from`, newest first and leaving out merges, with one
+/// `hash<TAB>subject` line per commit in `stdout`.
+///
+/// `target_dir` must be a git repository.
+/// `from` The revision to start after, i.e. the tag of the previous release.
+/// `to` The revision to end at.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_log_between(target_dir: &Path, from: &str, to: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["--no-pager", "log", "--no-merges", "--format=%h%x09%s"])
+            .arg(format!("{}..{}", from, to))
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 130;
+            output.stderr.push(format!(
+                "ERROR: Unable to list the commits in the component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    for line in String::from_utf8_lossy(&stdoutput.stdout).lines() {
+        if !line.trim().is_empty() {
+            output.stdout.push(super::redact::redact(line));
+        }
+    }
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Reads a file as it was at a revision, without checking it out. The contents are placed in `stdout[0]`.
+///
+/// `target_dir` must be a git repository.
+/// `revision` The revision to read the file at, i.e. a tag.
+/// `path` The path of the file relative to the top of the repository, with `/` separators.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_show_file(target_dir: &Path, revision: &str, path: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["--no-pager", "show"])
+            .arg(format!("{}:{}", revision, path))
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 131;
+            output.stderr.push(format!(
+                "ERROR: Unable to read a file from the component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    output
+        .stdout
+        .push(String::from_utf8_lossy(&stdoutput.stdout).to_string());
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Lists every file that was tracked at a revision, with one path per entry in `stdout`, relative to the top
+/// of the repository.
+///
+/// `target_dir` must be a git repository.
+/// `revision` The revision to list the files of, i.e. a tag.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_files_at(target_dir: &Path, revision: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(UNQUOTED_PATHS)
+            .args(["ls-tree", "-r", "--name-only", revision])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 132;
+            output.stderr.push(format!(
+                "ERROR: Unable to list the files in the component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    for line in String::from_utf8_lossy(&stdoutput.stdout).lines() {
+        if !line.is_empty() {
+            output.stdout.push(line.to_string());
+        }
+    }
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Lists the files that are different between two commits, with one path per entry in `stdout`, relative to the
+/// top of the repository.
+///
+/// `target_dir` must be a git repository, and can be a bare one.
+/// `from` The earlier commit.
+/// `to` The later commit.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_changed_files(target_dir: &Path, from: &str, to: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(UNQUOTED_PATHS)
+            .args(["--no-pager", "diff", "--name-only", from, to])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 133;
+            output.stderr.push(format!(
+                "ERROR: Unable to list the changed files in the component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    for line in String::from_utf8_lossy(&stdoutput.stdout).lines() {
+        if !line.is_empty() {
+            output.stdout.push(line.to_string());
+        }
+    }
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Runs `git diff` between two commits, with renames found, and places the whole patch in `stdout[0]`.
+///
+/// `target_dir` must be a git repository, and can be a bare one.
+/// `from` The earlier commit.
+/// `to` The later commit.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_diff_between(target_dir: &Path, from: &str, to: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(UNQUOTED_PATHS)
+            .args([
+                "--no-pager",
+                "diff",
+                "--no-color",
+                "--no-ext-diff",
+                "--find-renames",
+                from,
+                to,
+            ])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 134;
+            output.stderr.push(format!(
+                "ERROR: Unable to compare commits in the component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    output
+        .stdout
+        .push(String::from_utf8_lossy(&stdoutput.stdout).to_string());
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Writes every branch and tag of a repository into a single bundle file, which can be cloned from later without
+/// any network access.
+///
+/// `target_dir` must be a git repository, and can be a bare one.
+/// `bundle_file` The file to write the bundle to.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_bundle_create(target_dir: &Path, bundle_file: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["bundle", "create", "--quiet"])
+            .arg(bundle_file)
+            .arg("--all")
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 135;
+            output.stderr.push(format!(
+                "ERROR: Unable to bundle the component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Lists the author of every commit that changed something in `target_dir`, newest first, with one `name<TAB>email`
+/// line per commit in `stdout`. Names and emails go through the repository's `.mailmap`, so people who have
+/// committed under more than one identity can be brought together there.
+///
+/// `target_dir` must be in a git repository with at least one commit.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_authors(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args([
+                "--no-pager",
+                "log",
+                "--format=%aN%x09%aE",
+                "HEAD",
+                "--",
+                ".",
+            ])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 136;
+            output.stderr.push(format!(
+                "ERROR: Unable to list the authors in the component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    for line in String::from_utf8_lossy(&stdoutput.stdout).lines() {
+        if !line.trim().is_empty() {
+            output.stdout.push(super::redact::redact(line));
+        }
+    }
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Tags the commit that's checked out in a component with an annotated tag, i.e. to mark a release.
+///
+/// `target_dir` must be a git repository with at least one commit.
+/// `tag` The name of the tag, which must not exist yet.
+/// `message` The message that the tag is annotated with.
+///
+/// The tag is made by the author from the project's configuration if there is one, and otherwise by git's own
+/// `user.name` and `user.email`.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_tag(target_dir: &Path, tag: &str, message: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    // The tag is made by the author from the project's configuration, the same as commits are
+    let mut tag_command = Command::new("git");
+    if let Some(a) = super::config::config_or_default(target_dir).author() {
+        tag_command
+            .arg("-c")
+            .arg(format!("user.name={}", a.name))
+            .arg("-c")
+            .arg(format!("user.email={}", a.email));
+    }
+    let stdoutput = match super::backend::run(
+        tag_command
+            .args(["tag", "--annotate", tag, "--message", message])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 137;
+            output.stderr.push(format!(
+                "ERROR: Unable to tag the component repository with {}: {}",
+                tag, e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Deletes a tag from a component's repository, leaving the commit that it pointed to where it is.
+///
+/// `target_dir` must be a git repository.
+/// `tag` The name of the tag to delete.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_delete_tag(target_dir: &Path, tag: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["tag", "--delete", tag])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 138;
+            output.stderr.push(format!(
+                "ERROR: Unable to delete the tag {} from the component repository: {}",
+                tag, e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Moves the branch that's checked out in a component back to `revision`, throwing away every change to the
+/// files that git tracks since then. Files that git ignores are left alone.
+///
+/// `target_dir` must be a git repository.
+/// `revision` The commit to go back to.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_reset_hard(target_dir: &Path, revision: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["reset", "--hard", "--quiet", revision])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 139;
+            output.stderr.push(format!(
+                "ERROR: Unable to reset the component repository to {}: {}",
+                revision, e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Pushes the branch that's checked out in a component and a tag to `origin` together, so that either both of them
+/// are pushed or neither is.
+///
+/// `target_dir` must be a git repository with an `origin` remote.
+/// `tag` The name of the tag to push along with the branch.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_push_atomic(target_dir: &Path, tag: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let mut push = Command::new("git");
+    use_token(&mut push, &origin_url(target_dir));
+    let stdoutput = match super::backend::run(
+        push.args(["push", "--atomic", "origin", "HEAD"])
+            .arg(format!("refs/tags/{}", tag))
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 140;
+            output.stderr.push(format!(
+                "ERROR: Unable to push the release to the remote repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Finds the directory that git runs a component's hooks from, which is put in `stdout[0]`. It's usually
+/// `.git/hooks`, but honors `core.hooksPath` and repositories whose `.git` is somewhere else. A relative path is
+/// relative to `target_dir`.
+///
+/// `target_dir` must be a git repository.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_hooks_dir(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["rev-parse", "--git-path", "hooks"])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 141;
+            output.stderr.push(format!(
+                "ERROR: Unable to find the hooks directory of the component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    output.stdout.push(
+        String::from_utf8_lossy(&stdoutput.stdout)
+            .trim()
+            .to_string(),
+    );
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Sets up git-annex in a component's repository, which is safe to do again in a repository that already has it,
+/// i.e. one that was just cloned.
+///
+/// `target_dir` must be a git repository.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_annex_init(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["annex", "init"])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 142;
+            output.stderr.push(format!(
+                "ERROR: Unable to set up git-annex in the component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Adds the files in a component to git-annex, or to git for the ones that `.gitattributes` doesn't make large.
+///
+/// `target_dir` must be a git repository that git-annex has been set up in.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_annex_add(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["annex", "add", "."])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 143;
+            output
+                .stderr
+                .push(format!("ERROR: Unable to add files to git-annex: {}", e));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Brings what git-annex knows about a component up to date with its remotes. With `push`, the contents of the
+/// annexed files are sent to the remotes as well, otherwise nothing is sent anywhere.
+///
+/// `target_dir` must be a git repository that git-annex has been set up in.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_annex_sync(target_dir: &Path, push: bool) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let mut sync = Command::new("git");
+    use_token(&mut sync, &origin_url(target_dir));
+    sync.args(["annex", "sync"]);
+    if push {
+        sync.arg("--content");
+    } else {
+        sync.arg("--no-push");
+    }
+    let stdoutput =
+        match super::backend::run(sync.env("GIT_TERMINAL_PROMPT", "0").current_dir(target_dir)) {
+            Ok(out) => out,
+            Err(e) => {
+                output.status = 144;
+                output.stderr.push(format!(
+                    "ERROR: Unable to sync git-annex with the remote repository: {}",
+                    e
+                ));
+                return output;
+            }
+        };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Downloads the contents of every annexed file in a component that isn't on this machine yet.
+///
+/// `target_dir` must be a git repository that git-annex has been set up in.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_annex_get(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["annex", "get", "."])
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 145;
+            output.stderr.push(format!(
+                "ERROR: Unable to download the files in git-annex: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Checks that every commit in a component's repository can be reached along with all of the objects that it
+/// needs, which is what a download that stopped partway may have left out.
+///
+/// `target_dir` must be a git repository.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_fsck_connectivity(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let stdoutput = match super::backend::run(
+        Command::new("git")
+            .args(["fsck", "--connectivity-only", "--no-progress"])
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 146;
+            output.stderr.push(format!(
+                "ERROR: Unable to check the objects in the component repository: {}",
+                e
+            ));
+            return output;
+        }
+    };
+
+    // Collect all of the other stdout entries
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "git", &stdoutput.status);
+
+    output
+}
+
+/// Does what's left of a clone once everything has been fetched from `origin`, which is checking out the remote's
+/// default branch and bringing in its submodules. Any changes to the files that git tracks are thrown away.
+///
+/// `target_dir` must be a git repository with an `origin` remote.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn git_finish_clone(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    // Find out which branch the remote checks out by default, i.e. origin/main
+    let mut set_head = Command::new("git");
+    use_token(&mut set_head, &origin_url(target_dir));
+    set_head.args(["remote", "set-head", "origin", "--auto"]);
+    if !run_clone_step(&mut output, &mut set_head, target_dir) {
+        return output;
+    }
+
+    let mut rev_parse = Command::new("git");
+    rev_parse.args(["rev-parse", "--abbrev-ref", "origin/HEAD"]);
+    if !run_clone_step(&mut output, &mut rev_parse, target_dir) {
+        return output;
+    }
+    let remote_branch = output
+        .stdout
+        .last()
+        .map(|b| b.trim().to_string())
+        .unwrap_or_default();
+    let branch = remote_branch
+        .strip_prefix("origin/")
+        .unwrap_or(&remote_branch)
+        .to_string();
+
+    let mut checkout = Command::new("git");
+    checkout
+        .args(["checkout", "--force", "-B"])
+        .arg(&branch)
+        .args(["--track", &remote_branch]);
+    if !run_clone_step(&mut output, &mut checkout, target_dir) {
+        return output;
+    }
+
+    let mut submodules = Command::new("git");
+    submodules.args(["submodule", "update", "--init", "--recursive"]);
+    run_clone_step(&mut output, &mut submodules, target_dir);
+
+    output
+}
+
+/*
+ * Runs one of the steps of git_finish_clone, returning false if it failed.
+ */
+fn run_clone_step(output: &mut super::SROutput, command: &mut Command, target_dir: &Path) -> bool {
+    let stdoutput = match super::backend::run(
+        command
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .current_dir(target_dir),
+    ) {
+        Ok(out) => out,
+        Err(e) => {
+            output.status = 147;
+            output.stderr.push(format!(
+                "ERROR: Unable to check out the component after downloading it: {}",
+                e
+            ));
+            return false;
+        }
+    };
+
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    super::backend::record_exit(output, "git", &stdoutput.status);
+
+    output.status == 0 && output.wrapped_status == 0
 }
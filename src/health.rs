@@ -0,0 +1,1009 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The problems found with a single component during a health check, grouped by the kind of check
+/// that found them.
+pub struct ComponentHealth {
+    pub name: String,
+    pub path: PathBuf,
+    pub validation: Vec<String>,
+    pub licenses: Vec<String>,
+    pub dependencies: Vec<String>,
+    pub git_status: Vec<String>,
+}
+
+impl ComponentHealth {
+    /// Returns true if none of the checks found a problem with this component.
+    pub fn is_healthy(&self) -> bool {
+        self.validation.is_empty()
+            && self.licenses.is_empty()
+            && self.dependencies.is_empty()
+            && self.git_status.is_empty()
+    }
+}
+
+/// The result of a project-wide health check, with one section per component in the hierarchy.
+/// The project itself is always the first entry.
+pub struct HealthReport {
+    pub components: Vec<ComponentHealth>,
+}
+
+impl HealthReport {
+    /// Returns true if every component in the project passed all of the checks.
+    pub fn is_healthy(&self) -> bool {
+        self.components.iter().all(|c| c.is_healthy())
+    }
+
+    /// The total number of problems found across all components.
+    pub fn problem_count(&self) -> usize {
+        self.components
+            .iter()
+            .map(|c| {
+                c.validation.len() + c.licenses.len() + c.dependencies.len() + c.git_status.len()
+            })
+            .sum()
+    }
+}
+
+/// Checks that a component has all of the directories and files that Sliderule expects.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+///
+/// Files that can hold sensitive data, like the .git/config file and the user's tokens file, are also checked to
+/// make sure that other users can't read them, and directories that Sliderule installs or caches things in are
+/// checked to make sure that anyone can't write to them.
+///
+/// The project's configuration files have to be readable, and a warning is given for each of the project's
+/// `[tools]` settings that are skipped because the user hasn't allowed them. The component's licenses have to be
+/// allowed by the project's license policy, `config::ProjectConfig::allowed_licenses`. That includes the licenses of
+/// single files in the component's `file_licenses::LICENSES_FILE`, which is checked with
+/// `file_licenses::validate_file_licenses`.
+///
+/// The output status is 23 if the component is missing any part of its structure or its configuration is broken,
+/// 39 if the only problems are with permissions, 47 if the only problems are licenses that the policy doesn't
+/// allow, or 510 if the only problems are in the licenses file, and each problem is added to `stderr`.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(
+///     &temp_dir,
+///     String::from("validproject"),
+///     String::from("Valid Project"),
+///     String::from("Unlicense"),
+///     String::from("CC0-1.0"),
+/// );
+///
+/// let output = sliderule::health::validate_component(&temp_dir.join("validproject"));
+///
+/// assert_eq!(0, output.status);
+/// assert!(output.stderr.is_empty());
+/// ```
+pub fn validate_component(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let mut problems = structure_problems(target_dir);
+    match super::config::load_config(target_dir) {
+        Ok(config) => {
+            for setting in config.untrusted_tools {
+                output.stderr.push(format!(
+                    "WARNING: Skipped {} in the project's configuration, since tools.allow_project isn't turned on.",
+                    setting
+                ));
+            }
+        }
+        Err(e) => problems.push(format!("The configuration can't be read, {}", e)),
+    }
+    for problem in problems {
+        output.status = 23;
+        output.stderr.push(format!("ERROR: {}", problem));
+    }
+
+    let mut problems = permission_problems(target_dir);
+    problems.extend(user_permission_problems());
+    for problem in problems {
+        if output.status == 0 {
+            output.status = 39;
+        }
+        output.stderr.push(format!("ERROR: {}", problem));
+    }
+
+    for problem in license_policy_problems(target_dir) {
+        if output.status == 0 {
+            output.status = 47;
+        }
+        output.stderr.push(format!("ERROR: {}", problem));
+    }
+
+    for problem in super::file_licenses::file_license_problems(target_dir) {
+        if output.status == 0 {
+            output.status = 510;
+        }
+        output.stderr.push(format!("ERROR: {}", problem));
+    }
+
+    if output.status == 0 {
+        output
+            .stdout
+            .push(String::from("Component structure is valid."));
+    }
+
+    output
+}
+
+/// Walks the whole component hierarchy of a project and collects structure, license, dependency and
+/// git status problems into a single report with one section per component.
+///
+/// `project_dir` must be a valid Sliderule component directory.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(
+///     &temp_dir,
+///     String::from("healthyproject"),
+///     String::from("Healthy Project"),
+///     String::from("Unlicense"),
+///     String::from("CC0-1.0"),
+/// );
+///
+/// let report = sliderule::health::health_check(&temp_dir.join("healthyproject"));
+///
+/// assert!(report.is_healthy());
+/// assert_eq!(report.components[0].name, "healthyproject");
+/// ```
+pub fn health_check(project_dir: &Path) -> HealthReport {
+    let mut report = HealthReport {
+        components: Vec::new(),
+    };
+
+    // The project itself might be missing the .sr file, so make sure it is always checked
+    let mut component_dirs = vec![project_dir.to_path_buf()];
+    for sr_path in super::get_sr_paths(project_dir) {
+        let component_dir = super::get_parent_dir(&sr_path);
+
+        if !component_dirs.contains(&component_dir) {
+            component_dirs.push(component_dir);
+        }
+    }
+
+    for component_dir in component_dirs {
+        let name = match component_dir.file_name() {
+            Some(n) => n.to_string_lossy().to_string(),
+            None => component_dir.display().to_string(),
+        };
+
+        let mut validation = structure_problems(&component_dir);
+        validation.extend(permission_problems(&component_dir));
+
+        // The user's own files aren't part of any one component, so they're reported with the project
+        if report.components.is_empty() {
+            validation.extend(user_permission_problems());
+        }
+
+        report.components.push(ComponentHealth {
+            name,
+            validation,
+            licenses: license_problems(&component_dir),
+            dependencies: dependency_problems(&component_dir),
+            git_status: git_status_problems(&component_dir),
+            path: component_dir,
+        });
+    }
+
+    report
+}
+
+/// The result of checking that the repository behind one dependency in a `package.json` file still exists.
+pub struct DependencyUrlCheck {
+    pub package_file: PathBuf,
+    pub name: String,
+    pub url: String,
+    pub reachable: bool,
+    pub moved: bool,
+    pub message: String,
+}
+
+/// Checks every dependency URL in every `package.json` file in the project's hierarchy using
+/// `git ls-remote`, so that dead or moved component repositories are found before they break
+/// `update_dependencies`.
+///
+/// `project_dir` must be a valid Sliderule component directory.
+///
+/// Dependencies that don't point at a repository (i.e. registry version ranges) are skipped. Each URL is
+/// only contacted once, no matter how many components depend on it.
+///
+/// # Examples
+///
+/// ```no_run
+/// let checks = sliderule::health::check_dependency_urls(&std::env::temp_dir().join("toplevel"));
+///
+/// for check in checks.iter().filter(|c| !c.reachable || c.moved) {
+///     println!("{} ({}): {}", check.name, check.url, check.message);
+/// }
+/// ```
+pub fn check_dependency_urls(project_dir: &Path) -> Vec<DependencyUrlCheck> {
+    let mut checks: Vec<DependencyUrlCheck> = Vec::new();
+
+    for package_file in package_files(project_dir) {
+        for (name, url) in super::get_json_dependencies(&package_file) {
+            let git_url = match super::dependency_url_to_git_url(&url) {
+                Some(u) => u,
+                None => continue,
+            };
+
+            // Reuse the answer if this URL has already been checked
+            let previous = checks
+                .iter()
+                .find(|c| c.url == url)
+                .map(|c| (c.reachable, c.moved, c.message.to_owned()));
+
+            let (reachable, moved, message) = match previous {
+                Some(p) => p,
+                None => check_git_url(&git_url),
+            };
+
+            checks.push(DependencyUrlCheck {
+                package_file: package_file.to_owned(),
+                name,
+                url,
+                reachable,
+                moved,
+                message,
+            });
+        }
+    }
+
+    checks
+}
+
+/*
+ * Asks git whether the repository at the URL exists, and whether the host redirected us somewhere else.
+ */
+fn check_git_url(git_url: &str) -> (bool, bool, String) {
+    let output = super::git_sr::git_ls_remote(git_url);
+    let stderr = output.stderr.join("");
+
+    if output.status != 0 {
+        return (false, false, stderr.trim().to_string());
+    }
+
+    if output.wrapped_status != 0 {
+        let reason = stderr
+            .lines()
+            .find(|l| l.starts_with("fatal:") || l.starts_with("ERROR:"))
+            .unwrap_or("git could not reach the repository.");
+
+        return (false, false, reason.trim().to_string());
+    }
+
+    // git follows HTTP redirects, but warns about them
+    if let Some(line) = stderr.lines().find(|l| l.contains("redirecting to")) {
+        let new_url = line.split("redirecting to").last().unwrap_or("").trim();
+        return (
+            true,
+            true,
+            format!("The repository has moved to {}", new_url),
+        );
+    }
+
+    (true, false, String::from("The repository is reachable."))
+}
+
+/// Runs the checks that should pass before a component is published to a shared repository.
+///
+/// Structure and license problems are blocking, and cause the output status to be 26 with each problem
+/// added to `stderr`. Lint problems are not blocking and are added to `stdout` as warnings.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(
+///     &temp_dir,
+///     String::from("readyproject"),
+///     String::from("Ready Project"),
+///     String::from("Unlicense"),
+///     String::from("CC0-1.0"),
+/// );
+///
+/// let output = sliderule::health::pre_upload_check(&temp_dir.join("readyproject"));
+///
+/// assert_eq!(0, output.status);
+/// ```
+pub fn pre_upload_check(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let mut blocking = structure_problems(target_dir);
+    blocking.append(&mut license_problems(target_dir));
+
+    for problem in blocking {
+        output.status = 26;
+        output.stderr.push(format!("ERROR: {}", problem));
+    }
+
+    for problem in lint_problems(target_dir) {
+        output.stdout.push(format!("WARNING: {}", problem));
+    }
+
+    if output.status == 0 {
+        output
+            .stdout
+            .push(String::from("Component is ready to be uploaded."));
+    }
+
+    output
+}
+
+/*
+ * Collects the directories and files that are missing from the component's structure.
+ */
+fn structure_problems(target_dir: &Path) -> Vec<String> {
+    structure_problems_in(&super::backend::StdFileSystem, target_dir)
+}
+
+/// Collects the directories and files that are missing from a component's structure on any file system. The
+/// directories are the ones in the project's `config::Layout`, which is read from the same file system.
+///
+/// This is primarily for sliderule-rs use, and `validate_component` or `backend::validate_component` should be
+/// used instead in most situations.
+pub fn structure_problems_in(
+    file_system: &dyn super::backend::FileSystem,
+    target_dir: &Path,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    let layout = super::config::config_or_default_in(file_system, target_dir).layout;
+    for dir in layout.dirs() {
+        if !file_system.is_dir(&target_dir.join(dir)) {
+            problems.push(format!("The {} directory is missing.", dir));
+        }
+    }
+
+    for file in &[
+        ".sr",
+        "package.json",
+        "README.md",
+        "parts.yaml",
+        "tools.yaml",
+        "precautions.yaml",
+    ] {
+        if !file_system.is_file(&target_dir.join(file)) {
+            problems.push(format!("The {} file is missing.", file));
+        }
+    }
+
+    problems
+}
+
+/*
+ * Collects the files and directories of a component that other users could read secrets from or tamper with.
+ */
+fn permission_problems(target_dir: &Path) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    for dir in &[".git", "node_modules"] {
+        let dir = target_dir.join(dir);
+        if is_world_writable_dir(&dir) {
+            problems.push(format!(
+                "The {} directory can be written to by any user.",
+                dir.display()
+            ));
+        }
+    }
+
+    let config_file = target_dir.join(".git").join("config");
+    if is_world_writable(&config_file) {
+        problems.push(format!(
+            "The {} file can be written to by any user.",
+            config_file.display()
+        ));
+    }
+
+    // git creates the config file so that anyone can read it, which is only a problem if it holds credentials
+    if is_readable_by_others(&config_file) {
+        let contents = fs::read_to_string(&config_file).unwrap_or_default();
+        let has_credentials = contents.lines().any(|l| {
+            let l = l.trim_start();
+            l.starts_with("password") || l.starts_with("token")
+        }) || remote_urls_from_config(&contents)
+            .iter()
+            .any(|(_, url)| has_embedded_credentials(url));
+
+        if has_credentials {
+            problems.push(format!(
+                "The {} file holds credentials and can be read by other users.",
+                config_file.display()
+            ));
+        }
+    }
+
+    problems
+}
+
+/*
+ * Collects the problems with the permissions of the user's tokens file and Sliderule's cache, which are shared by
+ * every project.
+ */
+fn user_permission_problems() -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if let Some(config_dir) = super::auth::config_dir() {
+        let tokens_file = config_dir.join(super::auth::TOKENS_FILE);
+        if is_readable_by_others(&tokens_file) {
+            problems.push(format!(
+                "The {} file holds tokens and can be read by other users.",
+                tokens_file.display()
+            ));
+        }
+
+        if is_world_writable_dir(&config_dir) {
+            problems.push(format!(
+                "The {} directory can be written to by any user.",
+                config_dir.display()
+            ));
+        }
+    }
+
+    if let Some(cache_file) = super::sr_cache::disk_cache_file() {
+        if is_readable_by_others(&cache_file) {
+            problems.push(format!(
+                "The {} cache file can be read by other users.",
+                cache_file.display()
+            ));
+        }
+
+        if let Some(cache_dir) = cache_file.parent() {
+            if is_world_writable_dir(cache_dir) {
+                problems.push(format!(
+                    "The {} cache directory can be written to by any user.",
+                    cache_dir.display()
+                ));
+            }
+        }
+    }
+
+    problems
+}
+
+/*
+ * Checks whether anyone on the system can write to a file or directory. Permissions aren't checked on other
+ * platforms, so this is always false there.
+ */
+#[cfg(unix)]
+fn is_world_writable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    match fs::metadata(path) {
+        Ok(m) => m.permissions().mode() & 0o002 != 0,
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_world_writable(_path: &Path) -> bool {
+    false
+}
+
+/*
+ * Checks for a directory anyone can write to, other than ones like /tmp where the sticky bit keeps users from
+ * replacing each other's files.
+ */
+#[cfg(unix)]
+fn is_world_writable_dir(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    match fs::metadata(path) {
+        Ok(m) => m.is_dir() && m.permissions().mode() & 0o1002 == 0o002,
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_world_writable_dir(_path: &Path) -> bool {
+    false
+}
+
+/*
+ * Checks whether users other than the owner can read a file.
+ */
+#[cfg(unix)]
+fn is_readable_by_others(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    match fs::metadata(path) {
+        Ok(m) => m.permissions().mode() & 0o044 != 0,
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_readable_by_others(_path: &Path) -> bool {
+    false
+}
+
+/*
+ * Makes sure the licenses in the .sr file are set and have been amalgamated into package.json.
+ */
+fn license_problems(target_dir: &Path) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    let sr_file = target_dir.join(".sr");
+    let package_file = target_dir.join("package.json");
+
+    // Missing files are reported by the structure check
+    if !sr_file.exists() {
+        return problems;
+    }
+
+    let source_license = super::get_yaml_value(&sr_file, "source_license");
+    let doc_license = super::get_yaml_value(&sr_file, "documentation_license");
+
+    if source_license.is_empty() {
+        problems.push(String::from("No source license is set in the .sr file."));
+    }
+    if doc_license.is_empty() {
+        problems.push(String::from(
+            "No documentation license is set in the .sr file.",
+        ));
+    }
+
+    if package_file.exists() {
+        let package_license = super::get_json_value(&package_file, "license");
+
+        for license in &[source_license, doc_license] {
+            if !license.is_empty() && !package_license.contains(license.as_str()) {
+                problems.push(format!(
+                    "The license {} is not listed in package.json, the licenses need to be amalgamated.",
+                    license
+                ));
+            }
+        }
+
+        let file_licenses =
+            super::file_licenses::read_file_licenses(target_dir).unwrap_or_default();
+        for file_license in file_licenses {
+            if !package_license.contains(file_license.license.as_str()) {
+                problems.push(format!(
+                    "The license {} of {} is not listed in package.json, the licenses need to be amalgamated.",
+                    file_license.license, file_license.pattern
+                ));
+            }
+        }
+    }
+
+    problems.extend(super::file_licenses::file_license_problems(target_dir));
+    problems.extend(license_policy_problems(target_dir));
+
+    problems
+}
+
+/*
+ * Checks the licenses in a component's .sr file against the project's license policy.
+ */
+fn license_policy_problems(target_dir: &Path) -> Vec<String> {
+    let sr_file = target_dir.join(".sr");
+    if !sr_file.exists() {
+        return Vec::new();
+    }
+
+    let config = super::config::config_or_default(target_dir);
+
+    let mut problems: Vec<String> = ["source_license", "documentation_license"]
+        .iter()
+        .map(|key| super::get_yaml_value(&sr_file, key))
+        .filter(|license| !license.is_empty() && !config.allows_license(license))
+        .map(|license| {
+            format!(
+                "The license {} is not allowed by the project's license policy.",
+                license
+            )
+        })
+        .collect();
+
+    let file_licenses = super::file_licenses::read_file_licenses(target_dir).unwrap_or_default();
+    for file_license in file_licenses {
+        if !config.allows_license(&file_license.license) {
+            problems.push(format!(
+                "The license {} of {} is not allowed by the project's license policy.",
+                file_license.license, file_license.pattern
+            ));
+        }
+    }
+
+    problems
+}
+
+/*
+ * Makes sure that every dependency listed in package.json is installed in node_modules.
+ */
+fn dependency_problems(target_dir: &Path) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    let package_file = target_dir.join("package.json");
+
+    if !package_file.exists() {
+        return problems;
+    }
+
+    for (name, url) in super::get_json_dependencies(&package_file) {
+        if !target_dir.join("node_modules").join(&name).exists() {
+            problems.push(format!(
+                "The dependency {} ({}) is not installed, the dependencies need to be updated.",
+                name, url
+            ));
+        }
+    }
+
+    problems
+}
+
+/*
+ * Reports uncommitted changes for components that are set up as repositories.
+ */
+fn git_status_problems(target_dir: &Path) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if !target_dir.join(".git").exists() {
+        return problems;
+    }
+
+    let output = super::git_sr::git_status(target_dir);
+
+    if output.status != 0 || output.wrapped_status != 0 {
+        problems.push(String::from(
+            "Unable to get the git status of the component.",
+        ));
+    } else if !output.stdout.join("").contains("nothing to commit") {
+        problems.push(String::from(
+            "There are changes that have not been uploaded.",
+        ));
+    }
+
+    problems
+}
+
+/*
+ * Finds metadata that is usable but will probably cause trouble for the people consuming the component.
+ */
+fn lint_problems(target_dir: &Path) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    let package_file = target_dir.join("package.json");
+    if package_file.exists() {
+        let name = super::get_json_value(&package_file, "name");
+
+        // npm will refuse to install packages with names like these
+        if name.is_empty() {
+            problems.push(String::from("package.json does not have a name set."));
+        } else if name != name.to_lowercase() || name.contains(' ') {
+            problems.push(format!(
+                "The name {} in package.json should be lowercase and not contain spaces.",
+                name
+            ));
+        }
+    }
+
+    if let Ok(contents) = fs::read_to_string(target_dir.join("README.md")) {
+        if !contents.starts_with("# ") {
+            problems.push(String::from(
+                "README.md does not start with a heading for the component.",
+            ));
+        }
+    }
+
+    let sr_file = target_dir.join(".sr");
+    if sr_file.exists() {
+        for key in &["source_license", "documentation_license"] {
+            if super::get_yaml_value(&sr_file, key).contains("{{") {
+                problems.push(format!(
+                    "The {} in the .sr file still contains template text.",
+                    key
+                ));
+            }
+        }
+    }
+
+    problems
+}
+
+/// The kinds of risky configuration that `audit` looks for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuditIssue {
+    /// A component is fetched over a connection that isn't encrypted, like `http://` or `git://`
+    InsecureUrl,
+    /// A password or token is written into a dependency URL or a repository's remote URL
+    EmbeddedCredentials,
+    /// A dependency isn't pinned to a commit, so what gets installed can change without anyone noticing
+    UnpinnedDependency,
+    /// The commit that was installed is no longer in the history of the remote branch
+    ForcePushed,
+}
+
+/// One risky configuration found by `audit`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditFinding {
+    pub issue: AuditIssue,
+    /// The `package.json` file with the dependency, or the directory of the repository with the remote
+    pub path: PathBuf,
+    /// The name of the dependency or remote
+    pub name: String,
+    pub message: String,
+}
+
+/// Looks through a project's whole hierarchy for configurations that put the integrity of the project at risk:
+/// components fetched over unencrypted connections, credentials embedded in URLs, dependencies that aren't
+/// pinned to a commit, and installed components whose remote has been force-pushed so that the installed
+/// commit is no longer part of its history.
+///
+/// `project_dir` must be a valid Sliderule component directory.
+///
+/// The force-push check has to fetch each remote that an installed component came from. Any secrets found are
+/// masked in the messages.
+///
+/// # Examples
+///
+/// ```no_run
+/// let findings = sliderule::health::audit(&std::env::temp_dir().join("toplevel"));
+///
+/// for finding in findings {
+///     println!("{:?} {} ({}): {}", finding.issue, finding.name, finding.path.display(), finding.message);
+/// }
+/// ```
+pub fn audit(project_dir: &Path) -> Vec<AuditFinding> {
+    let mut findings = Vec::new();
+
+    for package_file in package_files(project_dir) {
+        let component_dir = super::get_parent_dir(&package_file);
+
+        for (name, spec) in super::get_json_dependencies(&package_file) {
+            let mut add = |issue: AuditIssue, message: String| {
+                findings.push(AuditFinding {
+                    issue,
+                    path: package_file.to_owned(),
+                    name: name.to_owned(),
+                    message: super::redact::redact(&message),
+                });
+            };
+
+            let git_url = match super::dependency_url_to_git_url(&spec) {
+                Some(u) => u,
+                None => {
+                    if !is_exact_version(&spec) {
+                        add(
+                            AuditIssue::UnpinnedDependency,
+                            format!("{} is not pinned to an exact version.", spec),
+                        );
+                    }
+                    continue;
+                }
+            };
+
+            if is_insecure_url(&spec) {
+                add(
+                    AuditIssue::InsecureUrl,
+                    format!("{} is not fetched over an encrypted connection.", spec),
+                );
+            }
+
+            if has_embedded_credentials(&spec) {
+                add(
+                    AuditIssue::EmbeddedCredentials,
+                    format!("{} has credentials written into it.", spec),
+                );
+            }
+
+            let pinned = match spec.rfind('#') {
+                Some(i) => super::is_commit_hash(&spec[i + 1..]),
+                None => false,
+            };
+            if !pinned {
+                add(
+                    AuditIssue::UnpinnedDependency,
+                    format!("{} is not pinned to a commit.", spec),
+                );
+            }
+
+            if let Some(installed) = super::installed_commit(&component_dir, &name) {
+                let reference = match spec.rfind('#') {
+                    Some(i) => &spec[i + 1..],
+                    None => "HEAD",
+                };
+
+                if let Some(message) = check_force_push(&git_url, reference, &installed) {
+                    add(AuditIssue::ForcePushed, message);
+                }
+            }
+        }
+    }
+
+    for component in
+        super::hierarchy::components_iter(project_dir, super::hierarchy::TraversalOrder::PreOrder)
+    {
+        if !component.path.join(".git").exists() {
+            continue;
+        }
+
+        // The URLs are read straight from the config so that the credentials can be seen
+        let config =
+            fs::read_to_string(component.path.join(".git").join("config")).unwrap_or_default();
+        for (remote, url) in remote_urls_from_config(&config) {
+            if has_embedded_credentials(&url) {
+                findings.push(AuditFinding {
+                    issue: AuditIssue::EmbeddedCredentials,
+                    path: component.path.to_owned(),
+                    name: remote.to_owned(),
+                    message: super::redact::redact(&format!(
+                        "The {} remote has credentials written into its URL {}.",
+                        remote, url
+                    )),
+                });
+            }
+
+            if is_insecure_url(&url) {
+                findings.push(AuditFinding {
+                    issue: AuditIssue::InsecureUrl,
+                    path: component.path.to_owned(),
+                    name: remote.to_owned(),
+                    message: super::redact::redact(&format!(
+                        "The {} remote {} is not reached over an encrypted connection.",
+                        remote, url
+                    )),
+                });
+            }
+        }
+    }
+
+    findings
+}
+
+/*
+ * Collects the package.json file of the project and of every component in it.
+ */
+fn package_files(project_dir: &Path) -> Vec<PathBuf> {
+    let mut package_files = vec![project_dir.join("package.json")];
+
+    for sr_path in super::get_sr_paths(project_dir) {
+        let package_file = super::get_parent_dir(&sr_path).join("package.json");
+
+        if !package_files.contains(&package_file) {
+            package_files.push(package_file);
+        }
+    }
+
+    package_files
+}
+
+/*
+ * Checks for registry versions like `1.2.3`, as opposed to ranges like `^1.2.3` or tags like `latest`.
+ */
+fn is_exact_version(spec: &str) -> bool {
+    let version = spec.trim().trim_start_matches('=').trim_start_matches('v');
+
+    !version.is_empty()
+        && version.chars().next().is_some_and(|c| c.is_ascii_digit())
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '+')
+        && !version.split('.').any(|p| p == "x" || p == "X")
+}
+
+/*
+ * Checks for URL schemes that send everything in the clear.
+ */
+fn is_insecure_url(url: &str) -> bool {
+    let url = url.trim().to_lowercase();
+
+    url.starts_with("http://") || url.starts_with("git://") || url.starts_with("git+http://")
+}
+
+/*
+ * Checks for a password or token in the user part of a URL. ssh URLs normally have a user like `git@`, which
+ * is only a problem if it comes with a password.
+ */
+fn has_embedded_credentials(url: &str) -> bool {
+    let scheme_end = match url.find("://") {
+        Some(i) => i,
+        None => return false,
+    };
+
+    let rest = &url[scheme_end + 3..];
+    let authority = match rest.find('/') {
+        Some(i) => &rest[..i],
+        None => rest,
+    };
+    let userinfo = match authority.rfind('@') {
+        Some(i) => &authority[..i],
+        None => return false,
+    };
+
+    if url[..scheme_end].contains("ssh") {
+        userinfo.contains(':')
+    } else {
+        !userinfo.is_empty()
+    }
+}
+
+/*
+ * Pulls the remote names and URLs out of the contents of a .git/config file.
+ */
+fn remote_urls_from_config(config: &str) -> Vec<(String, String)> {
+    let mut urls = Vec::new();
+    let mut remote: Option<String> = None;
+
+    for line in config.lines() {
+        let line = line.trim();
+
+        if line.starts_with('[') {
+            remote = line
+                .strip_prefix("[remote \"")
+                .and_then(|r| r.strip_suffix("\"]"))
+                .map(|r| r.to_string());
+        } else if let Some(ref name) = remote {
+            let parts: Vec<&str> = line.splitn(2, '=').collect();
+            if parts.len() == 2 && parts[0].trim() == "url" {
+                urls.push((name.to_owned(), parts[1].trim().to_string()));
+            }
+        }
+    }
+
+    urls
+}
+
+/*
+ * Fetches a remote and checks that the commit that was installed is still part of the history of the branch
+ * it was installed from, returning a description of the problem if it isn't.
+ */
+fn check_force_push(git_url: &str, reference: &str, installed: &str) -> Option<String> {
+    // Nothing can have been rewritten if the branch still points at the installed commit
+    let head_output = super::git_sr::git_remote_head(git_url, reference);
+    let remote_head = head_output.stdout.join("").trim().to_string();
+    if head_output.status != 0 || remote_head.is_empty() || remote_head.starts_with(installed) {
+        return None;
+    }
+
+    let clone_dir = super::scratch_dir("audit");
+    let clone_output = super::git_sr::git_clone_bare(&clone_dir, git_url);
+
+    let message = if clone_output.status != 0 || clone_output.wrapped_status != 0 {
+        None
+    } else {
+        let output = super::git_sr::git_is_ancestor(&clone_dir, installed, &remote_head);
+
+        match output.wrapped_status {
+            0 => None,
+            1 => Some(format!(
+                "The installed commit {} is no longer in the history of {} in {}.",
+                installed, reference, git_url
+            )),
+            _ => Some(format!(
+                "The installed commit {} no longer exists in {}.",
+                installed, git_url
+            )),
+        }
+    };
+
+    let _ = fs::remove_dir_all(&clone_dir);
+
+    message
+}
@@ -0,0 +1,780 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::json::JsonValue;
+use super::ComponentLevel;
+
+/// The formats that a component hierarchy can be exported in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// A Graphviz `digraph` that can be rendered with `dot`
+    Dot,
+    /// A nested JSON object for each component, with its children in a `children` array
+    Json,
+}
+
+/// The formats that a dependency manifest can be exported in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ManifestFormat {
+    /// A JSON array with an object for each remote component
+    Json,
+    /// A header row followed by a row for each remote component, quoted as described in RFC 4180
+    Csv,
+}
+
+/// One remote component in a project's dependency manifest, as listed by `dependency_manifest`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestEntry {
+    pub name: String,
+    /// The path of the component relative to the project's parent, with `/` separators, as in `export_tree`
+    pub path: String,
+    /// Where the component was installed from, with any credentials removed
+    pub url: String,
+    /// The commit that was installed, which is empty if it can't be worked out
+    pub commit: String,
+    pub source_license: String,
+    pub doc_license: String,
+    /// How deep in the hierarchy the component is, with 1 for the components directly inside the project
+    pub depth: usize,
+}
+
+/// A component found inside another component, with the metadata from its `.sr` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentEntry {
+    pub name: String,
+    pub path: PathBuf,
+    pub source_license: String,
+    pub doc_license: String,
+    pub level: ComponentLevel,
+}
+
+/// The components directly inside a component, split by where they are stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentList {
+    /// Components in the `components` directory
+    pub local: Vec<ComponentEntry>,
+    /// Components in the `node_modules` directory
+    pub remote: Vec<ComponentEntry>,
+}
+
+/// One component in a project's hierarchy, along with every component inside it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentNode {
+    pub component: ComponentEntry,
+    /// Local components come first, followed by remote components, each sorted by name
+    pub children: Vec<ComponentNode>,
+}
+
+impl ComponentNode {
+    /// The number of components in this part of the tree, including this one.
+    pub fn count(&self) -> usize {
+        1 + self.children.iter().map(|c| c.count()).sum::<usize>()
+    }
+}
+
+/// The order that components are handed out in when walking a hierarchy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TraversalOrder {
+    /// Each component comes before the components inside it
+    PreOrder,
+    /// Each component comes after the components inside it
+    PostOrder,
+}
+
+/// Receives each component as a hierarchy is walked by `visit_components`. Both methods do nothing by
+/// default, so only the one that's needed has to be written.
+pub trait ComponentVisitor {
+    /// Called when a component is reached, before any of the components inside it.
+    fn enter(&mut self, _component: &ComponentEntry, _depth: usize) {}
+
+    /// Called after all of the components inside a component have been visited.
+    fn leave(&mut self, _component: &ComponentEntry, _depth: usize) {}
+}
+
+/// An iterator over every component in a hierarchy, created by `components_iter`.
+pub struct ComponentsIter {
+    components: ::std::vec::IntoIter<ComponentEntry>,
+}
+
+impl Iterator for ComponentsIter {
+    type Item = ComponentEntry;
+
+    fn next(&mut self) -> Option<ComponentEntry> {
+        self.components.next()
+    }
+}
+
+/// Lists the local and remote components that are directly inside a component, without descending
+/// into them. Each list is sorted by name.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+///
+/// Only directories that contain a `.sr` file are listed, so ordinary npm packages that have been installed
+/// alongside remote components are left out. Scoped remote components are named `@scope/name`.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// sliderule::create_component(&temp_dir.join("toplevel"), String::from("level1"), String::from("Level 1 component"), String::from("MIT"), String::from("CC-BY-4.0"));
+///
+/// let list = sliderule::hierarchy::list_components(&temp_dir.join("toplevel"));
+///
+/// assert_eq!(list.local[0].name, "level1");
+/// assert_eq!(list.local[0].source_license, "MIT");
+/// assert!(list.remote.is_empty());
+/// ```
+pub fn list_components(target_dir: &Path) -> ComponentList {
+    ComponentList {
+        local: list_component_dir(&target_dir.join("components"), ""),
+        remote: list_component_dir(&target_dir.join("node_modules"), ""),
+    }
+}
+
+/// Builds a tree of every local and remote component in the project, to any depth.
+///
+/// `project_dir` must be a valid Sliderule component directory, and becomes the root of the tree.
+///
+/// Directories that turn out to be the same directory (i.e. through an `npm link` symlink) are only visited
+/// once, so a cycle of links can't cause endless recursion.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// sliderule::create_component(&temp_dir.join("toplevel"), String::from("level1"), String::from("Level 1 component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// sliderule::create_component(&temp_dir.join("toplevel").join("components").join("level1"), String::from("level2"), String::from("Level 2 component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let tree = sliderule::hierarchy::component_tree(&temp_dir.join("toplevel"));
+///
+/// assert_eq!(tree.component.name, "toplevel");
+/// assert_eq!(tree.children[0].children[0].component.name, "level2");
+/// assert_eq!(tree.count(), 3);
+/// ```
+pub fn component_tree(project_dir: &Path) -> ComponentNode {
+    let name = match project_dir.file_name() {
+        Some(n) => n.to_string_lossy().to_string(),
+        None => String::new(),
+    };
+
+    let mut visited = Vec::new();
+
+    build_node(
+        component_entry(project_dir.to_path_buf(), name),
+        &mut visited,
+    )
+}
+
+/// Iterates over every component in the project, including the project itself, in the given order.
+///
+/// `project_dir` must be a valid Sliderule component directory.
+///
+/// Local components are always visited before remote components at the same level.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// sliderule::create_component(&temp_dir.join("toplevel"), String::from("level1"), String::from("Level 1 component"), String::from("MIT"), String::from("CC0-1.0"));
+///
+/// let mit_count = sliderule::hierarchy::components_iter(&temp_dir.join("toplevel"), sliderule::hierarchy::TraversalOrder::PreOrder)
+///     .filter(|c| c.source_license == "MIT")
+///     .count();
+///
+/// assert_eq!(mit_count, 1);
+/// ```
+pub fn components_iter(project_dir: &Path, order: TraversalOrder) -> ComponentsIter {
+    let mut components = Vec::new();
+    flatten_node(component_tree(project_dir), order, &mut components);
+
+    ComponentsIter {
+        components: components.into_iter(),
+    }
+}
+
+/// Walks every component in the project, including the project itself, calling the visitor's `enter`
+/// and `leave` methods for each one. `depth` is 0 for the project, 1 for the components directly inside
+/// it, and so on.
+///
+/// `project_dir` must be a valid Sliderule component directory.
+///
+/// # Examples
+///
+/// ```
+/// use sliderule::hierarchy::{ComponentEntry, ComponentVisitor};
+///
+/// struct Outline {
+///     lines: Vec<String>,
+/// }
+///
+/// impl ComponentVisitor for Outline {
+///     fn enter(&mut self, component: &ComponentEntry, depth: usize) {
+///         self.lines.push(format!("{}{}", "  ".repeat(depth), component.name));
+///     }
+/// }
+///
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// sliderule::create_component(&temp_dir.join("toplevel"), String::from("level1"), String::from("Level 1 component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let mut outline = Outline { lines: Vec::new() };
+/// sliderule::hierarchy::visit_components(&temp_dir.join("toplevel"), &mut outline);
+///
+/// assert_eq!(outline.lines, vec!["toplevel", "  level1"]);
+/// ```
+pub fn visit_components<V: ComponentVisitor>(project_dir: &Path, visitor: &mut V) {
+    visit_node(&component_tree(project_dir), 0, visitor);
+}
+
+/// Exports the component hierarchy, and the dependencies declared in each component's `package.json`, so that
+/// it can be used to draw architecture diagrams for documentation.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+///
+/// Every component is annotated with its source and documentation licenses. Components are identified by their
+/// path relative to `target_dir`'s parent so that the output doesn't depend on where the project is stored.
+/// In DOT output, local components are joined to their parent by solid lines and remote components by dashed
+/// lines. Dependencies that have not been installed yet are drawn as dotted boxes.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// sliderule::create_component(&temp_dir.join("toplevel"), String::from("level1"), String::from("Level 1 component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let dot = sliderule::hierarchy::export_tree(&temp_dir.join("toplevel"), sliderule::hierarchy::ExportFormat::Dot);
+///
+/// assert!(dot.starts_with("digraph \"toplevel\" {"));
+/// assert!(dot.contains("\"toplevel\" -> \"toplevel/components/level1\";"));
+/// ```
+pub fn export_tree(target_dir: &Path, format: ExportFormat) -> String {
+    let tree = component_tree(target_dir);
+
+    let base_dir = match target_dir.parent() {
+        Some(p) => p.to_path_buf(),
+        None => target_dir.to_path_buf(),
+    };
+
+    match format {
+        ExportFormat::Dot => {
+            let mut lines = vec![format!(
+                "digraph {} {{",
+                super::json::quote(&tree.component.name)
+            )];
+            lines.push(String::from("    node [shape=box];"));
+            dot_node(&tree, &base_dir, &mut lines);
+            lines.push(String::from("}"));
+
+            let mut dot = lines.join(&super::get_newline());
+            dot.push_str(&super::get_newline());
+            dot
+        }
+        ExportFormat::Json => json_node(&tree, &base_dir).to_json(),
+    }
+}
+
+/// Lists every remote component in the project's hierarchy, at any depth and including the ones installed by
+/// local components, with where it came from, the commit that was installed and its licenses. Components are
+/// listed in tree order.
+///
+/// `project_dir` must be a valid Sliderule component directory.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let manifest = sliderule::hierarchy::dependency_manifest(&temp_dir.join("toplevel"));
+///
+/// assert!(manifest.is_empty());
+/// ```
+pub fn dependency_manifest(project_dir: &Path) -> Vec<ManifestEntry> {
+    let tree = component_tree(project_dir);
+
+    let base_dir = match project_dir.parent() {
+        Some(p) => p.to_path_buf(),
+        None => project_dir.to_path_buf(),
+    };
+
+    let mut entries = Vec::new();
+    manifest_node(&tree, &base_dir, 0, &mut entries);
+
+    entries
+}
+
+/// Exports the list from `dependency_manifest` in a machine-readable form, for procurement reviews and for
+/// archiving a record of exactly what went into a design.
+///
+/// `project_dir` must be a valid Sliderule component directory.
+///
+/// Both formats have the fields `name`, `path`, `url`, `commit`, `source_license`, `documentation_license` and
+/// `depth`, in that order.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let csv = sliderule::hierarchy::export_dependency_manifest(&temp_dir.join("toplevel"), sliderule::hierarchy::ManifestFormat::Csv);
+///
+/// assert!(csv.starts_with("name,path,url,commit,source_license,documentation_license,depth"));
+/// ```
+pub fn export_dependency_manifest(project_dir: &Path, format: ManifestFormat) -> String {
+    let entries = dependency_manifest(project_dir);
+
+    match format {
+        ManifestFormat::Json => JsonValue::Array(
+            entries
+                .into_iter()
+                .map(|e| {
+                    JsonValue::Object(vec![
+                        (String::from("name"), JsonValue::String(e.name)),
+                        (String::from("path"), JsonValue::String(e.path)),
+                        (String::from("url"), JsonValue::String(e.url)),
+                        (String::from("commit"), JsonValue::String(e.commit)),
+                        (
+                            String::from("source_license"),
+                            JsonValue::String(e.source_license),
+                        ),
+                        (
+                            String::from("documentation_license"),
+                            JsonValue::String(e.doc_license),
+                        ),
+                        (String::from("depth"), JsonValue::Number(e.depth as f64)),
+                    ])
+                })
+                .collect(),
+        )
+        .to_json(),
+        ManifestFormat::Csv => {
+            let nl = super::get_newline();
+
+            let mut csv =
+                String::from("name,path,url,commit,source_license,documentation_license,depth");
+            csv.push_str(&nl);
+            for e in entries {
+                let fields = [
+                    e.name,
+                    e.path,
+                    e.url,
+                    e.commit,
+                    e.source_license,
+                    e.doc_license,
+                    e.depth.to_string(),
+                ];
+                let fields: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+                csv.push_str(&fields.join(","));
+                csv.push_str(&nl);
+            }
+
+            csv
+        }
+    }
+}
+
+/*
+ * Adds the remote components beneath a node to a dependency manifest.
+ */
+fn manifest_node(
+    node: &ComponentNode,
+    base_dir: &Path,
+    depth: usize,
+    entries: &mut Vec<ManifestEntry>,
+) {
+    let dependencies = super::get_json_dependencies(&node.component.path.join("package.json"));
+
+    for child in &node.children {
+        if child.component.level == ComponentLevel::RemoteComponent {
+            // Components that npm installed for another dependency aren't listed by the parent
+            let url = match dependencies.iter().find(|d| d.0 == child.component.name) {
+                Some(d) => d.1.clone(),
+                None => fs::read_to_string(child.component.path.join("package.json"))
+                    .ok()
+                    .and_then(|c| super::json::parse(&c).ok())
+                    .map(|p| p.get_str("_resolved"))
+                    .unwrap_or_default(),
+            };
+
+            entries.push(ManifestEntry {
+                name: child.component.name.clone(),
+                path: export_id(&child.component.path, base_dir),
+                url: super::redact::redact(&url),
+                commit: super::installed_commit(&node.component.path, &child.component.name)
+                    .unwrap_or_default(),
+                source_license: child.component.source_license.clone(),
+                doc_license: child.component.doc_license.clone(),
+                depth: depth + 1,
+            });
+        }
+
+        manifest_node(child, base_dir, depth + 1, entries);
+    }
+}
+
+/*
+ * Quotes a CSV field if it holds anything that would otherwise end it early.
+ */
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/*
+ * Gives the path of a component relative to the export's base directory, with / as the separator on all platforms.
+ */
+fn export_id(path: &Path, base_dir: &Path) -> String {
+    let relative = path.strip_prefix(base_dir).unwrap_or(path);
+
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+
+    parts.join("/")
+}
+
+/*
+ * The dependencies of a component that haven't been installed into its node_modules directory.
+ */
+fn missing_dependencies(node: &ComponentNode) -> Vec<(String, String)> {
+    super::get_json_dependencies(&node.component.path.join("package.json"))
+        .into_iter()
+        .filter(|d| !node.children.iter().any(|c| c.component.name == d.0))
+        .collect()
+}
+
+/*
+ * Adds the DOT statements for a node, its edges and everything beneath it.
+ */
+fn dot_node(node: &ComponentNode, base_dir: &Path, lines: &mut Vec<String>) {
+    let id = export_id(&node.component.path, base_dir);
+
+    lines.push(format!(
+        "    {} [label={}];",
+        super::json::quote(&id),
+        super::json::quote(&format!(
+            "{}\n{} / {}",
+            node.component.name, node.component.source_license, node.component.doc_license
+        ))
+    ));
+
+    for child in &node.children {
+        let child_id = export_id(&child.component.path, base_dir);
+
+        if child.component.level == ComponentLevel::RemoteComponent {
+            lines.push(format!(
+                "    {} -> {} [style=dashed];",
+                super::json::quote(&id),
+                super::json::quote(&child_id)
+            ));
+        } else {
+            lines.push(format!(
+                "    {} -> {};",
+                super::json::quote(&id),
+                super::json::quote(&child_id)
+            ));
+        }
+
+        dot_node(child, base_dir, lines);
+    }
+
+    for (name, url) in missing_dependencies(node) {
+        let dep_id = format!("{}/node_modules/{}", id, name);
+
+        lines.push(format!(
+            "    {} [label={}, style=dotted];",
+            super::json::quote(&dep_id),
+            super::json::quote(&format!("{}\n{}", name, url))
+        ));
+        lines.push(format!(
+            "    {} -> {} [style=dashed];",
+            super::json::quote(&id),
+            super::json::quote(&dep_id)
+        ));
+    }
+}
+
+/*
+ * Builds the JSON object for a node and everything beneath it.
+ */
+fn json_node(node: &ComponentNode, base_dir: &Path) -> JsonValue {
+    let level = match node.component.level {
+        ComponentLevel::NotAComponent => "not_a_component",
+        ComponentLevel::TopLevelProject => "project",
+        ComponentLevel::LocalSubComponent => "local",
+        ComponentLevel::RemoteComponent => "remote",
+    };
+
+    let mut dependencies = Vec::new();
+    for (name, url) in super::get_json_dependencies(&node.component.path.join("package.json")) {
+        let installed = node.children.iter().any(|c| c.component.name == name);
+
+        dependencies.push(JsonValue::Object(vec![
+            (String::from("name"), JsonValue::String(name)),
+            (String::from("url"), JsonValue::String(url)),
+            (String::from("installed"), JsonValue::Bool(installed)),
+        ]));
+    }
+
+    let children = node
+        .children
+        .iter()
+        .map(|c| json_node(c, base_dir))
+        .collect();
+
+    JsonValue::Object(vec![
+        (
+            String::from("name"),
+            JsonValue::String(node.component.name.clone()),
+        ),
+        (
+            String::from("path"),
+            JsonValue::String(export_id(&node.component.path, base_dir)),
+        ),
+        (String::from("level"), JsonValue::String(level.to_string())),
+        (
+            String::from("source_license"),
+            JsonValue::String(node.component.source_license.clone()),
+        ),
+        (
+            String::from("documentation_license"),
+            JsonValue::String(node.component.doc_license.clone()),
+        ),
+        (String::from("dependencies"), JsonValue::Array(dependencies)),
+        (String::from("children"), JsonValue::Array(children)),
+    ])
+}
+
+/*
+ * Calls the visitor on a node and everything beneath it.
+ */
+fn visit_node<V: ComponentVisitor>(node: &ComponentNode, depth: usize, visitor: &mut V) {
+    visitor.enter(&node.component, depth);
+
+    for child in &node.children {
+        visit_node(child, depth + 1, visitor);
+    }
+
+    visitor.leave(&node.component, depth);
+}
+
+/*
+ * Turns a tree into a flat list in the requested order.
+ */
+fn flatten_node(node: ComponentNode, order: TraversalOrder, components: &mut Vec<ComponentEntry>) {
+    if order == TraversalOrder::PreOrder {
+        components.push(node.component.clone());
+    }
+
+    for child in node.children {
+        flatten_node(child, order, components);
+    }
+
+    if order == TraversalOrder::PostOrder {
+        components.push(node.component);
+    }
+}
+
+/// Searches the whole project hierarchy, local and remote components at every level, for components with
+/// the given name.
+///
+/// `project_dir` must be a valid Sliderule component directory. The project itself is not included in the search.
+///
+/// Every match is returned in tree order, so the caller has to decide what to do when the same name is used in
+/// more than one place (i.e. a local component and a remote component that share a name in different parts of
+/// the tree). The `level` of each match tells whether it is local or remote.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// sliderule::create_component(&temp_dir.join("toplevel"), String::from("level1"), String::from("Level 1 component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let matches = sliderule::hierarchy::find_component(&temp_dir.join("toplevel"), "level1");
+///
+/// match matches.len() {
+///     0 => println!("No component with that name."),
+///     1 => println!("Found it at {}", matches[0].path.display()),
+///     _ => println!("That name is ambiguous."),
+/// }
+/// # assert_eq!(matches.len(), 1);
+/// # assert_eq!(matches[0].level, sliderule::ComponentLevel::LocalSubComponent);
+/// ```
+pub fn find_component(project_dir: &Path, name: &str) -> Vec<ComponentEntry> {
+    components_iter(project_dir, TraversalOrder::PreOrder)
+        .skip(1)
+        .filter(|c| c.name == name)
+        .collect()
+}
+
+/// Finds the component that a path belongs to by walking upward until a directory with a `.sr` file is found.
+/// The path itself is checked first, so a component directory is its own root.
+///
+/// This lets tools that are run from somewhere deep inside a component, like `source/cad/parts`, find the
+/// component they are working on. `None` is returned if the path is not inside a component.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// sliderule::create_component(&temp_dir.join("toplevel"), String::from("level1"), String::from("Level 1 component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let level1_dir = temp_dir.join("toplevel").join("components").join("level1");
+/// let root = sliderule::hierarchy::find_component_root(&level1_dir.join("source"));
+///
+/// assert_eq!(root, Some(level1_dir.canonicalize().unwrap()));
+/// ```
+pub fn find_component_root(path: &Path) -> Option<PathBuf> {
+    full_path(path)
+        .ancestors()
+        .find(|a| a.join(".sr").exists())
+        .map(|a| a.to_path_buf())
+}
+
+/// Finds the top level project that a path belongs to. This is the outermost directory at or above the path
+/// that has a `.sr` file, so it is the same as the component root when the path is not inside a sub-component.
+///
+/// `None` is returned if the path is not inside a component.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// sliderule::create_component(&temp_dir.join("toplevel"), String::from("level1"), String::from("Level 1 component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let level1_dir = temp_dir.join("toplevel").join("components").join("level1");
+/// let root = sliderule::hierarchy::find_project_root(&level1_dir.join("source"));
+///
+/// assert_eq!(root, Some(temp_dir.join("toplevel").canonicalize().unwrap()));
+/// ```
+pub fn find_project_root(path: &Path) -> Option<PathBuf> {
+    full_path(path)
+        .ancestors()
+        .filter(|a| a.join(".sr").exists())
+        .last()
+        .map(|a| a.to_path_buf())
+}
+
+/*
+ * Resolves relative paths like "." so that their ancestors can be walked.
+ */
+fn full_path(path: &Path) -> PathBuf {
+    match fs::canonicalize(path) {
+        Ok(p) => p,
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/*
+ * Recursively collects the children of a component, skipping any directory that's already been seen.
+ */
+fn build_node(component: ComponentEntry, visited: &mut Vec<PathBuf>) -> ComponentNode {
+    let mut node = ComponentNode {
+        component,
+        children: Vec::new(),
+    };
+
+    let real_path = full_path(&node.component.path);
+    if visited.contains(&real_path) {
+        return node;
+    }
+    visited.push(real_path);
+
+    let list = list_components(&node.component.path);
+    for child in list.local.into_iter().chain(list.remote) {
+        node.children.push(build_node(child, visited));
+    }
+
+    node
+}
+
+/*
+ * Gathers the metadata for the component at the given path.
+ */
+fn component_entry(path: PathBuf, name: String) -> ComponentEntry {
+    let licenses = super::get_licenses(&path);
+    let (level, _) = super::get_level(&path);
+
+    ComponentEntry {
+        name,
+        source_license: licenses.0,
+        doc_license: licenses.1,
+        level,
+        path,
+    }
+}
+
+/*
+ * Reads the components out of a single components or node_modules directory.
+ */
+fn list_component_dir(dir: &Path, prefix: &str) -> Vec<ComponentEntry> {
+    let mut entries = Vec::new();
+
+    let read_dir = match fs::read_dir(dir) {
+        Ok(r) => r,
+        Err(_) => return entries,
+    };
+
+    for entry in read_dir.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+
+        let dir_name = entry.file_name().to_string_lossy().to_string();
+
+        // Scoped packages are stored one directory deeper than the others
+        if prefix.is_empty() && dir_name.starts_with('@') {
+            entries.append(&mut list_component_dir(&path, &dir_name));
+            continue;
+        }
+
+        if !path.join(".sr").exists() {
+            continue;
+        }
+
+        let name = if prefix.is_empty() {
+            dir_name
+        } else {
+            format!("{}/{}", prefix, dir_name)
+        };
+
+        entries.push(component_entry(path, name));
+    }
+
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    entries
+}
@@ -0,0 +1,490 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use super::json::JsonValue;
+
+/// The largest request body that is accepted, in bytes.
+pub const MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// How long a connection can sit without sending anything before it's closed.
+pub const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/*
+ * Operations change files on disk, so only one runs at a time, no matter how many people are using the dashboard.
+ */
+static RUNNING: Mutex<()> = Mutex::new(());
+
+/// A request as the server received it. The path has had its query string removed and is percent-decoded one
+/// segment at a time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub method: String,
+    pub segments: Vec<String>,
+    /// The value of the `Authorization` header, if there was one
+    pub authorization: Option<String>,
+    pub body: String,
+}
+
+/// A response to send back, with a JSON body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Listens for HTTP requests at `address` and answers them with `respond`, on a thread for each connection. This
+/// only returns if the address can't be listened on.
+///
+/// Every request has to carry `token` as a bearer token, i.e. `Authorization: Bearer <token>`. The server doesn't
+/// do TLS, so it should only be reachable from the machine it runs on or through a proxy that does.
+///
+/// # Examples
+///
+/// ```no_run
+/// let projects = vec![std::path::PathBuf::from("/srv/projects/toplevel")];
+///
+/// sliderule::http_api::serve_http("127.0.0.1:8080", &projects, "change-me").expect("Unable to start the API.");
+/// ```
+pub fn serve_http<A: ToSocketAddrs>(
+    address: A,
+    project_dirs: &[PathBuf],
+    token: &str,
+) -> io::Result<()> {
+    serve_listener(TcpListener::bind(address)?, project_dirs, token)
+}
+
+/// Answers requests on a listener that has already been bound, the same way as `serve_http`.
+pub fn serve_listener(
+    listener: TcpListener,
+    project_dirs: &[PathBuf],
+    token: &str,
+) -> io::Result<()> {
+    if token.trim().is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "A token is required to serve the API.",
+        ));
+    }
+
+    let project_dirs = Arc::new(project_dirs.to_vec());
+    let token = Arc::new(token.to_string());
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(s) => s,
+            Err(_) => continue,
+        };
+
+        let project_dirs = Arc::clone(&project_dirs);
+        let token = Arc::clone(&token);
+        thread::spawn(move || {
+            let _ = handle_connection(stream, &project_dirs, &token);
+        });
+    }
+
+    Ok(())
+}
+
+/// Works out the response to a request. These are the endpoints, where `{project}` is the name of one of the
+/// project directories:
+///
+/// - `GET /projects` lists the projects with their licenses
+/// - `GET /projects/{project}` gives the project's component tree, as `hierarchy::export_tree` does
+/// - `GET /projects/{project}/status` gives the repository state of the project and its remote components
+/// - `GET /projects/{project}/summary` gives the state of every component, as `status::summarize_project` does
+/// - `GET /projects/{project}/licenses` gives the report from `list_all_licenses`
+/// - `GET /projects/{project}/manifest` gives every remote component, as `hierarchy::export_dependency_manifest` does
+/// - `GET /projects/{project}/validate` runs `health::validate_component`
+/// - `GET /projects/{project}/verify` runs `verify_locked`
+/// - `POST /projects/{project}/update` runs `update_dependencies`
+/// - `POST /projects/{project}/components` adds the remote component at the `url` in a JSON body
+/// - `DELETE /projects/{project}/components/{name}` removes a component
+///
+/// Operations answer with the JSON from `ci::to_json`, with a response status of 200 if they succeeded, 422 if a
+/// check failed, 502 if a tool failed and 500 otherwise. A missing or wrong token gets 401.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn respond(request: &HttpRequest, project_dirs: &[PathBuf], token: &str) -> HttpResponse {
+    let authorized = match request.authorization {
+        Some(ref a) => match a.strip_prefix("Bearer ") {
+            Some(t) => super::auth::tokens_match(t.trim(), token),
+            None => false,
+        },
+        None => false,
+    };
+    if !authorized {
+        return error(401, "A valid bearer token is required.");
+    }
+
+    let segments: Vec<&str> = request.segments.iter().map(|s| s.as_str()).collect();
+    if segments.first() != Some(&"projects") {
+        return error(404, "There is nothing here.");
+    }
+
+    if segments.len() == 1 {
+        return match request.method.as_str() {
+            "GET" => list_projects(project_dirs),
+            _ => error(405, "Only GET is allowed here."),
+        };
+    }
+
+    let project_dir = match project_dirs
+        .iter()
+        .find(|d| d.file_name().map(|n| n.to_string_lossy() == segments[1]) == Some(true))
+    {
+        Some(d) => d.clone(),
+        None => return error(404, &format!("There is no project named {}.", segments[1])),
+    };
+
+    // Component names come from the URL, so they have to be plain names before they're used to find a directory
+    if let ["components", name] = segments[2..] {
+        let mut parts = Path::new(name).components();
+        let single = matches!(
+            (parts.next(), parts.next()),
+            (Some(Component::Normal(_)), None)
+        );
+        if !single || super::validate_name(name).status != 0 {
+            return error(400, &format!("{} is not a valid component name.", name));
+        }
+    }
+
+    let body = request.body.clone();
+    let route: Option<Box<dyn FnOnce() -> HttpResponse>> =
+        match (request.method.as_str(), &segments[2..]) {
+            ("GET", []) => Some(Box::new(move || {
+                ok(super::hierarchy::export_tree(
+                    &project_dir,
+                    super::hierarchy::ExportFormat::Json,
+                ))
+            })),
+            ("GET", ["status"]) => Some(Box::new(move || project_status(&project_dir))),
+            ("GET", ["summary"]) => Some(Box::new(move || project_summary(&project_dir))),
+            ("GET", ["licenses"]) => Some(Box::new(move || {
+                ok(JsonValue::Object(vec![(
+                    String::from("report"),
+                    JsonValue::String(super::list_all_licenses(&project_dir)),
+                )])
+                .to_json())
+            })),
+            ("GET", ["manifest"]) => Some(Box::new(move || {
+                ok(super::hierarchy::export_dependency_manifest(
+                    &project_dir,
+                    super::hierarchy::ManifestFormat::Json,
+                ))
+            })),
+            ("GET", ["validate"]) => Some(Box::new(move || {
+                operation(super::health::validate_component(&project_dir))
+            })),
+            ("GET", ["verify"]) => Some(Box::new(move || {
+                operation(super::verify_locked(&project_dir))
+            })),
+            ("POST", ["update"]) => Some(Box::new(move || {
+                operation(super::update_dependencies(&project_dir))
+            })),
+            ("POST", ["components"]) => Some(Box::new(move || {
+                let url = match super::json::parse(&body) {
+                    Ok(b) => b.get_str("url"),
+                    Err(e) => return error(400, &format!("The body isn't valid JSON: {}", e)),
+                };
+                if url.is_empty() {
+                    return error(400, "The url of the component is required.");
+                }
+
+                operation(super::add_remote_component(&project_dir, &url, None))
+            })),
+            ("DELETE", ["components", name]) => {
+                let name = name.to_string();
+                Some(Box::new(move || {
+                    operation(super::remove(&project_dir, &name))
+                }))
+            }
+            _ => None,
+        };
+
+    let route = match route {
+        Some(r) => r,
+        None => return error(404, "There is nothing here."),
+    };
+
+    let _running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+    panic::catch_unwind(AssertUnwindSafe(route))
+        .unwrap_or_else(|_| error(500, "The operation failed unexpectedly."))
+}
+
+/*
+ * Reads one request from a connection and answers it. Connections aren't kept alive.
+ */
+fn handle_connection(stream: TcpStream, project_dirs: &[PathBuf], token: &str) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    let response = match read_request(&mut reader) {
+        Ok(r) => respond(&r, project_dirs, token),
+        Err(e) => error(400, &e),
+    };
+
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        502 => "Bad Gateway",
+        _ => "Internal Server Error",
+    };
+
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason,
+        response.body.len()
+    );
+    if response.status == 401 {
+        head.push_str("WWW-Authenticate: Bearer\r\n");
+    }
+    head.push_str("\r\n");
+
+    writer.write_all(head.as_bytes())?;
+    writer.write_all(response.body.as_bytes())?;
+    writer.flush()
+}
+
+/*
+ * Parses the request line, the headers that matter and the body.
+ */
+fn read_request(reader: &mut dyn BufRead) -> Result<HttpRequest, String> {
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|e| e.to_string())?;
+
+    let mut parts = line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(m), Some(t)) => (m.to_string(), t.to_string()),
+        _ => return Err(String::from("The request line is malformed.")),
+    };
+
+    let mut authorization = None;
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).map_err(|e| e.to_string())?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some(i) = header.find(':') {
+            let value = header[i + 1..].trim();
+            match header[..i].to_lowercase().as_str() {
+                "authorization" => authorization = Some(value.to_string()),
+                "content-length" => {
+                    length = value
+                        .parse()
+                        .map_err(|_| String::from("The Content-Length header is malformed."))?
+                }
+                _ => (),
+            }
+        }
+    }
+
+    if length > MAX_BODY_SIZE {
+        return Err(String::from("The request body is too large."));
+    }
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+
+    let path = target.split('?').next().unwrap_or_default();
+    let segments = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(percent_decode)
+        .collect::<Result<Vec<String>, String>>()?;
+
+    Ok(HttpRequest {
+        method,
+        segments,
+        authorization,
+        body: String::from_utf8_lossy(&body).to_string(),
+    })
+}
+
+fn list_projects(project_dirs: &[PathBuf]) -> HttpResponse {
+    let projects = project_dirs
+        .iter()
+        .map(|d| {
+            let (source_license, doc_license) = super::get_licenses(d);
+            JsonValue::Object(vec![
+                (
+                    String::from("name"),
+                    JsonValue::String(
+                        d.file_name()
+                            .map(|n| n.to_string_lossy().to_string())
+                            .unwrap_or_default(),
+                    ),
+                ),
+                (
+                    String::from("path"),
+                    JsonValue::String(d.display().to_string()),
+                ),
+                (
+                    String::from("source_license"),
+                    JsonValue::String(source_license),
+                ),
+                (String::from("doc_license"), JsonValue::String(doc_license)),
+            ])
+        })
+        .collect();
+
+    ok(JsonValue::Array(projects).to_json())
+}
+
+fn project_status(project_dir: &Path) -> HttpResponse {
+    let status = super::status::project_status(project_dir);
+
+    let components = status
+        .components
+        .into_iter()
+        .map(|c| {
+            let up_to_date = c.is_up_to_date();
+            JsonValue::Object(vec![
+                (String::from("name"), JsonValue::String(c.name)),
+                (
+                    String::from("path"),
+                    JsonValue::String(c.path.display().to_string()),
+                ),
+                (String::from("is_repo"), JsonValue::Bool(c.is_repo)),
+                (String::from("branch"), JsonValue::String(c.branch)),
+                (String::from("upstream"), JsonValue::String(c.upstream)),
+                (String::from("ahead"), JsonValue::Number(c.ahead as f64)),
+                (String::from("behind"), JsonValue::Number(c.behind as f64)),
+                (
+                    String::from("changed_files"),
+                    JsonValue::Number(c.changed_files as f64),
+                ),
+                (String::from("fetched"), JsonValue::Bool(c.fetched)),
+                (String::from("up_to_date"), JsonValue::Bool(up_to_date)),
+                (
+                    String::from("messages"),
+                    JsonValue::Array(c.messages.into_iter().map(JsonValue::String).collect()),
+                ),
+            ])
+        })
+        .collect();
+
+    ok(JsonValue::Object(vec![(
+        String::from("components"),
+        JsonValue::Array(components),
+    )])
+    .to_json())
+}
+
+fn project_summary(project_dir: &Path) -> HttpResponse {
+    let summary = super::status::summarize_project(project_dir);
+
+    let components = summary
+        .components
+        .into_iter()
+        .map(|c| {
+            let ok = c.is_ok();
+            // The same names as in the component tree
+            let level = match c.level {
+                super::ComponentLevel::NotAComponent => "not_a_component",
+                super::ComponentLevel::TopLevelProject => "project",
+                super::ComponentLevel::LocalSubComponent => "local",
+                super::ComponentLevel::RemoteComponent => "remote",
+            };
+            JsonValue::Object(vec![
+                (String::from("name"), JsonValue::String(c.name)),
+                (
+                    String::from("path"),
+                    JsonValue::String(c.path.display().to_string()),
+                ),
+                (String::from("level"), JsonValue::String(level.to_string())),
+                (String::from("is_repo"), JsonValue::Bool(c.is_repo)),
+                (String::from("dirty"), JsonValue::Bool(c.dirty)),
+                (String::from("ahead"), JsonValue::Number(c.ahead as f64)),
+                (String::from("behind"), JsonValue::Number(c.behind as f64)),
+                (
+                    String::from("warnings"),
+                    JsonValue::Number(c.warnings as f64),
+                ),
+                (
+                    String::from("outdated"),
+                    JsonValue::Number(c.outdated as f64),
+                ),
+                (String::from("ok"), JsonValue::Bool(ok)),
+                (
+                    String::from("messages"),
+                    JsonValue::Array(c.messages.into_iter().map(JsonValue::String).collect()),
+                ),
+            ])
+        })
+        .collect();
+
+    ok(JsonValue::Object(vec![(
+        String::from("components"),
+        JsonValue::Array(components),
+    )])
+    .to_json())
+}
+
+/*
+ * Answers with the output of an operation, with a response status that matches how it went.
+ */
+fn operation(output: super::SROutput) -> HttpResponse {
+    let status = match super::ci::exit_code(&output) {
+        super::ci::EXIT_OK => 200,
+        super::ci::EXIT_CHECK_FAILED => 422,
+        super::ci::EXIT_TOOL_FAILED => 502,
+        _ => 500,
+    };
+
+    HttpResponse {
+        status,
+        body: super::ci::to_json(&output),
+    }
+}
+
+fn ok(body: String) -> HttpResponse {
+    HttpResponse { status: 200, body }
+}
+
+fn error(status: u16, message: &str) -> HttpResponse {
+    HttpResponse {
+        status,
+        body: JsonValue::Object(vec![(
+            String::from("error"),
+            JsonValue::String(message.to_string()),
+        )])
+        .to_json(),
+    }
+}
+
+fn percent_decode(segment: &str) -> Result<String, String> {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = segment
+                .get(i + 1..i + 3)
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+                .ok_or_else(|| String::from("The path has a malformed escape in it."))?;
+            decoded.push(hex);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).map_err(|_| String::from("The path isn't valid UTF-8."))
+}
@@ -0,0 +1,244 @@
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// The file at the root of a project that records the operations that changed it, so that they can be undone
+/// with `undo_last`. Each line is one operation, with the time it was done, the directory it was done in relative
+/// to the project, what it was and what it was done to, all separated by tabs. New projects have git ignore it.
+pub const JOURNAL_FILE: &str = ".sr_journal";
+
+/// An operation that changed a project, with what's needed to reverse it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    /// A local component was created
+    Created { name: String },
+    /// A remote component was installed from a URL
+    AddedRemote { name: String, url: String },
+    /// A remote component that had been installed from a URL was removed
+    RemovedRemote { name: String, url: String },
+    /// A local component was deleted outright, which can't be undone
+    DeletedLocal { name: String },
+    /// A local component was moved into the trash by `remove_with_backup`
+    TrashedLocal { name: String },
+    /// A local component was brought back out of the trash by `restore_removed`
+    Restored { name: String },
+    /// A component was renamed
+    Renamed { name: String, new_name: String },
+    /// A local component was copied to start a new one
+    Duplicated { name: String, new_name: String },
+}
+
+impl Operation {
+    /*
+     * The name and values that the operation is written to the journal with.
+     */
+    fn fields(&self) -> (&'static str, Vec<&str>) {
+        match *self {
+            Operation::Created { ref name } => ("created", vec![name]),
+            Operation::AddedRemote { ref name, ref url } => ("added_remote", vec![name, url]),
+            Operation::RemovedRemote { ref name, ref url } => ("removed_remote", vec![name, url]),
+            Operation::DeletedLocal { ref name } => ("deleted_local", vec![name]),
+            Operation::TrashedLocal { ref name } => ("trashed_local", vec![name]),
+            Operation::Restored { ref name } => ("restored", vec![name]),
+            Operation::Renamed {
+                ref name,
+                ref new_name,
+            } => ("renamed", vec![name, new_name]),
+            Operation::Duplicated {
+                ref name,
+                ref new_name,
+            } => ("duplicated", vec![name, new_name]),
+        }
+    }
+
+    /*
+     * Reads an operation back from the fields that it was written with.
+     */
+    fn from_fields(kind: &str, values: &[&str]) -> Option<Operation> {
+        let value = |i: usize| values.get(i).map(|v| v.to_string());
+
+        match kind {
+            "created" => Some(Operation::Created { name: value(0)? }),
+            "added_remote" => Some(Operation::AddedRemote {
+                name: value(0)?,
+                url: value(1)?,
+            }),
+            "removed_remote" => Some(Operation::RemovedRemote {
+                name: value(0)?,
+                url: value(1)?,
+            }),
+            "deleted_local" => Some(Operation::DeletedLocal { name: value(0)? }),
+            "trashed_local" => Some(Operation::TrashedLocal { name: value(0)? }),
+            "restored" => Some(Operation::Restored { name: value(0)? }),
+            "renamed" => Some(Operation::Renamed {
+                name: value(0)?,
+                new_name: value(1)?,
+            }),
+            "duplicated" => Some(Operation::Duplicated {
+                name: value(0)?,
+                new_name: value(1)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Operation::Created { ref name } => write!(f, "creating {}", name),
+            Operation::AddedRemote { ref name, .. } => write!(f, "adding {}", name),
+            Operation::RemovedRemote { ref name, .. } => write!(f, "removing {}", name),
+            Operation::DeletedLocal { ref name } => write!(f, "deleting {}", name),
+            Operation::TrashedLocal { ref name } => write!(f, "removing {}", name),
+            Operation::Restored { ref name } => write!(f, "restoring {}", name),
+            Operation::Renamed {
+                ref name,
+                ref new_name,
+            } => write!(f, "renaming {} to {}", name, new_name),
+            Operation::Duplicated {
+                ref name,
+                ref new_name,
+            } => write!(f, "duplicating {} as {}", name, new_name),
+        }
+    }
+}
+
+/// One operation in a project's journal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    /// When the operation was done, in seconds since the Unix epoch
+    pub time: u64,
+    /// The component directory that the operation was done in
+    pub component_dir: PathBuf,
+    pub operation: Operation,
+}
+
+/// Adds an operation to the journal of the project that `component_dir` is part of. A journal that can't be
+/// written to is reported as a warning, since the operation itself has already been done.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn record(component_dir: &Path, operation: Operation) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let project_dir = super::config::project_root(component_dir);
+    let relative = match component_dir.strip_prefix(&project_dir) {
+        Ok(r) if r.as_os_str().is_empty() => String::from("."),
+        Ok(r) => r.to_string_lossy().replace('\\', "/"),
+        Err(_) => String::from("."),
+    };
+
+    let time = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let (kind, values) = operation.fields();
+    let mut line = format!("{}\t{}\t{}", time, relative, kind);
+    for value in values {
+        line.push('\t');
+        line.push_str(value);
+    }
+    line.push('\n');
+
+    let result = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(project_dir.join(JOURNAL_FILE))
+        .and_then(|mut f| f.write_all(line.as_bytes()));
+    if let Err(e) = result {
+        output.stderr.push(format!(
+            "WARNING: Could not record {} in the journal, so it can't be undone: {}",
+            operation, e
+        ));
+    }
+
+    output
+}
+
+/// Reads the journal of a project, oldest operation first. Lines that can't be read are skipped.
+///
+/// `project_dir` must be the top level component of a project.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// sliderule::create_component(&temp_dir.join("toplevel"), String::from("level1"), String::from("Level 1 component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let entries = sliderule::journal::entries(&temp_dir.join("toplevel"));
+///
+/// assert_eq!(entries[0].operation, sliderule::journal::Operation::Created { name: String::from("level1") });
+/// ```
+pub fn entries(project_dir: &Path) -> Vec<JournalEntry> {
+    let contents = match fs::read_to_string(project_dir.join(JOURNAL_FILE)) {
+        Ok(c) => c,
+        Err(_) => return Vec::new(),
+    };
+
+    contents
+        .lines()
+        .filter_map(|line| parse_line(project_dir, line))
+        .collect()
+}
+
+/// Cuts a project's journal down to its first `len` operations, forgetting the rest.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn truncate(project_dir: &Path, len: usize) -> std::io::Result<()> {
+    let journal_file = project_dir.join(JOURNAL_FILE);
+    let contents = match fs::read_to_string(&journal_file) {
+        Ok(c) => c,
+        Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+
+    // Count the same lines that entries() does, so unreadable ones don't throw the count off
+    let mut kept = String::new();
+    let mut count = 0;
+    for line in contents.lines() {
+        if count == len {
+            break;
+        }
+        if parse_line(project_dir, line).is_some() {
+            count += 1;
+        }
+        kept.push_str(line);
+        kept.push('\n');
+    }
+
+    fs::write(journal_file, kept)
+}
+
+/*
+ * Reads the entry on a single line of a journal, if there is one.
+*/
+fn parse_line(project_dir: &Path, line: &str) -> Option<JournalEntry> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    if fields.len() < 3 {
+        return None;
+    }
+
+    let time = fields[0].parse::<u64>().ok()?;
+    let component_dir = if fields[1] == "." {
+        project_dir.to_path_buf()
+    } else {
+        project_dir.join(fields[1])
+    };
+    let operation = Operation::from_fields(fields[2], &fields[3..])?;
+
+    Some(JournalEntry {
+        time,
+        component_dir,
+        operation,
+    })
+}
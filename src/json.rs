@@ -0,0 +1,316 @@
+// A small JSON reader and writer for documents that Sliderule doesn't generate itself (catalogs, requests),
+// where the line-based helpers in lib.rs can't be relied on.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /*
+     * Looks up a key if this is an object.
+     */
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match *self {
+            JsonValue::Object(ref members) => members.iter().find(|m| m.0 == key).map(|m| &m.1),
+            _ => None,
+        }
+    }
+
+    /*
+     * Returns the string contents if this is a string.
+     */
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            JsonValue::String(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /*
+     * Shorthand for getting a string member of an object, with an empty string if it's missing.
+     */
+    pub fn get_str(&self, key: &str) -> String {
+        match self.get(key).and_then(|v| v.as_str()) {
+            Some(s) => s.to_string(),
+            None => String::new(),
+        }
+    }
+
+    /*
+     * Writes the value out as compact JSON text.
+     */
+    pub fn to_json(&self) -> String {
+        match *self {
+            JsonValue::Null => String::from("null"),
+            JsonValue::Bool(b) => b.to_string(),
+            JsonValue::Number(n) => n.to_string(),
+            JsonValue::String(ref s) => quote(s),
+            JsonValue::Array(ref items) => {
+                let items: Vec<String> = items.iter().map(|i| i.to_json()).collect();
+                format!("[{}]", items.join(","))
+            }
+            JsonValue::Object(ref members) => {
+                let members: Vec<String> = members
+                    .iter()
+                    .map(|m| format!("{}:{}", quote(&m.0), m.1.to_json()))
+                    .collect();
+                format!("{{{}}}", members.join(","))
+            }
+        }
+    }
+}
+
+/*
+ * Escapes a string and wraps it in quotes so that it can be embedded in JSON text.
+ */
+pub fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+
+    quoted
+}
+
+/*
+ * Parses a complete JSON document, returning a description of the problem if it isn't valid.
+ */
+pub fn parse(text: &str) -> Result<JsonValue, String> {
+    // Files saved by some Windows editors start with a byte order mark
+    let mut parser = Parser {
+        chars: text.trim_start_matches('\u{feff}').chars().collect(),
+        pos: 0,
+    };
+
+    let value = parser.parse_value()?;
+
+    parser.skip_whitespace();
+    if parser.pos < parser.chars.len() {
+        return Err(format!(
+            "Unexpected text after the end of the document at character {}",
+            parser.pos
+        ));
+    }
+
+    Ok(value)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).cloned()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => Err(format!("Expected '{}' at character {}", expected, self.pos)),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue, String> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some('{') => self.parse_object(),
+            Some('[') => self.parse_array(),
+            Some('"') => Ok(JsonValue::String(self.parse_string()?)),
+            Some('t') => self.parse_literal("true", JsonValue::Bool(true)),
+            Some('f') => self.parse_literal("false", JsonValue::Bool(false)),
+            Some('n') => self.parse_literal("null", JsonValue::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.parse_number(),
+            Some(c) => Err(format!("Unexpected '{}' at character {}", c, self.pos)),
+            None => Err(String::from("Unexpected end of the document")),
+        }
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: JsonValue) -> Result<JsonValue, String> {
+        let end = self.pos + literal.len();
+
+        if end <= self.chars.len()
+            && self.chars[self.pos..end]
+                .iter()
+                .cloned()
+                .eq(literal.chars())
+        {
+            self.pos = end;
+            Ok(value)
+        } else {
+            Err(format!("Unexpected text at character {}", self.pos))
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue, String> {
+        let start = self.pos;
+
+        while let Some(c) = self.peek() {
+            if !(c.is_ascii_digit() || c == '-' || c == '+' || c == '.' || c == 'e' || c == 'E') {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        let text: String = self.chars[start..self.pos].iter().collect();
+        match text.parse::<f64>() {
+            Ok(n) => Ok(JsonValue::Number(n)),
+            Err(_) => Err(format!("Invalid number '{}' at character {}", text, start)),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+
+        let mut text = String::new();
+        loop {
+            let c = match self.peek() {
+                Some(c) => c,
+                None => return Err(String::from("Unterminated string")),
+            };
+            self.pos += 1;
+
+            match c {
+                '"' => break,
+                '\\' => {
+                    let escaped = match self.peek() {
+                        Some(e) => e,
+                        None => return Err(String::from("Unterminated string")),
+                    };
+                    self.pos += 1;
+
+                    match escaped {
+                        'n' => text.push('\n'),
+                        'r' => text.push('\r'),
+                        't' => text.push('\t'),
+                        'b' => text.push('\u{8}'),
+                        'f' => text.push('\u{c}'),
+                        'u' => text.push(self.parse_unicode_escape()?),
+                        other => text.push(other),
+                    }
+                }
+                c => text.push(c),
+            }
+        }
+
+        Ok(text)
+    }
+
+    fn parse_unicode_escape(&mut self) -> Result<char, String> {
+        let code = self.parse_hex4()?;
+
+        // Characters outside of the basic plane are written as a surrogate pair
+        if (0xD800..0xDC00).contains(&code) && self.chars[self.pos..].starts_with(&['\\', 'u']) {
+            self.pos += 2;
+            let low = self.parse_hex4()?;
+            let combined = 0x10000 + ((code - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+            return Ok(::std::char::from_u32(combined).unwrap_or('\u{FFFD}'));
+        }
+
+        Ok(::std::char::from_u32(code).unwrap_or('\u{FFFD}'))
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, String> {
+        if self.pos + 4 > self.chars.len() {
+            return Err(String::from("Unterminated unicode escape"));
+        }
+
+        let hex: String = self.chars[self.pos..self.pos + 4].iter().collect();
+        self.pos += 4;
+
+        u32::from_str_radix(&hex, 16).map_err(|_| format!("Invalid unicode escape '{}'", hex))
+    }
+
+    fn parse_array(&mut self) -> Result<JsonValue, String> {
+        self.expect('[')?;
+
+        let mut items = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(JsonValue::Array(items));
+        }
+
+        loop {
+            items.push(self.parse_value()?);
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => return Err(format!("Expected ',' or ']' at character {}", self.pos)),
+            }
+        }
+
+        Ok(JsonValue::Array(items))
+    }
+
+    fn parse_object(&mut self) -> Result<JsonValue, String> {
+        self.expect('{')?;
+
+        let mut members = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(JsonValue::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.expect(':')?;
+            let value = self.parse_value()?;
+            members.push((key, value));
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => return Err(format!("Expected ',' or '}}' at character {}", self.pos)),
+            }
+        }
+
+        Ok(JsonValue::Object(members))
+    }
+}
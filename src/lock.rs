@@ -0,0 +1,516 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use super::checksum::Sha256;
+use super::hierarchy::TraversalOrder;
+use super::json::JsonValue;
+use super::ComponentLevel;
+
+/// The file next to each `package-lock.json` that records a checksum of every installed package, so that changes
+/// made to them after they were installed can be found. Each line is a checksum and the package's path in the
+/// lock file, separated by a tab. It's written by `lock_installed`.
+pub const CHECKSUM_FILE: &str = ".sr_lock";
+
+/// The file next to a component's `package.json` that records the dependencies that have been pinned to a commit
+/// by `pin_dependency`, so that `update_dependencies` keeps them there. Each line is the name of a dependency, the
+/// commit it's pinned to, and the branch or tag that its URL had before it was pinned (which is empty if it had
+/// none), separated by tabs. It's meant to be committed along with the component.
+pub const PINS_FILE: &str = ".sr_pins";
+
+/// An installed package that doesn't match what the lock file says should be installed, as found by `find_drift`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockDrift {
+    /// The package's path in the lock file, i.e. `node_modules/part`
+    pub name: String,
+    /// The directory holding the lock file
+    pub path: PathBuf,
+    /// How the installed package is different
+    pub reason: String,
+}
+
+/// Compares everything installed in a project, and in each of its local components, with their
+/// `package-lock.json` files. Packages that are missing, weren't locked, were installed from a different commit
+/// or version than the one locked, or have been changed since `lock_installed` was run, are all reported.
+///
+/// `project_dir` must be a valid Sliderule component directory.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// std::fs::write(
+///     temp_dir.join("toplevel").join("package-lock.json"),
+///     r#"{"lockfileVersion": 3, "packages": {"": {}, "node_modules/part": {"version": "1.0.0"}}}"#,
+/// ).expect("Unable to write lock file.");
+///
+/// let drift = sliderule::lock::find_drift(&temp_dir.join("toplevel"));
+///
+/// assert_eq!(drift[0].name, "node_modules/part");
+/// assert_eq!(drift[0].reason, "Locked, but not installed.");
+/// ```
+pub fn find_drift(project_dir: &Path) -> Vec<LockDrift> {
+    let mut drift = Vec::new();
+
+    for lock_dir in lock_dirs(project_dir) {
+        let locked = match read_lock_file(&lock_dir) {
+            Ok(l) => l,
+            Err(reason) => {
+                drift.push(LockDrift {
+                    name: String::from("package-lock.json"),
+                    path: lock_dir,
+                    reason,
+                });
+                continue;
+            }
+        };
+        let checksums = read_checksum_file(&lock_dir);
+        let installed = read_hidden_lock_file(&lock_dir);
+
+        let mut report = |name: &str, reason: String| {
+            drift.push(LockDrift {
+                name: name.to_string(),
+                path: lock_dir.clone(),
+                reason,
+            });
+        };
+
+        for package in &locked {
+            let package_dir = lock_dir.join(&package.name);
+            if !package_dir.join("package.json").exists() {
+                report(&package.name, String::from("Locked, but not installed."));
+                continue;
+            }
+
+            let manifest = fs::read_to_string(package_dir.join("package.json"))
+                .ok()
+                .and_then(|c| super::json::parse(&c).ok());
+            let manifest = match manifest {
+                Some(m) => m,
+                None => {
+                    report(
+                        &package.name,
+                        String::from("The installed package.json file can't be read."),
+                    );
+                    continue;
+                }
+            };
+
+            if let Some(ref locked_commit) = package.commit {
+                if let Some(commit) = installed_commit(&manifest, &installed, &package.name) {
+                    if !commit.starts_with(locked_commit.as_str())
+                        && !locked_commit.starts_with(commit.as_str())
+                    {
+                        report(
+                            &package.name,
+                            format!(
+                                "Installed from commit {}, but commit {} is locked.",
+                                commit, locked_commit
+                            ),
+                        );
+                        continue;
+                    }
+                }
+            }
+
+            let version = manifest.get_str("version");
+            if !package.version.is_empty() && package.commit.is_none() && version != package.version
+            {
+                report(
+                    &package.name,
+                    format!(
+                        "Version {} is installed, but version {} is locked.",
+                        version, package.version
+                    ),
+                );
+                continue;
+            }
+
+            if let Some((_, checksum)) = checksums.iter().find(|c| c.0 == package.name) {
+                if tree_checksum(&package_dir).as_ref() != Some(checksum) {
+                    report(
+                        &package.name,
+                        String::from("Changed since it was installed."),
+                    );
+                }
+            }
+        }
+
+        for name in installed_packages(&lock_dir) {
+            if !locked.iter().any(|p| p.name == name) {
+                report(&name, String::from("Installed, but not locked."));
+            }
+        }
+    }
+
+    drift
+}
+
+/// Records a checksum of every package that is installed in a project, and in each of its local components, in
+/// the `CHECKSUM_FILE` next to their `package-lock.json` files. Only packages that are in a lock file are
+/// recorded, so `find_drift` should be clean before this is run.
+///
+/// `project_dir` must be a valid Sliderule component directory.
+///
+/// The output status is 42 if a checksum file can't be written.
+pub fn lock_installed(project_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    for lock_dir in lock_dirs(project_dir) {
+        let mut locked = match read_lock_file(&lock_dir) {
+            Ok(l) => l,
+            Err(reason) => {
+                output.status = 42;
+                output.stderr.push(format!(
+                    "ERROR: Unable to lock {}: {}",
+                    lock_dir.display(),
+                    reason
+                ));
+                continue;
+            }
+        };
+
+        // In the same order no matter how the lock file was written
+        locked.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut contents = String::new();
+        let mut count = 0;
+        for package in &locked {
+            if let Some(checksum) = tree_checksum(&lock_dir.join(&package.name)) {
+                contents.push_str(&format!("{}\t{}\n", checksum, package.name));
+                count += 1;
+            }
+        }
+
+        match fs::write(lock_dir.join(CHECKSUM_FILE), contents) {
+            Ok(_) => output.stdout.push(format!(
+                "Locked {} installed packages in {}.",
+                count,
+                lock_dir.display()
+            )),
+            Err(e) => {
+                output.status = 42;
+                output.stderr.push(format!(
+                    "ERROR: Unable to write {}: {}",
+                    lock_dir.join(CHECKSUM_FILE).display(),
+                    e
+                ));
+            }
+        }
+    }
+
+    output
+}
+
+/// What a lock file says about one package.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockedPackage {
+    /// The package's path in the lock file, i.e. `node_modules/part`
+    pub name: String,
+    pub version: String,
+    /// Where the package was installed from, i.e. `git+ssh://git@github.com/user/part.git#<commit>`
+    pub resolved: String,
+    /// The commit that a package from a git repository is locked to
+    pub commit: Option<String>,
+}
+
+/// Lists the project directory and the local component directories inside of it that have a lock file.
+pub fn lock_dirs(project_dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if project_dir.join("package-lock.json").exists() {
+        dirs.push(project_dir.to_path_buf());
+    }
+
+    for component in super::hierarchy::components_iter(project_dir, TraversalOrder::PreOrder) {
+        if component.level == ComponentLevel::LocalSubComponent
+            && component.path.join("package-lock.json").exists()
+            && !dirs.contains(&component.path)
+        {
+            dirs.push(component.path);
+        }
+    }
+
+    dirs
+}
+
+/// Reads the packages out of the `package-lock.json` file in `lock_dir`, in either the current format or the one
+/// from before npm 7. Links to local directories are left out, since they aren't installed from anywhere.
+pub fn read_lock_file(lock_dir: &Path) -> Result<Vec<LockedPackage>, String> {
+    let contents = fs::read_to_string(lock_dir.join("package-lock.json"))
+        .map_err(|e| format!("The lock file can't be read: {}", e))?;
+    let lock = super::json::parse(&contents)
+        .map_err(|e| format!("The lock file can't be parsed: {}", e))?;
+
+    let mut packages = Vec::new();
+
+    if let Some(JsonValue::Object(entries)) = lock.get("packages") {
+        for (name, package) in entries {
+            // The root project and links to local directories aren't installed from anywhere
+            if name.is_empty() || package.get("link") == Some(&JsonValue::Bool(true)) {
+                continue;
+            }
+
+            let resolved = package.get_str("resolved");
+            packages.push(LockedPackage {
+                name: name.to_owned(),
+                version: package.get_str("version"),
+                commit: resolved_commit(&resolved),
+                resolved,
+            });
+        }
+    } else if let Some(JsonValue::Object(entries)) = lock.get("dependencies") {
+        for (name, package) in entries {
+            let version = package.get_str("version");
+            let mut resolved = package.get_str("resolved");
+
+            // Git dependencies have their URL in the version field of the old format
+            let commit = resolved_commit(&resolved).or_else(|| resolved_commit(&version));
+            if resolved.is_empty() && commit.is_some() {
+                resolved = version.clone();
+            }
+
+            packages.push(LockedPackage {
+                name: format!("node_modules/{}", name),
+                version,
+                resolved,
+                commit,
+            });
+        }
+    }
+
+    Ok(packages)
+}
+
+/// A dependency that has been pinned to a commit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pin {
+    /// The name of the dependency in `package.json`
+    pub name: String,
+    /// The commit that the dependency is pinned to
+    pub commit: String,
+    /// The branch or tag that the dependency's URL had before it was pinned, which is empty if it had none
+    pub previous_reference: String,
+}
+
+/// Reads the pins in the `PINS_FILE` of a component. A component without the file has no pins.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+pub fn read_pins(target_dir: &Path) -> Vec<Pin> {
+    let contents = fs::read_to_string(target_dir.join(PINS_FILE)).unwrap_or_default();
+
+    contents
+        .lines()
+        .filter_map(|l| {
+            let mut parts = l.splitn(3, '\t');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(name), Some(commit), previous) if !name.is_empty() => Some(Pin {
+                    name: name.to_string(),
+                    commit: commit.to_string(),
+                    previous_reference: previous.unwrap_or_default().to_string(),
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Writes the pins of a component to its `PINS_FILE`, removing the file once there are no pins left.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn write_pins(target_dir: &Path, pins: &[Pin]) -> std::io::Result<()> {
+    let pins_file = target_dir.join(PINS_FILE);
+
+    if pins.is_empty() {
+        return match fs::remove_file(&pins_file) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+
+    // Sorted, so that the file only changes when the pins do
+    let mut pins = pins.to_vec();
+    pins.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut contents = String::new();
+    for pin in &pins {
+        contents.push_str(&format!(
+            "{}\t{}\t{}\n",
+            pin.name, pin.commit, pin.previous_reference
+        ));
+    }
+
+    fs::write(pins_file, contents)
+}
+
+/// Moves the pin and the recorded checksum of a dependency of a component over to a new name, i.e. when
+/// `rename_component` renames a remote component, so that `update_dependencies` keeps it pinned and `find_drift`
+/// still knows what was installed.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn rename_dependency(target_dir: &Path, name: &str, new_name: &str) -> std::io::Result<()> {
+    let mut pins = read_pins(target_dir);
+    if pins.iter().any(|p| p.name == name) {
+        for pin in pins.iter_mut().filter(|p| p.name == name) {
+            pin.name = new_name.to_string();
+        }
+        write_pins(target_dir, &pins)?;
+    }
+
+    // The packages installed inside of the dependency move along with it
+    let old_path = format!("node_modules/{}", name);
+    let new_path = format!("node_modules/{}", new_name);
+    let mut checksums = read_checksum_file(target_dir);
+    let mut renamed = false;
+    for (path, _) in checksums.iter_mut() {
+        let rest = match path.strip_prefix(&old_path) {
+            Some(r) if r.is_empty() || r.starts_with('/') => r.to_string(),
+            _ => continue,
+        };
+        *path = format!("{}{}", new_path, rest);
+        renamed = true;
+    }
+    if !renamed {
+        return Ok(());
+    }
+
+    checksums.sort();
+    let mut contents = String::new();
+    for (path, checksum) in &checksums {
+        contents.push_str(&format!("{}\t{}\n", checksum, path));
+    }
+
+    fs::write(target_dir.join(CHECKSUM_FILE), contents)
+}
+
+/*
+ * Reads the checksums that were recorded by lock_installed.
+ */
+fn read_checksum_file(lock_dir: &Path) -> Vec<(String, String)> {
+    let contents = fs::read_to_string(lock_dir.join(CHECKSUM_FILE)).unwrap_or_default();
+
+    contents
+        .lines()
+        .filter_map(|l| {
+            let mut parts = l.splitn(2, '\t');
+            match (parts.next(), parts.next()) {
+                (Some(checksum), Some(name)) => Some((name.to_string(), checksum.to_string())),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/*
+ * Reads the lock file that npm keeps inside of node_modules, which describes what is actually installed.
+ */
+fn read_hidden_lock_file(lock_dir: &Path) -> Option<JsonValue> {
+    let contents =
+        fs::read_to_string(lock_dir.join("node_modules").join(".package-lock.json")).ok()?;
+
+    super::json::parse(&contents).ok()
+}
+
+/*
+ * Works out which commit an installed package came from, using its package.json file first and npm's record of
+ * what it installed second.
+ */
+fn installed_commit(
+    manifest: &JsonValue,
+    installed: &Option<JsonValue>,
+    name: &str,
+) -> Option<String> {
+    let git_head = manifest.get_str("gitHead");
+    if super::is_commit_hash(&git_head) {
+        return Some(git_head);
+    }
+
+    resolved_commit(&manifest.get_str("_resolved")).or_else(|| {
+        installed
+            .as_ref()
+            .and_then(|i| i.get("packages"))
+            .and_then(|p| p.get(name))
+            .and_then(|p| resolved_commit(&p.get_str("resolved")))
+    })
+}
+
+/*
+ * Gets the commit out of a resolved git URL like `git+ssh://git@host/repo.git#<commit>`.
+ */
+fn resolved_commit(resolved: &str) -> Option<String> {
+    match resolved.rfind('#') {
+        Some(i) if super::is_commit_hash(&resolved[i + 1..]) => Some(resolved[i + 1..].to_string()),
+        _ => None,
+    }
+}
+
+/*
+ * Lists what's installed directly in a node_modules directory, with scoped packages one level deeper.
+ */
+fn installed_packages(lock_dir: &Path) -> Vec<String> {
+    let mut packages = Vec::new();
+
+    let mut dirs = vec![(lock_dir.join("node_modules"), String::from("node_modules"))];
+    while let Some((dir, prefix)) = dirs.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') || !entry.path().is_dir() {
+                continue;
+            }
+
+            if name.starts_with('@') && prefix == "node_modules" {
+                dirs.push((entry.path(), format!("{}/{}", prefix, name)));
+            } else if entry.path().join("package.json").exists() {
+                packages.push(format!("{}/{}", prefix, name));
+            }
+        }
+    }
+
+    packages.sort();
+    packages
+}
+
+/*
+ * Hashes the names and contents of every file in an installed package, leaving out the packages installed inside
+ * of it, which have their own entries in the lock file.
+ */
+fn tree_checksum(package_dir: &Path) -> Option<String> {
+    if !package_dir.is_dir() {
+        return None;
+    }
+
+    let mut hasher = Sha256::new();
+
+    let walker = WalkDir::new(package_dir)
+        .sort_by(|a, b| a.file_name().cmp(b.file_name()))
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0 || (e.file_name() != "node_modules" && e.file_name() != ".git")
+        });
+    for entry in walker {
+        let entry = entry.ok()?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let relative = entry.path().strip_prefix(package_dir).ok()?;
+        let checksum = super::checksum::sha256_file(entry.path()).ok()?;
+        hasher.update(format!("{}\t{}\n", relative.to_string_lossy(), checksum).as_bytes());
+    }
+
+    Some(hasher.finish())
+}
@@ -0,0 +1,1800 @@
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// The identifier given to lines of output that came from git, npm or another tool rather than from Sliderule,
+/// which are passed along as they are.
+pub const TOOL_OUTPUT: &str = "tool.output";
+
+/// Every message that Sliderule puts into an operation's output, as its identifier and the text it is written
+/// with. `{}` stands for a value that is filled in, like a path or a name, and is handed back in
+/// `Message::args`.
+///
+/// The identifiers are stable, so a frontend can use them to look up its own translated or formatted version of
+/// a message. The text can change between releases, and new messages can be added, but an identifier is never
+/// reused for a message that means something else.
+pub const MESSAGES: [(&str, &str); 410] = [
+    (
+        "archive.export_archive.not_sliderule_component_cant_archived",
+        "ERROR: {} is not a Sliderule component, so it can't be archived.",
+    ),
+    (
+        "archive.export_archive.unable_read_lock_file",
+        "ERROR: Unable to read the lock file in {}: {}",
+    ),
+    (
+        "archive.export_archive.locked_but_isnt_installed_run",
+        "ERROR: {} is locked in {}, but it isn't installed. Run update_dependencies first.",
+    ),
+    (
+        "archive.export_archive.archived_files_remote_components",
+        "Archived {} files and {} remote components to {}.",
+    ),
+    (
+        "archive.export_archive.unable_write_archive",
+        "ERROR: Unable to write the archive {}: {}",
+    ),
+    (
+        "archive.import_archive.imported_into",
+        "Imported {} into {}.",
+    ),
+    (
+        "archive.import_archive.unable_import",
+        "ERROR: Unable to import {}: {}",
+    ),
+    (
+        "archive.read_archive.could_not_recreate_link",
+        "Could not recreate the link {} to {}: {}",
+    ),
+    (
+        "archive.read_archive.manifest_but_wasnt_archive",
+        "{} is in the manifest, but it wasn't in the archive.",
+    ),
+    (
+        "archive.export_offline_bundle.already_exists",
+        "ERROR: {} already exists, so the bundle can't be written there.",
+    ),
+    (
+        "archive.export_offline_bundle.unable_create_directory",
+        "ERROR: Unable to create the bundle directory {}: {}",
+    ),
+    (
+        "archive.export_offline_bundle.unable_fetch_bundle",
+        "ERROR: Unable to fetch and bundle {} from {}.",
+    ),
+    (
+        "archive.export_offline_bundle.unable_write_manifest",
+        "ERROR: Unable to write the bundle manifest in {}: {}",
+    ),
+    (
+        "archive.export_offline_bundle.bundled",
+        "Bundled {} and {} remote component repositories into {}.",
+    ),
+    (
+        "archive.import_offline_bundle.not_bundle",
+        "ERROR: {} is not a Sliderule bundle: {}",
+    ),
+    (
+        "archive.import_offline_bundle.no_archive",
+        "ERROR: {} is not a Sliderule bundle: The manifest doesn't name the project's archive.",
+    ),
+    (
+        "archive.import_offline_bundle.no_project_name",
+        "ERROR: {} is not a Sliderule bundle: The project's archive doesn't have the project name.",
+    ),
+    (
+        "archive.import_offline_bundle.unable_create",
+        "ERROR: Unable to create {}: {}",
+    ),
+    (
+        "archive.import_offline_bundle.unable_restore",
+        "ERROR: Unable to restore the repository of {} from the bundle.",
+    ),
+    (
+        "archive.import_offline_bundle.restored",
+        "Restored {} remote component repositories into {}.",
+    ),
+    (
+        "archive.download_repository_archive.not_github_gitlab",
+        "ERROR: {} is not an HTTPS URL on GitHub or GitLab, so it can't be downloaded without git.",
+    ),
+    (
+        "archive.download_repository_archive.already_exists",
+        "ERROR: {} already exists, so the archive was not unpacked.",
+    ),
+    (
+        "archive.download_repository_archive.unable_find_commit",
+        "ERROR: Unable to find the commit that {} is at.",
+    ),
+    (
+        "archive.download_repository_archive.unable_download",
+        "ERROR: Unable to download the archive of {}.",
+    ),
+    (
+        "archive.download_repository_archive.unable_unpack",
+        "ERROR: Unable to unpack the archive of {}: {}",
+    ),
+    (
+        "archive.download_repository_archive.downloaded",
+        "Downloaded an archive of commit {} without git.",
+    ),
+    (
+        "bom.read_bom.unable_read",
+        "ERROR: Unable to read bom_data.yaml: {}",
+    ),
+    (
+        "bom.update_bom_docs.unable_read_page",
+        "ERROR: Unable to read {}: {}",
+    ),
+    (
+        "bom.update_bom_docs.unable_write_page",
+        "ERROR: Unable to write {}: {}",
+    ),
+    (
+        "bom.update_bom_docs.wrote_bom",
+        "Wrote the bill of materials into {} pages.",
+    ),
+    (
+        "file_licenses.file_license_problems.not_valid_expression",
+        "The license {} of {} in {} is not a valid SPDX license expression.",
+    ),
+    (
+        "file_licenses.file_license_problems.no_files_match",
+        "No files match {} in {}, it needs to be updated or removed.",
+    ),
+    (
+        "backend.validate_component.component_structure_valid",
+        "Component structure is valid.",
+    ),
+    (
+        "backend.record_exit.stopped_by_signal",
+        "ERROR: {} was stopped by signal {}.",
+    ),
+    (
+        "backend.record_exit.stopped_before_finish",
+        "ERROR: {} was stopped before it could finish.",
+    ),
+    (
+        "catalog.search.unable_read_component_catalog",
+        "ERROR: Unable to read the component catalog at {}: {}",
+    ),
+    (
+        "catalog.search.component_catalog_not_valid",
+        "ERROR: The component catalog at {} is not valid: {}",
+    ),
+    (
+        "catalog.search.found_matching_components",
+        "Found {} matching components.",
+    ),
+    (
+        "curl.not_found",
+        "ERROR: `curl` was not found, please install: {}",
+    ),
+    (
+        "curl.unable_run_curl",
+        "ERROR: Unable to run curl: {}",
+    ),
+    (
+        "docs_server.build_docs.no_dist_directory",
+        "ERROR: The layout of {} doesn't have a dist directory, so the documentation can't be built.",
+    ),
+    (
+        "docs_server.build_docs.built",
+        "Built {} documentation pages into {}.",
+    ),
+    (
+        "docs_server.build_docs.unable_build",
+        "ERROR: Unable to build the documentation into {}: {}",
+    ),
+    (
+        "environment.problems.not_installed",
+        "{} is not installed.",
+    ),
+    (
+        "environment.problems.installed_but_least_version_required",
+        "{} {} is installed, but at least version {} is required.",
+    ),
+    (
+        "environment.problems.not_reachable",
+        "{} is not reachable: {}",
+    ),
+    (
+        "environment.problems.cache_directory_not_writable",
+        "The cache directory {} is not writable: {}",
+    ),
+    (
+        "events.fire.skipped_untrusted_hook",
+        "WARNING: Skipped the {} hook {} in the project's configuration, since hooks.allow_project isn't turned on.",
+    ),
+    (
+        "events.fire.handler_failed",
+        "ERROR: A {} handler failed: {}",
+    ),
+    (
+        "events.fire.command_failed",
+        "ERROR: The {} command {} failed.",
+    ),
+    (
+        "events.fire.unable_run_command",
+        "ERROR: Unable to run the {} command {}: {}",
+    ),
+    (
+        "journal.record.not_recorded",
+        "WARNING: Could not record {} in the journal, so it can't be undone: {}",
+    ),
+    (
+        "git.not_found",
+        "ERROR: `git` was not found, please install: {}",
+    ),
+    (
+        "git.init.could_not_initialize_git_repository",
+        "ERROR: Could not initialize git repository: {}",
+    ),
+    (
+        "git.init.git_repository_initialized_project",
+        "git repository initialized for project.",
+    ),
+    (
+        "git.init.unable_switch_repository_branch",
+        "WARNING: Unable to switch the repository to the {} branch: {}",
+    ),
+    (
+        "git.init.unable_set_remote_url_project",
+        "ERROR: Unable to set remote URL for project: {}",
+    ),
+    (
+        "git.init.done_initializing_git_repository_project",
+        "Done initializing git repository for project.",
+    ),
+    (
+        "git.add_commit_and_push.unable_stage_changes_using_git",
+        "ERROR: Unable to stage changes using git: {}",
+    ),
+    (
+        "git.add_commit_and_push.changes_staged_using_git",
+        "Changes staged using git.",
+    ),
+    (
+        "git.add_commit_and_push.unable_disable_sendpack_sideband_git",
+        "ERROR: Unable to disable sendpack.sideband git option: {}",
+    ),
+    (
+        "git.add_commit_and_push.unable_commit_changes_using_git",
+        "ERROR: Unable to commit changes using git: {}",
+    ),
+    (
+        "git.add_commit_and_push.git_no_identity_commit_set",
+        "ERROR: git has no identity to commit with, set author_name and author_email in the [git] section of the configuration or pass an author.",
+    ),
+    (
+        "git.add_commit_and_push.changes_committed_using_git",
+        "Changes committed using git.",
+    ),
+    (
+        "git.add_commit_and_push.unable_push_changes_remote_git",
+        "ERROR: Unable to push changes to remote git repository: {}",
+    ),
+    (
+        "git.add_commit_and_push.changes_pushed_using_git",
+        "Changes pushed using git.",
+    ),
+    (
+        "git.pull.pull_remote_repository_not_successful",
+        "ERROR: Pull from remote repository not successful: {}",
+    ),
+    (
+        "git.pull.pull_failed_may_waiting_username",
+        "ERROR: Pull failed, may be waiting for username/password or passphrase.",
+    ),
+    (
+        "git.unable_clone_component_repository",
+        "ERROR: Unable to clone component repository: {}",
+    ),
+    (
+        "git.clone_blobless.git_newer_needed_blobless_clone",
+        "WARNING: git {} or newer is needed for a blobless clone, a full clone was made instead.",
+    ),
+    (
+        "git.unable_change_url_component_repository",
+        "ERROR: Unable to change the URL on the component repository: {}",
+    ),
+    (
+        "git.push_to.unable_push_component",
+        "ERROR: Unable to push the component to {}: {}",
+    ),
+    (
+        "git.pull_from.unable_pull",
+        "ERROR: Unable to pull from {}: {}",
+    ),
+    (
+        "git.conflicted_files.unable_list",
+        "ERROR: Unable to list the conflicted files in the component: {}",
+    ),
+    (
+        "git.log_between.unable_list",
+        "ERROR: Unable to list the commits in the component repository: {}",
+    ),
+    (
+        "git.show_file.unable_read",
+        "ERROR: Unable to read a file from the component repository: {}",
+    ),
+    (
+        "git.files_at.unable_list",
+        "ERROR: Unable to list the files in the component repository: {}",
+    ),
+    (
+        "git.changed_files.unable_list",
+        "ERROR: Unable to list the changed files in the component repository: {}",
+    ),
+    (
+        "git.bundle_create.unable_bundle",
+        "ERROR: Unable to bundle the component repository: {}",
+    ),
+    (
+        "component.contributors.unable_read_history",
+        "ERROR: Unable to read the history of {}.",
+    ),
+    (
+        "component.contributors.unable_read_readme",
+        "ERROR: Unable to read README.md: {}",
+    ),
+    (
+        "component.contributors.unable_write_readme",
+        "ERROR: Unable to write README.md: {}",
+    ),
+    (
+        "component.contributors.listed",
+        "Listed {} contributors in README.md.",
+    ),
+    (
+        "release.release.unable_prepare",
+        "ERROR: Unable to prepare release {}, nothing was changed: {}",
+    ),
+    (
+        "release.release.unable_commit",
+        "ERROR: Unable to commit and tag release {}, nothing was changed.",
+    ),
+    (
+        "release.release.unable_push",
+        "ERROR: Unable to push release {}, so it was undone.",
+    ),
+    (
+        "release.release.released",
+        "Released {} as {}{}.",
+    ),
+    (
+        "release.plan_release.not_repository",
+        "ERROR: Component is not a repository with any commits, so it can't be released.",
+    ),
+    (
+        "release.plan_release.uncommitted",
+        "ERROR: Component has uncommitted changes, commit or discard them before releasing.",
+    ),
+    (
+        "release.plan_release.not_semver",
+        "ERROR: The version {} in package.json is not a semantic version, so it can't be moved on.",
+    ),
+    (
+        "release.plan_release.tag_exists",
+        "ERROR: There is already a release called {} in the project.",
+    ),
+    (
+        "release.plan_release.would_bump",
+        "Would move the version on from {} to {}.",
+    ),
+    (
+        "release.plan_release.would_changelog",
+        "Would add release {} to {}.",
+    ),
+    (
+        "release.plan_release.would_build",
+        "Would build {}.",
+    ),
+    (
+        "release.plan_release.would_tag",
+        "Would commit the release and tag it {}.",
+    ),
+    (
+        "release.plan_release.would_push",
+        "Would push the release to origin.",
+    ),
+    (
+        "release.plan_release.no_origin",
+        "There is no origin remote, so the release would not be pushed.",
+    ),
+    (
+        "git_hooks.install_hooks.installed",
+        "Installed the {} hook.",
+    ),
+    (
+        "git_hooks.install_hooks.unable_install",
+        "ERROR: Unable to install the {} hook: {}",
+    ),
+    (
+        "git_hooks.uninstall_hooks.uninstalled",
+        "Uninstalled the {} hook.",
+    ),
+    (
+        "git_hooks.uninstall_hooks.unable_uninstall",
+        "ERROR: Unable to uninstall the {} hook: {}",
+    ),
+    (
+        "git_hooks.hooks_dir.not_repository",
+        "ERROR: Component is not set up as a repository, so it has no git hooks.",
+    ),
+    (
+        "annex.write_attributes.added",
+        "Added {} patterns for git-annex to {}.",
+    ),
+    (
+        "annex.write_attributes.unable_write",
+        "ERROR: Unable to write the patterns for git-annex to {}: {}",
+    ),
+    (
+        "annex.init_annex.unable_init",
+        "ERROR: Unable to set up git-annex for the component, make sure that it's installed.",
+    ),
+    (
+        "annex.init_annex.done",
+        "Set up git-annex for the component.",
+    ),
+    (
+        "annex.fetch_annexed.unable_fetch",
+        "ERROR: Unable to download the files that the component keeps in git-annex.",
+    ),
+    (
+        "annex.fetch_annexed.done",
+        "Downloaded the files kept in git-annex.",
+    ),
+    (
+        "annex.push_annexed.unable_push",
+        "ERROR: Unable to upload the files that the component keeps in git-annex.",
+    ),
+    (
+        "annex.push_annexed.done",
+        "Uploaded the files kept in git-annex.",
+    ),
+    (
+        "git.authors.unable_list",
+        "ERROR: Unable to list the authors in the component repository: {}",
+    ),
+    (
+        "git.tag.unable_tag",
+        "ERROR: Unable to tag the component repository with {}: {}",
+    ),
+    (
+        "git.delete_tag.unable_delete",
+        "ERROR: Unable to delete the tag {} from the component repository: {}",
+    ),
+    (
+        "git.reset_hard.unable_reset",
+        "ERROR: Unable to reset the component repository to {}: {}",
+    ),
+    (
+        "git.push_atomic.unable_push",
+        "ERROR: Unable to push the release to the remote repository: {}",
+    ),
+    (
+        "git.hooks_dir.unable_find",
+        "ERROR: Unable to find the hooks directory of the component repository: {}",
+    ),
+    (
+        "git.annex_init.unable_init",
+        "ERROR: Unable to set up git-annex in the component repository: {}",
+    ),
+    (
+        "git.annex_add.unable_add",
+        "ERROR: Unable to add files to git-annex: {}",
+    ),
+    (
+        "git.annex_sync.unable_sync",
+        "ERROR: Unable to sync git-annex with the remote repository: {}",
+    ),
+    (
+        "git.annex_get.unable_get",
+        "ERROR: Unable to download the files in git-annex: {}",
+    ),
+    (
+        "git.fsck_connectivity.unable_check",
+        "ERROR: Unable to check the objects in the component repository: {}",
+    ),
+    (
+        "git.finish_clone.unable_check_out",
+        "ERROR: Unable to check out the component after downloading it: {}",
+    ),
+    (
+        "git.version.unable_get_version_git",
+        "ERROR: Unable to get the version of git: {}",
+    ),
+    (
+        "git.unable_list_contents_remote_repository",
+        "ERROR: Unable to list the contents of the remote repository: {}",
+    ),
+    (
+        "git.fetch.unable_fetch_components_remote_repository",
+        "ERROR: Unable to fetch from the component's remote repository: {}",
+    ),
+    (
+        "git.status_porcelain.unable_get_status_component_repository",
+        "ERROR: Unable to get the status of the component repository: {}",
+    ),
+    (
+        "git.credential_approve.unable_store_credentials_git",
+        "ERROR: Unable to store the credentials with git: {}",
+    ),
+    (
+        "git.remote_urls.unable_read_remote_urls_component",
+        "ERROR: Unable to read the remote URLs of the component repository: {}",
+    ),
+    (
+        "git.config_set.unable_change_configuration_component_repository",
+        "ERROR: Unable to change the configuration of the component repository: {}",
+    ),
+    (
+        "git.verify_commit.unable_check_signature_commit",
+        "ERROR: Unable to check the signature on the commit: {}",
+    ),
+    (
+        "git.verify_tag.unable_check_signature_tag",
+        "ERROR: Unable to check the signature on the tag: {}",
+    ),
+    (
+        "git.tags_at.unable_list_tags_component_repository",
+        "ERROR: Unable to list the tags in the component repository: {}",
+    ),
+    (
+        "git.rev_parse.unable_look_up_revision_component",
+        "ERROR: Unable to look up the revision in the component repository: {}",
+    ),
+    (
+        "git.is_ancestor.unable_compare_commits_component_repository",
+        "ERROR: Unable to compare commits in the component repository: {}",
+    ),
+    (
+        "health.validate_component.skipped_untrusted_tool",
+        "WARNING: Skipped {} in the project's configuration, since tools.allow_project isn't turned on.",
+    ),
+    (
+        "health.validate_component.configuration_cant_read",
+        "The configuration can't be read, {}",
+    ),
+    (
+        "health.pre_upload_check.component_ready_uploaded",
+        "Component is ready to be uploaded.",
+    ),
+    (
+        "health.structure_problems_in.directory_missing",
+        "The {} directory is missing.",
+    ),
+    (
+        "health.structure_problems_in.file_missing",
+        "The {} file is missing.",
+    ),
+    (
+        "health.directory_can_written_any_user",
+        "The {} directory can be written to by any user.",
+    ),
+    (
+        "health.permission_problems.file_can_written_any_user",
+        "The {} file can be written to by any user.",
+    ),
+    (
+        "health.permission_problems.file_holds_credentials_can_read",
+        "The {} file holds credentials and can be read by other users.",
+    ),
+    (
+        "health.user_permission_problems.file_holds_tokens_can_read",
+        "The {} file holds tokens and can be read by other users.",
+    ),
+    (
+        "health.user_permission_problems.cache_file_can_read_other",
+        "The {} cache file can be read by other users.",
+    ),
+    (
+        "health.user_permission_problems.cache_directory_can_written_any",
+        "The {} cache directory can be written to by any user.",
+    ),
+    (
+        "health.license_problems.no_source_license_set_sr",
+        "No source license is set in the .sr file.",
+    ),
+    (
+        "health.license_problems.no_documentation_license_set_sr",
+        "No documentation license is set in the .sr file.",
+    ),
+    (
+        "health.license_problems.license_not_listed_package_json",
+        "The license {} is not listed in package.json, the licenses need to be amalgamated.",
+    ),
+    (
+        "health.license_problems.file_license_not_listed_package_json",
+        "The license {} of {} is not listed in package.json, the licenses need to be amalgamated.",
+    ),
+    (
+        "health.license_policy_problems.file_license_not_allowed",
+        "The license {} of {} is not allowed by the project's license policy.",
+    ),
+    (
+        "health.dependency_problems.dependency_not_installed_dependencies_need",
+        "The dependency {} ({}) is not installed, the dependencies need to be updated.",
+    ),
+    (
+        "health.status_problems.unable_get_git_status_component",
+        "Unable to get the git status of the component.",
+    ),
+    (
+        "health.status_problems.there_changes_not_been_uploaded",
+        "There are changes that have not been uploaded.",
+    ),
+    (
+        "health.lint_problems.package_json_does_not_name",
+        "package.json does not have a name set.",
+    ),
+    (
+        "health.lint_problems.name_package_json_should_lowercase",
+        "The name {} in package.json should be lowercase and not contain spaces.",
+    ),
+    (
+        "health.lint_problems.readme_md_does_not_start",
+        "README.md does not start with a heading for the component.",
+    ),
+    (
+        "health.lint_problems.sr_file_still_contains_template",
+        "The {} in the .sr file still contains template text.",
+    ),
+    (
+        "component.status.set",
+        "Set the status of the component to {}.",
+    ),
+    (
+        "component.status.deprecated",
+        "WARNING: {} is deprecated{}.",
+    ),
+    (
+        "component.status.archived",
+        "WARNING: {} is archived and is no longer maintained{}.",
+    ),
+    (
+        "component.clean.cache_not_removed",
+        "WARNING: Could not remove the .sr file cache at {}: {}",
+    ),
+    (
+        "component.clean.removed",
+        "Removed {}.",
+    ),
+    (
+        "component.clean.finished",
+        "Finished cleaning the component.",
+    ),
+    (
+        "component.dedupe.unable_move_top_level",
+        "ERROR: Unable to move {} up to the top level of the project: {}",
+    ),
+    (
+        "component.dedupe.moved_top_level",
+        "Moved {} up to the top level of the project.",
+    ),
+    (
+        "component.dedupe.removed_same_revision",
+        "Removed {}, which is the same revision as {}.",
+    ),
+    (
+        "component.dedupe.reclaimed",
+        "Removed {} copies of components, which reclaimed {} bytes.",
+    ),
+    (
+        "component.undo_last.nothing_to_undo",
+        "ERROR: There is nothing in the journal to undo.",
+    ),
+    (
+        "component.undo_last.deleted_without_backup",
+        "ERROR: {} was deleted without a backup, so removing it can't be undone.",
+    ),
+    (
+        "component.undo_last.failed",
+        "ERROR: Could not undo {}.",
+    ),
+    (
+        "component.undo_last.journal_not_updated",
+        "ERROR: Undid {}, but could not take it out of the journal: {}",
+    ),
+    (
+        "component.undo_last.finished",
+        "Undid {}.",
+    ),
+    (
+        "component.remove_with_backup.trash_failed",
+        "ERROR: Could not move {} into the trash, so it was not removed: {}",
+    ),
+    (
+        "component.remove_with_backup.finished",
+        "Moved component {} to {}, use restore_removed to bring it back.",
+    ),
+    (
+        "component.restore_removed.not_found",
+        "ERROR: There is no removed component called {} to restore.",
+    ),
+    (
+        "component.restore_removed.failed",
+        "ERROR: Could not move {} back out of the trash: {}",
+    ),
+    (
+        "component.restore_removed.finished",
+        "Restored component {} from {}.",
+    ),
+    (
+        "component.link_component.not_component",
+        "ERROR: {} is not a Sliderule component, so it can't be linked.",
+    ),
+    (
+        "component.link_component.checkout_not_found",
+        "ERROR: Unable to find the checkout of {}: {}",
+    ),
+    (
+        "component.link_component.failed",
+        "ERROR: Could not link {} to the checkout in {}: {}",
+    ),
+    (
+        "component.link_component.finished",
+        "Linked component {} to {}, use unlink_component to go back to the installed copy.",
+    ),
+    (
+        "component.unlink_component.not_linked",
+        "ERROR: Component {} is not linked to a local checkout.",
+    ),
+    (
+        "component.unlink_component.failed",
+        "ERROR: Could not put the installed copy of {} back: {}",
+    ),
+    (
+        "component.unlink_component.restored",
+        "Restored the installed copy of component {}.",
+    ),
+    (
+        "component.unlink_component.removed_link",
+        "Removed the link to component {}, use update_dependencies to install it.",
+    ),
+    (
+        "component.sync_upstream.not_repository",
+        "ERROR: Component is not set up as a repository, cannot sync it with its upstream.",
+    ),
+    (
+        "component.sync_upstream.no_upstream",
+        "ERROR: {} has no upstream recorded in its .sr file, so there is nothing to sync it with.",
+    ),
+    (
+        "component.sync_upstream.failed",
+        "ERROR: Could not sync {} with its upstream at {}.",
+    ),
+    (
+        "component.sync_upstream.conflict",
+        "CONFLICT: {}",
+    ),
+    (
+        "component.sync_upstream.conflicts",
+        "ERROR: Syncing {} with its upstream stopped with conflicts in {} file(s), resolve and commit them or abort the merge or rebase with git.",
+    ),
+    (
+        "component.sync_upstream.finished",
+        "Synced {} with its upstream at {}.",
+    ),
+    (
+        "component.release_notes.not_repository",
+        "ERROR: Component is not set up as a repository, cannot write release notes for it.",
+    ),
+    (
+        "component.release_notes.unknown_tag",
+        "ERROR: There is no release point called {} in the project.",
+    ),
+    (
+        "component.release_notes.log_failed",
+        "ERROR: Could not list the commits between {} and {}.",
+    ),
+    (
+        "component.fork.not_forkable",
+        "ERROR: {} is not a remote component installed from a git repository, so it can't be forked.",
+    ),
+    (
+        "component.fork.scratch_dir_failed",
+        "ERROR: Could not make a directory to clone {} into: {}",
+    ),
+    (
+        "component.fork.clone_failed",
+        "ERROR: Could not clone {} from {} to fork it.",
+    ),
+    (
+        "component.fork.push_failed",
+        "ERROR: Could not push the fork of {} to {}.",
+    ),
+    (
+        "component.fork.finished",
+        "Forked {} to {}, and the project now uses the fork.",
+    ),
+    (
+        "component.diff_against_remote.not_remote",
+        "ERROR: {} is not a remote component installed from a git repository, so there is no remote to compare it with.",
+    ),
+    (
+        "component.diff_against_remote.unknown_commit",
+        "ERROR: Could not work out which commit of {} is installed.",
+    ),
+    (
+        "component.diff_against_remote.fetch_failed",
+        "ERROR: Could not fetch {} from {}.",
+    ),
+    (
+        "component.diff_against_remote.compare_failed",
+        "ERROR: Could not compare the installed commit {} of {} with {} in its remote.",
+    ),
+    (
+        "component.diff_against_remote.same",
+        "{} is the same as its remote.",
+    ),
+    (
+        "component.diff_against_remote.different",
+        "{} has {} file(s) that are different in its remote.",
+    ),
+    (
+        "component.duplicate.failed",
+        "ERROR: Could not copy {} to {}: {}",
+    ),
+    (
+        "component.duplicate.finished",
+        "Duplicated component {} as {}.",
+    ),
+    (
+        "component.move.destination_not_component",
+        "ERROR: {} is not a Sliderule component, so components can't be moved into it.",
+    ),
+    (
+        "component.move.destination_inside_component",
+        "ERROR: {} is inside of {}, so it can't be moved there.",
+    ),
+    (
+        "component.move.failed",
+        "ERROR: Could not move {} into {}: {}",
+    ),
+    (
+        "component.move.finished",
+        "Moved component {} from {} to {}.",
+    ),
+    (
+        "component.rename.ambiguous",
+        "ERROR: The name '{}' is used by both a local and a remote component, so it isn't clear which one to rename.",
+    ),
+    (
+        "component.rename.not_found",
+        "ERROR: There is no component named '{}' to rename.",
+    ),
+    (
+        "component.rename.failed",
+        "ERROR: Could not rename {} to {}: {}",
+    ),
+    (
+        "component.readme_heading_failed",
+        "WARNING: Could not update the heading of the README for {}: {:?}",
+    ),
+    (
+        "component.rename.dependency_failed",
+        "WARNING: Could not rename the dependency on {} in package.json: {:?}",
+    ),
+    (
+        "component.rename.sort_failed",
+        "WARNING: Could not sort the dependencies in package.json: {}",
+    ),
+    (
+        "component.rename.lock_failed",
+        "WARNING: Could not move the pin and checksum of {} over to {}: {}",
+    ),
+    (
+        "component.rename.finished",
+        "Renamed component {} to {}.",
+    ),
+    (
+        "component.url_template_missing",
+        "ERROR: No URL was given for {}, and there is no remote.url_template in the configuration to make one from.",
+    ),
+    (
+        "component.create.dir_exists",
+        "ERROR: A component with the name '{}' already exists.",
+    ),
+    (
+        "component.create.nested_project",
+        "ERROR: {} is inside of the component at {}, create the component from that directory instead.",
+    ),
+    (
+        "component.name_collision_remote",
+        "ERROR: A remote component with the name '{}' is already installed in node_modules.",
+    ),
+    (
+        "component.create.could_not_create_component_directory",
+        "ERROR: Could not create component directory: {}",
+    ),
+    (
+        "component.create.strict_mode_stopped",
+        "ERROR: Stopped creating {} at the first error because of strict mode, and removed what had been made.",
+    ),
+    (
+        "component.create.finished",
+        "Finished setting up component.",
+    ),
+    (
+        "component.repair.component_directory_does_not_exist",
+        "ERROR: The component directory does not exist, cannot repair it.",
+    ),
+    (
+        "component.repair.could_not_recover_licenses_package",
+        "Could not recover the licenses from package.json, using {} and {}.",
+    ),
+    (
+        "component.repair.recreated_directory",
+        "Recreated the {} directory.",
+    ),
+    (
+        "component.repair.regenerated_readme_md",
+        "Regenerated README.md.",
+    ),
+    (
+        "component.repair.regenerated_parts_yaml",
+        "Regenerated parts.yaml.",
+    ),
+    (
+        "component.repair.regenerated_tools_yaml",
+        "Regenerated tools.yaml.",
+    ),
+    (
+        "component.repair.regenerated_precautions_yaml",
+        "Regenerated precautions.yaml.",
+    ),
+    (
+        "component.repair.regenerated_package_json",
+        "Regenerated package.json.",
+    ),
+    (
+        "component.repair.regenerated_sr",
+        "Regenerated .sr.",
+    ),
+    (
+        "component.repair.regenerated_gitignore",
+        "Regenerated .gitignore.",
+    ),
+    (
+        "component.repair.finished_repairing_component",
+        "Finished repairing component.",
+    ),
+    (
+        "component.adopt.left_where_since_already_one",
+        "Left {} where it is, since {} already has one.",
+    ),
+    (
+        "component.adopt.moved_into",
+        "Moved {} into {}.",
+    ),
+    (
+        "component.adopt.could_not_move",
+        "ERROR: Could not move {}: {}",
+    ),
+    (
+        "component.adopt.finished_adopting_component",
+        "Finished adopting component.",
+    ),
+    (
+        "component.remote_login.unable_restrict_permissions_git_configuration",
+        "WARNING: Unable to restrict the permissions of the git configuration: {}",
+    ),
+    (
+        "component.remote_login.password_not_stored_ci_mode",
+        "The password was not stored in CI mode, pass it to upload_component instead.",
+    ),
+    (
+        "component.scrub_remote_credentials.removed_credentials",
+        "Removed the credentials from {} in {}.",
+    ),
+    (
+        "component.scrub_remote_credentials.unable_restrict_permissions",
+        "WARNING: Unable to restrict the permissions of {}: {}",
+    ),
+    (
+        "component.scrub_remote_credentials.removed_credentials_remote_urls",
+        "Removed credentials from {} remote URLs.",
+    ),
+    (
+        "component.migrate_remote.unable_push_mirror",
+        "ERROR: Unable to push a mirror of {} to {}, so its remotes were left alone.",
+    ),
+    (
+        "component.migrate_remote.pushed_mirror",
+        "Pushed a mirror of {} to {}.",
+    ),
+    (
+        "component.migrate_remote.changed_remote",
+        "Changed {} in {} to {}.",
+    ),
+    (
+        "component.migrate_remote.changed_dependency",
+        "Changed the URL of {} in {} to {}.",
+    ),
+    (
+        "component.migrate_remote.unable_change_dependency",
+        "ERROR: Unable to change the URL of {} in {}: {:?}",
+    ),
+    (
+        "component.migrate_remote.unable_change_lock_file",
+        "ERROR: Unable to change the URLs in {}: {}",
+    ),
+    (
+        "component.migrate_remote.migrated",
+        "Migrated {} remote URLs and {} dependency URLs.",
+    ),
+    (
+        "component.upload.upload_aborted_before_upload_handler",
+        "ERROR: Upload aborted by a before_upload handler.",
+    ),
+    (
+        "component.upload.done_uploading_component",
+        "Done uploading component.",
+    ),
+    (
+        "component.upload.could_not_remove_gitignore",
+        "WARNING: Could not remove the .gitignore file that the upload made: {}",
+    ),
+    (
+        "component.upload.strict_mode_stopped",
+        "ERROR: Stopped uploading at the first error because of strict mode, and removed what the upload had set up.",
+    ),
+    (
+        "component.upload_checked.upload_aborted_because_problems_above",
+        "ERROR: Upload aborted because of the problems above, fix them or force the upload.",
+    ),
+    (
+        "component.upload_checked.problems_found_component_but_upload",
+        "Problems were found with the component, but the upload was forced.",
+    ),
+    (
+        "component.component_does_not_exist_components",
+        "ERROR: The component does not exist in the components directory.",
+    ),
+    (
+        "component.refactor.finished_refactoring_local_component_remote",
+        "Finished refactoring local component to remote repository.",
+    ),
+    (
+        "component.refactor.strict_mode_upload_failed",
+        "ERROR: Stopped refactoring {} because it could not be uploaded, the local component was left as it was.",
+    ),
+    (
+        "component.refactor.strict_mode_stopped",
+        "ERROR: Stopped refactoring {} at the first error because of strict mode, and put the local component back.",
+    ),
+    (
+        "component.remove.not_component_name",
+        "ERROR: '{}' is not the name of a component.",
+    ),
+    (
+        "component.remove.name_used_both_local_remote",
+        "ERROR: The name '{}' is used by both a local and a remote component, use remove_local_component or remove_remote_component instead.",
+    ),
+    (
+        "component.remove.component_successfully_removed",
+        "Component {} was successfully removed.",
+    ),
+    (
+        "component.remove_local.deleting_component_directory",
+        "Deleting component directory {}.",
+    ),
+    (
+        "component.remove_local.could_not_handle_entry_while",
+        "ERROR: Could not handle entry while walking components directory tree: {}",
+    ),
+    (
+        "component.remove_local.could_not_get_metadata_git",
+        "ERROR: Could not get metadata for a .git directory entry: {}",
+    ),
+    (
+        "component.remove_local.failed_set_permissions_git_directory",
+        "ERROR: Failed to set permissions on .git directory: {}",
+    ),
+    (
+        "component.remove_local.not_able_delete_component_directory",
+        "ERROR: not able to delete component directory: {}",
+    ),
+    (
+        "component.remove_local.not_confirmed",
+        "ERROR: {} was not removed, since removing it wasn't confirmed.",
+    ),
+    (
+        "component.add_remote.name_collision_local",
+        "ERROR: A local component with the name '{}' already exists in the components directory.",
+    ),
+    (
+        "component.add_remote.remote_component_not_successfully_added",
+        "ERROR: Remote component was not successfully added",
+    ),
+    (
+        "component.add_remote.remote_component_added_successfully",
+        "Remote component was added successfully.",
+    ),
+    (
+        "component.remove_remote.component_not_successfully_removed",
+        "ERROR: Component was not successfully removed",
+    ),
+    (
+        "component.remove_remote.component_removed_successfully",
+        "Component was removed successfully.",
+    ),
+    (
+        "component.download_verified.downloaded_component_removed_because_signature",
+        "ERROR: The downloaded component was removed because its signature could not be verified.",
+    ),
+    (
+        "component.finish_download.component_not_successfully_downloaded",
+        "ERROR: Component was not successfully downloaded",
+    ),
+    (
+        "component.finish_download.component_downloaded_successfully",
+        "Component was downloaded successfully.",
+    ),
+    (
+        "component.apply_install_profile.unable_remove",
+        "ERROR: Could not remove {}, which the project's install profile leaves out.",
+    ),
+    (
+        "component.apply_install_profile.left_out",
+        "Left out the {} directories of {} installed components, since the project's install profile is dist.",
+    ),
+    (
+        "component.clone_or_resume.removed_partial_download",
+        "Removed the partial download in {}, which could not be finished, so the component is being downloaded again.",
+    ),
+    (
+        "component.clone_or_resume.kept_partial_download",
+        "ERROR: The partial download was kept in {}, download the component again to pick up where it left off.",
+    ),
+    (
+        "component.download_archive.could_not_record_commit",
+        "WARNING: Could not record the commit that was downloaded in {}: {}",
+    ),
+    (
+        "component.download_archive.downloaded_without_history",
+        "WARNING: git was not found, so the component was downloaded from an archive of commit {} without its history.",
+    ),
+    (
+        "component.install_archive.unable_create_node_modules",
+        "ERROR: Unable to create the node_modules directory: {}",
+    ),
+    (
+        "component.install_archive.could_not_add_dependency",
+        "ERROR: Could not add {} to the dependencies in package.json: {}",
+    ),
+    (
+        "component.install_archive.installed_from_archive",
+        "WARNING: git was not found, so {} was installed from an archive of commit {}.",
+    ),
+    (
+        "component.resume_download.partial_download_from_elsewhere",
+        "ERROR: The partial download in {} is not from {}.",
+    ),
+    (
+        "component.resume_download.picking_up_partial_download",
+        "Picking up the partial download in {}.",
+    ),
+    (
+        "component.resume_download.unable_finish_partial_download",
+        "ERROR: Unable to finish the partial download, download the component again to pick up where it left off.",
+    ),
+    (
+        "component.resume_download.finished_partial_download",
+        "Finished the partial download.",
+    ),
+    (
+        "component.finish_download.component_downloaded_inside_component_which",
+        "WARNING: The component was downloaded inside of the component at {}, which will treat it as one of its own.",
+    ),
+    (
+        "component.verify_locked.message",
+        "ERROR: {} in {}: {}",
+    ),
+    (
+        "component.verify_locked.installed_components_match_lock_files",
+        "Installed components match the lock files.",
+    ),
+    (
+        "component.update_dependencies.not_one_allowed_hosts_not",
+        "ERROR: {} is not from one of the allowed hosts, so it was not installed.",
+    ),
+    (
+        "component.update_dependencies.dependencies_not_successfully_updated",
+        "ERROR: Dependencies were not successfully updated",
+    ),
+    (
+        "component.update_dependencies.dependencies_updated_successfully",
+        "Dependencies were updated successfully.",
+    ),
+    (
+        "component.update_dependencies.history_unavailable",
+        "WARNING: Could not fetch the history of {}, so what changed in it can't be listed.",
+    ),
+    (
+        "component.find_changed_dependencies.could_not_check_changes_keeping",
+        "WARNING: Could not check {} for changes, keeping the installed copy.",
+    ),
+    (
+        "component.find_changed_dependencies.already_up_date",
+        "{} is already up to date.",
+    ),
+    (
+        "component.find_changed_dependencies.changed_will_reinstalled",
+        "{} has changed and will be reinstalled.",
+    ),
+    (
+        "component.find_changed_dependencies.pinned",
+        "{} is pinned to {}.",
+    ),
+    (
+        "component.pin.not_git_dependency",
+        "ERROR: {} is not a dependency from a git repository, so it can't be pinned.",
+    ),
+    (
+        "component.pin.not_commit",
+        "ERROR: {} is not a commit hash, dependencies can only be pinned to a commit.",
+    ),
+    (
+        "component.pin.package_json_failed",
+        "ERROR: Could not pin {} in package.json: {:?}",
+    ),
+    (
+        "component.pin.record_failed",
+        "ERROR: Could not record the pin of {} in {}: {}",
+    ),
+    (
+        "component.pin.restore_failed",
+        "ERROR: Could not restore {} after the failed pin: {}",
+    ),
+    (
+        "component.pin.finished",
+        "Pinned {} to {}.",
+    ),
+    (
+        "component.unpin.not_pinned",
+        "ERROR: {} is not pinned.",
+    ),
+    (
+        "component.unpin.package_json_failed",
+        "ERROR: Could not unpin {} in package.json: {:?}",
+    ),
+    (
+        "component.unpin.record_failed",
+        "ERROR: Could not remove the pin of {} from {}: {}",
+    ),
+    (
+        "component.unpin.finished",
+        "Unpinned {}.",
+    ),
+    (
+        "component.update_local.component_updated_successfully",
+        "Component updated successfully.",
+    ),
+    (
+        "component.update_local.component_not_updated_successfully",
+        "Component not updated successfully.",
+    ),
+    (
+        "component.update_local.component_not_set_up_repository",
+        "ERROR: Component is not set up as a repository, cannot update it.",
+    ),
+    (
+        "component.begin_batch.batch_operations_already_been_started",
+        "ERROR: A batch of operations has already been started.",
+    ),
+    (
+        "component.begin_batch.batch_operations_started",
+        "Batch of operations started.",
+    ),
+    (
+        "component.commit_batch.batch_operations_committed",
+        "Batch of operations committed.",
+    ),
+    (
+        "component.validate_name.component_name_cannot_empty",
+        "ERROR: The component name cannot be empty.",
+    ),
+    (
+        "component.validate_name.component_name_cannot_longer_than",
+        "ERROR: The component name cannot be longer than {} characters.",
+    ),
+    (
+        "component.validate_name.component_name_cannot_contain_dots",
+        "ERROR: The component name cannot contain dots.",
+    ),
+    (
+        "component.validate_name.component_name_must_lowercase",
+        "ERROR: The component name must be lowercase.",
+    ),
+    (
+        "component.validate_name.component_name_contains_characters_not",
+        "ERROR: The component name contains characters that are not allowed: {}.",
+    ),
+    (
+        "component.validate_name.component_name_cannot_start_dash",
+        "ERROR: The component name cannot start with a dash.",
+    ),
+    (
+        "component.validate_name.reserved_name_cannot_used_component",
+        "ERROR: '{}' is a reserved name and cannot be used for a component.",
+    ),
+    (
+        "component.validate_license.not_valid_spdx_license_expression",
+        "ERROR: '{}' is not a valid SPDX license expression, {}.",
+    ),
+    (
+        "component.validate_url.url_cannot_empty",
+        "ERROR: The URL cannot be empty.",
+    ),
+    (
+        "component.validate_url.url_cannot_start_dash",
+        "ERROR: The URL cannot start with a dash.",
+    ),
+    (
+        "component.validate_url.url_cannot_contain_spaces_control",
+        "ERROR: The URL cannot contain spaces or control characters.",
+    ),
+    (
+        "component.validate_url.url_contains_characters_not_allowed",
+        "ERROR: The URL contains characters that are not allowed: {}.",
+    ),
+    (
+        "component.validate_url.urls_starting_not_supported_use",
+        "ERROR: URLs starting with '{}://' are not supported, use one of: {}.",
+    ),
+    (
+        "component.validate_url.not_valid_host_name_repository",
+        "ERROR: '{}' is not a valid host name for a repository.",
+    ),
+    (
+        "component.validate_url.url_must_look_like_scheme",
+        "ERROR: The URL must look like scheme://host/path or user@host:path.",
+    ),
+    (
+        "component.generate_standard_dir.could_not_create_directory",
+        "ERROR: Could not create {} directory: {}",
+    ),
+    (
+        "component.generate_standard_dir.could_not_create_placeholder_file",
+        "ERROR: Could not create placeholder file in {} directory: {}",
+    ),
+    (
+        "component.generate_standard_dir.directory_already_exists_using_existing",
+        "{} directory already exists, using existing directory.",
+    ),
+    (
+        "component.generate_readme.could_not_write_readme_md",
+        "Could not write to README.md file: {}",
+    ),
+    (
+        "component.generate_readme.readme_md_already_exists_using",
+        "README.md already exists, using existing file and refusing to overwrite.",
+    ),
+    (
+        "component.generate_bom.could_not_write_bom_data",
+        "Could not write to bom_data.yaml: {}",
+    ),
+    (
+        "component.generate_bom.bom_data_yaml_already_exists",
+        "bom_data.yaml already exists, using existing file and refusing to overwrite.",
+    ),
+    (
+        "component.generate_parts_yaml.could_not_write_parts_yaml",
+        "Could not write to parts.yaml: {}",
+    ),
+    (
+        "component.generate_parts_yaml.parts_yaml_already_exists_using",
+        "parts.yaml already exists, using existing file and refusing to overwrite.",
+    ),
+    (
+        "component.add_community_files.already_exists_using",
+        "{} already exists, using existing file and refusing to overwrite.",
+    ),
+    (
+        "component.add_community_files.could_not_write",
+        "Could not write to {}: {}",
+    ),
+    (
+        "component.generate_tools_yaml.could_not_write_tools_yaml",
+        "Could not write to tools.yaml: {}",
+    ),
+    (
+        "component.generate_tools_yaml.tools_yaml_already_exists_using",
+        "tools.yaml already exists, using existing file and refusing to overwrite.",
+    ),
+    (
+        "component.generate_precautions_yaml.could_not_write_precautions_yaml",
+        "Could not write to precautions.yaml: {}",
+    ),
+    (
+        "component.generate_precautions_yaml.precautions_yaml_already_exists_using",
+        "precautions.yaml already exists, using existing file and refusing to overwrite.",
+    ),
+    (
+        "component.generate_package_json.could_not_write_package_json",
+        "Could not write to package.json: {}",
+    ),
+    (
+        "component.generate_package_json.package_json_already_exists_using",
+        "package.json already exists, using existing file and refusing to overwrite.",
+    ),
+    (
+        "component.generate_gitignore.could_not_write_gitignore",
+        "Could not write to .gitignore: {}",
+    ),
+    (
+        "component.generate_gitignore.gitignore_already_exists_using_existing",
+        ".gitignore already exists, using existing file and refusing to overwrite.",
+    ),
+    (
+        "component.generate_dot_file.could_not_write_sr_file",
+        "Could not write to .sr file: {}",
+    ),
+    (
+        "component.generate_dot_file.sr_already_exists_using_existing",
+        ".sr already exists, using existing file and refusing to overwrite.",
+    ),
+    (
+        "component.update_yaml_value.yaml_file_updated_does_not",
+        "ERROR: YAML file to be updated does not exist.",
+    ),
+    (
+        "component.update_yaml_value.could_not_update_contents_yaml",
+        "ERROR: Could not update the contents of the YAML file: {}",
+    ),
+    (
+        "component.update_yaml_value.could_not_write_yaml_file",
+        "ERROR: Could not write to the YAML file: {}",
+    ),
+    (
+        "lock.lock_installed.unable_lock",
+        "ERROR: Unable to lock {}: {}",
+    ),
+    (
+        "lock.lock_installed.locked_installed_packages",
+        "Locked {} installed packages in {}.",
+    ),
+    (
+        "lock.unable_write",
+        "ERROR: Unable to write {}: {}",
+    ),
+    (
+        "npm.find_npm_windows.could_not_run_where_exe",
+        "ERROR: Could not run where.exe to find npm, set the npm executable in the [tools] section of the configuration instead: {}",
+    ),
+    (
+        "npm.not_found",
+        "ERROR: `npm` was not found, please install it.",
+    ),
+    (
+        "npm.install.could_not_install_component_remote",
+        "ERROR: Could not install component from remote repository: {}",
+    ),
+    (
+        "npm.install.component_installed_remote_repository",
+        "Component installed from remote repository.",
+    ),
+    (
+        "npm.install.component_successfully_installed_remote_repository",
+        "Component successfully installed from remote repository.",
+    ),
+    (
+        "npm.uninstall.could_not_uninstall_component_remote",
+        "ERROR: Could not uninstall component from remote repository: {}",
+    ),
+    (
+        "npm.uninstall.component_successfully_uninstalled_remote_repository",
+        "Component successfully uninstalled from remote repository.",
+    ),
+    (
+        "npm.version.unable_get_version_npm",
+        "ERROR: Unable to get the version of npm: {}",
+    ),
+    (
+        "npm.cache_dir.unable_get_cache_directory_npm",
+        "ERROR: Unable to get the cache directory of npm: {}",
+    ),
+    (
+        "policy.check_url.not_one_hosts_remote_components",
+        "ERROR: {} is not one of the hosts that remote components are allowed to come from in {}.",
+    ),
+    (
+        "publish.publish_dist.layout_has_no_dist_directory",
+        "ERROR: The layout of {} doesn't have a dist directory, so there's nothing to publish.",
+    ),
+    (
+        "publish.publish_dist.there_no_dist_directory_publish",
+        "ERROR: There is no dist directory to publish in {}.",
+    ),
+    (
+        "publish.publish_dist.unable_read_artifact",
+        "ERROR: Unable to read the artifact {}: {}",
+    ),
+    (
+        "publish.publish_dist.unable_read_manifest_publishing_target",
+        "ERROR: Unable to read the manifest from the publishing target.",
+    ),
+    (
+        "publish.publish_dist.unable_upload",
+        "ERROR: Unable to upload {}.",
+    ),
+    (
+        "publish.publish_dist.uploaded",
+        "Uploaded {}",
+    ),
+    (
+        "publish.publish_dist.no_longer_dist_but_left",
+        "{} is no longer in dist, but was left on the publishing target.",
+    ),
+    (
+        "publish.publish_dist.unable_upload_manifest_publishing_target",
+        "ERROR: Unable to upload the manifest to the publishing target.",
+    ),
+    (
+        "publish.publish_dist.published_changed_artifacts_already_up",
+        "Published {} changed artifacts, {} were already up to date.",
+    ),
+    (
+        "publish.upload.unable_copy",
+        "ERROR: Unable to copy {} to {}: {}",
+    ),
+    (
+        "signatures.verify_checkout.not_signed_trusted_key",
+        "ERROR: {} is not signed by a trusted key.",
+    ),
+    (
+        "signatures.verify_remote.unable_fetch_check_signature",
+        "ERROR: Unable to fetch {} to check its signature.",
+    ),
+    (
+        "signatures.verify_remote.could_not_found",
+        "ERROR: {} could not be found in {}.",
+    ),
+    (
+        "signatures.verify_remote.not_signed_trusted_key",
+        "ERROR: {} of {} is not signed by a trusted key.",
+    ),
+    (
+        "signatures.verify_revision.tag_trusted_signature",
+        "The tag {} has a trusted signature.",
+    ),
+    (
+        "signatures.verify_revision.commit_trusted_signature",
+        "The commit {} has a trusted signature.",
+    ),
+    (
+        "spdx.refresh_license_db.not_valid",
+        "ERROR: The SPDX license list that was downloaded is not valid.",
+    ),
+    (
+        "spdx.refresh_license_db.text_not_downloaded",
+        "WARNING: Unable to download the text of {}, the old one was kept: {}",
+    ),
+    (
+        "spdx.refresh_license_db.not_on_list",
+        "WARNING: {} is not on the SPDX license list, so its text was not downloaded.",
+    ),
+    (
+        "spdx.refresh_license_db.no_cache_dir",
+        "ERROR: There is no cache directory to save the SPDX license list in.",
+    ),
+    (
+        "spdx.refresh_license_db.unable_save",
+        "ERROR: Unable to save the SPDX license list to {}: {}",
+    ),
+    (
+        "spdx.refresh_license_db.refreshed",
+        "Refreshed the SPDX license list to version {}.",
+    ),
+    (
+        "spdx.check_ids.deprecated",
+        "WARNING: '{}' is a deprecated SPDX license identifier.",
+    ),
+    (
+        "spdx.check_ids.not_on_list",
+        "WARNING: '{}' is not on version {} of the SPDX license list.",
+    ),
+    (
+        "spdx.check_ids.not_on_list_suggestion",
+        "WARNING: '{}' is not on version {} of the SPDX license list, did you mean {}?",
+    ),
+    (
+        "spdx.write_license_file.no_text",
+        "ERROR: The text of {} is not in the SPDX license database, it can be downloaded with refresh_license_db.",
+    ),
+    (
+        "spdx.write_license_file.wrote",
+        "Wrote the text of {} licenses to LICENSE.",
+    ),
+    (
+        "spdx.write_license_file.unable_write",
+        "ERROR: Unable to write LICENSE: {}",
+    ),
+    (
+        "ssh.not_found",
+        "ERROR: `ssh` was not found, please install: {}",
+    ),
+    (
+        "ssh.run.unable_run_ssh",
+        "ERROR: Unable to run ssh: {}",
+    ),
+    (
+        "ssh.scp_not_found",
+        "ERROR: `scp` was not found, please install: {}",
+    ),
+    (
+        "ssh.scp_upload.unable_run_scp",
+        "ERROR: Unable to run scp: {}",
+    ),
+    (
+        "status.component_status.unable_fetch",
+        "Unable to fetch: {}",
+    ),
+    (
+        "status.component_status.unable_get_status",
+        "Unable to get the status: {}",
+    ),
+    (
+        "workspace.add_project.not_sliderule_project",
+        "ERROR: {} is not a Sliderule project, so it can't be added to the workspace.",
+    ),
+    (
+        "workspace.add_project.already_in_workspace",
+        "{} is already in the workspace.",
+    ),
+    (
+        "workspace.add_project.added",
+        "Added {} to the workspace.",
+    ),
+    (
+        "npm.scripts_skipped",
+        "Skipped the install scripts of remote components.",
+    ),
+];
+
+/// How serious a message is, going by whether it starts with `ERROR:` or `WARNING:`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// One line of an operation's output, matched up with the message it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    /// The identifier from `MESSAGES`, or `TOOL_OUTPUT`
+    pub id: &'static str,
+    pub severity: Severity,
+    /// The values that were filled into the message, in order
+    pub args: Vec<String>,
+    /// The line as it was in the output
+    pub text: String,
+}
+
+/// Works out which message a line of output is.
+///
+/// Some messages are built from others, i.e. validation problems written as `ERROR: ` followed by the problem, in
+/// which case the inner message is the one that's identified. When more than one message fits, the one with the
+/// most fixed text wins. Lines that aren't one of Sliderule's messages are given `TOOL_OUTPUT`.
+///
+/// # Examples
+///
+/// ```
+/// let message = sliderule::messages::identify("ERROR: A component with the name 'level1' already exists.");
+///
+/// assert_eq!(message.id, "component.create.dir_exists");
+/// assert_eq!(message.severity, sliderule::messages::Severity::Error);
+/// assert_eq!(message.args, vec!["level1"]);
+///
+/// let message = sliderule::messages::identify("Already up to date.");
+/// assert_eq!(message.id, sliderule::messages::TOOL_OUTPUT);
+/// ```
+pub fn identify(line: &str) -> Message {
+    let text = line.trim_end();
+
+    let severity = if text.starts_with("ERROR: ") {
+        Severity::Error
+    } else if text.starts_with("WARNING: ") {
+        Severity::Warning
+    } else {
+        Severity::Info
+    };
+
+    // Try the whole line, and then the line without its prefix for messages that were wrapped in one
+    let mut candidates = vec![text];
+    for prefix in ["ERROR: ", "WARNING: "] {
+        if let Some(rest) = text.strip_prefix(prefix) {
+            candidates.push(rest);
+        }
+    }
+
+    let mut best: Option<(usize, &'static str, Vec<String>)> = None;
+    for candidate in candidates {
+        for &(id, ref pattern, fixed) in patterns().iter() {
+            if best.as_ref().map(|b| b.0 >= fixed) == Some(true) {
+                continue;
+            }
+
+            if let Some(captures) = pattern.captures(candidate) {
+                let args = captures
+                    .iter()
+                    .skip(1)
+                    .map(|c| c.map(|c| c.as_str().to_string()).unwrap_or_default())
+                    .collect();
+                best = Some((fixed, id, args));
+            }
+        }
+    }
+
+    let (id, args) = match best {
+        Some((_, id, args)) => (id, args),
+        None => (TOOL_OUTPUT, Vec::new()),
+    };
+
+    Message {
+        id,
+        severity,
+        args,
+        text: text.to_string(),
+    }
+}
+
+/// Identifies every line of an operation's output, `stdout` first and then `stderr`, leaving out empty lines.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// let output = sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// let messages = sliderule::messages::identify_output(&output);
+///
+/// assert_eq!(messages.last().unwrap().id, "component.create.finished");
+/// ```
+pub fn identify_output(output: &super::SROutput) -> Vec<Message> {
+    output
+        .stdout
+        .iter()
+        .chain(output.stderr.iter())
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| identify(l))
+        .collect()
+}
+
+/*
+ * Turns every message's text into a pattern that captures the values filled into it, along with how many
+ * characters of fixed text it has.
+ */
+fn patterns() -> &'static Vec<(&'static str, Regex, usize)> {
+    static PATTERNS: OnceLock<Vec<(&'static str, Regex, usize)>> = OnceLock::new();
+
+    PATTERNS.get_or_init(|| {
+        MESSAGES
+            .iter()
+            .map(|&(id, text)| {
+                let text = text.replace("{{", "\u{1}").replace("}}", "\u{2}");
+                let placeholder = Regex::new(r"\{[^}]*\}").unwrap();
+
+                let mut pattern = String::from("^");
+                let mut fixed = 0;
+                for (i, piece) in placeholder.split(&text).enumerate() {
+                    if i > 0 {
+                        pattern.push_str("(.*?)");
+                    }
+                    let piece = piece.replace('\u{1}', "{").replace('\u{2}', "}");
+                    fixed += piece.chars().count();
+                    pattern.push_str(&regex::escape(&piece));
+                }
+                pattern.push('$');
+
+                // (?s) lets values that span lines, like a tool's error, still be captured
+                (id, Regex::new(&format!("(?s){}", pattern)).unwrap(), fixed)
+            })
+            .collect()
+    })
+}
@@ -1,31 +1,152 @@
-extern crate os_info;
-
 use std::path::Path;
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ALLOW_SCRIPTS: AtomicBool = AtomicBool::new(false);
+
+/// What is added to `stdout` when npm was told not to run the lifecycle scripts of the components it installed.
+pub const SCRIPTS_SKIPPED: &str = "Skipped the install scripts of remote components.";
+
+/// Allows npm to run the lifecycle scripts (i.e. `postinstall`) of the components it installs, for the rest of
+/// the process. Scripts are not run by default, since any repository that a remote component comes from could
+/// use one to run whatever it wants on the user's machine.
+///
+/// # Examples
+///
+/// ```
+/// assert!(!sliderule::npm_sr::scripts_allowed());
+///
+/// sliderule::npm_sr::set_allow_scripts(true);
+/// assert!(sliderule::npm_sr::scripts_allowed());
+///
+/// sliderule::npm_sr::set_allow_scripts(false);
+/// ```
+pub fn set_allow_scripts(allow: bool) {
+    ALLOW_SCRIPTS.store(allow, Ordering::SeqCst);
+}
+
+/// Returns true if `set_allow_scripts` has been used to let npm run lifecycle scripts.
+pub fn scripts_allowed() -> bool {
+    ALLOW_SCRIPTS.load(Ordering::SeqCst)
+}
+
+/// Works out which program to run for npm: the one from the configuration (see `config::ProjectConfig`) if
+/// there is one, otherwise `npm` on the PATH, or on Windows, the `npm.cmd` that `where.exe` finds. If the
+/// configured program is npm's `npm-cli.js`, it's run with node.
+///
+/// The program is placed in `stdout`, with the script it runs after it if there is one. The output status is 200
+/// if npm can't be found on Windows, or 205 if `where.exe` can't be run to look for it.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// std::env::set_var("SR_NPM_BIN", "/opt/node/lib/node_modules/npm/bin/npm-cli.js");
+/// std::env::set_var("SR_NODE_BIN", "/opt/node/bin/node");
+///
+/// let output = sliderule::npm_sr::find_npm(&temp_dir);
+///
+/// assert_eq!(output.stdout, vec!["/opt/node/bin/node", "/opt/node/lib/node_modules/npm/bin/npm-cli.js"]);
+/// ```
+pub fn find_npm(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let config = super::config::config_or_default(target_dir);
+
+    let program = match config.npm_bin {
+        Some(p) => p,
+        None if super::on_windows() => match find_npm_windows() {
+            Ok(p) => p,
+            Err(e) => return e,
+        },
+        None => String::from("npm"),
+    };
+
+    if program.ends_with(".js") {
+        output.stdout.push(config.node_bin);
+    }
+    output.stdout.push(program);
+
+    output
+}
+
+/*
+ * Sets up a command that runs npm, in target_dir if it's given.
+ */
+fn npm_command(target_dir: Option<&Path>) -> Result<Command, super::SROutput> {
+    let npm = find_npm(target_dir.unwrap_or_else(|| Path::new(".")));
+    if npm.status != 0 {
+        return Err(npm);
+    }
+
+    let mut command = Command::new(&npm.stdout[0]);
+    command.args(&npm.stdout[1..]);
+    if let Some(dir) = target_dir {
+        command.current_dir(dir);
+    }
+
+    Ok(command)
+}
+
+/*
+ * Looks for npm.cmd on Windows, first where where.exe says it is and then where the Node.js installer puts it.
+ */
+fn find_npm_windows() -> Result<String, super::SROutput> {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
 
-fn find_npm_windows() -> String {
     // Run the where command to attempt to find the npm.cmd script
-    let output = match Command::new("where.exe").args(&["npm.cmd"]).output() {
-        Ok(output) => output,
-        Err(_) => {
-            println!("Could not run where.exe which is needed for this CLI to work.");
-            std::process::exit(2);
+    let found = match super::backend::run(Command::new("where.exe").args(["npm.cmd"])) {
+        Ok(found) => found,
+        Err(e) => {
+            output.status = 205;
+            output.stderr.push(format!(
+                "ERROR: Could not run where.exe to find npm, set the npm executable in the [tools] section of the configuration instead: {}",
+                e
+            ));
+            return Err(output);
         }
     };
 
-    let mut output_str = String::from("C:\\Program Files\\nodejs\\npm.cmd");
+    // where.exe lists every match on the PATH, so take the first one that's really there
+    let lines = String::from_utf8_lossy(&found.stdout);
+    let mut candidates: Vec<String> = lines
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect();
 
-    // If there is not output, there will be no command path to extract
-    if !output.stdout.is_empty() {
-        // Convert the output into a string iterator that we can work with
-        let lines = String::from_utf8_lossy(&output.stdout);
-        let lines: Vec<&str> = lines.lines().collect();
-
-        // Take just the first line
-        output_str = lines[0].trim().to_string();
+    for var in ["ProgramFiles", "ProgramFiles(x86)"] {
+        if let Ok(dir) = std::env::var(var) {
+            candidates.push(format!("{}\\nodejs\\npm.cmd", dir));
+        }
     }
+    if let Ok(dir) = std::env::var("APPDATA") {
+        candidates.push(format!("{}\\npm\\npm.cmd", dir));
+    }
+    candidates.push(String::from("C:\\Program Files\\nodejs\\npm.cmd"));
 
-    output_str
+    match candidates.into_iter().find(|c| Path::new(c).is_file()) {
+        Some(c) => Ok(c),
+        None => {
+            output.status = 200;
+            output.stderr.push(String::from(
+                "ERROR: `npm` was not found, please install it.",
+            ));
+            Err(output)
+        }
+    }
 }
 
 /// Attempts to use npm to install a remote component, given a URL of a remote repository.
@@ -34,6 +155,9 @@ fn find_npm_windows() -> String {
 /// 'url' The URL of the remote repository for npm to pull the component from.
 /// 'cache` Allows a user to specify a temporary cache for npm to use. Mostly for testing purposes.
 ///
+/// Lifecycle scripts are only run if `set_allow_scripts` has allowed them, otherwise `SCRIPTS_SKIPPED` is
+/// added to the end of `stdout`.
+///
 /// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
 pub fn npm_install(target_dir: &Path, url: &str, cache: Option<String>) -> super::SROutput {
     let mut output = super::SROutput {
@@ -45,14 +169,17 @@ pub fn npm_install(target_dir: &Path, url: &str, cache: Option<String>) -> super
     let mut vec = Vec::new();
     vec.push("install");
 
-    let info = os_info::get();
-    let mut cmd_name = String::from("npm");
-
-    // Set the command name properly based on which OS the user is running
-    if info.os_type() == os_info::Type::Windows {
-        cmd_name = find_npm_windows();
+    // Keep the components that are being installed from running anything unless the user has allowed it
+    let skip_scripts = !scripts_allowed();
+    if skip_scripts {
+        vec.push("--ignore-scripts");
     }
 
+    let mut command = match npm_command(Some(target_dir)) {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+
     // If the caller has selected to use a temporary cache, configure npm to use that
     if cache.is_some() {
         vec.push("--cache");
@@ -66,11 +193,7 @@ pub fn npm_install(target_dir: &Path, url: &str, cache: Option<String>) -> super
     }
 
     // Try to run the npm command line and gather the output and errors so that they can be used later
-    let stdoutput = match Command::new(&cmd_name)
-        .args(&vec)
-        .current_dir(target_dir)
-        .output()
-    {
+    let stdoutput = match super::backend::run(command.args(&vec)) {
         Ok(out) => out,
         Err(e) => {
             if let std::io::ErrorKind::NotFound = e.kind() {
@@ -106,16 +229,22 @@ pub fn npm_install(target_dir: &Path, url: &str, cache: Option<String>) -> super
     // Collect all of the other stdout entries
     output
         .stdout
-        .push(String::from_utf8_lossy(&stdoutput.stdout).to_string());
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
 
     // If there were errors, make sure we collect them
     output
         .stderr
-        .push(String::from_utf8_lossy(&stdoutput.stderr).to_string());
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
 
     // If we have something other than a 0 exit status, report that
-    if stdoutput.status.code().unwrap() != 0 {
-        output.wrapped_status = stdoutput.status.code().unwrap();
+    super::backend::record_exit(&mut output, "npm", &stdoutput.status);
+
+    if skip_scripts {
+        output.stdout.push(String::from(SCRIPTS_SKIPPED));
     }
 
     output
@@ -139,14 +268,17 @@ pub fn npm_uninstall(target_dir: &Path, name: &str, cache: Option<String>) -> su
     let mut vec = Vec::new();
     vec.push("uninstall");
 
-    let info = os_info::get();
-    let mut cmd_name = String::from("npm");
-
-    // Set the command name properly based on which OS the user is running
-    if info.os_type() == os_info::Type::Windows {
-        cmd_name = find_npm_windows();
+    // Keep the components that are being installed from running anything unless the user has allowed it
+    let skip_scripts = !scripts_allowed();
+    if skip_scripts {
+        vec.push("--ignore-scripts");
     }
 
+    let mut command = match npm_command(Some(target_dir)) {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+
     // If the caller has selected to use a temporary cache, configure npm to use that
     if cache.is_some() {
         vec.push("--cache");
@@ -160,11 +292,7 @@ pub fn npm_uninstall(target_dir: &Path, name: &str, cache: Option<String>) -> su
     }
 
     // Attempt to install the component using npm
-    let stdoutput = match Command::new(&cmd_name)
-        .args(&vec)
-        .current_dir(target_dir)
-        .output()
-    {
+    let stdoutput = match super::backend::run(command.args(&vec)) {
         Ok(out) => out,
         Err(e) => {
             if let std::io::ErrorKind::NotFound = e.kind() {
@@ -194,17 +322,134 @@ pub fn npm_uninstall(target_dir: &Path, name: &str, cache: Option<String>) -> su
     // Collect all of the other stdout entries
     output
         .stdout
-        .push(String::from_utf8_lossy(&stdoutput.stdout).to_string());
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
 
     // If there were errors, make sure we collect them
     output
         .stderr
-        .push(String::from_utf8_lossy(&stdoutput.stderr).to_string());
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
 
     // If we have something other than a 0 exit status, report that
-    if stdoutput.status.code().unwrap() != 0 {
-        output.wrapped_status = stdoutput.status.code().unwrap();
+    super::backend::record_exit(&mut output, "npm", &stdoutput.status);
+
+    if skip_scripts {
+        output.stdout.push(String::from(SCRIPTS_SKIPPED));
     }
 
     output
 }
+
+/// Runs `npm --version` to find out which version of npm is installed.
+///
+/// The version number is placed in `stdout`. The output status is 200 if npm could not be found.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn npm_version() -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let mut command = match npm_command(None) {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+
+    let stdoutput = match super::backend::run(command.args(["--version"])) {
+        Ok(out) => out,
+        Err(e) => {
+            if let std::io::ErrorKind::NotFound = e.kind() {
+                output.status = 200;
+                output.stderr.push(String::from(
+                    "ERROR: `npm` was not found, please install it.",
+                ));
+            } else {
+                output.status = 203;
+                output
+                    .stderr
+                    .push(format!("ERROR: Unable to get the version of npm: {}", e));
+            }
+            return output;
+        }
+    };
+
+    output.stdout.push(
+        String::from_utf8_lossy(&stdoutput.stdout)
+            .trim()
+            .to_string(),
+    );
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "npm", &stdoutput.status);
+
+    output
+}
+
+/// Asks npm where its cache directory is, so that it can be checked before npm needs it.
+///
+/// The path of the cache directory is placed in `stdout`.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn npm_cache_dir() -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let mut command = match npm_command(None) {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+
+    let stdoutput = match super::backend::run(command.args(["config", "get", "cache"])) {
+        Ok(out) => out,
+        Err(e) => {
+            if let std::io::ErrorKind::NotFound = e.kind() {
+                output.status = 200;
+                output.stderr.push(String::from(
+                    "ERROR: `npm` was not found, please install it.",
+                ));
+            } else {
+                output.status = 204;
+                output.stderr.push(format!(
+                    "ERROR: Unable to get the cache directory of npm: {}",
+                    e
+                ));
+            }
+            return output;
+        }
+    };
+
+    output.stdout.push(
+        String::from_utf8_lossy(&stdoutput.stdout)
+            .trim()
+            .to_string(),
+    );
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "npm", &stdoutput.status);
+
+    output
+}
@@ -0,0 +1,198 @@
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+/// The name of the file in a project's top level directory that lists the places remote components are
+/// allowed to come from, one pattern per line. Blank lines and lines starting with `#` are skipped.
+///
+/// A pattern is a host, which allows anything on that host, or a host followed by a path, where `*` matches
+/// anything within one level of the path and `**` matches any number of levels. For example, `github.com/ourorg/*`
+/// allows every repository of the `ourorg` organization on GitHub, and `*.example.com` allows every subdomain of
+/// `example.com`. Without the file, or if it has no patterns in it, remote components can come from anywhere.
+pub const ALLOWED_HOSTS_FILE: &str = ".srhosts";
+
+/// Reads the allowed host patterns of the project that a component belongs to. The file is always read from the
+/// top level of the project, so that every component in it follows the same policy.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// std::fs::write(
+///     temp_dir.join("toplevel").join(".srhosts"),
+///     "# Our own parts\ngithub.com/ourorg/*\n\ngitlab.example.com\n",
+/// ).expect("Unable to write allowed hosts file.");
+///
+/// assert_eq!(
+///     sliderule::policy::read_allowed_hosts(&temp_dir.join("toplevel")),
+///     vec!["github.com/ourorg/*", "gitlab.example.com"]
+/// );
+/// ```
+pub fn read_allowed_hosts(target_dir: &Path) -> Vec<String> {
+    let root = super::config::project_root(target_dir);
+    let contents = fs::read_to_string(root.join(ALLOWED_HOSTS_FILE)).unwrap_or_default();
+
+    let mut patterns: Vec<String> = contents
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(|l| l.to_string())
+        .collect();
+
+    // The project's configuration can list more patterns
+    patterns.extend(super::config::config_or_default(&root).allowed_hosts);
+
+    patterns
+}
+
+/// Checks whether a remote component URL matches any of a list of allowed host patterns. Every URL is allowed
+/// when there are no patterns. Local paths and `file://` URLs don't come from a host, so they are always allowed,
+/// while shorthands like `github:user/repo` are checked against the host they stand for.
+///
+/// # Examples
+///
+/// ```
+/// let patterns = vec![String::from("github.com/ourorg/*"), String::from("*.example.com")];
+///
+/// assert!(sliderule::policy::is_allowed("https://github.com/ourorg/part.git", &patterns));
+/// assert!(sliderule::policy::is_allowed("git+ssh://git@git.example.com:parts/part.git", &patterns));
+/// assert!(!sliderule::policy::is_allowed("https://github.com/someone/part.git", &patterns));
+/// ```
+pub fn is_allowed(url: &str, patterns: &[String]) -> bool {
+    if patterns.is_empty() {
+        return true;
+    }
+
+    let location = match url_location(url) {
+        Some(l) => l,
+        None => return true,
+    };
+
+    patterns
+        .iter()
+        .any(|p| pattern_regex(p).is_match(&location))
+}
+
+/// Makes sure that a remote component URL is allowed by the policy of the project that `target_dir` belongs to.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+/// `url` The URL of the remote component.
+///
+/// The output status is 40 if the URL isn't allowed.
+pub fn check_url(target_dir: &Path, url: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    if !is_allowed(url, &read_allowed_hosts(target_dir)) {
+        output.status = 40;
+        output.stderr.push(format!(
+            "ERROR: {} is not one of the hosts that remote components are allowed to come from in {}.",
+            super::redact::redact(url),
+            ALLOWED_HOSTS_FILE
+        ));
+    }
+
+    output
+}
+
+/*
+ * Turns a URL into the host followed by the path, without the user, port or a trailing .git, so that it can be
+ * matched against the patterns. Gives None for anything that isn't on a remote host.
+ */
+fn url_location(url: &str) -> Option<String> {
+    let url = url.trim();
+    let url = match url.find('#') {
+        Some(i) => &url[..i],
+        None => url,
+    };
+
+    if url.starts_with("file:") || url.starts_with('/') || url.starts_with('.') {
+        return None;
+    }
+
+    let git_url = super::dependency_url_to_git_url(url).unwrap_or_else(|| url.to_string());
+
+    let (authority, path) = match git_url.find("://") {
+        Some(i) => {
+            let rest = &git_url[i + 3..];
+            match rest.find('/') {
+                Some(j) => (&rest[..j], &rest[j + 1..]),
+                None => (rest, ""),
+            }
+        }
+        None => {
+            // An scp-style address, like git@host:path
+            let i = git_url.find(':')?;
+            (&git_url[..i], &git_url[i + 1..])
+        }
+    };
+
+    let host = match authority.rfind('@') {
+        Some(i) => &authority[i + 1..],
+        None => authority,
+    };
+    let host = match host.rfind(':') {
+        Some(i) if !host.ends_with(']') => &host[..i],
+        _ => host,
+    };
+    if host.is_empty() {
+        return None;
+    }
+
+    let path = path.trim_matches('/').trim_end_matches(".git");
+
+    Some(format!("{}/{}", host, path))
+}
+
+/*
+ * Builds the regular expression that matches the locations a pattern allows.
+ */
+fn pattern_regex(pattern: &str) -> Regex {
+    let pattern = pattern.trim().trim_end_matches('/');
+    let pattern = match pattern.find("://") {
+        Some(i) => &pattern[i + 3..],
+        None => pattern,
+    };
+
+    let (host, path) = match pattern.find('/') {
+        Some(i) => (&pattern[..i], Some(&pattern[i + 1..])),
+        None => (pattern, None),
+    };
+
+    // Hosts aren't case sensitive, and neither are the user and organization names of the hosting providers
+    let mut expression = format!("(?i)^{}/", glob_to_regex(host, "[^/.]*"));
+    match path {
+        Some(p) => {
+            expression.push_str(&glob_to_regex(p.trim_end_matches(".git"), "[^/]*"));
+            expression.push('$');
+        }
+        // A bare host allows everything on it
+        None => expression.push_str(".*$"),
+    }
+
+    // Everything other than the wildcards was escaped, so this can't fail
+    Regex::new(&expression).unwrap()
+}
+
+/*
+ * Escapes a glob for use in a regular expression, with `*` matching what `star` does and `**` matching anything.
+ */
+fn glob_to_regex(glob: &str, star: &str) -> String {
+    glob.split("**")
+        .map(|part| {
+            part.split('*')
+                .map(regex::escape)
+                .collect::<Vec<String>>()
+                .join(star)
+        })
+        .collect::<Vec<String>>()
+        .join(".*")
+}
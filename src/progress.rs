@@ -0,0 +1,159 @@
+use std::sync::{Mutex, OnceLock};
+
+/// Something that happened while an operation was running, passed to the callback set with
+/// `set_progress_callback`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    /// What Sliderule is doing, i.e. `Running git clone https://github.com/jmwright/toplevel.git`
+    pub message: String,
+    /// How far along a download is, when the message is one of git's progress lines
+    pub transfer: Option<Transfer>,
+}
+
+/// How far along git is with one phase of a clone or fetch, taken from the progress lines that git writes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transfer {
+    /// What git is doing, i.e. `Receiving objects` or `Resolving deltas`
+    pub phase: String,
+    /// How many objects have been handled so far
+    pub objects: u64,
+    /// How many objects there are in all, which git doesn't know while it's still counting them
+    pub total_objects: Option<u64>,
+    /// How many bytes have been received so far, which git only gives while receiving objects
+    pub bytes: Option<u64>,
+}
+
+/// A function that is told about progress as operations run.
+pub type ProgressCallback = Box<dyn Fn(&Progress) + Send + Sync>;
+
+fn callback() -> &'static Mutex<Option<ProgressCallback>> {
+    static CALLBACK: OnceLock<Mutex<Option<ProgressCallback>>> = OnceLock::new();
+
+    CALLBACK.get_or_init(|| Mutex::new(None))
+}
+
+/// Sets the function that is told about progress for the rest of the process, i.e. each time git or npm is
+/// started. Operations that run steps at the same time may call it from more than one thread. Passing `None`
+/// stops progress from being reported.
+///
+/// # Examples
+///
+/// ```
+/// sliderule::progress::set_progress_callback(Some(Box::new(|p| println!("{}", p.message))));
+///
+/// sliderule::git_sr::git_version();
+///
+/// sliderule::progress::set_progress_callback(None);
+/// ```
+pub fn set_progress_callback(progress_callback: Option<ProgressCallback>) {
+    *callback().lock().unwrap_or_else(|e| e.into_inner()) = progress_callback;
+}
+
+/// Tells the progress callback, if there is one, about something that has happened. Secrets are redacted from
+/// the message first.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn report(message: &str) {
+    let callback = callback().lock().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(ref c) = *callback {
+        c(&Progress {
+            message: super::redact::redact(message),
+            transfer: None,
+        });
+    }
+}
+
+/// Tells the progress callback, if there is one, how far along a download is. Secrets are redacted from the
+/// message first.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn report_transfer(message: &str, transfer: Transfer) {
+    let callback = callback().lock().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(ref c) = *callback {
+        c(&Progress {
+            message: super::redact::redact(message),
+            transfer: Some(transfer),
+        });
+    }
+}
+
+/// Reads one of the progress lines that git writes while cloning or fetching with `--progress`, i.e.
+/// `Receiving objects:  45% (450/1000), 1.20 MiB | 1.10 MiB/s`. Lines that aren't progress lines give `None`.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+///
+/// # Examples
+///
+/// ```
+/// let transfer = sliderule::progress::parse_transfer("Receiving objects:  45% (450/1000), 1.50 KiB | 1.00 KiB/s").unwrap();
+///
+/// assert_eq!("Receiving objects", transfer.phase);
+/// assert_eq!(450, transfer.objects);
+/// assert_eq!(Some(1000), transfer.total_objects);
+/// assert_eq!(Some(1536), transfer.bytes);
+///
+/// assert_eq!(None, sliderule::progress::parse_transfer("Cloning into 'toplevel'..."));
+/// ```
+pub fn parse_transfer(line: &str) -> Option<Transfer> {
+    let line = line.trim();
+    let line = line.strip_prefix("remote: ").unwrap_or(line).trim();
+
+    let (phase, rest) = line.split_once(": ")?;
+    if phase.is_empty() || !phase.chars().all(|c| c.is_ascii_alphabetic() || c == ' ') {
+        return None;
+    }
+
+    let mut parts = rest.splitn(2, ',');
+    let counts = parts.next()?.trim();
+    let details = parts.next().unwrap_or("").trim();
+
+    let (objects, total_objects) = match counts.split_once('(') {
+        // i.e. 45% (450/1000)
+        Some((percent, fraction)) => {
+            if !percent.trim().ends_with('%') {
+                return None;
+            }
+            let (done, total) = fraction.strip_suffix(')')?.split_once('/')?;
+            (done.parse::<u64>().ok()?, Some(total.parse::<u64>().ok()?))
+        }
+        // Git is still counting, i.e. 1234
+        None => (counts.parse::<u64>().ok()?, None),
+    };
+
+    // The byte count comes before the rate, i.e. 1.20 MiB | 1.10 MiB/s
+    let bytes = details
+        .split('|')
+        .next()
+        .and_then(|size| parse_size(size.trim()));
+
+    Some(Transfer {
+        phase: phase.to_string(),
+        objects,
+        total_objects,
+        bytes,
+    })
+}
+
+/*
+ * Reads a size the way git writes it, i.e. 1.20 MiB or 512 bytes.
+ */
+fn parse_size(size: &str) -> Option<u64> {
+    let (number, unit) = size.split_once(' ')?;
+    let multiplier: u64 = match unit {
+        "byte" | "bytes" => 1,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        _ => return None,
+    };
+
+    // Git gives two decimal places, which are parsed separately so that large sizes stay exact
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, "0"));
+    let whole = whole.parse::<u64>().ok()?;
+    let fraction_digits = fraction.len() as u32;
+    let fraction = fraction.parse::<u64>().ok()?;
+
+    Some(whole * multiplier + fraction * multiplier / 10u64.checked_pow(fraction_digits)?)
+}
@@ -0,0 +1,185 @@
+use std::sync::{Arc, Mutex, OnceLock};
+
+use super::git_sr::GitCredentials;
+
+/// Which of a component's licenses `Prompter::choose_license` is being asked about.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LicenseKind {
+    /// The license of the design files in the `source` directory
+    Source,
+    /// The license of the documentation in the `docs` directory
+    Documentation,
+}
+
+/// Asks the person using Sliderule for something that an operation needs but wasn't given. CLI and GUI hosts set
+/// their own with `set_prompter` to ask in whatever way suits them. Every method has a default that doesn't ask
+/// anything, and an operation goes on the same way it would without a prompter when a method doesn't have an
+/// answer, so only the methods a host cares about have to be written.
+///
+/// Prompters are never asked anything in CI mode, see `ci::ci_mode`.
+///
+/// # Examples
+///
+/// ```
+/// use sliderule::prompt::{LicenseKind, Prompter};
+///
+/// struct Host;
+///
+/// impl Prompter for Host {
+///     fn confirm(&self, _action: &str) -> bool {
+///         false
+///     }
+///
+///     fn choose_license(&self, kind: LicenseKind, choices: &[String]) -> Option<usize> {
+///         let wanted = match kind {
+///             LicenseKind::Source => "MIT",
+///             LicenseKind::Documentation => "CC-BY-4.0",
+///         };
+///         choices.iter().position(|c| c == wanted)
+///     }
+/// }
+///
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// sliderule::create_component(&temp_dir.join("toplevel"), String::from("level1"), String::from("Level 1 component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// sliderule::prompt::set_prompter(Some(Box::new(Host)));
+///
+/// // Nothing is deleted without the host's say-so
+/// let output = sliderule::remove_local_component(&temp_dir.join("toplevel"), "level1");
+/// assert_eq!(506, output.status);
+/// assert!(temp_dir.join("toplevel").join("components").join("level1").exists());
+///
+/// // Licenses that can't be recovered are picked by the host
+/// let component_dir = temp_dir.join("toplevel").join("components").join("level1");
+/// let package_file = component_dir.join("package.json");
+/// let contents = std::fs::read_to_string(&package_file).unwrap();
+/// std::fs::write(&package_file, contents.replace("(Unlicense AND CC0-1.0)", "(MIT AND CC-BY-4.0 AND Apache-2.0)")).unwrap();
+/// std::fs::remove_file(component_dir.join(".sr")).unwrap();
+///
+/// let output = sliderule::repair_component(&component_dir);
+/// assert_eq!(0, output.status);
+/// assert_eq!(
+///     sliderule::get_licenses(&component_dir),
+///     (String::from("MIT"), String::from("CC-BY-4.0"))
+/// );
+///
+/// sliderule::prompt::set_prompter(None);
+/// ```
+pub trait Prompter: Send + Sync {
+    /// Gets the credentials for an HTTPS URL that git has nothing to log in to with. `None` leaves git to find
+    /// them, i.e. with a credential helper.
+    fn ask_credentials(&self, url: &str) -> Option<GitCredentials> {
+        let _ = url;
+        None
+    }
+
+    /// Asks whether an action that can't be undone, described by `action`, should go ahead.
+    fn confirm(&self, action: &str) -> bool {
+        let _ = action;
+        true
+    }
+
+    /// Picks one of `choices` for one of a component's licenses, giving its index. `None` leaves the license
+    /// that the operation would use anyway.
+    fn choose_license(&self, kind: LicenseKind, choices: &[String]) -> Option<usize> {
+        let _ = (kind, choices);
+        None
+    }
+}
+
+/// A prompter that never asks anything, which is what operations do when no prompter has been set.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NonInteractive;
+
+impl Prompter for NonInteractive {}
+
+fn prompter() -> &'static Mutex<Option<Arc<dyn Prompter>>> {
+    static PROMPTER: OnceLock<Mutex<Option<Arc<dyn Prompter>>>> = OnceLock::new();
+
+    PROMPTER.get_or_init(|| Mutex::new(None))
+}
+
+/*
+ * Gets the prompter that's set, without holding the lock while it's asked anything. A prompter can take as long
+ * as it likes to answer, and can set another prompter while it does.
+ */
+fn current() -> Option<Arc<dyn Prompter>> {
+    lock(prompter()).clone()
+}
+
+/// Sets the prompter that operations ask for the rest of the process. Passing `None` goes back to not asking
+/// anything.
+///
+/// # Examples
+///
+/// ```
+/// struct Careful;
+///
+/// impl sliderule::prompt::Prompter for Careful {
+///     fn confirm(&self, _action: &str) -> bool {
+///         false
+///     }
+/// }
+///
+/// sliderule::prompt::set_prompter(Some(Box::new(Careful)));
+/// assert!(!sliderule::prompt::confirm("Delete everything"));
+///
+/// sliderule::prompt::set_prompter(None);
+/// assert!(sliderule::prompt::confirm("Delete everything"));
+/// ```
+pub fn set_prompter(new_prompter: Option<Box<dyn Prompter>>) {
+    *lock(prompter()) = new_prompter.map(Arc::from);
+}
+
+/// Asks the prompter for the credentials for `url`, see `Prompter::ask_credentials`. Without a prompter, or in
+/// CI mode, there aren't any.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn ask_credentials(url: &str) -> Option<GitCredentials> {
+    if super::ci::ci_mode() {
+        return None;
+    }
+
+    current().and_then(|p| p.ask_credentials(url))
+}
+
+/// Asks the prompter whether `action` should go ahead, see `Prompter::confirm`. Without a prompter, or in CI
+/// mode, it always does.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn confirm(action: &str) -> bool {
+    if super::ci::ci_mode() {
+        return true;
+    }
+
+    match current() {
+        Some(p) => p.confirm(action),
+        None => true,
+    }
+}
+
+/// Asks the prompter to pick a license out of `choices`, see `Prompter::choose_license`. Without a prompter, in
+/// CI mode, or when the prompter picks something that isn't one of the choices, nothing is picked.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn choose_license(kind: LicenseKind, choices: &[String]) -> Option<String> {
+    if super::ci::ci_mode() || choices.is_empty() {
+        return None;
+    }
+
+    current()
+        .and_then(|p| p.choose_license(kind, choices))
+        .and_then(|i| choices.get(i).cloned())
+}
+
+/*
+ * Gets the guarded value even if another thread panicked while holding the lock.
+ */
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(g) => g,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
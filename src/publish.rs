@@ -0,0 +1,556 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The file at the root of a publishing target that records what has been published there, so that the next
+/// publish only has to upload what changed.
+pub const MANIFEST_FILE: &str = ".sr_dist_manifest";
+
+/// Somewhere that a component's `dist` artifacts can be published to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PublishTarget {
+    /// A directory on this machine, or on a mounted network share
+    Local(PathBuf),
+    /// A directory on a host that can be reached with ssh and scp, using the user's own keys
+    Ssh {
+        /// The host in `[user@]host` form
+        destination: String,
+        port: Option<u16>,
+        path: String,
+    },
+    /// A bucket on an S3-compatible object store
+    S3 {
+        /// The base URL of the object store, i.e. `https://s3.us-east-1.amazonaws.com`
+        endpoint: String,
+        bucket: String,
+        /// Prepended to the path of every artifact, which can be empty
+        prefix: String,
+        region: String,
+        access_key: String,
+        secret_key: String,
+    },
+}
+
+impl PublishTarget {
+    /// Works out a publishing target from the way a user would write it down.
+    ///
+    /// * `ssh://[user@]host[:port]/path` is a directory on a remote host.
+    /// * `s3://bucket/prefix` is a bucket on an object store. The credentials come from the `AWS_ACCESS_KEY_ID` and
+    ///   `AWS_SECRET_ACCESS_KEY` environment variables, the object store from `AWS_ENDPOINT_URL` (Amazon's by
+    ///   default) and the region from `AWS_REGION` (`us-east-1` by default).
+    /// * Anything else, including `file://` URLs, is a local directory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let target = sliderule::publish::PublishTarget::parse("ssh://builds@example.com:2222/srv/dist").unwrap();
+    ///
+    /// assert_eq!(target, sliderule::publish::PublishTarget::Ssh {
+    ///     destination: String::from("builds@example.com"),
+    ///     port: Some(2222),
+    ///     path: String::from("/srv/dist"),
+    /// });
+    /// ```
+    pub fn parse(target: &str) -> Result<PublishTarget, String> {
+        if let Some(rest) = target.strip_prefix("ssh://") {
+            let (host, path) = match rest.find('/') {
+                Some(i) => (&rest[..i], &rest[i..]),
+                None => (rest, "."),
+            };
+
+            // Only a trailing run of digits is a port, anything else is left for ssh to make sense of
+            let (destination, port) = match host.rfind(':') {
+                Some(i) => match host[i + 1..].parse::<u16>() {
+                    Ok(p) => (&host[..i], Some(p)),
+                    Err(_) => {
+                        return Err(format!("The port in {} is not a valid number.", target));
+                    }
+                },
+                None => (host, None),
+            };
+
+            if destination.is_empty() {
+                return Err(format!("There is no host in {}.", target));
+            }
+
+            return Ok(PublishTarget::Ssh {
+                destination: destination.to_string(),
+                port,
+                path: path.to_string(),
+            });
+        }
+
+        if let Some(rest) = target.strip_prefix("s3://") {
+            let (bucket, prefix) = match rest.find('/') {
+                Some(i) => (&rest[..i], rest[i + 1..].trim_matches('/')),
+                None => (rest, ""),
+            };
+
+            if bucket.is_empty() {
+                return Err(format!("There is no bucket in {}.", target));
+            }
+
+            let access_key = match ::std::env::var("AWS_ACCESS_KEY_ID") {
+                Ok(k) => k,
+                Err(_) => {
+                    return Err(String::from(
+                        "AWS_ACCESS_KEY_ID must be set to publish to an S3-compatible object store.",
+                    ));
+                }
+            };
+            let secret_key = match ::std::env::var("AWS_SECRET_ACCESS_KEY") {
+                Ok(k) => k,
+                Err(_) => {
+                    return Err(String::from(
+                        "AWS_SECRET_ACCESS_KEY must be set to publish to an S3-compatible object store.",
+                    ));
+                }
+            };
+
+            return Ok(PublishTarget::S3 {
+                endpoint: ::std::env::var("AWS_ENDPOINT_URL")
+                    .unwrap_or_else(|_| String::from("https://s3.amazonaws.com")),
+                bucket: bucket.to_string(),
+                prefix: prefix.to_string(),
+                region: ::std::env::var("AWS_REGION").unwrap_or_else(|_| String::from("us-east-1")),
+                access_key,
+                secret_key,
+            });
+        }
+
+        let path = target.strip_prefix("file://").unwrap_or(target);
+        if path.is_empty() {
+            return Err(String::from("The publishing target is empty."));
+        }
+
+        Ok(PublishTarget::Local(PathBuf::from(path)))
+    }
+}
+
+/// One file in a component's `dist` directory, as recorded in a publishing target's manifest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistArtifact {
+    /// The path relative to the `dist` directory, always with `/` separators
+    pub path: String,
+    /// The SHA-256 of the file's contents in lowercase hex
+    pub checksum: String,
+    pub size: u64,
+}
+
+/// Publishes the artifacts in a component's `dist` directory, or whichever directory the layout uses instead (see
+/// `config::Layout`), only uploading the ones whose checksums don't match
+/// what the target's manifest says was published last time. Files that were removed from `dist` are reported but
+/// left where they are, since other releases may still link to them.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+///
+/// The output status is 33 if there is no `dist` directory or the layout leaves it out, 34 if the artifacts can't be read, 35 if the target's
+/// manifest can't be read and 36 if an upload fails. When an upload fails, the manifest is still updated with
+/// everything that made it, so the next publish carries on where this one stopped.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// std::fs::write(temp_dir.join("toplevel").join("dist").join("gerbers.zip"), "Not really a zip file").expect("Unable to write artifact.");
+///
+/// let target = sliderule::publish::PublishTarget::Local(temp_dir.join("published"));
+/// let output = sliderule::publish::publish_dist(&temp_dir.join("toplevel"), &target);
+///
+/// assert_eq!(0, output.status);
+/// assert!(temp_dir.join("published").join("gerbers.zip").exists());
+/// ```
+pub fn publish_dist(target_dir: &Path, target: &PublishTarget) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let dist_dir = match super::config::config_or_default(target_dir).layout.dist_dir {
+        Some(d) => target_dir.join(d),
+        None => {
+            output.status = 33;
+            output.stderr.push(format!(
+                "ERROR: The layout of {} doesn't have a dist directory, so there's nothing to publish.",
+                target_dir.display()
+            ));
+            return output;
+        }
+    };
+    if !dist_dir.is_dir() {
+        output.status = 33;
+        output.stderr.push(format!(
+            "ERROR: There is no dist directory to publish in {}.",
+            target_dir.display()
+        ));
+        return output;
+    }
+
+    let local = match dist_artifacts(&dist_dir) {
+        Ok(a) => a,
+        Err((file, e)) => {
+            output.status = 34;
+            output.stderr.push(format!(
+                "ERROR: Unable to read the artifact {}: {}",
+                file.display(),
+                e
+            ));
+            return output;
+        }
+    };
+
+    let remote = match read_manifest(target) {
+        Ok(m) => m,
+        Err(manifest_output) => {
+            output = super::combine_sroutputs(output, manifest_output);
+            output.status = 35;
+            output.stderr.push(String::from(
+                "ERROR: Unable to read the manifest from the publishing target.",
+            ));
+            return output;
+        }
+    };
+
+    // What ends up in the manifest, which starts out as whatever doesn't need uploading
+    let mut published: Vec<DistArtifact> = Vec::new();
+    let mut changed: Vec<&DistArtifact> = Vec::new();
+    for artifact in &local {
+        if remote.contains(artifact) {
+            published.push(artifact.clone());
+        } else {
+            changed.push(artifact);
+        }
+    }
+    let unchanged = published.len();
+
+    for artifact in changed {
+        let upload_output = upload(target, &dist_dir, &artifact.path);
+        let failed = upload_output.status != 0 || upload_output.wrapped_status != 0;
+        output = super::combine_sroutputs(output, upload_output);
+
+        if failed {
+            output.status = 36;
+            output
+                .stderr
+                .push(format!("ERROR: Unable to upload {}.", artifact.path));
+            break;
+        }
+
+        output.stdout.push(format!("Uploaded {}", artifact.path));
+        published.push(artifact.clone());
+    }
+
+    for artifact in &remote {
+        if !local.iter().any(|a| a.path == artifact.path) {
+            output.stdout.push(format!(
+                "{} is no longer in dist, but was left on the publishing target.",
+                artifact.path
+            ));
+        }
+    }
+
+    let manifest_output = write_manifest(target, &published);
+    let manifest_failed = manifest_output.status != 0 || manifest_output.wrapped_status != 0;
+    output = super::combine_sroutputs(output, manifest_output);
+    if manifest_failed && output.status == 0 {
+        output.status = 36;
+        output.stderr.push(String::from(
+            "ERROR: Unable to upload the manifest to the publishing target.",
+        ));
+    }
+
+    if output.status == 0 {
+        output.stdout.push(format!(
+            "Published {} changed artifacts, {} were already up to date.",
+            published.len() - unchanged,
+            unchanged
+        ));
+    }
+
+    output
+}
+
+/*
+ * Checksums everything in the dist directory, in a stable order.
+ */
+fn dist_artifacts(dist_dir: &Path) -> Result<Vec<DistArtifact>, (PathBuf, ::std::io::Error)> {
+    let mut artifacts = Vec::new();
+
+    // The placeholder that keeps an empty dist directory in git isn't an artifact
+    let files = super::walker::TreeWalker::new(dist_dir)
+        .use_ignore_file(false)
+        .exclude(".ph")
+        .walk();
+
+    for file in files {
+        if !file.is_file() {
+            continue;
+        }
+
+        let relative = match file.strip_prefix(dist_dir) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        let path: Vec<String> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+
+        let checksum = super::checksum::sha256_file(&file).map_err(|e| (file.clone(), e))?;
+        let size = fs::metadata(&file).map_err(|e| (file.clone(), e))?.len();
+
+        artifacts.push(DistArtifact {
+            path: path.join("/"),
+            checksum,
+            size,
+        });
+    }
+
+    Ok(artifacts)
+}
+
+/*
+ * Gets the manifest from the last publish, which is empty if nothing has been published to the target yet.
+ */
+fn read_manifest(target: &PublishTarget) -> Result<Vec<DistArtifact>, super::SROutput> {
+    let contents = match *target {
+        PublishTarget::Local(ref dir) => match fs::read_to_string(dir.join(MANIFEST_FILE)) {
+            Ok(c) => c,
+            Err(ref e) if e.kind() == ::std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(super::SROutput {
+                    status: 0,
+                    wrapped_status: 0,
+                    stdout: Vec::new(),
+                    stderr: vec![format!("ERROR: {}", e)],
+                });
+            }
+        },
+        PublishTarget::Ssh {
+            ref destination,
+            port,
+            ref path,
+        } => {
+            let command = format!(
+                "cat {} 2>/dev/null || true",
+                shell_quote(&format!("{}/{}", path, MANIFEST_FILE))
+            );
+            let ssh_output = super::ssh_sr::ssh_run(destination, port, &command);
+            if ssh_output.status != 0 || ssh_output.wrapped_status != 0 {
+                return Err(ssh_output);
+            }
+            ssh_output.stdout.join("")
+        }
+        PublishTarget::S3 {
+            ref region,
+            ref access_key,
+            ref secret_key,
+            ..
+        } => {
+            let url = s3_url(target, MANIFEST_FILE);
+            let curl_output = super::curl_sr::curl_s3(&url, None, region, access_key, secret_key);
+
+            // curl exits with 22 for HTTP errors, and a missing manifest just means this is the first publish
+            if curl_output.wrapped_status == 22 && curl_output.stderr.join("").contains("404") {
+                String::new()
+            } else if curl_output.status != 0 || curl_output.wrapped_status != 0 {
+                return Err(curl_output);
+            } else {
+                curl_output.stdout.join("")
+            }
+        }
+    };
+
+    Ok(contents.lines().filter_map(parse_manifest_line).collect())
+}
+
+/*
+ * Turns one line of a manifest back into an artifact, skipping anything that doesn't look right.
+ */
+fn parse_manifest_line(line: &str) -> Option<DistArtifact> {
+    let parts: Vec<&str> = line.splitn(3, '\t').collect();
+    if parts.len() != 3 {
+        return None;
+    }
+
+    Some(DistArtifact {
+        checksum: parts[0].to_string(),
+        size: parts[1].parse::<u64>().ok()?,
+        path: parts[2].to_string(),
+    })
+}
+
+/*
+ * Replaces the target's manifest with one listing the given artifacts.
+ */
+fn write_manifest(target: &PublishTarget, artifacts: &[DistArtifact]) -> super::SROutput {
+    let mut contents = String::new();
+    for artifact in artifacts {
+        contents.push_str(&format!(
+            "{}\t{}\t{}\n",
+            artifact.checksum, artifact.size, artifact.path
+        ));
+    }
+
+    let manifest_dir = super::scratch_dir("publish");
+    let mut output = write_file(&manifest_dir.join(MANIFEST_FILE), contents.as_bytes());
+    if output.status == 0 {
+        output = upload(target, &manifest_dir, MANIFEST_FILE);
+    }
+
+    let _ = fs::remove_dir_all(&manifest_dir);
+
+    output
+}
+
+/*
+ * Writes a local file, creating its directory if needed.
+ */
+fn write_file(file: &Path, contents: &[u8]) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let result = match file.parent() {
+        Some(parent) => fs::create_dir_all(parent).and_then(|_| fs::write(file, contents)),
+        None => fs::write(file, contents),
+    };
+
+    if let Err(e) = result {
+        output.status = 36;
+        output
+            .stderr
+            .push(format!("ERROR: Unable to write {}: {}", file.display(), e));
+    }
+
+    output
+}
+
+/*
+ * Copies one file, given by its path relative to a local directory, to the same relative path on the target.
+ */
+fn upload(target: &PublishTarget, local_dir: &Path, relative_path: &str) -> super::SROutput {
+    let local_file = local_dir.join(relative_path);
+
+    match *target {
+        PublishTarget::Local(ref dir) => {
+            let target_file = dir.join(relative_path);
+            let partial_file = dir.join(format!("{}.sr_partial", relative_path));
+
+            // Copy next to the destination first so that a half-copied file is never mistaken for the real one
+            let mut output = write_file(&partial_file, &[]);
+            if output.status == 0 {
+                if let Err(e) = fs::copy(&local_file, &partial_file)
+                    .and_then(|_| fs::rename(&partial_file, &target_file))
+                {
+                    let _ = fs::remove_file(&partial_file);
+                    output.status = 36;
+                    output.stderr.push(format!(
+                        "ERROR: Unable to copy {} to {}: {}",
+                        local_file.display(),
+                        target_file.display(),
+                        e
+                    ));
+                }
+            }
+
+            output
+        }
+        PublishTarget::Ssh {
+            ref destination,
+            port,
+            ref path,
+        } => {
+            let remote_file = format!("{}/{}", path, relative_path);
+            let remote_dir = match remote_file.rfind('/') {
+                Some(i) => &remote_file[..i],
+                None => ".",
+            };
+
+            let output = super::ssh_sr::ssh_run(
+                destination,
+                port,
+                &format!("mkdir -p {}", shell_quote(remote_dir)),
+            );
+            if output.status != 0 || output.wrapped_status != 0 {
+                return output;
+            }
+
+            let scp_output =
+                super::ssh_sr::scp_upload(&local_file, destination, port, &remote_file);
+
+            super::combine_sroutputs(output, scp_output)
+        }
+        PublishTarget::S3 {
+            ref region,
+            ref access_key,
+            ref secret_key,
+            ..
+        } => {
+            let url = s3_url(target, relative_path);
+
+            super::curl_sr::curl_s3(&url, Some(&local_file), region, access_key, secret_key)
+        }
+    }
+}
+
+/*
+ * Builds the URL of an object in the target's bucket, using path-style addressing since every S3-compatible
+ * object store supports it.
+ */
+fn s3_url(target: &PublishTarget, relative_path: &str) -> String {
+    match *target {
+        PublishTarget::S3 {
+            ref endpoint,
+            ref bucket,
+            ref prefix,
+            ..
+        } => {
+            let key = if prefix.is_empty() {
+                relative_path.to_string()
+            } else {
+                format!("{}/{}", prefix, relative_path)
+            };
+
+            format!(
+                "{}/{}/{}",
+                endpoint.trim_end_matches('/'),
+                bucket,
+                encode_key(&key)
+            )
+        }
+        _ => String::new(),
+    }
+}
+
+/*
+ * Percent-encodes an object key, leaving the slashes between its parts alone.
+ */
+fn encode_key(key: &str) -> String {
+    let mut encoded = String::new();
+
+    for byte in key.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+/*
+ * Wraps text in single quotes so that a remote shell treats it as one word.
+ */
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
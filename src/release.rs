@@ -0,0 +1,512 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The file at the root of a project that `release` adds the notes for each release to, newest first.
+pub const CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+/// Which part of a project's version a release moves on, following semantic versioning.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReleaseLevel {
+    /// Changes that break designs built on the project, i.e. a connector that moved
+    Major,
+    /// Changes that add something without breaking what was there
+    Minor,
+    /// Fixes that don't change how the project is used
+    Patch,
+}
+
+impl ReleaseLevel {
+    /// The name of the level, the same as what `from_name` reads.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ReleaseLevel::Major => "major",
+            ReleaseLevel::Minor => "minor",
+            ReleaseLevel::Patch => "patch",
+        }
+    }
+
+    /// Reads a level from its name, ignoring case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(
+    ///     sliderule::release::ReleaseLevel::from_name("Minor"),
+    ///     Some(sliderule::release::ReleaseLevel::Minor)
+    /// );
+    /// ```
+    pub fn from_name(name: &str) -> Option<ReleaseLevel> {
+        match name.trim().to_lowercase().as_str() {
+            "major" => Some(ReleaseLevel::Major),
+            "minor" => Some(ReleaseLevel::Minor),
+            "patch" => Some(ReleaseLevel::Patch),
+            _ => None,
+        }
+    }
+
+    /// Works out the version that comes after `version` at this level. A pre-release, i.e. `2.0.0-beta.1`, is
+    /// finished by the release it leads up to, so its patch release is `2.0.0` and not `2.0.1`. Build metadata is
+    /// dropped. Gives `None` if `version` isn't a semantic version.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sliderule::release::ReleaseLevel;
+    ///
+    /// assert_eq!(ReleaseLevel::Minor.bump("1.4.2"), Some(String::from("1.5.0")));
+    /// assert_eq!(ReleaseLevel::Patch.bump("2.0.0-beta.1"), Some(String::from("2.0.0")));
+    /// assert_eq!(ReleaseLevel::Major.bump("one"), None);
+    /// ```
+    pub fn bump(&self, version: &str) -> Option<String> {
+        let version = version.trim();
+        let core = version.split('+').next().unwrap_or_default();
+        let (core, pre_release) = match core.find('-') {
+            Some(i) => (&core[..i], true),
+            None => (core, false),
+        };
+
+        let parts: Vec<&str> = core.split('.').collect();
+        if parts.len() != 3 {
+            return None;
+        }
+        let mut numbers = [0u64; 3];
+        for (number, part) in numbers.iter_mut().zip(parts.iter()) {
+            if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            *number = part.parse().ok()?;
+        }
+        let [major, minor, patch] = numbers;
+
+        Some(match *self {
+            ReleaseLevel::Major if pre_release && minor == 0 && patch == 0 => {
+                format!("{}.0.0", major)
+            }
+            ReleaseLevel::Major => format!("{}.0.0", major + 1),
+            ReleaseLevel::Minor if pre_release && patch == 0 => format!("{}.{}.0", major, minor),
+            ReleaseLevel::Minor => format!("{}.{}.0", major, minor + 1),
+            ReleaseLevel::Patch if pre_release => format!("{}.{}.{}", major, minor, patch),
+            ReleaseLevel::Patch => format!("{}.{}.{}", major, minor, patch + 1),
+        })
+    }
+}
+
+/// What `release` does, or would do, for a release.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReleasePlan {
+    /// The version in `package.json` before the release
+    pub previous_version: String,
+    pub version: String,
+    /// The tag that marks the release, which is the version with a `v` in front of it
+    pub tag: String,
+    /// The section that is added to the top of `CHANGELOG_FILE`
+    pub changelog_entry: String,
+    /// The artifacts that are written to the `dist` directory
+    pub artifacts: Vec<PathBuf>,
+    /// Whether the release is pushed, which it is whenever the project has an `origin` remote
+    pub push: bool,
+}
+
+/// Releases a project in one go. The version in `package.json` (and `package-lock.json`) is moved on by `level`,
+/// `notes` are added to the top of `CHANGELOG_FILE`, and the artifacts in the `dist` directory are built again:
+/// an archive of the project from `archive::export_release_archive` and a software bill of materials from
+/// `hierarchy::export_dependency_manifest`, both named after the project and the new version. Then the changes are
+/// committed, the commit is tagged with the version, and the branch and tag are pushed to `origin` together.
+///
+/// If `notes` is empty, the changelog lists the commits made since the previous release instead.
+///
+/// The release is all or nothing. If any step fails, everything that was done before it is undone: the commit and
+/// tag are removed, and the files that were written are put back the way they were. A push that fails leaves the
+/// remote repository untouched, since the branch and tag are pushed atomically.
+///
+/// `project_dir` must be the top level component of a project that is a git repository with at least one commit
+/// and no uncommitted changes, so that nothing unrelated ends up in the release.
+///
+/// The output status is 82 if the project isn't a repository or has no commits, 83 if it has uncommitted changes,
+/// 84 if the version in `package.json` isn't a semantic version, 85 if the release's tag already exists, 86 if the
+/// version, changelog or artifacts can't be written, 87 if the changes can't be committed or tagged, and 88 if
+/// they can't be pushed.
+///
+/// # Examples
+///
+/// ```no_run
+/// let output = sliderule::release::release(
+///     &std::env::temp_dir().join("toplevel"),
+///     sliderule::release::ReleaseLevel::Minor,
+///     "- Added mounting holes to the enclosure.",
+/// );
+///
+/// assert_eq!(0, output.status);
+/// ```
+pub fn release(project_dir: &Path, level: ReleaseLevel, notes: &str) -> super::SROutput {
+    let (mut output, plan) = plan_release(project_dir, level, notes);
+    let plan = match plan {
+        Some(p) => p,
+        None => return output,
+    };
+    output.stdout.clear();
+
+    let previous_head = super::git_sr::git_rev_parse(project_dir, "HEAD")
+        .stdout
+        .first()
+        .map(|h| h.trim().to_string())
+        .unwrap_or_default();
+
+    // Everything the release writes, as it was beforehand, so that it can be put back
+    let mut written = vec![
+        project_dir.join("package.json"),
+        project_dir.join("package-lock.json"),
+        project_dir.join(CHANGELOG_FILE),
+    ];
+    written.extend(plan.artifacts.iter().cloned());
+    let backups: Vec<(PathBuf, Option<Vec<u8>>)> = written
+        .into_iter()
+        .map(|path| {
+            let contents = fs::read(&path).ok();
+            (path, contents)
+        })
+        .collect();
+
+    if let Err(e) = write_release_files(project_dir, &plan) {
+        restore(&backups);
+        output.status = 86;
+        output.stderr.push(format!(
+            "ERROR: Unable to prepare release {}, nothing was changed: {}",
+            plan.version, e
+        ));
+        return output;
+    }
+
+    let message = format!("Release {}", plan.version);
+    let commit_output = super::git_sr::git_add_and_commit_as(project_dir, message.clone(), None);
+    // git doesn't always exit with an error when it doesn't commit, i.e. when a hook stops it, so check for the commit
+    let head = super::git_sr::git_rev_parse(project_dir, "HEAD")
+        .stdout
+        .first()
+        .map(|h| h.trim().to_string())
+        .unwrap_or_default();
+    let tag_output = if commit_output.status == 0 && !head.is_empty() && head != previous_head {
+        Some(super::git_sr::git_tag(project_dir, &plan.tag, &message))
+    } else {
+        None
+    };
+    let committed = match tag_output {
+        Some(ref t) => t.status == 0 && t.wrapped_status == 0,
+        None => false,
+    };
+    output = super::combine_sroutputs(output, commit_output);
+    if let Some(t) = tag_output {
+        output = super::combine_sroutputs(output, t);
+    }
+    if !committed {
+        super::git_sr::git_delete_tag(project_dir, &plan.tag);
+        super::git_sr::git_reset_hard(project_dir, &previous_head);
+        restore(&backups);
+        output.status = 87;
+        output.stderr.push(format!(
+            "ERROR: Unable to commit and tag release {}, nothing was changed.",
+            plan.version
+        ));
+        return output;
+    }
+
+    if plan.push {
+        let push_output = super::git_sr::git_push_atomic(project_dir, &plan.tag);
+        let pushed = push_output.status == 0 && push_output.wrapped_status == 0;
+        output = super::combine_sroutputs(output, push_output);
+        if !pushed {
+            super::git_sr::git_delete_tag(project_dir, &plan.tag);
+            super::git_sr::git_reset_hard(project_dir, &previous_head);
+            restore(&backups);
+            output.status = 88;
+            output.stderr.push(format!(
+                "ERROR: Unable to push release {}, so it was undone.",
+                plan.version
+            ));
+            return output;
+        }
+    }
+
+    output.stdout.push(format!(
+        "Released {} as {}{}.",
+        plan.version,
+        plan.tag,
+        if plan.push { " and pushed it" } else { "" }
+    ));
+
+    output
+}
+
+/// Works out everything that `release` would do, without changing anything, so that a release can be tried out
+/// first. Each step is described in `stdout`, and the plan is only given if the release would be able to go ahead.
+/// The output status is the same as `release` would give for the checks it makes before it starts.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// // The project isn't a repository yet, so it can't be released
+/// let (output, plan) = sliderule::release::plan_release(&temp_dir.join("toplevel"), sliderule::release::ReleaseLevel::Patch, "");
+///
+/// assert_eq!(82, output.status);
+/// assert!(plan.is_none());
+/// ```
+pub fn plan_release(
+    project_dir: &Path,
+    level: ReleaseLevel,
+    notes: &str,
+) -> (super::SROutput, Option<ReleasePlan>) {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let head_output = super::git_sr::git_rev_parse(project_dir, "HEAD");
+    if !project_dir.join(".git").exists()
+        || head_output.status != 0
+        || head_output.wrapped_status != 0
+    {
+        output.status = 82;
+        output.stderr.push(String::from(
+            "ERROR: Component is not a repository with any commits, so it can't be released.",
+        ));
+        return (output, None);
+    }
+
+    let status_output = super::git_sr::git_status_porcelain(project_dir);
+    let uncommitted = status_output
+        .stdout
+        .join("")
+        .lines()
+        .any(|line| !line.starts_with("##") && !line.trim().is_empty());
+    if status_output.status != 0 || status_output.wrapped_status != 0 || uncommitted {
+        output.status = 83;
+        output.stderr.push(String::from(
+            "ERROR: Component has uncommitted changes, commit or discard them before releasing.",
+        ));
+        return (output, None);
+    }
+
+    let previous_version = super::get_json_value(&project_dir.join("package.json"), "version");
+    let version = match level.bump(&previous_version) {
+        Some(v) => v,
+        None => {
+            output.status = 84;
+            output.stderr.push(format!(
+                "ERROR: The version {} in package.json is not a semantic version, so it can't be moved on.",
+                previous_version
+            ));
+            return (output, None);
+        }
+    };
+
+    let tag = format!("v{}", version);
+    let tag_output = super::git_sr::git_rev_parse(project_dir, &format!("refs/tags/{}", tag));
+    if tag_output.status == 0 && tag_output.wrapped_status == 0 {
+        output.status = 85;
+        output.stderr.push(format!(
+            "ERROR: There is already a release called {} in the project.",
+            tag
+        ));
+        return (output, None);
+    }
+
+    let changelog_entry = changelog_entry(project_dir, &previous_version, &version, notes);
+
+    let name = super::get_json_value(&project_dir.join("package.json"), "name");
+    let mut artifacts = Vec::new();
+    if let Some(dist_dir) = super::config::config_or_default(project_dir)
+        .layout
+        .dist_dir
+    {
+        let dist_dir = project_dir.join(dist_dir);
+        artifacts.push(dist_dir.join(format!("{}-{}.tar", name, version)));
+        artifacts.push(dist_dir.join(format!("{}-{}.sbom.json", name, version)));
+    }
+
+    let remotes = super::git_sr::git_remote_urls(project_dir).stdout.join("");
+    let push = remotes
+        .lines()
+        .any(|line| line.starts_with("remote.origin.url "));
+
+    output.stdout.push(format!(
+        "Would move the version on from {} to {}.",
+        previous_version, version
+    ));
+    output.stdout.push(format!(
+        "Would add release {} to {}.",
+        version, CHANGELOG_FILE
+    ));
+    for artifact in &artifacts {
+        output
+            .stdout
+            .push(format!("Would build {}.", artifact.display()));
+    }
+    output
+        .stdout
+        .push(format!("Would commit the release and tag it {}.", tag));
+    if push {
+        output
+            .stdout
+            .push(String::from("Would push the release to origin."));
+    } else {
+        output.stdout.push(String::from(
+            "There is no origin remote, so the release would not be pushed.",
+        ));
+    }
+
+    (
+        output,
+        Some(ReleasePlan {
+            previous_version,
+            version,
+            tag,
+            changelog_entry,
+            artifacts,
+            push,
+        }),
+    )
+}
+
+/*
+ * Writes the new version, the changelog and the artifacts for a release.
+ */
+fn write_release_files(project_dir: &Path, plan: &ReleasePlan) -> Result<(), String> {
+    for file in ["package.json", "package-lock.json"].iter() {
+        super::update_json_value(&project_dir.join(file), "version", &plan.version);
+    }
+
+    let changelog_file = project_dir.join(CHANGELOG_FILE);
+    let changelog = fs::read_to_string(&changelog_file).unwrap_or_default();
+    fs::write(
+        &changelog_file,
+        add_changelog_entry(&changelog, &plan.changelog_entry),
+    )
+    .map_err(|e| format!("{}: {}", CHANGELOG_FILE, e))?;
+
+    for artifact in &plan.artifacts {
+        if let Some(dir) = artifact.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        }
+    }
+    for artifact in &plan.artifacts {
+        let file_name = artifact.to_string_lossy();
+        if file_name.ends_with(".tar") {
+            let archive_output = super::archive::export_release_archive(project_dir, artifact);
+            if archive_output.status != 0 {
+                return Err(archive_output.stderr.join(" "));
+            }
+        } else if file_name.ends_with(".sbom.json") {
+            let sbom = super::hierarchy::export_dependency_manifest(
+                project_dir,
+                super::hierarchy::ManifestFormat::Json,
+            );
+            fs::write(artifact, sbom).map_err(|e| format!("{}: {}", artifact.display(), e))?;
+        }
+    }
+
+    Ok(())
+}
+
+/*
+ * Puts files back the way they were before a release started, removing the ones that didn't exist.
+ */
+fn restore(backups: &[(PathBuf, Option<Vec<u8>>)]) {
+    for (path, contents) in backups {
+        match contents {
+            Some(c) => {
+                let _ = fs::write(path, c);
+            }
+            None => {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/*
+ * The section of the changelog for a release, which lists the commits since the previous release if there
+ * aren't any notes.
+ */
+fn changelog_entry(
+    project_dir: &Path,
+    previous_version: &str,
+    version: &str,
+    notes: &str,
+) -> String {
+    let mut entry = format!("## {} - {}\n\n", version, today());
+
+    let notes = notes.trim();
+    if !notes.is_empty() {
+        entry.push_str(notes);
+        entry.push('\n');
+        return entry;
+    }
+
+    let previous_tag = format!("v{}", previous_version);
+    let log_output = super::git_sr::git_log_between(project_dir, &previous_tag, "HEAD");
+    if log_output.status == 0 && log_output.wrapped_status == 0 {
+        for line in &log_output.stdout {
+            let subject = line
+                .split_once('\t')
+                .map(|x| x.1)
+                .unwrap_or_default()
+                .trim();
+            if !subject.is_empty() {
+                entry.push_str(&format!("- {}\n", subject));
+            }
+        }
+    }
+
+    entry
+}
+
+/*
+ * Adds a release's section above every earlier one, below the changelog's own heading.
+ */
+fn add_changelog_entry(changelog: &str, entry: &str) -> String {
+    if changelog.trim().is_empty() {
+        return format!("# Changelog\n\n{}", entry);
+    }
+
+    match changelog.find("\n## ") {
+        Some(i) => format!("{}\n{}\n{}", &changelog[..i], entry, &changelog[i + 1..]),
+        None if changelog.starts_with("## ") => format!("{}\n{}", entry, changelog),
+        None => {
+            let mut updated = changelog.trim_end().to_string();
+            updated.push_str("\n\n");
+            updated.push_str(entry);
+            updated
+        }
+    }
+}
+
+/*
+ * Today's date in UTC, as YYYY-MM-DD.
+ */
+fn today() -> String {
+    let days = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or(0) as i64;
+
+    // Converts days since 1970-01-01 to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -0,0 +1,143 @@
+use std::env;
+use std::io::IsTerminal;
+
+use super::messages::{Message, Severity};
+
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Returns true if text written to the terminal should be colored. It isn't when the `NO_COLOR` environment
+/// variable is set to anything, when `TERM` is `dumb`, in CI mode (see `ci::ci_mode`) or when stdout isn't a
+/// terminal, i.e. when it's piped into a file.
+pub fn use_color() -> bool {
+    if env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+
+    if env::var("TERM").map(|t| t == "dumb").unwrap_or(false) {
+        return false;
+    }
+
+    !super::ci::ci_mode() && std::io::stdout().is_terminal()
+}
+
+/// Formats the output of an operation for a terminal, see `render_messages`. Secrets are masked the same way that
+/// `redact::redact` masks them.
+///
+/// # Examples
+///
+/// ```
+/// let output = sliderule::SROutput {
+///     status: 2,
+///     wrapped_status: 0,
+///     stdout: vec![String::from("Component is valid.")],
+///     stderr: vec![
+///         String::from("WARNING: The license FOO is not on the SPDX license list."),
+///         String::from("ERROR: Unable to read bom_data.yaml: not found"),
+///     ],
+/// };
+///
+/// assert_eq!(
+///     sliderule::render::render(&output, false),
+///     "Component is valid.\n\nWarnings (1):\n  The license FOO is not on the SPDX license list.\n\nErrors (1):\n  Unable to read bom_data.yaml: not found\n"
+/// );
+/// ```
+pub fn render(output: &super::SROutput, color: bool) -> String {
+    render_messages(&super::messages::identify_output(output), color)
+}
+
+/// Formats messages for a terminal. The ordinary lines come first in the order they're in, followed by a
+/// `Warnings` section and then an `Errors` section, each with the number of messages in it. The `WARNING: ` and
+/// `ERROR: ` prefixes are taken off, since the section says it already, and messages that span lines are indented
+/// so that each one stays together. Sections that would be empty are left out, and nothing at all is written for
+/// no messages.
+///
+/// With `color`, warnings are yellow, errors are red and the section headings are bold. Pass `use_color()` to
+/// color the text only when the terminal is able to show it.
+///
+/// # Examples
+///
+/// ```
+/// let messages = vec![sliderule::messages::identify("ERROR: A component with the name 'level1' already exists.")];
+///
+/// assert_eq!(
+///     sliderule::render::render_messages(&messages, true),
+///     "\x1b[1mErrors (1):\x1b[0m\n  \x1b[31mA component with the name 'level1' already exists.\x1b[0m\n"
+/// );
+/// ```
+pub fn render_messages(messages: &[Message], color: bool) -> String {
+    let of = |severity: Severity| -> Vec<&Message> {
+        messages.iter().filter(|m| m.severity == severity).collect()
+    };
+    let info = of(Severity::Info);
+    let warnings = of(Severity::Warning);
+    let errors = of(Severity::Error);
+
+    let mut sections = Vec::new();
+
+    if !info.is_empty() {
+        let lines: Vec<String> = info.iter().map(|m| indent(&text(m), "")).collect();
+        sections.push(lines.join("\n"));
+    }
+
+    for (heading, found, paint) in [("Warnings", warnings, YELLOW), ("Errors", errors, RED)] {
+        if found.is_empty() {
+            continue;
+        }
+
+        let mut lines = vec![style(
+            &format!("{} ({}):", heading, found.len()),
+            BOLD,
+            color,
+        )];
+        lines.extend(
+            found
+                .iter()
+                .map(|m| format!("  {}", style(&indent(&text(m), "  "), paint, color))),
+        );
+        sections.push(lines.join("\n"));
+    }
+
+    if sections.is_empty() {
+        return String::new();
+    }
+
+    format!("{}\n", sections.join("\n\n"))
+}
+
+/*
+ * The text of a message with its severity prefix taken off and any secrets masked.
+ */
+fn text(message: &Message) -> String {
+    let mut text = message.text.as_str();
+    for prefix in ["ERROR: ", "WARNING: "] {
+        if let Some(rest) = text.strip_prefix(prefix) {
+            text = rest;
+            break;
+        }
+    }
+
+    super::redact::redact(text.trim_end())
+}
+
+/*
+ * Indents every line of a message after the first, so that the lines of a multi-line message line up.
+ */
+fn indent(text: &str, prefix: &str) -> String {
+    text.lines()
+        .collect::<Vec<&str>>()
+        .join(&format!("\n{}", prefix))
+}
+
+/*
+ * Wraps text in an ANSI style when color is turned on.
+ */
+fn style(text: &str, code: &str, color: bool) -> String {
+    if color {
+        format!("{}{}{}", code, text, RESET)
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,635 @@
+use std::io::{self, BufRead, Write};
+use std::net::{TcpListener, ToSocketAddrs};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use super::json::JsonValue;
+use super::progress::Progress;
+
+/// The JSON-RPC error code for a message that isn't valid JSON.
+pub const PARSE_ERROR: i32 = -32700;
+/// The JSON-RPC error code for a message that isn't a valid request.
+pub const INVALID_REQUEST: i32 = -32600;
+/// The JSON-RPC error code for a method that doesn't exist.
+pub const METHOD_NOT_FOUND: i32 = -32601;
+/// The JSON-RPC error code for missing or mistyped parameters.
+pub const INVALID_PARAMS: i32 = -32602;
+/// The JSON-RPC error code for an operation that failed without giving an `SROutput`.
+pub const INTERNAL_ERROR: i32 = -32603;
+/// The JSON-RPC error code for a connection whose first request isn't `authenticate` with the right token.
+pub const UNAUTHORIZED: i32 = -32001;
+
+/// The methods that a server answers. Each one takes its parameters by name, with the same names as the
+/// arguments of the function it runs, and `shutdown` stops the server once it has been answered.
+///
+/// Everything that gives an `SROutput` in the library has it as the result, as an object with the `status`,
+/// `wrapped_status`, `stdout` and `stderr` members. `create_component` also has a `manifest` member, with
+/// the `created` and `skipped` lists of `CreationManifest` as objects with `path` and `is_dir` members.
+pub const METHODS: &[&str] = &[
+    "add_remote_component",
+    "change_licenses",
+    "create_component",
+    "download_component",
+    "get_level",
+    "get_licenses",
+    "get_version",
+    "get_version_info",
+    "health_check",
+    "list_all_licenses",
+    "list_changes",
+    "refactor",
+    "remove",
+    "shutdown",
+    "update_dependencies",
+    "update_local_component",
+    "upload_component",
+    "validate_component",
+    "verify_locked",
+];
+
+/*
+ * Only one request is run at a time, across every connection, since the progress callback is shared by the
+ * whole process.
+ */
+static RUNNING: Mutex<()> = Mutex::new(());
+
+/// Answers JSON-RPC 2.0 requests, one per line, from `input` until it is closed or `shutdown` is called. Each
+/// response is written to `output` as a single line, and while a request is running a `progress` notification is
+/// written for everything that is reported with `progress::report`, with the `id` of the request and a `message`.
+/// Progress for downloads also has the `phase`, `objects`, `total_objects` and `bytes` of `progress::Transfer`.
+///
+/// This is how editor extensions and GUIs can drive Sliderule from a single long-running process, i.e. over
+/// the standard input and output of a program that calls `serve(stdin.lock(), stdout.lock())`. Any callback set
+/// with `progress::set_progress_callback` is replaced while a request runs.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// let requests = format!(
+///     "{{\"jsonrpc\": \"2.0\", \"id\": 1, \"method\": \"create_component\", \"params\": {{\"target_dir\": {:?}, \"name\": \"toplevel\", \"description\": \"Top level component\", \"source_license\": \"Unlicense\", \"doc_license\": \"CC0-1.0\"}}}}\n",
+///     temp_dir.display().to_string()
+/// );
+///
+/// let mut responses = Vec::new();
+/// sliderule::rpc::serve(requests.as_bytes(), &mut responses).expect("Unable to serve requests.");
+///
+/// let responses = String::from_utf8(responses).unwrap();
+/// assert!(responses.lines().last().unwrap().contains("\"status\":0"));
+/// ```
+pub fn serve<R: BufRead, W: Write>(input: R, output: W) -> io::Result<()> {
+    serve_lines(input, output, None)
+}
+
+/// Listens for connections at `address`, i.e. `127.0.0.1:7878`, and serves each one in its own thread. Requests
+/// from different connections are run one at a time. This only returns if the address can't be listened on.
+///
+/// The first request on a connection has to be `authenticate` with `token` as its `token` parameter, i.e.
+/// `{"jsonrpc": "2.0", "id": 1, "method": "authenticate", "params": {"token": "<token>"}}`, which is answered
+/// with a `null` result. A wrong token gets an `UNAUTHORIZED` error. The connection is closed after a wrong
+/// token, a line that isn't a valid request, or anything that looks like an HTTP request, so that web pages
+/// can't drive the server. Nothing is encrypted, so the address should only be reachable from the machine
+/// the server runs on.
+///
+/// # Examples
+///
+/// ```no_run
+/// sliderule::rpc::serve_tcp("127.0.0.1:7878", "change-me").expect("Unable to start the server.");
+/// ```
+pub fn serve_tcp<A: ToSocketAddrs>(address: A, token: &str) -> io::Result<()> {
+    serve_tcp_listener(TcpListener::bind(address)?, token)
+}
+
+/// Serves connections on a listener that has already been bound, the same way as `serve_tcp`.
+pub fn serve_tcp_listener(listener: TcpListener, token: &str) -> io::Result<()> {
+    if token.trim().is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "A token is required to serve requests over TCP.",
+        ));
+    }
+
+    let token = Arc::new(token.to_string());
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(s) => s,
+            Err(_) => continue,
+        };
+
+        let token = Arc::clone(&token);
+        thread::spawn(move || {
+            let input = match stream.try_clone() {
+                Ok(s) => io::BufReader::new(s),
+                Err(_) => return,
+            };
+            let _ = serve_lines(input, stream, Some(&token));
+        });
+    }
+
+    Ok(())
+}
+
+/*
+ * Answers requests one line at a time. When there is a token, the connection has to authenticate with it first,
+ * and anything that isn't a valid request ends the connection.
+ */
+fn serve_lines<R: BufRead, W: Write>(
+    input: R,
+    mut output: W,
+    token: Option<&str>,
+) -> io::Result<()> {
+    let mut authenticated = token.is_none();
+
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(token) = token {
+            if is_http_request_line(&line) {
+                break;
+            }
+
+            let request = match super::json::parse(&line) {
+                Ok(r) if r.get_str("jsonrpc") == "2.0" && !r.get_str("method").is_empty() => r,
+                _ => {
+                    // Says what was wrong with the line before hanging up
+                    if let Some(r) = handle_message(&line, &mut |_| {}, &mut false) {
+                        writeln!(output, "{}", r)?;
+                        output.flush()?;
+                    }
+                    break;
+                }
+            };
+
+            if !authenticated {
+                let id = request.get("id").cloned().unwrap_or(JsonValue::Null);
+                let given = request
+                    .get("params")
+                    .and_then(|p| p.get("token"))
+                    .and_then(|t| t.as_str());
+
+                match given {
+                    Some(t)
+                        if request.get_str("method") == "authenticate"
+                            && super::auth::tokens_match(t, token) =>
+                    {
+                        writeln!(output, "{}", result_response(&id, JsonValue::Null))?;
+                        output.flush()?;
+                        authenticated = true;
+                        continue;
+                    }
+                    _ => {
+                        writeln!(
+                            output,
+                            "{}",
+                            error_response(
+                                &id,
+                                UNAUTHORIZED,
+                                "The first request must be authenticate with a valid token."
+                            )
+                        )?;
+                        output.flush()?;
+                        break;
+                    }
+                }
+            }
+        }
+
+        let mut shutdown = false;
+        let response = {
+            let mut notify = |n: String| {
+                let _ = writeln!(output, "{}", n).and_then(|_| output.flush());
+            };
+            handle_message(&line, &mut notify, &mut shutdown)
+        };
+
+        if let Some(r) = response {
+            writeln!(output, "{}", r)?;
+            output.flush()?;
+        }
+
+        if shutdown {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/*
+ * Whether a line looks like the start of an HTTP request, i.e. `POST / HTTP/1.1`, which is what a web page
+ * that is pointed at the server sends.
+ */
+fn is_http_request_line(line: &str) -> bool {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+
+    parts.len() == 3 && parts[2].starts_with("HTTP/")
+}
+
+/// Answers a single JSON-RPC 2.0 message, returning the response, or `None` if the message was a notification
+/// that doesn't get one. Progress notifications are given to `notify` while the request runs, and `shutdown` is
+/// set if the server should stop.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn handle_message(
+    message: &str,
+    notify: &mut dyn FnMut(String),
+    shutdown: &mut bool,
+) -> Option<String> {
+    let request = match super::json::parse(message) {
+        Ok(r) => r,
+        Err(e) => return Some(error_response(&JsonValue::Null, PARSE_ERROR, &e)),
+    };
+
+    let id = request.get("id").cloned();
+    let response_id = id.clone().unwrap_or(JsonValue::Null);
+
+    let method = match request.get("method").and_then(|m| m.as_str()) {
+        Some(m) if request.get_str("jsonrpc") == "2.0" => m.to_string(),
+        _ => {
+            return Some(error_response(
+                &response_id,
+                INVALID_REQUEST,
+                "Requests must be objects with \"jsonrpc\": \"2.0\" and a method.",
+            ))
+        }
+    };
+
+    let params = match request.get("params") {
+        None => JsonValue::Object(Vec::new()),
+        Some(p @ &JsonValue::Object(_)) => p.clone(),
+        Some(_) => {
+            return Some(error_response(
+                &response_id,
+                INVALID_PARAMS,
+                "Parameters must be given by name.",
+            ))
+        }
+    };
+
+    if method == "shutdown" {
+        *shutdown = true;
+        return id.map(|i| result_response(&i, JsonValue::Null));
+    }
+
+    if !METHODS.contains(&method.as_str()) {
+        return id.map(|i| {
+            error_response(
+                &i,
+                METHOD_NOT_FOUND,
+                &format!("There is no method named {}.", method),
+            )
+        });
+    }
+
+    let result = run_method(&method, params, &response_id, notify);
+
+    id.map(|i| match result {
+        Ok(r) => result_response(&i, r),
+        Err((code, message)) => error_response(&i, code, &message),
+    })
+}
+
+/*
+ * Runs a method on its own thread, passing along progress as it comes in. Panics in the library are turned into
+ * internal errors so that the server keeps running.
+ */
+fn run_method(
+    method: &str,
+    params: JsonValue,
+    id: &JsonValue,
+    notify: &mut dyn FnMut(String),
+) -> Result<JsonValue, (i32, String)> {
+    enum Event {
+        Progress(Progress),
+        Done(Result<JsonValue, (i32, String)>),
+    }
+
+    let _running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+
+    let (sender, receiver) = mpsc::channel();
+    let progress_sender = Mutex::new(sender.clone());
+    super::progress::set_progress_callback(Some(Box::new(move |p| {
+        let _ = progress_sender
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .send(Event::Progress(p.clone()));
+    })));
+
+    let method = method.to_string();
+    let worker = thread::spawn(move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| call(&method, &params)))
+            .unwrap_or_else(|p| {
+                let reason = p
+                    .downcast_ref::<String>()
+                    .cloned()
+                    .or_else(|| p.downcast_ref::<&str>().map(|s| s.to_string()))
+                    .unwrap_or_default();
+                Err((
+                    INTERNAL_ERROR,
+                    super::redact::redact(&format!("The operation failed: {}", reason)),
+                ))
+            });
+        let _ = sender.send(Event::Done(result));
+    });
+
+    let mut result = Err((
+        INTERNAL_ERROR,
+        String::from("The operation did not finish."),
+    ));
+    for event in receiver.iter() {
+        match event {
+            Event::Progress(p) => {
+                let mut params = vec![
+                    (String::from("id"), id.clone()),
+                    (String::from("message"), JsonValue::String(p.message)),
+                ];
+
+                // Downloads also say how far along they are
+                if let Some(t) = p.transfer {
+                    let count = |c: Option<u64>| match c {
+                        Some(c) => JsonValue::Number(c as f64),
+                        None => JsonValue::Null,
+                    };
+                    params.push((String::from("phase"), JsonValue::String(t.phase)));
+                    params.push((String::from("objects"), count(Some(t.objects))));
+                    params.push((String::from("total_objects"), count(t.total_objects)));
+                    params.push((String::from("bytes"), count(t.bytes)));
+                }
+
+                notify(
+                    JsonValue::Object(vec![
+                        (String::from("jsonrpc"), string("2.0")),
+                        (String::from("method"), string("progress")),
+                        (String::from("params"), JsonValue::Object(params)),
+                    ])
+                    .to_json(),
+                )
+            }
+            Event::Done(r) => {
+                result = r;
+                break;
+            }
+        }
+    }
+
+    super::progress::set_progress_callback(None);
+    let _ = worker.join();
+
+    result
+}
+
+/*
+ * Calls the library function behind a method.
+ */
+fn call(method: &str, params: &JsonValue) -> Result<JsonValue, (i32, String)> {
+    let target_dir = || path_param(params, "target_dir");
+    let text = |name: &str| string_param(params, name);
+
+    let output = match method {
+        "add_remote_component" => super::add_remote_component(
+            &target_dir()?,
+            &text("url")?,
+            optional_string_param(params, "cache")?,
+        ),
+        "change_licenses" => super::change_licenses(
+            &target_dir()?,
+            text("source_license")?,
+            text("doc_license")?,
+        ),
+        "create_component" => {
+            let (output, manifest) = super::create_component_with_manifest(
+                &target_dir()?,
+                text("name")?,
+                text("description")?,
+                text("source_license")?,
+                text("doc_license")?,
+            );
+
+            let items = |items: Vec<super::ManifestItem>| {
+                JsonValue::Array(
+                    items
+                        .into_iter()
+                        .map(|item| {
+                            JsonValue::Object(vec![
+                                (
+                                    String::from("path"),
+                                    JsonValue::String(item.path.display().to_string()),
+                                ),
+                                (String::from("is_dir"), JsonValue::Bool(item.is_dir)),
+                            ])
+                        })
+                        .collect(),
+                )
+            };
+
+            let mut members = output_members(output);
+            members.push((
+                String::from("manifest"),
+                JsonValue::Object(vec![
+                    (String::from("created"), items(manifest.created)),
+                    (String::from("skipped"), items(manifest.skipped)),
+                ]),
+            ));
+            return Ok(JsonValue::Object(members));
+        }
+        "download_component" => super::download_component(&target_dir()?, &text("url")?),
+        "get_level" => {
+            let (level, project_dir) = super::get_level(&target_dir()?);
+            return Ok(JsonValue::Object(vec![
+                (String::from("level"), string(level_name(&level))),
+                (
+                    String::from("project_dir"),
+                    match project_dir {
+                        Some(d) => JsonValue::String(d.display().to_string()),
+                        None => JsonValue::Null,
+                    },
+                ),
+            ]));
+        }
+        "get_licenses" => {
+            let (source_license, doc_license) = super::get_licenses(&target_dir()?);
+            return Ok(JsonValue::Object(vec![
+                (
+                    String::from("source_license"),
+                    JsonValue::String(source_license),
+                ),
+                (String::from("doc_license"), JsonValue::String(doc_license)),
+            ]));
+        }
+        "get_version" => return Ok(JsonValue::String(super::get_version())),
+        "get_version_info" => {
+            let info = super::get_version_info();
+            return Ok(JsonValue::Object(vec![
+                (String::from("version"), JsonValue::String(info.version)),
+                (
+                    String::from("git_sha"),
+                    match info.git_sha {
+                        Some(sha) => JsonValue::String(sha),
+                        None => JsonValue::Null,
+                    },
+                ),
+                (
+                    String::from("build_time"),
+                    match info.build_time {
+                        Some(t) => JsonValue::Number(t as f64),
+                        None => JsonValue::Null,
+                    },
+                ),
+            ]));
+        }
+        "health_check" => {
+            let report = super::health::health_check(&path_param(params, "project_dir")?);
+            let components = report
+                .components
+                .into_iter()
+                .map(|c| {
+                    JsonValue::Object(vec![
+                        (String::from("name"), JsonValue::String(c.name)),
+                        (
+                            String::from("path"),
+                            JsonValue::String(c.path.display().to_string()),
+                        ),
+                        (String::from("validation"), strings(c.validation)),
+                        (String::from("licenses"), strings(c.licenses)),
+                        (String::from("dependencies"), strings(c.dependencies)),
+                        (String::from("git_status"), strings(c.git_status)),
+                    ])
+                })
+                .collect();
+            return Ok(JsonValue::Array(components));
+        }
+        "list_all_licenses" => {
+            return Ok(JsonValue::String(super::list_all_licenses(&target_dir()?)))
+        }
+        "list_changes" => super::list_changes(&target_dir()?),
+        "refactor" => super::refactor(
+            &target_dir()?,
+            text("name")?,
+            text("url")?,
+            optional_string_param(params, "username")?,
+            optional_string_param(params, "password")?,
+        ),
+        "remove" => super::remove(&target_dir()?, &text("name")?),
+        "update_dependencies" => super::update_dependencies(&target_dir()?),
+        "update_local_component" => super::update_local_component(&target_dir()?),
+        "upload_component" => super::upload_component(
+            &target_dir()?,
+            text("message")?,
+            text("url")?,
+            optional_string_param(params, "username")?,
+            optional_string_param(params, "password")?,
+        ),
+        "validate_component" => super::health::validate_component(&target_dir()?),
+        "verify_locked" => super::verify_locked(&path_param(params, "project_dir")?),
+        _ => {
+            return Err((
+                METHOD_NOT_FOUND,
+                format!("There is no method named {}.", method),
+            ))
+        }
+    };
+
+    Ok(JsonValue::Object(output_members(output)))
+}
+
+/*
+ * The members of the object that an SROutput is given back as.
+ */
+fn output_members(output: super::SROutput) -> Vec<(String, JsonValue)> {
+    vec![
+        (
+            String::from("status"),
+            JsonValue::Number(f64::from(output.status)),
+        ),
+        (
+            String::from("wrapped_status"),
+            JsonValue::Number(f64::from(output.wrapped_status)),
+        ),
+        (String::from("stdout"), strings(output.stdout)),
+        (String::from("stderr"), strings(output.stderr)),
+    ]
+}
+
+/*
+ * The name a component level has in responses.
+ */
+fn level_name(level: &super::ComponentLevel) -> &'static str {
+    match *level {
+        super::ComponentLevel::NotAComponent => "not_a_component",
+        super::ComponentLevel::TopLevelProject => "top_level_project",
+        super::ComponentLevel::LocalSubComponent => "local_sub_component",
+        super::ComponentLevel::RemoteComponent => "remote_component",
+    }
+}
+
+fn string(text: &str) -> JsonValue {
+    JsonValue::String(text.to_string())
+}
+
+fn strings(lines: Vec<String>) -> JsonValue {
+    JsonValue::Array(lines.into_iter().map(JsonValue::String).collect())
+}
+
+/*
+ * Gets a required string parameter.
+ */
+fn string_param(params: &JsonValue, name: &str) -> Result<String, (i32, String)> {
+    match params.get(name) {
+        Some(JsonValue::String(s)) => Ok(s.to_owned()),
+        Some(_) => Err((
+            INVALID_PARAMS,
+            format!("The {} parameter must be a string.", name),
+        )),
+        None => Err((
+            INVALID_PARAMS,
+            format!("The {} parameter is required.", name),
+        )),
+    }
+}
+
+/*
+ * Gets a string parameter that can be left out or set to null.
+ */
+fn optional_string_param(params: &JsonValue, name: &str) -> Result<Option<String>, (i32, String)> {
+    match params.get(name) {
+        None | Some(&JsonValue::Null) => Ok(None),
+        Some(_) => string_param(params, name).map(Some),
+    }
+}
+
+fn path_param(params: &JsonValue, name: &str) -> Result<PathBuf, (i32, String)> {
+    string_param(params, name).map(PathBuf::from)
+}
+
+fn result_response(id: &JsonValue, result: JsonValue) -> String {
+    JsonValue::Object(vec![
+        (String::from("jsonrpc"), string("2.0")),
+        (String::from("id"), id.clone()),
+        (String::from("result"), result),
+    ])
+    .to_json()
+}
+
+fn error_response(id: &JsonValue, code: i32, message: &str) -> String {
+    JsonValue::Object(vec![
+        (String::from("jsonrpc"), string("2.0")),
+        (String::from("id"), id.clone()),
+        (
+            String::from("error"),
+            JsonValue::Object(vec![
+                (String::from("code"), JsonValue::Number(f64::from(code))),
+                (String::from("message"), string(message)),
+            ]),
+        ),
+    ])
+    .to_json()
+}
@@ -0,0 +1,256 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::git_sr::SignatureKeyring;
+use super::hierarchy::TraversalOrder;
+use super::ComponentLevel;
+
+/// A component whose repository doesn't carry a signature from a trusted key, as found by `find_unverified`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnverifiedComponent {
+    pub name: String,
+    pub path: PathBuf,
+    /// Why the component couldn't be verified
+    pub reason: String,
+}
+
+/// Checks that the checked out commit of a component's repository, or a tag pointing at it, is signed by one of
+/// the keys in `keyring`. Signed tags are checked first, since releases are usually signed that way.
+///
+/// `component_dir` must be a git repository.
+///
+/// The output status is 38 if there is no trusted signature.
+pub fn verify_checkout(component_dir: &Path, keyring: &SignatureKeyring) -> super::SROutput {
+    let mut output = verify_revision(component_dir, "HEAD", keyring);
+
+    if output.status != 0 {
+        output.stderr.push(format!(
+            "ERROR: {} is not signed by a trusted key.",
+            component_dir.display()
+        ));
+    }
+
+    output
+}
+
+/// Checks the signature on a revision of a remote repository without installing anything, and gives back the
+/// commit hash that was verified. Installing that exact commit makes sure that what gets installed is what was
+/// checked, even if the remote changes in between.
+///
+/// `url` URL of the remote repository.
+/// `reference` The tag, branch or commit to check, or `None` for the repository's default branch.
+/// `keyring` The trusted keys that the signature has to be made with.
+///
+/// The output status is 38 if the revision can't be fetched or doesn't have a trusted signature.
+///
+/// # Examples
+///
+/// ```no_run
+/// let keyring = sliderule::git_sr::SignatureKeyring {
+///     gpg_home: None,
+///     allowed_signers: Some(std::path::PathBuf::from("/etc/sliderule/allowed_signers")),
+/// };
+///
+/// let (output, commit) = sliderule::signatures::verify_remote(
+///     "https://github.com/jmwright/toplevel.git",
+///     Some("v1.0.0"),
+///     &keyring,
+/// );
+///
+/// if output.status == 0 {
+///     println!("v1.0.0 is commit {}", commit);
+/// }
+/// ```
+pub fn verify_remote(
+    url: &str,
+    reference: Option<&str>,
+    keyring: &SignatureKeyring,
+) -> (super::SROutput, String) {
+    let clone_dir = super::scratch_dir("verify");
+
+    let mut output = super::git_sr::git_clone_bare(&clone_dir, url);
+    if output.status != 0 || output.wrapped_status != 0 {
+        output.status = 38;
+        output.stderr.push(format!(
+            "ERROR: Unable to fetch {} to check its signature.",
+            url
+        ));
+        let _ = fs::remove_dir_all(&clone_dir);
+        return (output, String::new());
+    }
+
+    let revision = reference.unwrap_or("HEAD");
+    let parse_output = super::git_sr::git_rev_parse(&clone_dir, revision);
+    let commit = parse_output.stdout.join("").trim().to_string();
+    output = super::combine_sroutputs(output, parse_output);
+
+    if commit.is_empty() {
+        output.status = 38;
+        output.stderr.push(format!(
+            "ERROR: {} could not be found in {}.",
+            revision, url
+        ));
+        let _ = fs::remove_dir_all(&clone_dir);
+        return (output, commit);
+    }
+
+    let verify_output = verify_revision(&clone_dir, revision, keyring);
+    output = super::combine_sroutputs(output, verify_output);
+    if output.status != 0 {
+        output.stderr.push(format!(
+            "ERROR: {} of {} is not signed by a trusted key.",
+            revision, url
+        ));
+    }
+
+    let _ = fs::remove_dir_all(&clone_dir);
+
+    (output, commit)
+}
+
+/// Finds every component in a project that can't be traced back to a trusted signature. Components with their
+/// own repository have their checked out commit checked, and remote components are checked by fetching the
+/// commit that was installed. Local components live in the project's repository, so they are covered by
+/// the project's own check.
+///
+/// `project_dir` must be a valid Sliderule component directory.
+///
+/// # Examples
+///
+/// ```no_run
+/// let keyring = sliderule::git_sr::SignatureKeyring {
+///     gpg_home: Some(std::path::PathBuf::from("/etc/sliderule/gnupg")),
+///     allowed_signers: None,
+/// };
+///
+/// for component in sliderule::signatures::find_unverified(&std::env::temp_dir().join("toplevel"), &keyring) {
+///     println!("{} ({}): {}", component.name, component.path.display(), component.reason);
+/// }
+/// ```
+pub fn find_unverified(project_dir: &Path, keyring: &SignatureKeyring) -> Vec<UnverifiedComponent> {
+    let mut unverified = Vec::new();
+
+    for component in super::hierarchy::components_iter(project_dir, TraversalOrder::PreOrder) {
+        let reason = if component.path.join(".git").exists() {
+            let output = verify_checkout(&component.path, keyring);
+            if output.status == 0 {
+                continue;
+            }
+            String::from("The checked out commit is not signed by a trusted key.")
+        } else if component.level == ComponentLevel::RemoteComponent {
+            match verify_installed(&component.path, keyring) {
+                Ok(()) => continue,
+                Err(reason) => reason,
+            }
+        } else {
+            continue;
+        };
+
+        unverified.push(UnverifiedComponent {
+            name: component.name,
+            path: component.path,
+            reason,
+        });
+    }
+
+    unverified
+}
+
+/*
+ * Looks for a trusted signature on any tag pointing at a revision, then on the commit itself.
+ */
+fn verify_revision(repo_dir: &Path, revision: &str, keyring: &SignatureKeyring) -> super::SROutput {
+    let tags_output = super::git_sr::git_tags_at(repo_dir, revision);
+    for tag in tags_output.stdout.join("").lines() {
+        let tag_output = super::git_sr::git_verify_tag(repo_dir, tag.trim(), keyring);
+        if tag_output.status == 0 && tag_output.wrapped_status == 0 {
+            let mut output = tag_output;
+            output
+                .stdout
+                .push(format!("The tag {} has a trusted signature.", tag.trim()));
+            return output;
+        }
+    }
+
+    let mut output = super::git_sr::git_verify_commit(repo_dir, revision, keyring);
+    if output.status != 0 || output.wrapped_status != 0 {
+        output.status = 38;
+    } else {
+        output
+            .stdout
+            .push(format!("The commit {} has a trusted signature.", revision));
+    }
+
+    output
+}
+
+/*
+ * Checks the commit that a remote component was installed from, using the dependency in the package.json file
+ * of the component that it was installed into.
+ */
+fn verify_installed(component_dir: &Path, keyring: &SignatureKeyring) -> Result<(), String> {
+    // Scoped components are one directory deeper inside of node_modules
+    let modules_dir = match component_dir
+        .ancestors()
+        .find(|a| a.file_name().is_some_and(|n| n == "node_modules"))
+    {
+        Some(d) => d,
+        None => {
+            return Err(String::from(
+                "The component is not in a node_modules directory.",
+            ))
+        }
+    };
+    let parent_dir = match modules_dir.parent() {
+        Some(p) => p,
+        None => {
+            return Err(String::from(
+                "The component is not in a node_modules directory.",
+            ))
+        }
+    };
+
+    let name: Vec<String> = match component_dir.strip_prefix(modules_dir) {
+        Ok(rel) => rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect(),
+        Err(_) => {
+            return Err(String::from(
+                "The component is not in a node_modules directory.",
+            ))
+        }
+    };
+    let name = name.join("/");
+
+    let spec = super::get_json_dependencies(&parent_dir.join("package.json"))
+        .into_iter()
+        .find(|d| d.0 == name)
+        .map(|d| d.1);
+    let url = match spec
+        .as_ref()
+        .and_then(|s| super::dependency_url_to_git_url(s))
+    {
+        Some(u) => u,
+        None => {
+            return Err(String::from(
+                "The component was not installed from a repository.",
+            ))
+        }
+    };
+
+    let commit = match super::installed_commit(parent_dir, &name) {
+        Some(c) => c,
+        None => return Err(String::from("The installed commit is not recorded.")),
+    };
+
+    let (output, _) = verify_remote(&url, Some(&commit), keyring);
+    if output.status == 0 {
+        Ok(())
+    } else {
+        Err(format!(
+            "The installed commit {} is not signed by a trusted key.",
+            commit
+        ))
+    }
+}
@@ -0,0 +1,667 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use super::json::JsonValue;
+
+/// Where `refresh_license_db` downloads the SPDX license list from.
+pub const SPDX_URL: &str = "https://spdx.org/licenses/";
+
+// The snapshot of the license list that Sliderule ships with, in the same form as the refreshed copy
+const BUNDLED_DB: &str = include_str!("spdx_licenses.json");
+
+/// A license or license exception on the SPDX license list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct License {
+    /// The SPDX identifier, i.e. `CERN-OHL-S-2.0`
+    pub id: String,
+    /// The full name, which the bundled snapshot only has for commonly used licenses until the database is refreshed
+    pub name: Option<String>,
+    /// Whether SPDX has replaced the identifier with another one, like `GPL-2.0` with `GPL-2.0-only`
+    pub deprecated: bool,
+    /// The full text of the license, which is only kept for some licenses, see `refresh_license_db`
+    pub text: Option<String>,
+}
+
+/// The SPDX license list, as read by `license_db`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LicenseDb {
+    /// The version of the SPDX license list, i.e. `3.27.0`
+    pub version: String,
+    pub licenses: Vec<License>,
+    /// The exceptions that can follow `WITH` in a license expression
+    pub exceptions: Vec<License>,
+}
+
+impl LicenseDb {
+    /// Finds a license by its identifier. SPDX identifiers don't depend on case, so `mit` finds `MIT`.
+    pub fn license(&self, id: &str) -> Option<&License> {
+        self.licenses.iter().find(|l| l.id.eq_ignore_ascii_case(id))
+    }
+
+    /// Finds a license exception by its identifier, without regard to case.
+    pub fn exception(&self, id: &str) -> Option<&License> {
+        self.exceptions
+            .iter()
+            .find(|l| l.id.eq_ignore_ascii_case(id))
+    }
+
+    /// Suggests up to three licenses for something that was meant to be one, closest first, i.e. `MIT` for
+    /// `mit`, `Apache-2.0` for `Apache 2` or `Apache License 2.0`, or `CC-BY-4.0` for `CC-BY-40`. Deprecated
+    /// identifiers are never suggested.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let db = sliderule::spdx::license_db();
+    ///
+    /// assert_eq!(db.suggest("Apache License 2.0")[0], "Apache-2.0");
+    /// assert_eq!(db.suggest("cern-ohl-s-2")[0], "CERN-OHL-S-2.0");
+    /// ```
+    pub fn suggest(&self, text: &str) -> Vec<String> {
+        let wanted = normalize(text.trim_end_matches('+'));
+        if wanted.is_empty() {
+            return Vec::new();
+        }
+
+        let mut scored: Vec<(usize, &str)> = Vec::new();
+        for license in self.licenses.iter().filter(|l| !l.deprecated) {
+            let mut keys = vec![normalize(&license.id)];
+            if let Some(ref name) = license.name {
+                keys.push(normalize(name));
+            }
+            keys.retain(|k| !k.is_empty());
+
+            let best = keys
+                .iter()
+                .map(|key| {
+                    if *key == wanted {
+                        0
+                    } else if key.starts_with(&wanted) || wanted.starts_with(key.as_str()) {
+                        1 + key.len().abs_diff(wanted.len())
+                    } else {
+                        10 + edit_distance(key, &wanted)
+                    }
+                })
+                .min()
+                .unwrap_or(usize::MAX);
+
+            // Anything further away than a couple of typos isn't what was meant
+            if best <= 12 {
+                scored.push((best, &license.id));
+            }
+        }
+
+        scored.sort();
+        scored
+            .into_iter()
+            .take(3)
+            .map(|s| s.1.to_string())
+            .collect()
+    }
+}
+
+/// Reads a license database in the form that `refresh_license_db` writes it. This is the form of the SPDX
+/// `licenses.json` document, with the license exceptions in an `exceptions` member and the text of each license
+/// in a `licenseText` member of its own.
+pub fn parse_db(contents: &str) -> Result<LicenseDb, String> {
+    let document = super::json::parse(contents)?;
+
+    let version = document.get_str("licenseListVersion");
+    if version.is_empty() {
+        return Err(String::from(
+            "There is no licenseListVersion in the license list",
+        ));
+    }
+
+    Ok(LicenseDb {
+        version,
+        licenses: read_licenses(&document, "licenses", "licenseId"),
+        exceptions: read_licenses(&document, "exceptions", "licenseExceptionId"),
+    })
+}
+
+/// The snapshot of the SPDX license list that is bundled with Sliderule. It has every license and exception
+/// identifier, so that licenses can be checked without a network connection, but only some of the texts: those
+/// of 0BSD, Apache-2.0, BSD-2-Clause, BSD-3-Clause, CC0-1.0, CC-BY-SA-4.0, ISC, MIT, MPL-1.1, MPL-2.0, Unlicense
+/// and the GFDL, GPL and LGPL licenses. The open hardware licenses, like CERN-OHL-S-2.0 and TAPR-OHL-1.0, and
+/// CC-BY-4.0 don't have their texts bundled, so `refresh_license_db` has to download them before
+/// `write_license_file` can be used with them.
+///
+/// # Examples
+///
+/// ```
+/// let db = sliderule::spdx::bundled();
+///
+/// assert!(db.license("CERN-OHL-S-2.0").is_some());
+/// assert!(db.license("CC-BY-SA-4.0").and_then(|l| l.text.clone()).is_some());
+/// ```
+pub fn bundled() -> LicenseDb {
+    parse_db(BUNDLED_DB).expect("The bundled SPDX license list is not valid.")
+}
+
+/// The file that `refresh_license_db` keeps the refreshed license list in, which is `sliderule/spdx.json` inside
+/// of `XDG_CACHE_HOME` (or `~/.cache`) on Linux and macOS, and inside of `LOCALAPPDATA` on Windows.
+pub fn db_file() -> Option<PathBuf> {
+    if let Ok(dir) = ::std::env::var("XDG_CACHE_HOME") {
+        if !dir.is_empty() {
+            return Some(PathBuf::from(dir).join("sliderule").join("spdx.json"));
+        }
+    }
+
+    if cfg!(windows) {
+        if let Ok(dir) = ::std::env::var("LOCALAPPDATA") {
+            return Some(PathBuf::from(dir).join("sliderule").join("spdx.json"));
+        }
+    }
+
+    match ::std::env::var("HOME") {
+        Ok(dir) => Some(
+            PathBuf::from(dir)
+                .join(".cache")
+                .join("sliderule")
+                .join("spdx.json"),
+        ),
+        Err(_) => None,
+    }
+}
+
+/// The SPDX license list that licenses are checked against, which is the copy that `refresh_license_db` last
+/// downloaded, or the `bundled` snapshot if it has never been refreshed or the refreshed copy can't be read.
+/// The list is only read once, and is shared by every operation in the process.
+///
+/// # Examples
+///
+/// ```
+/// let db = sliderule::spdx::license_db();
+///
+/// assert!(db.license("CERN-OHL-S-2.0").is_some());
+/// assert!(db.license("MIT").and_then(|l| l.text.clone()).is_some());
+/// ```
+pub fn license_db() -> Arc<LicenseDb> {
+    let mut loaded = lock(loaded_db());
+
+    if let Some(ref db) = *loaded {
+        return db.clone();
+    }
+
+    let db = db_file()
+        .and_then(|f| fs::read_to_string(f).ok())
+        .and_then(|c| parse_db(&c).ok())
+        .unwrap_or_else(bundled);
+    let db = Arc::new(db);
+    *loaded = Some(db.clone());
+
+    db
+}
+
+/// Downloads the latest SPDX license list from `SPDX_URL` into the user's cache (see `db_file`), which
+/// `license_db` uses from then on. The texts of the licenses that the database already had texts for are
+/// downloaded again, along with the texts of the licenses in `text_ids`, so that LICENSE files can be written
+/// for licenses that aren't in the bundled snapshot. A text that can't be downloaded is kept from the old
+/// database, with a warning.
+///
+/// curl has to be installed.
+///
+/// The output status is 507 if the license list can't be downloaded or read, and 508 if it can't be saved to
+/// the cache.
+///
+/// # Examples
+///
+/// ```no_run
+/// let output = sliderule::spdx::refresh_license_db(&["TAPR-OHL-1.0"]);
+///
+/// assert_eq!(0, output.status);
+/// assert!(sliderule::spdx::license_text("TAPR-OHL-1.0").is_some());
+/// ```
+pub fn refresh_license_db(text_ids: &[&str]) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let old_db = license_db();
+
+    let licenses_doc = match download_json("licenses.json") {
+        Ok(d) => d,
+        Err(e) => {
+            output.status = 507;
+            output.stderr.push(e);
+            return output;
+        }
+    };
+    let exceptions_doc = match download_json("exceptions.json") {
+        Ok(d) => d,
+        Err(e) => {
+            output.status = 507;
+            output.stderr.push(e);
+            return output;
+        }
+    };
+
+    let version = licenses_doc.get_str("licenseListVersion");
+    let mut licenses = read_licenses(&licenses_doc, "licenses", "licenseId");
+    let exceptions = read_licenses(&exceptions_doc, "exceptions", "licenseExceptionId");
+    if version.is_empty() || licenses.is_empty() {
+        output.status = 507;
+        output.stderr.push(String::from(
+            "ERROR: The SPDX license list that was downloaded is not valid.",
+        ));
+        return output;
+    }
+
+    for license in licenses.iter_mut() {
+        let old_text = old_db.license(&license.id).and_then(|l| l.text.clone());
+        let wanted = text_ids
+            .iter()
+            .any(|id| id.eq_ignore_ascii_case(&license.id));
+        if old_text.is_none() && !wanted {
+            continue;
+        }
+
+        match download_json(&format!("{}.json", license.id)) {
+            Ok(details) => {
+                let text = details.get_str("licenseText");
+                license.text = if text.is_empty() {
+                    old_text
+                } else {
+                    Some(text)
+                };
+            }
+            Err(e) => {
+                output.stderr.push(format!(
+                    "WARNING: Unable to download the text of {}, the old one was kept: {}",
+                    license.id, e
+                ));
+                license.text = old_text;
+            }
+        }
+    }
+
+    for id in text_ids {
+        if !licenses.iter().any(|l| l.id.eq_ignore_ascii_case(id)) {
+            output.stderr.push(format!(
+                "WARNING: {} is not on the SPDX license list, so its text was not downloaded.",
+                id
+            ));
+        }
+    }
+
+    let db = LicenseDb {
+        version,
+        licenses,
+        exceptions,
+    };
+
+    let db_file = match db_file() {
+        Some(f) => f,
+        None => {
+            output.status = 508;
+            output.stderr.push(String::from(
+                "ERROR: There is no cache directory to save the SPDX license list in.",
+            ));
+            return output;
+        }
+    };
+    let saved = match db_file.parent() {
+        Some(dir) => fs::create_dir_all(dir).and_then(|_| fs::write(&db_file, to_json(&db))),
+        None => fs::write(&db_file, to_json(&db)),
+    };
+    if let Err(e) = saved {
+        output.status = 508;
+        output.stderr.push(format!(
+            "ERROR: Unable to save the SPDX license list to {}: {}",
+            db_file.display(),
+            e
+        ));
+        return output;
+    }
+
+    output.stdout.push(format!(
+        "Refreshed the SPDX license list to version {}.",
+        db.version
+    ));
+    *lock(loaded_db()) = Some(Arc::new(db));
+
+    output
+}
+
+/// Gets the full text of a license from `license_db`, if it has one. A `+` on the end of the identifier, as in
+/// `GPL-2.0+`, is ignored.
+pub fn license_text(id: &str) -> Option<String> {
+    let db = license_db();
+
+    db.license(id)
+        .or_else(|| db.license(id.trim_end_matches('+')))
+        .and_then(|l| l.text.clone())
+}
+
+/// Checks the identifiers in a well-formed license expression against `license_db`, giving a warning for each
+/// one that isn't on the SPDX license list, with suggestions for what was meant, and for each one that is
+/// deprecated. `LicenseRef-` identifiers are a project's own, and are never warned about.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+///
+/// # Examples
+///
+/// ```
+/// assert!(sliderule::spdx::check_ids("CERN-OHL-S-2.0 AND CC-BY-4.0").is_empty());
+///
+/// let warnings = sliderule::spdx::check_ids("CERN-OHL-S-2 AND CC-BY-4.0");
+/// assert_eq!(1, warnings.len());
+/// assert!(warnings[0].contains("did you mean CERN-OHL-S-2.0"));
+/// ```
+pub fn check_ids(license: &str) -> Vec<String> {
+    let db = license_db();
+    let mut warnings = Vec::new();
+
+    for (id, is_exception) in expression_ids(license) {
+        if id.contains("LicenseRef-") {
+            continue;
+        }
+
+        let found = if is_exception {
+            db.exception(&id)
+        } else {
+            db.license(&id)
+                .or_else(|| db.license(id.trim_end_matches('+')))
+        };
+
+        match found {
+            Some(l) if l.deprecated => warnings.push(format!(
+                "WARNING: '{}' is a deprecated SPDX license identifier.",
+                l.id
+            )),
+            Some(_) => {}
+            None => {
+                let suggestions = if is_exception {
+                    Vec::new()
+                } else {
+                    db.suggest(&id)
+                };
+                if suggestions.is_empty() {
+                    warnings.push(format!(
+                        "WARNING: '{}' is not on version {} of the SPDX license list.",
+                        id, db.version
+                    ));
+                } else {
+                    warnings.push(format!(
+                        "WARNING: '{}' is not on version {} of the SPDX license list, did you mean {}?",
+                        id,
+                        db.version,
+                        suggestions.join(" or ")
+                    ));
+                }
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Writes a component's `LICENSE` file with the full text of every license in its source and documentation
+/// licenses (see `get_licenses`), taken from `license_db`. When there is more than one license, each text comes
+/// under a heading with its identifier. An existing `LICENSE` file is replaced.
+///
+/// `target_dir` must be a valid Sliderule component directory.
+///
+/// The output status is 509 if the database doesn't have the text of one of the licenses, which
+/// `refresh_license_db` can download, or if the file can't be written.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("MIT"), String::from("CC0-1.0"));
+///
+/// let output = sliderule::spdx::write_license_file(&temp_dir.join("toplevel"));
+///
+/// assert_eq!(0, output.status);
+///
+/// let license = std::fs::read_to_string(temp_dir.join("toplevel").join("LICENSE")).unwrap();
+/// assert!(license.contains("MIT License"));
+/// assert!(license.contains("CC0 1.0 Universal"));
+/// ```
+pub fn write_license_file(target_dir: &Path) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let (source_license, doc_license) = super::get_licenses(target_dir);
+
+    let mut ids: Vec<String> = Vec::new();
+    for (id, is_exception) in expression_ids(&source_license)
+        .into_iter()
+        .chain(expression_ids(&doc_license))
+    {
+        let id = id.trim_end_matches('+').to_string();
+        if !is_exception && !id.contains("LicenseRef-") && !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+
+    let mut texts: Vec<(String, String)> = Vec::new();
+    for id in ids {
+        match license_text(&id) {
+            Some(text) => texts.push((id, text)),
+            None => {
+                output.status = 509;
+                output.stderr.push(format!(
+                    "ERROR: The text of {} is not in the SPDX license database, it can be downloaded with refresh_license_db.",
+                    id
+                ));
+            }
+        }
+    }
+    if output.status != 0 {
+        return output;
+    }
+
+    let nl = super::get_newline();
+    let contents = if texts.len() == 1 {
+        texts[0].1.clone()
+    } else {
+        let sections: Vec<String> = texts
+            .iter()
+            .map(|t| format!("{}\n{}\n\n{}", t.0, "=".repeat(t.0.len()), t.1.trim_end()))
+            .collect();
+        format!("{}\n", sections.join("\n\n\n"))
+    };
+    let contents = contents.replace("\r\n", "\n").replace('\n', &nl);
+
+    match fs::write(target_dir.join("LICENSE"), contents) {
+        Ok(()) => output.stdout.push(format!(
+            "Wrote the text of {} licenses to LICENSE.",
+            texts.len()
+        )),
+        Err(e) => {
+            output.status = 509;
+            output
+                .stderr
+                .push(format!("ERROR: Unable to write LICENSE: {}", e));
+        }
+    }
+
+    output
+}
+
+fn loaded_db() -> &'static Mutex<Option<Arc<LicenseDb>>> {
+    static LOADED: OnceLock<Mutex<Option<Arc<LicenseDb>>>> = OnceLock::new();
+
+    LOADED.get_or_init(|| Mutex::new(None))
+}
+
+/*
+ * Gets the guarded value even if another thread panicked while holding the lock.
+ */
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(g) => g,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/*
+ * Reads the licenses or exceptions out of a license list document, with the identifier in id_key.
+ */
+fn read_licenses(document: &JsonValue, list_key: &str, id_key: &str) -> Vec<License> {
+    let items = match document.get(list_key) {
+        Some(JsonValue::Array(items)) => items,
+        _ => return Vec::new(),
+    };
+
+    items
+        .iter()
+        .filter_map(|item| {
+            let id = item.get_str(id_key);
+            if id.is_empty() {
+                return None;
+            }
+
+            let name = item.get_str("name");
+            let text = item.get_str("licenseText");
+            Some(License {
+                id,
+                name: if name.is_empty() { None } else { Some(name) },
+                deprecated: matches!(
+                    item.get("isDeprecatedLicenseId"),
+                    Some(JsonValue::Bool(true))
+                ),
+                text: if text.is_empty() { None } else { Some(text) },
+            })
+        })
+        .collect()
+}
+
+/*
+ * Writes a license database out in the form that parse_db reads.
+ */
+fn to_json(db: &LicenseDb) -> String {
+    let entries = |licenses: &[License], id_key: &str| -> JsonValue {
+        JsonValue::Array(
+            licenses
+                .iter()
+                .map(|l| {
+                    let mut members = vec![(id_key.to_string(), JsonValue::String(l.id.clone()))];
+                    if let Some(ref name) = l.name {
+                        members.push((String::from("name"), JsonValue::String(name.clone())));
+                    }
+                    members.push((
+                        String::from("isDeprecatedLicenseId"),
+                        JsonValue::Bool(l.deprecated),
+                    ));
+                    if let Some(ref text) = l.text {
+                        members
+                            .push((String::from("licenseText"), JsonValue::String(text.clone())));
+                    }
+                    JsonValue::Object(members)
+                })
+                .collect(),
+        )
+    };
+
+    JsonValue::Object(vec![
+        (
+            String::from("licenseListVersion"),
+            JsonValue::String(db.version.clone()),
+        ),
+        (String::from("licenses"), entries(&db.licenses, "licenseId")),
+        (
+            String::from("exceptions"),
+            entries(&db.exceptions, "licenseExceptionId"),
+        ),
+    ])
+    .to_json()
+}
+
+/*
+ * Downloads and parses one of the JSON documents under SPDX_URL.
+ */
+fn download_json(name: &str) -> Result<JsonValue, String> {
+    let url = format!("{}{}", SPDX_URL, name);
+
+    let curl_output = super::curl_sr::curl_get(&url);
+    if curl_output.status != 0 || curl_output.wrapped_status != 0 {
+        return Err(format!(
+            "ERROR: Unable to download {}: {}",
+            url,
+            curl_output.stderr.join(" ").trim()
+        ));
+    }
+
+    super::json::parse(&curl_output.stdout.join(""))
+        .map_err(|e| format!("ERROR: {} is not valid JSON: {}", url, e))
+}
+
+/*
+ * The identifiers in a license expression, with whether each one is an exception that follows WITH.
+ */
+fn expression_ids(license: &str) -> Vec<(String, bool)> {
+    let spaced = license.replace(['(', ')'], " ");
+
+    let mut ids = Vec::new();
+    let mut after_with = false;
+    for token in spaced.split_whitespace() {
+        match token.to_uppercase().as_str() {
+            "AND" | "OR" => after_with = false,
+            "WITH" => after_with = true,
+            _ => {
+                ids.push((token.to_string(), after_with));
+                after_with = false;
+            }
+        }
+    }
+
+    ids
+}
+
+/*
+ * Lowercases a license identifier or name and drops everything that isn't a letter or a digit, along with the
+ * words that names tend to have in them, so that different ways of writing the same license compare equal.
+ */
+fn normalize(text: &str) -> String {
+    let lower = text.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '.')
+        .filter(|w| !w.is_empty() && !["license", "licence", "the", "version", "v"].contains(w))
+        .collect();
+
+    words
+        .concat()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .trim_end_matches('0')
+        .to_string()
+}
+
+/*
+ * The number of single character edits that it takes to turn one string into another.
+ */
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(row[j + 1])
+            };
+            previous = current;
+        }
+    }
+
+    row[b.len()]
+}
@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The metadata that is read out of a component's `.sr` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SrData {
+    pub source_license: String,
+    pub doc_license: String,
+}
+
+// What the file looked like when it was read, so that changes can be noticed
+#[derive(Clone, Copy, PartialEq)]
+struct FileStamp {
+    modified: SystemTime,
+    len: u64,
+}
+
+struct SrCache {
+    entries: HashMap<PathBuf, (FileStamp, SrData)>,
+    disk_file: Option<PathBuf>,
+    dirty: bool,
+}
+
+fn cache() -> MutexGuard<'static, SrCache> {
+    static CACHE: OnceLock<Mutex<SrCache>> = OnceLock::new();
+
+    let mutex = CACHE.get_or_init(|| {
+        Mutex::new(SrCache {
+            entries: HashMap::new(),
+            disk_file: None,
+            dirty: false,
+        })
+    });
+
+    // A panic while the lock was held can't leave the map half-updated, so keep using it
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Reads the licenses out of a `.sr` file, reusing what was read last time if the file hasn't been modified since.
+///
+/// The cache is shared by every operation in the process, so a batch of operations on the same project only reads
+/// each `.sr` file once. Changes that Sliderule makes to `.sr` files always invalidate the cache, and changes made by
+/// other programs are noticed through the file's modification time and size.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let data = sliderule::sr_cache::read_sr(&temp_dir.join("toplevel").join(".sr"));
+///
+/// assert_eq!(data.source_license, "Unlicense");
+/// assert_eq!(data.doc_license, "CC0-1.0");
+/// ```
+pub fn read_sr(sr_file: &Path) -> SrData {
+    let sr_file = sr_file.to_path_buf();
+
+    // Without a stamp there's no way to tell if a cached copy is stale
+    let stamp = match file_stamp(&sr_file) {
+        Some(s) => s,
+        None => return read_sr_file(&sr_file),
+    };
+
+    if let Some(&(cached_stamp, ref data)) = cache().entries.get(&sr_file) {
+        if cached_stamp == stamp {
+            return data.clone();
+        }
+    }
+
+    // Do the reading without holding the lock so that other threads can keep going
+    let data = read_sr_file(&sr_file);
+
+    let mut cache = cache();
+    cache.entries.insert(sr_file, (stamp, data.clone()));
+    cache.dirty = true;
+
+    data
+}
+
+/// Forgets what was read from a `.sr` file, so that it will be read again the next time it's needed.
+pub fn invalidate(sr_file: &Path) {
+    let mut cache = cache();
+
+    if cache.entries.remove(sr_file).is_some() {
+        cache.dirty = true;
+    }
+}
+
+/// Forgets everything that has been read from `.sr` files.
+pub fn clear() {
+    let mut cache = cache();
+
+    cache.entries.clear();
+    cache.dirty = true;
+}
+
+/// Keeps a copy of the cache in a file, so that it can be reused by later runs. Anything already in the file is
+/// loaded right away, and `save_disk_cache` writes the cache back out. Passing `None` stops using
+/// the file.
+///
+/// # Examples
+///
+/// ```
+/// let cache_file = std::env::temp_dir().join(format!("sr_cache_{}", uuid::Uuid::new_v4()));
+///
+/// sliderule::sr_cache::set_disk_cache(Some(cache_file.clone()));
+///
+/// // Work with components here
+///
+/// sliderule::sr_cache::save_disk_cache();
+/// sliderule::sr_cache::set_disk_cache(None);
+/// ```
+pub fn set_disk_cache(cache_file: Option<PathBuf>) {
+    let mut cache = cache();
+
+    if let Some(ref file) = cache_file {
+        if let Ok(contents) = fs::read_to_string(file) {
+            for line in contents.lines() {
+                if let Some((path, entry)) = parse_disk_line(line) {
+                    cache.entries.entry(path).or_insert(entry);
+                }
+            }
+        }
+    }
+
+    cache.disk_file = cache_file;
+}
+
+/// Returns the file given to `set_disk_cache`, if there is one.
+pub fn disk_cache_file() -> Option<PathBuf> {
+    cache().disk_file.clone()
+}
+
+/// Writes the cache out to the file given to `set_disk_cache`, if one was given and anything has changed.
+/// The file is only readable by the current user.
+/// Failing to write the file only costs the next run some time, so errors are otherwise ignored. Nothing is
+/// written in CI mode.
+pub fn save_disk_cache() {
+    let mut cache = cache();
+
+    // The cache file is outside of the workspace
+    if !cache.dirty || super::ci::ci_mode() {
+        return;
+    }
+
+    let disk_file = match cache.disk_file {
+        Some(ref f) => f.clone(),
+        None => return,
+    };
+
+    let mut paths: Vec<&PathBuf> = cache.entries.keys().collect();
+    paths.sort();
+
+    let mut contents = String::new();
+    for path in paths {
+        let (stamp, ref data) = cache.entries[path];
+        let modified = stamp
+            .modified
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        contents.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\n",
+            modified.as_secs(),
+            modified.subsec_nanos(),
+            stamp.len,
+            data.source_license,
+            data.doc_license,
+            path.display()
+        ));
+    }
+
+    // The cache lists every project path that has been worked on, so keep it private to the user
+    if super::write_private_file(&disk_file, &contents).is_ok() {
+        cache.dirty = false;
+    }
+}
+
+/*
+ * Gets the modification time and size of a file, if the file system can provide them.
+ */
+fn file_stamp(file: &Path) -> Option<FileStamp> {
+    let metadata = fs::metadata(file).ok()?;
+
+    Some(FileStamp {
+        modified: metadata.modified().ok()?,
+        len: metadata.len(),
+    })
+}
+
+/*
+ * Reads the licenses straight from the file.
+ */
+fn read_sr_file(sr_file: &Path) -> SrData {
+    SrData {
+        source_license: super::get_yaml_value(sr_file, "source_license"),
+        doc_license: super::get_yaml_value(sr_file, "documentation_license"),
+    }
+}
+
+/*
+ * Turns one line of the on-disk cache back into an entry, skipping anything that doesn't look right.
+ */
+fn parse_disk_line(line: &str) -> Option<(PathBuf, (FileStamp, SrData))> {
+    let parts: Vec<&str> = line.splitn(6, '\t').collect();
+    if parts.len() != 6 {
+        return None;
+    }
+
+    let secs = parts[0].parse::<u64>().ok()?;
+    let nanos = parts[1].parse::<u32>().ok()?;
+    let len = parts[2].parse::<u64>().ok()?;
+
+    let stamp = FileStamp {
+        modified: UNIX_EPOCH + Duration::new(secs, nanos),
+        len,
+    };
+
+    let data = SrData {
+        source_license: parts[3].to_string(),
+        doc_license: parts[4].to_string(),
+    };
+
+    Some((PathBuf::from(parts[5]), (stamp, data)))
+}
@@ -0,0 +1,135 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Uses ssh to run a shell command on a remote host, with whatever the command printed added to `stdout`
+/// as a single entry.
+///
+/// `destination` The host to connect to, in the `[user@]host` form that ssh accepts.
+/// `port` The port to connect to, or `None` for ssh's default.
+/// `command` The shell command to run on the remote host.
+///
+/// ssh is run in batch mode so that it fails instead of waiting for a password that will never be typed.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn ssh_run(destination: &str, port: Option<u16>, command: &str) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let mut args = vec![String::from("-o"), String::from("BatchMode=yes")];
+    if let Some(p) = port {
+        args.push(String::from("-p"));
+        args.push(p.to_string());
+    }
+    args.push(destination.to_string());
+    args.push(command.to_string());
+
+    let stdoutput = match super::backend::run(Command::new("ssh").args(&args)) {
+        Ok(out) => out,
+        Err(e) => {
+            if let std::io::ErrorKind::NotFound = e.kind() {
+                output.status = 400;
+                output
+                    .stderr
+                    .push(format!("ERROR: `ssh` was not found, please install: {}", e));
+            } else {
+                output.status = 401;
+                output
+                    .stderr
+                    .push(format!("ERROR: Unable to run ssh: {}", e));
+            }
+            return output;
+        }
+    };
+
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "ssh", &stdoutput.status);
+
+    output
+}
+
+/// Uses scp to copy a local file to a remote host.
+///
+/// `local_file` The file to copy.
+/// `destination` The host to copy to, in the `[user@]host` form that scp accepts.
+/// `port` The port to connect to, or `None` for scp's default.
+/// `remote_path` Where to put the file on the remote host. The directory it goes in must already exist.
+///
+/// This module is primarily for sliderule-rs use, and direct use should be avoided in most situations.
+pub fn scp_upload(
+    local_file: &Path,
+    destination: &str,
+    port: Option<u16>,
+    remote_path: &str,
+) -> super::SROutput {
+    let mut output = super::SROutput {
+        status: 0,
+        wrapped_status: 0,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+
+    let mut args = vec![
+        String::from("-q"),
+        String::from("-o"),
+        String::from("BatchMode=yes"),
+    ];
+    if let Some(p) = port {
+        args.push(String::from("-P"));
+        args.push(p.to_string());
+    }
+    args.push(local_file.display().to_string());
+    args.push(format!("{}:{}", destination, remote_path));
+
+    let stdoutput = match super::backend::run(Command::new("scp").args(&args)) {
+        Ok(out) => out,
+        Err(e) => {
+            if let std::io::ErrorKind::NotFound = e.kind() {
+                output.status = 402;
+                output
+                    .stderr
+                    .push(format!("ERROR: `scp` was not found, please install: {}", e));
+            } else {
+                output.status = 403;
+                output
+                    .stderr
+                    .push(format!("ERROR: Unable to run scp: {}", e));
+            }
+            return output;
+        }
+    };
+
+    output
+        .stdout
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stdout,
+        )));
+
+    // If there were errors, make sure we collect them
+    output
+        .stderr
+        .push(super::redact::redact(&String::from_utf8_lossy(
+            &stdoutput.stderr,
+        )));
+
+    // If we have something other than a 0 exit status, report that
+    super::backend::record_exit(&mut output, "scp", &stdoutput.status);
+
+    output
+}
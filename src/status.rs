@@ -0,0 +1,285 @@
+use std::path::{Path, PathBuf};
+
+use super::hierarchy::TraversalOrder;
+use super::ComponentLevel;
+
+/// The most git commands that `project_status` will run at the same time.
+pub const MAX_STATUS_THREADS: usize = 8;
+
+/// The repository state of one component, as reported by `project_status`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentStatus {
+    pub name: String,
+    pub path: PathBuf,
+    /// False for components that were not installed from a git repository, which have no status to report
+    pub is_repo: bool,
+    pub branch: String,
+    /// The branch being tracked, i.e. `origin/master`, which is empty if there isn't one
+    pub upstream: String,
+    pub ahead: usize,
+    pub behind: usize,
+    /// The number of added, removed, modified and untracked files
+    pub changed_files: usize,
+    /// Whether the latest changes could be fetched from the remote repository
+    pub fetched: bool,
+    /// Anything that went wrong while gathering the status
+    pub messages: Vec<String>,
+}
+
+impl ComponentStatus {
+    /// Returns true if the component has no local changes and is level with its upstream branch.
+    pub fn is_up_to_date(&self) -> bool {
+        self.changed_files == 0 && self.ahead == 0 && self.behind == 0
+    }
+}
+
+/// The repository state of a project and its remote components.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectStatus {
+    /// The project comes first, followed by the remote components in tree order
+    pub components: Vec<ComponentStatus>,
+}
+
+impl ProjectStatus {
+    /// The components that have local changes or are not level with their upstream branch.
+    pub fn needs_attention(&self) -> Vec<&ComponentStatus> {
+        self.components
+            .iter()
+            .filter(|c| c.is_repo && !c.is_up_to_date())
+            .collect()
+    }
+}
+
+/// Fetches and checks the status of the project's repository and the repository of every remote component in it.
+/// Local components are part of the project's repository, so they are covered by the project's status.
+///
+/// `project_dir` must be a valid Sliderule component directory.
+///
+/// Each component is handled on a pool of up to `MAX_STATUS_THREADS` threads, so the network round trips for
+/// the fetches overlap instead of being made one after another. The order of the report doesn't depend on
+/// which thread finishes first.
+///
+/// # Examples
+///
+/// ```no_run
+/// let status = sliderule::status::project_status(&std::env::temp_dir().join("toplevel"));
+///
+/// for component in status.needs_attention() {
+///     println!(
+///         "{}: {} changed files, {} ahead, {} behind",
+///         component.name, component.changed_files, component.ahead, component.behind
+///     );
+/// }
+/// ```
+pub fn project_status(project_dir: &Path) -> ProjectStatus {
+    let components: Vec<(String, PathBuf)> =
+        super::hierarchy::components_iter(project_dir, TraversalOrder::PreOrder)
+            .filter(|c| c.level == ComponentLevel::RemoteComponent || c.path == project_dir)
+            .map(|c| (c.name, c.path))
+            .collect();
+
+    // The network round trips for the fetches are what take the time, so overlap them
+    let results = super::parallel_map(&components, MAX_STATUS_THREADS, |c| {
+        component_status(&c.0, &c.1)
+    });
+
+    ProjectStatus {
+        components: results,
+    }
+}
+
+/// One component's entry in the `summarize_project` dashboard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentSummary {
+    pub name: String,
+    pub path: PathBuf,
+    pub level: ComponentLevel,
+    /// False for local components, which are part of the project's repository and covered by its entry, and for
+    /// remote components that were not installed from a git repository
+    pub is_repo: bool,
+    /// Whether the component's repository has added, removed, modified or untracked files
+    pub dirty: bool,
+    pub ahead: usize,
+    pub behind: usize,
+    /// The number of problems that `health::validate_component` found
+    pub warnings: usize,
+    /// The number of the component's dependencies that `update_dependencies` would install or reinstall
+    pub outdated: usize,
+    /// Anything that went wrong while gathering the repository state
+    pub messages: Vec<String>,
+}
+
+impl ComponentSummary {
+    /// Returns true if the component is clean, level with its remote, valid and up to date.
+    pub fn is_ok(&self) -> bool {
+        !self.dirty
+            && self.ahead == 0
+            && self.behind == 0
+            && self.warnings == 0
+            && self.outdated == 0
+    }
+}
+
+/// The state of every component in a project, as gathered by `summarize_project`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectSummary {
+    /// The project comes first, followed by the rest of the components in tree order
+    pub components: Vec<ComponentSummary>,
+}
+
+impl ProjectSummary {
+    /// The components that aren't `ComponentSummary::is_ok`.
+    pub fn needs_attention(&self) -> Vec<&ComponentSummary> {
+        self.components.iter().filter(|c| !c.is_ok()).collect()
+    }
+}
+
+/// Summarizes every component in a project, local and remote, in one call: whether its repository is dirty or
+/// clean, how far it is ahead of or behind its remote, how many problems `health::validate_component` finds with
+/// it, and how many of its dependencies are outdated. This is everything that a project dashboard needs to show.
+///
+/// `project_dir` must be a valid Sliderule component directory.
+///
+/// The repositories are fetched like `project_status` does, and outdated dependencies are found the same way as
+/// `workspace::Workspace::outdated`, so the remote repositories have to be reachable for those counts to be
+/// right. Each component is handled on a pool of up to `MAX_STATUS_THREADS` threads.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// sliderule::create_component(&temp_dir.join("toplevel"), String::from("level1"), String::from("Level 1 component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let summary = sliderule::status::summarize_project(&temp_dir.join("toplevel"));
+///
+/// assert_eq!(summary.components.len(), 2);
+/// assert_eq!(summary.components[1].name, "level1");
+/// assert!(summary.needs_attention().is_empty());
+/// ```
+pub fn summarize_project(project_dir: &Path) -> ProjectSummary {
+    let components: Vec<(String, PathBuf, ComponentLevel)> =
+        super::hierarchy::components_iter(project_dir, TraversalOrder::PreOrder)
+            .map(|c| (c.name, c.path, c.level))
+            .collect();
+
+    let results = super::parallel_map(&components, MAX_STATUS_THREADS, |c| {
+        // Local components are in the project's repository, so they don't have a state of their own
+        let repository = if c.2 == ComponentLevel::RemoteComponent || c.1 == project_dir {
+            Some(component_status(&c.0, &c.1))
+        } else {
+            None
+        };
+        let repository = repository.filter(|r| r.is_repo);
+
+        ComponentSummary {
+            name: c.0.clone(),
+            path: c.1.clone(),
+            level: c.2,
+            is_repo: repository.is_some(),
+            dirty: repository
+                .as_ref()
+                .map(|r| r.changed_files > 0)
+                .unwrap_or(false),
+            ahead: repository.as_ref().map(|r| r.ahead).unwrap_or(0),
+            behind: repository.as_ref().map(|r| r.behind).unwrap_or(0),
+            warnings: super::health::validate_component(&c.1).stderr.len(),
+            outdated: super::outdated_dependencies(&c.1).len(),
+            messages: repository.map(|r| r.messages).unwrap_or_default(),
+        }
+    });
+
+    ProjectSummary {
+        components: results,
+    }
+}
+
+/*
+ * Fetches and gets the status of a single component's repository.
+ */
+fn component_status(name: &str, path: &Path) -> ComponentStatus {
+    let mut status = ComponentStatus {
+        name: name.to_string(),
+        path: path.to_path_buf(),
+        is_repo: path.join(".git").exists(),
+        branch: String::new(),
+        upstream: String::new(),
+        ahead: 0,
+        behind: 0,
+        changed_files: 0,
+        fetched: false,
+        messages: Vec::new(),
+    };
+
+    if !status.is_repo {
+        return status;
+    }
+
+    let fetch_output = super::git_sr::git_fetch(path);
+    if fetch_output.status == 0 && fetch_output.wrapped_status == 0 {
+        status.fetched = true;
+    } else {
+        status.messages.push(format!(
+            "Unable to fetch: {}",
+            fetch_output.stderr.join(" ").trim()
+        ));
+    }
+
+    let status_output = super::git_sr::git_status_porcelain(path);
+    if status_output.status != 0 || status_output.wrapped_status != 0 {
+        status.messages.push(format!(
+            "Unable to get the status: {}",
+            status_output.stderr.join(" ").trim()
+        ));
+        return status;
+    }
+
+    let porcelain = status_output.stdout.join("");
+    for line in porcelain.lines() {
+        if let Some(branch_line) = line.strip_prefix("## ") {
+            parse_branch_line(branch_line, &mut status);
+        } else if !line.trim().is_empty() {
+            status.changed_files += 1;
+        }
+    }
+
+    status
+}
+
+/*
+ * Pulls the branch names and ahead/behind counts out of a line like `master...origin/master [ahead 1, behind 2]`.
+ */
+fn parse_branch_line(line: &str, status: &mut ComponentStatus) {
+    let (branches, counts) = match line.find(" [") {
+        Some(i) => (&line[..i], line[i + 2..].trim_end_matches(']')),
+        None => (line, ""),
+    };
+
+    match branches.find("...") {
+        Some(i) => {
+            status.branch = branches[..i].to_string();
+            status.upstream = branches[i + 3..].to_string();
+        }
+        None => {
+            status.branch = branches
+                .trim_start_matches("No commits yet on ")
+                .to_string()
+        }
+    }
+
+    for count in counts.split(", ") {
+        let parts: Vec<&str> = count.split(' ').collect();
+        if parts.len() != 2 {
+            continue;
+        }
+
+        let n = parts[1].parse::<usize>().unwrap_or(0);
+        match parts[0] {
+            "ahead" => status.ahead = n,
+            "behind" => status.behind = n,
+            _ => {}
+        }
+    }
+}
@@ -1,5 +1,3 @@
-extern crate os_info;
-
 /*
  * Returns the Liquid template for the bom_data.yaml file
  */
@@ -46,10 +44,17 @@ pub fn gitignore_template() -> String {
     contents.push_str("node_modules/");
     contents.push_str(nl);
     contents.push_str(nl);
+    contents.push_str("# Record of operations that can be undone");
+    contents.push_str(nl);
+    contents.push_str(".sr_journal");
+    contents.push_str(nl);
+    contents.push_str("{% if dist_dir != \"\" %}");
+    contents.push_str(nl);
     contents.push_str("# Distribution directory");
     contents.push_str(nl);
-    contents.push_str("dist/");
+    contents.push_str("{{dist_dir}}/");
     contents.push_str(nl);
+    contents.push_str("{% endif %}");
 
     contents
 }
@@ -113,6 +118,165 @@ pub fn sr_file_template() -> String {
     contents
 }
 
+/*
+ * Returns the Liquid template for the CONTRIBUTING.md file
+ */
+pub fn contributing_template() -> String {
+    let nl = &get_newline();
+
+    let mut contents = String::from("# Contributing to {{name}}");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("Thank you for helping to improve {{name}}. Changes to the design, the documentation and the bill of materials are all welcome.");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("## Reporting problems");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("If something doesn't fit, can't be sourced, or doesn't match the documentation, please open an issue. Say which revision you were building, what you expected, and what happened instead. Photos of the problem help a lot.");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("## Making changes");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("1. Fork the repository and make your changes on a new branch.");
+    contents.push_str(nl);
+    contents.push_str("2. Keep design files in `source` and documentation in `docs`, and update `parts.yaml` and `tools.yaml` if the parts or tools change.");
+    contents.push_str(nl);
+    contents.push_str("3. Describe what the change does and how it was checked, i.e. whether a part was printed or a board was assembled.");
+    contents.push_str(nl);
+    contents.push_str("4. Open a pull request.");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("## Licensing");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("By contributing, you agree that your contributions are licensed under the same licenses as the rest of the project, which are listed in `package.json`.");
+    contents.push_str(nl);
+
+    contents
+}
+
+/*
+ * Returns the Liquid template for the CODE_OF_CONDUCT.md file
+ */
+pub fn code_of_conduct_template() -> String {
+    let nl = &get_newline();
+
+    let mut contents = String::from("# Code of Conduct");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("Everyone who takes part in {{name}}, whether by building it, reporting problems, or changing its design, is expected to help keep the project welcoming.");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("## Expected behavior");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("- Be respectful of people with different backgrounds, skills and tools.");
+    contents.push_str(nl);
+    contents.push_str(
+        "- Give feedback on the work rather than the person, and accept feedback in the same way.",
+    );
+    contents.push_str(nl);
+    contents
+        .push_str("- Help newcomers, since everyone has built their first project at some point.");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("## Unacceptable behavior");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("- Harassment, insults or personal attacks, in public or in private.");
+    contents.push_str(nl);
+    contents.push_str("- Posting other people's private information without their permission.");
+    contents.push_str(nl);
+    contents.push_str("- Anything else that would make people feel unwelcome or unsafe.");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("## Enforcement");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("Problems can be reported to the maintainers of {{name}} privately. The maintainers may remove comments, commits or issues, and may ban anyone who doesn't follow this code of conduct from the project.");
+    contents.push_str(nl);
+
+    contents
+}
+
+/*
+ * Returns the Liquid template for the issue template that problems are reported with
+ */
+pub fn bug_report_template() -> String {
+    let nl = &get_newline();
+
+    let mut contents = String::from("---");
+    contents.push_str(nl);
+    contents.push_str("name: Problem report");
+    contents.push_str(nl);
+    contents.push_str("about: Something about {{name}} doesn't work, doesn't fit, or doesn't match the documentation");
+    contents.push_str(nl);
+    contents.push_str("labels: bug");
+    contents.push_str(nl);
+    contents.push_str("---");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("## What happened");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("## What was expected");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("## How to reproduce it");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("1. ");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("## Build details");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("- Revision or commit:");
+    contents.push_str(nl);
+    contents.push_str("- Parts that were substituted:");
+    contents.push_str(nl);
+    contents.push_str("- Tools and machines that were used:");
+    contents.push_str(nl);
+
+    contents
+}
+
+/*
+ * Returns the Liquid template for the issue template that improvements are suggested with
+ */
+pub fn feature_request_template() -> String {
+    let nl = &get_newline();
+
+    let mut contents = String::from("---");
+    contents.push_str(nl);
+    contents.push_str("name: Improvement");
+    contents.push_str(nl);
+    contents.push_str("about: Suggest a change to the design or documentation of {{name}}");
+    contents.push_str(nl);
+    contents.push_str("labels: enhancement");
+    contents.push_str(nl);
+    contents.push_str("---");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("## What should change");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("## Why");
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str(nl);
+    contents.push_str("## Other approaches that were considered");
+    contents.push_str(nl);
+    contents.push_str(nl);
+
+    contents
+}
+
 /*
  * Returns the Liquid template text for a part item entry in parts.yaml or tools.yaml
  */
@@ -144,9 +308,7 @@ pub fn item_template() -> String {
  * Gets the line ending that's appropriate for the OS we are running on.
  */
 fn get_newline() -> String {
-    let info = os_info::get();
-
-    if info.os_type() == os_info::Type::Windows {
+    if super::on_windows() {
         String::from("\r\n")
     } else {
         String::from("\n")
@@ -0,0 +1,202 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The name of the optional file in a project's root directory that lists paths for Sliderule to ignore.
+///
+/// Each line is a glob pattern relative to the project, in the same style as `.gitignore`. Blank lines and
+/// lines starting with `#` are skipped.
+pub const IGNORE_FILE: &str = ".srignore";
+
+/// How many directories deep a walk goes unless told otherwise.
+pub const DEFAULT_MAX_DEPTH: usize = 100;
+
+/// The environment variable that can be used to change the default depth of every walk, which mostly matters for
+/// the license tools on projects with very deep `node_modules` trees.
+pub const MAX_DEPTH_VAR: &str = "SR_MAX_DEPTH";
+
+/// Directories that never hold anything Sliderule needs, but can hold tens of thousands of files. Walks skip these
+/// without descending into them.
+pub const PRUNED_DIRS: [&str; 8] = [
+    ".git",
+    ".hg",
+    ".svn",
+    "**/node_modules/.bin",
+    "**/node_modules/.cache",
+    "**/.sliderule/trash",
+    "**/.sliderule/offline",
+    "**/.sliderule/linked",
+];
+
+/// A configurable walk over the files in a component's directory tree, returning the paths in a stable order.
+///
+/// Patterns are globs relative to the root directory, i.e. `.sr`, `**/*.md` or `node_modules/old-part`.
+/// Only paths matching at least one include pattern are returned, and anything matching an exclude pattern
+/// (including everything beneath an excluded directory) is skipped.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// sliderule::create_component(&temp_dir.join("toplevel"), String::from("level1"), String::from("Level 1 component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let readmes = sliderule::walker::TreeWalker::new(&temp_dir.join("toplevel"))
+///     .include("README.md")
+///     .exclude("components/level1")
+///     .walk();
+///
+/// assert_eq!(readmes, vec![temp_dir.join("toplevel").join("README.md")]);
+/// ```
+pub struct TreeWalker {
+    root: PathBuf,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    max_depth: usize,
+    use_ignore_file: bool,
+    prune: bool,
+}
+
+impl TreeWalker {
+    /// Sets up a walk of everything beneath `root` that honors the project's ignore file and skips `PRUNED_DIRS`.
+    /// The walk goes `DEFAULT_MAX_DEPTH` directories deep, unless the `SR_MAX_DEPTH` environment variable
+    /// holds a different number.
+    pub fn new(root: &Path) -> TreeWalker {
+        let max_depth = match ::std::env::var(MAX_DEPTH_VAR) {
+            Ok(d) => d.trim().parse::<usize>().unwrap_or(DEFAULT_MAX_DEPTH),
+            Err(_) => DEFAULT_MAX_DEPTH,
+        };
+
+        TreeWalker {
+            root: root.to_path_buf(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            max_depth,
+            use_ignore_file: true,
+            prune: true,
+        }
+    }
+
+    /// Adds a pattern that paths must match to be returned. If no include patterns are given, every path matches.
+    pub fn include(mut self, pattern: &str) -> TreeWalker {
+        self.include.push(pattern.to_string());
+        self
+    }
+
+    /// Adds a pattern for paths that should be skipped.
+    pub fn exclude(mut self, pattern: &str) -> TreeWalker {
+        self.exclude.push(pattern.to_string());
+        self
+    }
+
+    /// Sets how many directories deep the walk will go, where 1 only looks at the root directory's contents.
+    pub fn max_depth(mut self, depth: usize) -> TreeWalker {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Sets whether the patterns in the root directory's ignore file are added to the exclude patterns.
+    pub fn use_ignore_file(mut self, use_it: bool) -> TreeWalker {
+        self.use_ignore_file = use_it;
+        self
+    }
+
+    /// Sets whether `PRUNED_DIRS` are skipped. Turning this off is only useful when the contents of those directories
+    /// are actually wanted.
+    pub fn prune_known_dirs(mut self, prune: bool) -> TreeWalker {
+        self.prune = prune;
+        self
+    }
+
+    /// Walks the tree and returns every matching path, with each directory's contents in natural order, see
+    /// `get_sr_paths`.
+    pub fn walk(&self) -> Vec<PathBuf> {
+        let mut patterns: Vec<String> = if self.include.is_empty() {
+            vec![String::from("**")]
+        } else {
+            self.include.clone()
+        };
+
+        let mut excludes = self.exclude.clone();
+        if self.prune {
+            excludes.extend(PRUNED_DIRS.iter().map(|d| d.to_string()));
+        }
+        if self.use_ignore_file {
+            excludes.append(&mut read_ignore_file(&self.root));
+            excludes.append(&mut configured_excludes(&self.root));
+        }
+
+        for exclude in excludes {
+            patterns.push(format!("!{}", exclude));
+        }
+
+        let walker = match globwalk::GlobWalkerBuilder::from_patterns(&self.root, &patterns)
+            .max_depth(self.max_depth)
+            .follow_links(false)
+            .sort_by(super::path_cmp)
+            .build()
+        {
+            Ok(w) => w,
+            Err(_) => return Vec::new(),
+        };
+
+        walker
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|e| e.path().to_path_buf())
+            .collect()
+    }
+}
+
+/// Reads the patterns out of the ignore file in a directory, which is an empty list if there is no ignore file.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// std::fs::write(temp_dir.join(".srignore"), "# Old revisions\nnode_modules/old-part\n\n").expect("Unable to write ignore file.");
+///
+/// assert_eq!(sliderule::walker::read_ignore_file(&temp_dir), vec!["node_modules/old-part"]);
+/// ```
+pub fn read_ignore_file(dir: &Path) -> Vec<String> {
+    let contents = match fs::read_to_string(dir.join(IGNORE_FILE)) {
+        Ok(c) => c,
+        Err(_) => return Vec::new(),
+    };
+
+    contents
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(|l| l.to_string())
+        .collect()
+}
+
+/*
+ * The exclude patterns from the project's configuration, which are relative to the project's top level directory,
+ * made relative to the root of a walk. Patterns starting with ** apply anywhere, so they're kept as they are.
+ */
+fn configured_excludes(root: &Path) -> Vec<String> {
+    let full_root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let project_root = super::config::project_root(&full_root);
+    let prefix = match full_root.strip_prefix(&project_root) {
+        Ok(p) => p.to_string_lossy().replace('\\', "/"),
+        Err(_) => String::new(),
+    };
+
+    super::config::config_or_default(root)
+        .excluded_dirs
+        .into_iter()
+        .filter_map(|p| {
+            if prefix.is_empty() || p.starts_with("**/") {
+                Some(p)
+            } else {
+                p.strip_prefix(&format!("{}/", prefix))
+                    .map(|p| p.to_string())
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// The files that `watch` looks for changes to, anywhere in a project's tree.
+pub const WATCHED_FILES: [&str; 3] = [".sr", "package.json", "bom_data.yaml"];
+
+/// How often `watch` looks for changes.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Something that `watch` noticed or did, passed to its callback.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchEvent {
+    /// A watched file was added, changed or removed
+    FileChanged { path: PathBuf },
+    /// The licenses in a component's `package.json` were amalgamated again
+    LicensesUpdated {
+        component_dir: PathBuf,
+        license: String,
+    },
+    /// A component was validated after one of its files changed. `problems` is empty if it's valid.
+    Validated {
+        component_dir: PathBuf,
+        problems: Vec<String>,
+    },
+}
+
+/// A function that is told about everything `watch` does. It's called from the watcher's own thread.
+pub type WatchCallback = Box<dyn Fn(&WatchEvent) + Send>;
+
+/*
+ * The modification time and size of a file, which is how changes to it are noticed.
+ */
+type Stamp = (Option<SystemTime>, u64);
+
+/// A running watch, which stops when it's dropped or `stop` is called.
+pub struct Watcher {
+    stopped: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Watcher {
+    /// Stops watching, waiting for anything the watcher is in the middle of to finish.
+    pub fn stop(mut self) {
+        self.shut_down();
+    }
+
+    fn shut_down(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.shut_down();
+    }
+}
+
+/// Watches the `WATCHED_FILES` across a project's tree, i.e. for an editor that gives live feedback. Whenever any of
+/// them change, the licenses are amalgamated again for the component they belong to and every component above it,
+/// and the component is checked with `health::validate_component`. Each of those steps is passed to `callback` as it
+/// happens. Changes are looked for every `POLL_INTERVAL`, and everything that changed between two looks is handled
+/// together, so saving several files at once only updates each component once.
+///
+/// Files written by the watcher itself, like the amalgamated licenses in `package.json`, don't start another round,
+/// but anything else that changes while a round is running is picked up by the next one. Components without a
+/// `package.json` are validated, but their licenses can't be amalgamated, so they're skipped. Files that Sliderule
+/// ignores while walking a project, like the ones in `walker::PRUNED_DIRS`, aren't watched, and neither is anything
+/// in `node_modules`, which npm manages.
+///
+/// Polling is used instead of the operating system's file notifications, since the watched files are few and
+/// small, and polling behaves the same on every platform and on network file systems, where notifications are
+/// often missing, without adding a dependency.
+///
+/// `project_dir` must be a valid Sliderule component directory.
+///
+/// # Examples
+///
+/// ```no_run
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("toplevel"), String::from("Top level component"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let watcher = sliderule::watch::watch(
+///     &temp_dir.join("toplevel"),
+///     Box::new(|event| println!("{:?}", event)),
+/// );
+///
+/// sliderule::change_licenses(&temp_dir.join("toplevel"), String::from("MIT"), String::from("CC-BY-4.0"));
+/// std::thread::sleep(std::time::Duration::from_secs(1));
+///
+/// watcher.stop();
+/// ```
+pub fn watch(project_dir: &Path, callback: WatchCallback) -> Watcher {
+    watch_every(project_dir, POLL_INTERVAL, callback)
+}
+
+/// Does the same thing as `watch`, but looks for changes every `interval` instead of every `POLL_INTERVAL`.
+pub fn watch_every(project_dir: &Path, interval: Duration, callback: WatchCallback) -> Watcher {
+    let project_dir = fs::canonicalize(project_dir).unwrap_or_else(|_| project_dir.to_path_buf());
+    let stopped = Arc::new(AtomicBool::new(false));
+
+    // The first look happens before returning, so that nothing changed after this call is missed
+    let mut files = snapshot(&project_dir);
+
+    let thread_stopped = Arc::clone(&stopped);
+    let thread = thread::spawn(move || {
+        while !thread_stopped.load(Ordering::SeqCst) {
+            thread::sleep(interval);
+
+            let mut current = snapshot(&project_dir);
+            let changed = changed_files(&files, &current);
+            if changed.is_empty() {
+                continue;
+            }
+
+            for path in &changed {
+                callback(&WatchEvent::FileChanged { path: path.clone() });
+            }
+
+            // Only what the watcher wrote itself is left out of the next look, so edits made meanwhile still count
+            for (path, written) in maintain(&project_dir, &changed, &callback) {
+                match written {
+                    Some(w) => current.insert(path, w),
+                    None => current.remove(&path),
+                };
+            }
+            files = current;
+        }
+    });
+
+    Watcher {
+        stopped,
+        thread: Some(thread),
+    }
+}
+
+/*
+ * The modification time and size of every watched file in a project.
+ */
+fn snapshot(project_dir: &Path) -> BTreeMap<PathBuf, Stamp> {
+    let mut walker = super::walker::TreeWalker::new(project_dir).exclude("**/node_modules");
+    for name in WATCHED_FILES.iter() {
+        walker = walker.include(&format!("**/{}", name));
+    }
+
+    walker
+        .walk()
+        .into_iter()
+        .filter_map(|path| {
+            let stamp = stamp(&path)?;
+            Some((path, stamp))
+        })
+        .collect()
+}
+
+/*
+ * The modification time and size of a file, if it's there.
+ */
+fn stamp(path: &Path) -> Option<Stamp> {
+    let metadata = fs::metadata(path).ok()?;
+
+    Some((metadata.modified().ok(), metadata.len()))
+}
+
+/*
+ * The files that were added, changed or removed between two snapshots.
+ */
+fn changed_files(
+    before: &BTreeMap<PathBuf, Stamp>,
+    after: &BTreeMap<PathBuf, Stamp>,
+) -> Vec<PathBuf> {
+    let mut changed: Vec<PathBuf> = after
+        .iter()
+        .filter(|(path, stamp)| before.get(*path) != Some(stamp))
+        .map(|(path, _)| path.clone())
+        .collect();
+    changed.extend(
+        before
+            .keys()
+            .filter(|path| !after.contains_key(*path))
+            .cloned(),
+    );
+    changed.sort();
+
+    changed
+}
+
+/*
+ * Amalgamates the licenses of, and validates, the components that the changed files belong to. Each package.json
+ * file that was written is given back with how it looked right afterwards.
+ */
+fn maintain(
+    project_dir: &Path,
+    changed: &[PathBuf],
+    callback: &WatchCallback,
+) -> Vec<(PathBuf, Option<Stamp>)> {
+    let mut changed_components: Vec<PathBuf> = Vec::new();
+    for path in changed {
+        if let Some(component_dir) = owning_component(project_dir, path) {
+            if !changed_components.contains(&component_dir) {
+                changed_components.push(component_dir);
+            }
+        }
+    }
+
+    // Every component above a changed one includes its licenses, so those have to be updated too
+    let mut to_amalgamate: Vec<PathBuf> = Vec::new();
+    for component_dir in &changed_components {
+        let mut dir = Some(component_dir.clone());
+        while let Some(d) = dir {
+            if !d.starts_with(project_dir) {
+                break;
+            }
+            if !to_amalgamate.contains(&d) {
+                to_amalgamate.push(d.clone());
+            }
+            dir = super::find_enclosing_sr_dir(&d);
+        }
+    }
+    to_amalgamate.sort();
+
+    let mut written = Vec::new();
+    for component_dir in to_amalgamate {
+        // There's nowhere to put the licenses, which validation reports for the component that changed
+        let package_file = component_dir.join("package.json");
+        if !package_file.is_file() {
+            continue;
+        }
+
+        let output = super::amalgamate_licenses(&component_dir);
+        written.push((package_file.clone(), stamp(&package_file)));
+        if output.status != 0 {
+            continue;
+        }
+
+        let license = match fs::read_to_string(&package_file) {
+            Ok(contents) => super::json_value(&contents, "license"),
+            Err(_) => continue,
+        };
+        callback(&WatchEvent::LicensesUpdated {
+            component_dir,
+            license,
+        });
+    }
+
+    for component_dir in changed_components {
+        let output = super::health::validate_component(&component_dir);
+        callback(&WatchEvent::Validated {
+            component_dir,
+            problems: output.stderr,
+        });
+    }
+
+    written
+}
+
+/*
+ * The closest directory at or above a file that is still a component, which is where its metadata belongs.
+ */
+fn owning_component(project_dir: &Path, path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(project_dir))
+        .find(|dir| dir.join(".sr").exists())
+        .map(|dir| dir.to_path_buf())
+}
@@ -0,0 +1,258 @@
+use std::path::{Path, PathBuf};
+
+use super::hierarchy::ComponentEntry;
+use super::status::ProjectStatus;
+
+/// The most projects that will be worked on at the same time. Each project may start threads of its own,
+/// i.e. to run `project_status`.
+pub const MAX_PROJECT_THREADS: usize = 4;
+
+/// The operations that can be run across a set of projects with `run_across_projects`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProjectOperation {
+    /// Runs `update_dependencies` on each project
+    Update,
+    /// Runs `status::project_status` on each project
+    Status,
+    /// Runs `list_all_licenses` on each project
+    LicenseReport,
+}
+
+/// What an operation produced for one project, which depends on the operation that was run.
+pub enum OperationResult {
+    Update(super::SROutput),
+    Status(ProjectStatus),
+    LicenseReport(String),
+}
+
+/// The result of running an operation on one project in a set.
+pub struct ProjectResult {
+    pub project_dir: PathBuf,
+    pub result: OperationResult,
+}
+
+/// A set of projects that are worked on together, i.e. the products a team maintains, with a download cache that
+/// they all share so that a component used by several of them is only downloaded once.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("project1"), String::from("First project"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// sliderule::create_component(&temp_dir.join("project1"), String::from("bracket"), String::from("Bracket"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// sliderule::create_component(&temp_dir, String::from("project2"), String::from("Second project"), String::from("MIT"), String::from("CC0-1.0"));
+///
+/// let mut workspace = sliderule::workspace::Workspace::new().with_cache_dir(&temp_dir.join("cache"));
+/// workspace.add_project(&temp_dir.join("project1"));
+/// workspace.add_project(&temp_dir.join("project2"));
+///
+/// let users = workspace.projects_using("bracket");
+///
+/// assert_eq!(users.len(), 1);
+/// assert_eq!(users[0].0, temp_dir.join("project1"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Workspace {
+    project_dirs: Vec<PathBuf>,
+    cache_dir: Option<PathBuf>,
+}
+
+impl Workspace {
+    /// An empty workspace, which uses npm's own cache until `with_cache_dir` is used.
+    pub fn new() -> Workspace {
+        Workspace::default()
+    }
+
+    /// Sets the directory that npm caches downloads in for every project in the workspace.
+    pub fn with_cache_dir(mut self, cache_dir: &Path) -> Workspace {
+        self.cache_dir = Some(cache_dir.to_path_buf());
+        self
+    }
+
+    /// The cache directory that the projects share, if one has been set.
+    pub fn cache_dir(&self) -> Option<&Path> {
+        self.cache_dir.as_deref()
+    }
+
+    /// The projects in the workspace, in the order that they were added.
+    pub fn projects(&self) -> &[PathBuf] {
+        &self.project_dirs
+    }
+
+    /// Adds a project to the workspace. Adding a project that's already there does nothing.
+    ///
+    /// The output status is 78 if `project_dir` isn't a Sliderule component directory.
+    pub fn add_project(&mut self, project_dir: &Path) -> super::SROutput {
+        let mut output = super::SROutput {
+            status: 0,
+            wrapped_status: 0,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        };
+
+        if !project_dir.join(".sr").exists() {
+            output.status = 78;
+            output.stderr.push(format!(
+                "ERROR: {} is not a Sliderule project, so it can't be added to the workspace.",
+                project_dir.display()
+            ));
+        } else if self.project_dirs.iter().any(|d| d == project_dir) {
+            output.stdout.push(format!(
+                "{} is already in the workspace.",
+                project_dir.display()
+            ));
+        } else {
+            self.project_dirs.push(project_dir.to_path_buf());
+            output
+                .stdout
+                .push(format!("Added {} to the workspace.", project_dir.display()));
+        }
+
+        output
+    }
+
+    /// Takes a project out of the workspace, returning whether it was there. Nothing on disk is changed.
+    pub fn remove_project(&mut self, project_dir: &Path) -> bool {
+        let before = self.project_dirs.len();
+        self.project_dirs.retain(|d| d != project_dir);
+
+        self.project_dirs.len() != before
+    }
+
+    /// Runs an operation on every project in the workspace, as `run_across_projects` does. Updates download
+    /// into the workspace's cache directory.
+    pub fn run(&self, operation: ProjectOperation) -> Vec<ProjectResult> {
+        if operation != ProjectOperation::Update {
+            return run_across_projects(&self.project_dirs, operation);
+        }
+
+        let cache = self.cache();
+        map_projects(&self.project_dirs, |project_dir| {
+            OperationResult::Update(super::update_dependencies_cached(
+                project_dir,
+                cache.clone(),
+            ))
+        })
+        .into_iter()
+        .map(|(project_dir, result)| ProjectResult {
+            project_dir,
+            result,
+        })
+        .collect()
+    }
+
+    /// Adds a remote component to one of the projects, as `add_remote_component` does, downloading into the
+    /// workspace's cache directory.
+    pub fn add_remote_component(&self, project_dir: &Path, url: &str) -> super::SROutput {
+        super::add_remote_component(project_dir, url, self.cache())
+    }
+
+    /// Finds every project that has a component with a name anywhere in its hierarchy, whether it's local or
+    /// remote. Each project is paired with the components that matched, and projects without any are left out.
+    pub fn projects_using(&self, name: &str) -> Vec<(PathBuf, Vec<ComponentEntry>)> {
+        map_projects(&self.project_dirs, |project_dir| {
+            super::hierarchy::find_component(project_dir, name)
+        })
+        .into_iter()
+        .filter(|p| !p.1.is_empty())
+        .collect()
+    }
+
+    /// Finds every project with remote components that `update_dependencies` would install or reinstall, because
+    /// they're missing or their repositories have moved on. Each project is paired with the names of those
+    /// components, and projects that are up to date are left out. Components whose repositories can't be reached
+    /// aren't counted as outdated.
+    pub fn outdated(&self) -> Vec<(PathBuf, Vec<String>)> {
+        map_projects(&self.project_dirs, super::outdated_dependencies)
+            .into_iter()
+            .filter(|p| !p.1.is_empty())
+            .collect()
+    }
+
+    /*
+     * The cache directory in the form that npm is given it.
+     */
+    fn cache(&self) -> Option<String> {
+        self.cache_dir
+            .as_ref()
+            .map(|d| d.to_string_lossy().to_string())
+    }
+}
+
+/// Runs an operation on every project in a set at the same time, on up to `MAX_PROJECT_THREADS` threads.
+/// There is one result per project, in the same order as `project_dirs`.
+///
+/// Each entry in `project_dirs` must be a valid Sliderule component directory.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("project1"), String::from("First project"), String::from("Unlicense"), String::from("CC0-1.0"));
+/// sliderule::create_component(&temp_dir, String::from("project2"), String::from("Second project"), String::from("MIT"), String::from("CC0-1.0"));
+///
+/// let results = sliderule::workspace::run_across_projects(
+///     &[temp_dir.join("project1"), temp_dir.join("project2")],
+///     sliderule::workspace::ProjectOperation::LicenseReport,
+/// );
+///
+/// for project in &results {
+///     if let sliderule::workspace::OperationResult::LicenseReport(ref report) = project.result {
+///         println!("{}:\n{}", project.project_dir.display(), report);
+///     }
+/// }
+/// # assert_eq!(results.len(), 2);
+/// ```
+pub fn run_across_projects(
+    project_dirs: &[PathBuf],
+    operation: ProjectOperation,
+) -> Vec<ProjectResult> {
+    map_projects(project_dirs, |project_dir| match operation {
+        ProjectOperation::Update => {
+            OperationResult::Update(super::update_dependencies(project_dir))
+        }
+        ProjectOperation::Status => {
+            OperationResult::Status(super::status::project_status(project_dir))
+        }
+        ProjectOperation::LicenseReport => {
+            OperationResult::LicenseReport(super::list_all_licenses(project_dir))
+        }
+    })
+    .into_iter()
+    .map(|(project_dir, result)| ProjectResult {
+        project_dir,
+        result,
+    })
+    .collect()
+}
+
+/// Runs any function on every project in a set at the same time, for operations that `run_across_projects`
+/// doesn't cover. The results are paired with their project directories, in the same order as `project_dirs`.
+///
+/// # Examples
+///
+/// ```
+/// let temp_dir = std::env::temp_dir().join(format!("temp_{}", uuid::Uuid::new_v4()));
+/// std::fs::create_dir(&temp_dir).expect("Unable to create temporary directory.");
+///
+/// sliderule::create_component(&temp_dir, String::from("project1"), String::from("First project"), String::from("Unlicense"), String::from("CC0-1.0"));
+///
+/// let counts = sliderule::workspace::map_projects(&[temp_dir.join("project1")], |dir| {
+///     sliderule::hierarchy::component_tree(dir).count()
+/// });
+///
+/// assert_eq!(counts[0].1, 1);
+/// ```
+pub fn map_projects<T, F>(project_dirs: &[PathBuf], f: F) -> Vec<(PathBuf, T)>
+where
+    T: Send,
+    F: Fn(&Path) -> T + Sync,
+{
+    super::parallel_map(project_dirs, MAX_PROJECT_THREADS, |dir| {
+        (dir.to_path_buf(), f(dir))
+    })
+}